@@ -1,24 +1,28 @@
 use std::path::PathBuf;
 
 use super::{
-    arg_parser::check_input_file_extension_is_allowed,
+    arg_parser::check_input_file_exists,
     query_reader::QueryReader,
     write_results::ResFormatter,
 };
-use crate::{error, reference::ReferencePathDetector, Result};
+use crate::{error, error_msg, reference::ReferencePathDetector, Result};
 use clap::{arg, value_parser, Arg, ArgMatches, Command};
 use sigalign::{
     algorithms::{
         Algorithm, Local, LocalWithChunk, LocalWithLimit, SemiGlobal, SemiGlobalWithChunk,
         SemiGlobalWithLimit,
     },
+    results::{PairOrientation, PairingOptions},
     Aligner, Reference,
 };
+use sigalign_utils::sequence_manipulation::preprocessing::QueryPreprocessor;
 
 mod thread_pool;
 use thread_pool::ThreadPool;
 
 const THREAD_BATCH_SIZE: usize = 64;
+// Symbol for the masked bases. Not in the reference, so never matched.
+const MASK_SYMBOL: u8 = b'#';
 
 pub struct ManualAlignmentApp;
 
@@ -26,8 +30,11 @@ pub struct ManualAlignmentApp;
 struct Config {
     // Input
     input_file: PathBuf,
-    is_gzip_compressed: bool,
-    is_fasta_file: bool, // true: FASTA, false: FASTQ
+    // Paired-end
+    second_input_file: Option<PathBuf>,
+    pairing_options: Option<PairingOptions>, // Some if paired-end
+    // Preprocessing
+    preprocessor: Option<QueryPreprocessor>, // Some if any step is set
     reference_path_detector: ReferencePathDetector,
     // Alignment Regulator
     px: u32,
@@ -53,7 +60,7 @@ impl ManualAlignmentApp {
         Command::new(Self::tag())
             .about("Align reads to reference with manually defined parameters")
             .arg_required_else_help(true)
-            .arg(arg!(-i --input <FILE> "Input query FASTA or FASTQ file path (can be gzipped)")
+            .arg(arg!(-i --input <FILE> "Input query FASTA or FASTQ file path (can be compressed)")
                 .value_parser(value_parser!(PathBuf))
                 .required(true)
                 .display_order(1))
@@ -100,6 +107,53 @@ impl ManualAlignmentApp {
             .arg(arg!(--sam "Output format is SAM instead of TSV")
                 .display_order(10)
                 .required(false))
+            .arg(arg!(-I --input2 <FILE> "Second mate FASTQ file of paired-end reads (requires --sam)")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with("interleaved")
+                .required(false)
+                .display_order(11))
+            .arg(arg!(--interleaved "Input is an interleaved paired-end FASTQ file (requires --sam)")
+                .display_order(12)
+                .required(false))
+            .arg(Arg::new("insert").long("insert")
+                .help("Minimum and maximum insert size of concordant pairs")
+                .value_names(["INT", "INT"])
+                .num_args(2)
+                .value_parser(value_parser!(u32))
+                .default_values(["0", "1000"])
+                .required(false)
+                .display_order(13))
+            .arg(Arg::new("orientation").long("orientation")
+                .help("Orientation of concordant pairs")
+                .value_parser(["fr", "rf", "ff"])
+                .default_value("fr")
+                .required(false)
+                .display_order(14))
+            .arg(Arg::new("trim").long("trim")
+                .help("Trim 3' end from the first window with lower mean quality (window size, quality)")
+                .value_names(["INT", "INT"])
+                .num_args(2)
+                .value_parser(value_parser!(u32))
+                .required(false)
+                .display_order(15))
+            .arg(Arg::new("adapter").long("adapter")
+                .help("Adapter sequence to trim from 3' end (can be repeated)")
+                .value_name("SEQ")
+                .action(clap::ArgAction::Append)
+                .required(false)
+                .display_order(16))
+            .arg(Arg::new("adapter_overlap").long("adapter_overlap")
+                .help("Minimum length of the adapter prefix matched at 3' end")
+                .default_value("5")
+                .value_parser(value_parser!(u32))
+                .required(false)
+                .display_order(17))
+            .arg(Arg::new("mask").long("mask")
+                .help("Mask bases with lower quality so that they never match")
+                .value_name("INT")
+                .value_parser(value_parser!(u32))
+                .required(false)
+                .display_order(18))
     }
     pub fn run(matches: &ArgMatches) -> Result<()> {
         // Parse arguments
@@ -179,11 +233,21 @@ impl ManualAlignmentApp {
         config: &Config,
         aligner: Aligner<A>,
     ) -> Result<()> {
+        let reference_paths = config
+            .reference_path_detector
+            .load_reference_chunk_paths()?;
+
         // Write header for results
         {
             let mut res_writer = ResFormatter::new(config.output_is_sam);
             let mut stdout = std::io::stdout();
-            res_writer.write_header(&mut stdout, &config.reference_path_detector)?;
+            res_writer.write_header(&mut stdout)?;
+            // Targets of all chunks are written before the records
+            for reference_path in reference_paths.iter() {
+                // SAFETY: The reference files are not modified during the alignment.
+                let reference = unsafe { Reference::load_from_mapped_file(reference_path)? };
+                res_writer.write_reference_header(&mut stdout, &reference)?;
+            }
         };
 
         let thread_pool: ThreadPool = ThreadPool::new(
@@ -192,12 +256,11 @@ impl ManualAlignmentApp {
             aligner,
             config.with_reverse_complementary,
             config.output_is_sam,
+            config.pairing_options.clone(),
+            config.preprocessor.clone(),
         );
 
         let start_time = std::time::Instant::now();
-        let reference_paths = config
-            .reference_path_detector
-            .load_reference_chunk_paths()?;
         let reference_chunk_count = reference_paths.len();
         for (reference_index, reference_path) in reference_paths.into_iter().enumerate() {
             eprint!(
@@ -215,13 +278,18 @@ impl ManualAlignmentApp {
                 eprintln!(" (loaded in {:.8} s)", elapsed_time.as_secs_f64());
                 reference
             };
+            if (config.with_reverse_complementary || config.pairing_options.is_some())
+                && !reference.has_complementary_strand()
+            {
+                error_msg!("Reference has no complementary strand (use --forward for single-end reads)")
+            }
 
             // Load query
-            let query_reader = QueryReader::new(
-                &config.input_file,
-                config.is_gzip_compressed,
-                config.is_fasta_file,
-            )?;
+            let query_reader = if config.pairing_options.is_some() {
+                QueryReader::new_paired(&config.input_file, config.second_input_file.as_ref())?
+            } else {
+                QueryReader::new(&config.input_file)?
+            };
 
             // Execute alignment
             thread_pool.execute(reference, query_reader);
@@ -238,8 +306,54 @@ impl Config {
     fn from_matches(matches: &ArgMatches) -> Result<Self> {
         // Input file paths
         let input_file = matches.get_one::<PathBuf>("input").unwrap().clone();
-        let (is_gzip_compressed, is_fasta_file) =
-            check_input_file_extension_is_allowed(&input_file)?;
+        check_input_file_exists(&input_file)?;
+
+        // Paired-end
+        let second_input_file = matches.get_one::<PathBuf>("input2").cloned();
+        if let Some(second_input_file) = &second_input_file {
+            check_input_file_exists(second_input_file)?;
+        }
+        let pairing_options = if second_input_file.is_some() || matches.get_flag("interleaved") {
+            if !matches.get_flag("sam") {
+                error_msg!("Paired-end reads can be written only in SAM format (use --sam)")
+            }
+            if matches.get_flag("forward") {
+                error_msg!("Both strands are aligned for paired-end reads (--forward is not allowed)")
+            }
+            let mut insert_sizes = matches.get_many::<u32>("insert").unwrap();
+            let min_insert_size = *insert_sizes.next().unwrap();
+            let max_insert_size = *insert_sizes.next().unwrap();
+            if min_insert_size > max_insert_size {
+                error_msg!("Minimum insert size must not be greater than maximum insert size")
+            }
+            let orientation = match matches.get_one::<String>("orientation").map(|x| x.as_str()) {
+                Some("rf") => PairOrientation::ReverseForward,
+                Some("ff") => PairOrientation::ForwardForward,
+                _ => PairOrientation::ForwardReverse,
+            };
+            Some(PairingOptions::new(orientation, min_insert_size, max_insert_size))
+        } else {
+            None
+        };
+
+        // Preprocessing
+        let preprocessor = {
+            let mut preprocessor = QueryPreprocessor::new();
+            if let Some(mut values) = matches.get_many::<u32>("trim") {
+                let window_size = *values.next().unwrap();
+                let min_quality = quality_value(*values.next().unwrap())?;
+                preprocessor = preprocessor.with_sliding_window_trimming(window_size as usize, min_quality);
+            }
+            if let Some(adapters) = matches.get_many::<String>("adapter") {
+                let adapters: Vec<Vec<u8>> = adapters.map(|x| x.to_ascii_uppercase().into_bytes()).collect();
+                let min_overlap = matches.get_one::<u32>("adapter_overlap").copied().unwrap();
+                preprocessor = preprocessor.with_adapters(adapters, min_overlap as usize);
+            }
+            if let Some(min_quality) = matches.get_one::<u32>("mask") {
+                preprocessor = preprocessor.with_quality_masking(quality_value(*min_quality)?, MASK_SYMBOL);
+            }
+            if preprocessor.is_empty() { None } else { Some(preprocessor) }
+        };
 
         let reference_path = {
             let path = matches
@@ -305,8 +419,9 @@ impl Config {
 
         Ok(Self {
             input_file,
-            is_gzip_compressed,
-            is_fasta_file,
+            second_input_file,
+            pairing_options,
+            preprocessor,
             reference_path_detector: reference_path,
             px,
             po,
@@ -322,3 +437,10 @@ impl Config {
         })
     }
 }
+
+fn quality_value(quality: u32) -> Result<u8> {
+    if quality > 93 {
+        error_msg!("Quality must be in range of 0 to 93")
+    }
+    Ok(quality as u8)
+}
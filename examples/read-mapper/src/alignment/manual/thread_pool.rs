@@ -4,8 +4,13 @@ use std::sync::{mpsc, Arc};
 use std::thread;
 
 use sigalign::algorithms::Algorithm;
-use sigalign::{Aligner, Reference};
-use sigalign_utils::sequence_manipulation::reverse_complementary::reverse_complement_of_dna_sequence_in_place;
+use sigalign::results::PairingOptions;
+use sigalign::{Aligner, Alphabet, Reference};
+use sigalign_utils::sequence_manipulation::{
+    preprocessing::{QueryPreprocessor, TrimmedRange},
+    reverse_complementary::reverse_complement_of_dna_sequence_in_place,
+};
+use sigalign_utils::sequence_reader::ReadPair;
 
 use crate::alignment::write_results::ResFormatter;
 
@@ -35,6 +40,8 @@ impl ThreadPool {
         aligner: Aligner<A>,
         with_reverse_complementary: bool,
         output_is_sam: bool,
+        pairing_options: Option<PairingOptions>,
+        preprocessor: Option<QueryPreprocessor>,
     ) -> Self {
         let mut workers = Vec::with_capacity(num_workers);
 
@@ -55,6 +62,8 @@ impl ThreadPool {
                 res_sender.clone(),
                 with_reverse_complementary,
                 output_is_sam,
+                pairing_options.clone(),
+                preprocessor.clone(),
             ));
         }
 
@@ -103,12 +112,19 @@ impl Worker {
         res_sender: mpsc::Sender<JobCompleteSign>,
         with_reverse_complementary: bool,
         output_is_sam: bool,
+        pairing_options: Option<PairingOptions>,
+        preprocessor: Option<QueryPreprocessor>,
     ) -> Worker {
         eprintln!("[Worker {}] spawned", id);
 
-        // Vec of (sequence, label) buffer
-        let mut query_buffers: Vec<(Vec<u8>, String)> =
-            vec![(Vec::new(), String::new()); batch_size];
+        // Vec of (sequence, quality, label, trimmed range) buffer
+        //  - The sequence is replaced with the preprocessed one.
+        let mut query_buffers: Vec<(Vec<u8>, Vec<u8>, String, TrimmedRange)> =
+            vec![(Vec::new(), Vec::new(), String::new(), TrimmedRange::full(0)); batch_size];
+        // Buffer for read pairs
+        let mut pair_buffers: Vec<ReadPair> = vec![ReadPair::default(); batch_size];
+        // Buffers for the preprocessed sequences
+        let mut preprocessed_buffers: (Vec<u8>, Vec<u8>) = (Vec::new(), Vec::new());
         // Bytes to write as results
         let results_buffer = Vec::new();
         let mut buf_writer = BufWriter::new(results_buffer);
@@ -116,7 +132,7 @@ impl Worker {
         let thread = thread::spawn(move || {
             let mut res_formatter = ResFormatter::new(
                 output_is_sam,
-            );
+            ).with_mapping_quality_estimator(aligner.mapping_quality_estimator());
             let stdout = std::io::stdout();
 
             loop {
@@ -130,56 +146,148 @@ impl Worker {
                     }
                 };
 
+                if let Some(pairing_options) = &pairing_options {
+                    loop {
+                        // Fill buffers
+                        let optional_last_buffer_index = {
+                            let mut last_buffer_index = None;
+                            let mut query_reader = query_reader.lock();
+                            for (buffer_index, pair_buffer) in pair_buffers.iter_mut().enumerate() {
+                                match query_reader.fill_pair_buffer(pair_buffer) {
+                                    Ok(true) => {},
+                                    Ok(false) => {
+                                        last_buffer_index = Some(buffer_index);
+                                        break;
+                                    },
+                                    Err(err) => {
+                                        eprintln!("[Worker {}] failed to read query: {}", id, err);
+                                        std::process::exit(1);
+                                    },
+                                }
+                            }
+                            last_buffer_index
+                        };
+
+                        // Align
+                        let last_buffer_index =
+                            optional_last_buffer_index.unwrap_or(pair_buffers.len());
+                        for pair in pair_buffers[..last_buffer_index].iter() {
+                            let result = match &preprocessor {
+                                Some(preprocessor) => {
+                                    let (first, second) = &mut preprocessed_buffers;
+                                    let first_trimmed_range = preprocessor.preprocess(
+                                        &pair.first_sequence, Some(&pair.first_quality), first,
+                                    );
+                                    let second_trimmed_range = preprocessor.preprocess(
+                                        &pair.second_sequence, Some(&pair.second_quality), second,
+                                    );
+                                    aligner.align_preprocessed_read_pair(
+                                        first,
+                                        &first_trimmed_range,
+                                        second,
+                                        &second_trimmed_range,
+                                        &reference,
+                                        pairing_options,
+                                    )
+                                },
+                                None => aligner.align_read_pair(
+                                    &pair.first_sequence,
+                                    &pair.second_sequence,
+                                    &reference,
+                                    pairing_options,
+                                ),
+                            };
+                            // The strand is checked before the alignment
+                            let result = result.unwrap();
+                            res_formatter.write_pair_record(
+                                &mut buf_writer,
+                                &result,
+                                pair.name(),
+                                &reference,
+                            ).unwrap();
+                        }
+
+                        // Write results
+                        {
+                            buf_writer.flush().unwrap();
+                            let inner = buf_writer.get_mut();
+
+                            let mut lock = stdout.lock();
+                            lock.write_all(&inner).unwrap();
+                            inner.clear();
+                        }
+
+                        // If no more pairs: break
+                        if optional_last_buffer_index.is_some() {
+                            break;
+                        }
+                    }
+                    res_sender.send(JobCompleteSign).unwrap();
+                    eprintln!("[Worker {}] job completed", id);
+                    continue;
+                }
+
                 loop {
                     // Fill buffers
                     let optional_last_buffer_index = {
                         let mut last_buffer_index = None;
                         let mut query_reader = query_reader.lock();
-                        for (buffer_index, (sequence_buffer, label_buffer)) in
+                        for (buffer_index, (sequence_buffer, quality_buffer, label_buffer, _)) in
                             query_buffers.iter_mut().enumerate()
                         {
-                            if let Err(_) =
-                                query_reader.fill_record_buffer(sequence_buffer, label_buffer)
-                            {
-                                last_buffer_index = Some(buffer_index);
-                                break;
+                            match query_reader.fill_record_buffer(sequence_buffer, quality_buffer, label_buffer) {
+                                Ok(true) => {},
+                                Ok(false) => {
+                                    last_buffer_index = Some(buffer_index);
+                                    break;
+                                },
+                                Err(err) => {
+                                    eprintln!("[Worker {}] failed to read query: {}", id, err);
+                                    std::process::exit(1);
+                                },
                             }
                         }
                         last_buffer_index
                     };
 
-                    // Align
                     let last_buffer_index =
                         optional_last_buffer_index.unwrap_or(query_buffers.len());
-                    // Forward
-                    for (query, label) in query_buffers[..last_buffer_index].iter() {
-                        let result = aligner.align(query, &reference);
-                        let labeled_result = reference.label_query_alignment(result);
+                    // Preprocess
+                    for (query, quality, _, trimmed_range) in query_buffers[..last_buffer_index].iter_mut() {
+                        *trimmed_range = match &preprocessor {
+                            Some(preprocessor) => {
+                                let quality = if quality.is_empty() { None } else { Some(quality.as_slice()) };
+                                let trimmed_range = preprocessor.preprocess(query, quality, &mut preprocessed_buffers.0);
+                                std::mem::swap(query, &mut preprocessed_buffers.0);
+                                trimmed_range
+                            },
+                            None => TrimmedRange::full(query.len() as u32),
+                        };
+                    }
+
+                    // Align both strands of each query, to estimate MAPQ over them together
+                    for (query, _, label, trimmed_range) in query_buffers[..last_buffer_index].iter_mut() {
+                        let result = aligner.align_preprocessed(query, trimmed_range, &reference);
+                        let forward_result = reference.label_query_alignment(result);
+                        let reverse_result = if with_reverse_complementary {
+                            match reference.get_alphabet() {
+                                None | Some(Alphabet::Dna) => reverse_complement_of_dna_sequence_in_place(query),
+                                // The strand is checked before the alignment
+                                Some(_) => *query = reference.reverse_complement(query).unwrap(),
+                            }
+                            let result = aligner.align_preprocessed(query, &trimmed_range.reverse(), &reference);
+                            Some(reference.label_query_alignment(result))
+                        } else {
+                            None
+                        };
                         res_formatter.write_record(
                             &mut buf_writer,
-                            &labeled_result,
+                            &forward_result,
+                            reverse_result.as_ref(),
                             label,
-                            query.len() as u32,
-                            true,
+                            trimmed_range.original_length,
                         ).unwrap();
                     }
-                    // Reverse
-                    if with_reverse_complementary {
-                        query_buffers[..last_buffer_index]
-                            .iter_mut()
-                            .for_each(|(query, label)| {
-                                reverse_complement_of_dna_sequence_in_place(query);
-
-                                let result = aligner.align(query, &reference);
-                                let labeled_result = reference.label_query_alignment(result);
-                                res_formatter.write_record(&mut buf_writer,
-                                    &labeled_result,
-                                    label,
-                                    query.len() as u32,
-                                    false,
-                                ).unwrap();
-                            });
-                    }
 
                     // Write results
                     {
@@ -1,61 +1,60 @@
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{io::Read, path::PathBuf};
 
 use crate::{error, Result};
 
 use sigalign_utils::sequence_reader::{
-    decompress::get_gzip_decoder, fasta::FastaReader, fastq::FastqReader, IdRecord, SeqRecord as _,
+    open_sequence_file, PairedFastqReader, ReadPair, SequenceFileReader,
 };
 
 pub enum QueryReader {
-    Fasta(FastaReader<Box<dyn Read + Send>>),
-    Fastq(FastqReader<Box<dyn Read + Send>>),
+    Single(SequenceFileReader),
+    Paired(PairedFastqReader<Box<dyn Read + Send>>),
 }
 
 impl QueryReader {
-    pub fn new(file_path: &PathBuf, is_gzip_compressed: bool, is_fasta_file: bool) -> Result<Self> {
-        let read: Box<dyn Read + Send> = {
-            let file = File::open(file_path)?;
-            if is_gzip_compressed {
-                Box::new(get_gzip_decoder(file))
-            } else {
-                Box::new(file)
-            }
+    pub fn new(file_path: &PathBuf) -> Result<Self> {
+        Ok(Self::Single(open_sequence_file(file_path)?))
+    }
+    /// If `second_file_path` is `None`, the file is interleaved.
+    pub fn new_paired(file_path: &PathBuf, second_file_path: Option<&PathBuf>) -> Result<Self> {
+        let reader = match second_file_path {
+            Some(second_file_path) => PairedFastqReader::from_paths(file_path, second_file_path)?,
+            None => PairedFastqReader::interleaved_from_path(file_path)?,
         };
-
-        if is_fasta_file {
-            Ok(Self::Fasta(FastaReader::new(read)))
-        } else {
-            Ok(Self::Fastq(FastqReader::new(read)))
-        }
+        Ok(Self::Paired(reader))
     }
+    /// Fill the buffers with the next record.
+    ///  - The quality buffer is left empty for FASTA.
+    /// Returns `false` if there are no more records.
     pub fn fill_record_buffer(
         &mut self,
         sequence_buffer: &mut Vec<u8>,
+        quality_buffer: &mut Vec<u8>,
         label_buffer: &mut String,
-    ) -> Result<()> {
-        match self {
-            Self::Fasta(reader) => {
-                if let Some(mut record) = reader.next() {
-                    sequence_buffer.clear();
-                    label_buffer.clear();
-                    record.extend_seq_buf(sequence_buffer);
-                    record.extend_id_string(label_buffer)?;
-                    Ok(())
-                } else {
-                    Err(error!("No more records"))
-                }
+    ) -> Result<bool> {
+        let Self::Single(reader) = self else {
+            return Err(error!("Paired reader cannot read a single record"));
+        };
+        match reader.read_record_with_quality(sequence_buffer, quality_buffer, label_buffer) {
+            Some(result) => {
+                result?;
+                Ok(true)
             }
-            Self::Fastq(reader) => {
-                if let Some(mut record) = reader.next() {
-                    sequence_buffer.clear();
-                    label_buffer.clear();
-                    record.extend_seq_buf(sequence_buffer);
-                    record.extend_id_string(label_buffer)?;
-                    Ok(())
-                } else {
-                    Err(error!("No more records"))
-                }
+            None => Ok(false),
+        }
+    }
+    /// Fill the buffer with the next read pair.
+    /// Returns `false` if there are no more pairs.
+    pub fn fill_pair_buffer(&mut self, pair_buffer: &mut ReadPair) -> Result<bool> {
+        let Self::Paired(reader) = self else {
+            return Err(error!("Single-end reader cannot read a pair"));
+        };
+        match reader.read_pair(pair_buffer) {
+            Some(result) => {
+                result?;
+                Ok(true)
             }
+            None => Ok(false),
         }
     }
 }
@@ -1,13 +1,15 @@
-use std::{fs::File, io::{BufRead as _, BufReader, Write}};
-use anyhow::Result;
+use std::io::Write;
+use anyhow::{anyhow as error, Result};
 
-use sigalign::{results::LabeledQueryAlignment, utils::formatter::SamFormatter};
+use sigalign::{
+    results::{LabeledQueryAlignment, MappingQualityEstimator, ReadPairAlignment},
+    utils::formatter::SamFormatter,
+    Reference,
+};
 
 mod tsv;
 use tsv::TsvFormatter;
 
-use crate::reference::ReferencePathDetector;
-
 pub enum ResFormatter {
     TSV(TsvFormatter),
     SAM(SamFormatter),
@@ -21,47 +23,86 @@ impl ResFormatter {
             Self::TSV(TsvFormatter::new())
         }
     }
-    pub fn write_header(
-        &mut self,
-        writer: &mut impl Write,
-        reference_path_detector: &ReferencePathDetector,
-    ) -> Result<()> {
+    /// Only SAM has MAPQ field.
+    pub fn with_mapping_quality_estimator(self, estimator: MappingQualityEstimator) -> Self {
+        match self {
+            Self::SAM(formatter) => Self::SAM(formatter.with_mapping_quality_estimator(estimator)),
+            other => other,
+        }
+    }
+    pub fn write_header(&mut self, writer: &mut impl Write) -> Result<()> {
         match self {
             Self::TSV(formatter) => formatter.write_header(writer),
             Self::SAM(formatter) => {
                 formatter.write_hd_header(writer)?;
-                // Fields of manifest file:
-                // file_index, file_name, record_index, reference_index, target_index, target_label, target_length
-                let manifest_file = File::open(reference_path_detector.get_manifest_file_path())?;
-                let reader = BufReader::new(manifest_file);
-                for line in reader.lines().skip(1) {
-                    let line = line?;
-                    let fields: Vec<&str> = line.split('\t').collect();
-                    formatter.write_sq_header(
-                        writer,
-                        &fields[5],
-                        &fields[6].parse::<u32>().unwrap(),
-                    )?;
+                Ok(())
+            },
+        }
+    }
+    /// Only SAM has the `@SQ` lines for the targets of the reference.
+    ///  - The description of the target metadata is written in the `DS` field.
+    pub fn write_reference_header(
+        &mut self,
+        writer: &mut impl Write,
+        reference: &Reference,
+    ) -> Result<()> {
+        match self {
+            Self::TSV(_) => Ok(()),
+            Self::SAM(_) => {
+                for target_index in 0..reference.get_num_targets() {
+                    let label = reference.get_label_str(target_index).unwrap_or_default();
+                    let length = reference.get_sequence_length(target_index).unwrap_or_default();
+                    write!(writer, "@SQ\tSN:{}\tLN:{}", label, length)?;
+                    if let Some(metadata) = reference.get_target_metadata(target_index) {
+                        if !metadata.description.is_empty() {
+                            write!(writer, "\tDS:{}", metadata.description.replace('\t', " "))?;
+                        }
+                    }
+                    writer.write_all(b"\n")?;
                 }
                 Ok(())
             },
         }
     }
+    /// Write the alignments of both strands of a read.
+    ///  - MAPQ is estimated over both strands together.
     pub fn write_record(
         &mut self,
         writer: &mut impl Write,
-        labeled_query_alignment: &LabeledQueryAlignment,
+        forward: &LabeledQueryAlignment,
+        reverse: Option<&LabeledQueryAlignment>,
         query_name: &str,
         query_length: u32,
-        is_forward: bool,
     ) -> Result<()> {
         match self {
-            Self::TSV(formatter) => formatter.write_tsv_record(
-                writer, labeled_query_alignment, query_name, is_forward,
-            ),
+            Self::TSV(formatter) => {
+                formatter.write_tsv_record(writer, forward, query_name, true)?;
+                if let Some(reverse) = reverse {
+                    formatter.write_tsv_record(writer, reverse, query_name, false)?;
+                }
+                Ok(())
+            },
+            Self::SAM(formatter) => {
+                formatter.write_labeled_read_alignment_with_hclip(
+                    writer, forward, reverse, query_name, query_length,
+                )?;
+                Ok(())
+            }
+        }
+    }
+    /// Only SAM has the fields for the mates.
+    pub fn write_pair_record(
+        &mut self,
+        writer: &mut impl Write,
+        read_pair_alignment: &ReadPairAlignment,
+        pair_name: &str,
+        reference: &Reference,
+    ) -> Result<()> {
+        match self {
+            Self::TSV(_) => Err(error!("TSV output does not support read pairs")),
             Self::SAM(formatter) => {
-                formatter.write_labeled_query_alignment_with_hclip(
-                    writer, labeled_query_alignment, query_name, is_forward, query_length,
+                formatter.write_read_pair_alignment(
+                    writer, read_pair_alignment, pair_name, reference,
                 )?;
                 Ok(())
             }
@@ -1,25 +1,17 @@
 use crate::{error_msg, Result};
 use clap::{arg, value_parser, Arg, ArgMatches, Command};
-use sigalign::Reference;
+use sigalign::{Reference, TargetMetadata};
 use sigalign_core::reference::{Reference as RawReference, SequenceStorage};
 use sigalign_impl::{
     pattern_index::dynamic_lfi::DynamicLfiOption, sequence_storage::in_memory::InMemoryStorage,
 };
-use sigalign_utils::{
-    file_extension_checker::{is_fasta_file, is_gzip_file},
-    sequence_reader::{decompress::get_gzip_decoder, fasta::FastaReader, IdRecord, SeqRecord},
-};
-use std::{
-    fs::File,
-    io::{BufWriter, Read},
-    path::PathBuf,
-    time::Instant,
+use sigalign_utils::sequence_reader::{
+    open_sequence_file, IdRecord, SeqRecord, SequenceFileReader, SequenceFormat,
 };
+use std::{fs::File, path::PathBuf, time::Instant};
 
 mod path_detector;
 pub use path_detector::ReferencePathDetector;
-mod manifest;
-pub use manifest::{write_manifest_header, write_manifest_line};
 
 const DEFAULT_MAX_LENGTH: u32 = u32::MAX - 1;
 
@@ -39,7 +31,7 @@ impl ReferenceApp {
             .about("Generate reference file")
             .arg_required_else_help(true)
             .arg(
-                arg!(-i --input <FILE> "Input file(s) path (FASTA format; can be gzipped)")
+                arg!(-i --input <FILE> "Input file(s) path (FASTA format; can be compressed)")
                     .display_order(1)
                     .num_args(1..)
                     .value_parser(value_parser!(PathBuf))
@@ -111,11 +103,8 @@ impl Config {
                     error_msg!("Input file ({}) must be a file", input_file.display())
                 } else if !input_file.exists() {
                     error_msg!("Input file ({}) does not exist", input_file.display())
-                } else if !input_file_extension_is_allowed(input_file) {
-                    error_msg!(
-                        "Input file ({}) extension is not allowed",
-                        input_file.display()
-                    )
+                } else if open_sequence_file(input_file)?.format() != SequenceFormat::Fasta {
+                    error_msg!("Input file ({}) is not a FASTA file", input_file.display())
                 }
             }
 
@@ -175,59 +164,39 @@ impl Config {
         let reference_path_detector = ReferencePathDetector::new(&self.output_file);
         let mut reference_index = 0;
 
-        // Manifest file
-        let mut manifest_file = File::create(reference_path_detector.get_manifest_file_path())?;
-        let mut manifest_writer = BufWriter::new(&mut manifest_file);
-        write_manifest_header(&mut manifest_writer)?;
-
         // Save reference by chunk
         let mut sequence_buffer = Vec::new();
         let mut label_buffer = String::new();
         let mut sequence_storage = InMemoryStorage::new();
+        let mut target_metadata = Vec::new();
 
         // For each input file
-        for (input_file_index, input_file) in self.input_files.iter().enumerate() {
-            let input_file_name = input_file
-                .file_name()
-                .unwrap_or_default()
-                .to_str()
-                .unwrap_or_default();
-            let readable_file: Box<dyn Read> = {
-                let file = File::open(input_file)?;
-                if is_gzip_file(input_file) {
-                    Box::new(get_gzip_decoder(file))
-                } else {
-                    Box::new(file)
+        for input_file in self.input_files.iter() {
+            let mut fasta_reader = match open_sequence_file(input_file)? {
+                SequenceFileReader::Fasta(reader) => reader,
+                SequenceFileReader::Fastq(_) => {
+                    error_msg!("Input file ({}) is not a FASTA file", input_file.display())
                 }
             };
-            let mut fasta_reader = FastaReader::new(readable_file);
-            let mut record_index = 0;
-
-            let mut target_index = sequence_storage.num_targets();
             let mut total_length = sequence_storage.get_total_length();
 
-            while let Some(mut record) = fasta_reader.next() {
+            while let Some(record) = fasta_reader.next() {
+                let mut record = record?;
                 sequence_buffer.clear();
                 record.extend_seq_buf(&mut sequence_buffer);
                 label_buffer.clear();
                 record.extend_id_string(&mut label_buffer)?;
-
-                write_manifest_line(
-                    &mut manifest_writer,
-                    &input_file_index,
-                    input_file_name,
-                    &record_index,
-                    &reference_index,
-                    &target_index,
-                    label_buffer.as_str(),
-                    &sequence_buffer.len(),
-                )?;
+                let description = record.desc_str().transpose()?.unwrap_or_default();
+                let metadata = TargetMetadata::from_fasta_description(description);
 
                 if (total_length != 0)
                     && (total_length + sequence_buffer.len() as u32 > self.max_length)
                 {
                     // Build and Save
-                    let reference = get_reference_with_default_option(sequence_storage)?;
+                    let reference = get_reference_with_default_option(
+                        sequence_storage,
+                        std::mem::take(&mut target_metadata),
+                    )?;
                     let reference_path = reference_path_detector.get_path_of_index(reference_index);
                     eprintln!(
                         "Saving reference chunk index {} to {} ({} bp, {} targets)",
@@ -246,16 +215,15 @@ impl Config {
                 }
 
                 sequence_storage.add_target(&label_buffer, &sequence_buffer);
+                target_metadata.push(metadata);
 
                 total_length += sequence_buffer.len() as u32;
-                record_index += 1;
-                target_index += 1;
             }
         }
 
         // Save last reference
         if sequence_storage.num_targets() != 0 {
-            let reference = get_reference_with_default_option(sequence_storage)?;
+            let reference = get_reference_with_default_option(sequence_storage, target_metadata)?;
             let reference_path = reference_path_detector.get_path_of_index(reference_index);
             eprintln!(
                 "Saving reference chunk index {} to {} ({} bp, {} targets)",
@@ -268,16 +236,14 @@ impl Config {
             reference.save_to(out_file)?;
         }
 
-        eprintln!(
-            "Target manifest file has been saved to {}",
-            reference_path_detector.get_manifest_file_path().display()
-        );
-
         Ok(())
     }
 }
 
-fn get_reference_with_default_option(mut sequence_storage: InMemoryStorage) -> Result<Reference> {
+fn get_reference_with_default_option(
+    mut sequence_storage: InMemoryStorage,
+    target_metadata: Vec<TargetMetadata>,
+) -> Result<Reference> {
     // Preparing sequence storage
     sequence_storage.set_sequences_to_uppercase();
     sequence_storage.change_bases_to(b"N", b'?');
@@ -292,21 +258,7 @@ fn get_reference_with_default_option(mut sequence_storage: InMemoryStorage) -> R
         use_safe_guard: true,
     };
     let raw_reference = RawReference::new(sequence_storage, dynamic_lfi_option)?;
-    let reference = Reference::from(raw_reference);
+    let mut reference = Reference::from(raw_reference);
+    reference.set_target_metadata(target_metadata)?;
     Ok(reference)
 }
-
-fn input_file_extension_is_allowed(path: &PathBuf) -> bool {
-    if is_gzip_file(path) {
-        let path_without_gz = path.with_extension("");
-        if is_fasta_file(path_without_gz) {
-            return true;
-        } else {
-            return false;
-        }
-    } else if is_fasta_file(path) {
-        return true;
-    } else {
-        return false;
-    }
-}
@@ -24,11 +24,6 @@ impl ReferencePathDetector {
             self.original_path.with_file_name(file_name)
         }
     }
-    pub fn get_manifest_file_path(&self) -> PathBuf {
-        let mut manifest_file_name = self.original_path.file_name().unwrap().to_os_string();
-        manifest_file_name.push(".manifest");
-        self.original_path.with_file_name(manifest_file_name)
-    }
     pub fn to_clean_up_paths(&self) -> Result<Vec<PathBuf>> {
         let mut reference_paths = Vec::new();
         // Original reference file
@@ -57,12 +52,6 @@ impl ReferencePathDetector {
             }
         }
 
-        // Manifest file
-        let manifest_file_path = self.get_manifest_file_path();
-        if manifest_file_path.exists() {
-            reference_paths.push(manifest_file_path);
-        }
-
         Ok(reference_paths)
     }
     pub fn load_reference_chunk_paths(&self) -> Result<Vec<PathBuf>> {
@@ -172,7 +172,8 @@ impl AlignmentConfig {
             let query_file = File::open(&self.input_fasta_file)?;
             let mut fasta_reader = FastaReader::new(query_file);
 
-            while let Some(mut record) = fasta_reader.next() {
+            while let Some(record) = fasta_reader.next() {
+                let mut record = record.unwrap();
                 // Forward
                 query.clear();
                 record.extend_seq_buf(&mut query);
@@ -83,7 +83,8 @@ impl ThreadPool {
         let mut remained_batch_size = self.batch_size;
 
         let mut total_jobs = 0;
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record.unwrap();
             record.extend_seq_buf(&mut sequence_buffer);
             set_sequence_to_uppercase(&mut sequence_buffer);
             record.extend_id_string(&mut label_string)?;
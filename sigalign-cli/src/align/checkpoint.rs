@@ -0,0 +1,128 @@
+//! Checkpoints to resume the interrupted alignment.
+//!  - The checkpoint records the number of queries whose results are fully written,
+//!    and the length of the output at that point.
+//!  - On resume, the output is truncated to the recorded length and the written queries are skipped,
+//!    so that the final output is identical to that of the uninterrupted run.
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::error_msg;
+
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub num_queries: u64,
+    pub output_offset: u64,
+}
+
+pub struct Checkpointer {
+    path: PathBuf,
+    // Settings affecting the output; a checkpoint of the other settings cannot be resumed.
+    settings: String,
+    interval: u64,
+    last_num_queries: u64,
+}
+
+impl Checkpointer {
+    pub fn new(path: &str, settings: String, interval: u64) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            settings,
+            interval,
+            last_num_queries: 0,
+        }
+    }
+    /// Load the saved checkpoint, `None` if not saved yet.
+    pub fn load(&mut self) -> Result<Option<Checkpoint>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read the checkpoint ({})", self.path.display())),
+        };
+        let checkpoint = self.parse(&contents)
+            .with_context(|| format!("Invalid checkpoint ({})", self.path.display()))?;
+        self.last_num_queries = checkpoint.num_queries;
+        Ok(Some(checkpoint))
+    }
+    /// Save the checkpoint if `interval` queries are written after the last one.
+    pub fn save_if_due(&mut self, output: &mut BufWriter<File>, num_queries: u64) -> Result<()> {
+        if num_queries - self.last_num_queries >= self.interval {
+            self.save(output, num_queries)?;
+        }
+        Ok(())
+    }
+    /// Save the checkpoint after the output is flushed and synced to the disk.
+    ///  - The checkpoint is replaced atomically, so that the previous one remains on failure.
+    pub fn save(&mut self, output: &mut BufWriter<File>, num_queries: u64) -> Result<()> {
+        output.flush()?;
+        let file = output.get_mut();
+        file.sync_data()?;
+        let checkpoint = Checkpoint {
+            num_queries,
+            output_offset: file.stream_position()?,
+        };
+
+        let mut temporary_path = self.path.clone().into_os_string();
+        temporary_path.push(".tmp");
+        let temporary_path = PathBuf::from(temporary_path);
+        {
+            let mut file = File::create(&temporary_path)
+                .with_context(|| format!("Failed to create the checkpoint ({})", temporary_path.display()))?;
+            write!(
+                file,
+                "version\t{}\nsettings\t{}\nnum_queries\t{}\noutput_offset\t{}\n",
+                VERSION, self.settings, checkpoint.num_queries, checkpoint.output_offset,
+            )?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&temporary_path, &self.path)
+            .with_context(|| format!("Failed to save the checkpoint ({})", self.path.display()))?;
+        self.last_num_queries = num_queries;
+        Ok(())
+    }
+
+    fn parse(&self, contents: &str) -> Result<Checkpoint> {
+        let mut fields = contents.lines().filter_map(|line| line.split_once('\t'));
+        let mut next_value = |key: &str| -> Result<&str> {
+            match fields.next() {
+                Some((k, value)) if k == key => Ok(value),
+                _ => error_msg!("'{}' is missing", key),
+            }
+        };
+        let version: u32 = next_value("version")?.parse()?;
+        if version != VERSION {
+            error_msg!("Unsupported version {} (expected {})", version, VERSION)
+        }
+        let settings = next_value("settings")?;
+        if settings != self.settings {
+            error_msg!(
+                "The checkpoint was saved with the other settings\n  saved: {}\n  current: {}",
+                settings, self.settings,
+            )
+        }
+        Ok(Checkpoint {
+            num_queries: next_value("num_queries")?.parse()?,
+            output_offset: next_value("output_offset")?.parse()?,
+        })
+    }
+}
+
+/// Open the output of the checkpoint, truncating the contents written after the checkpoint.
+pub fn reopen_output(path: &str, checkpoint: &Checkpoint) -> Result<File> {
+    let mut file = OpenOptions::new().write(true).open(path)
+        .with_context(|| format!("Failed to open the output of the checkpoint ({})", path))?;
+    let length = file.metadata()?.len();
+    if length < checkpoint.output_offset {
+        error_msg!(
+            "Output file ({}) is shorter than the checkpoint ({} < {} bytes)",
+            path, length, checkpoint.output_offset,
+        )
+    }
+    file.set_len(checkpoint.output_offset)?;
+    file.seek(SeekFrom::End(0))?;
+    Ok(file)
+}
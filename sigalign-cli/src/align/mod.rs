@@ -0,0 +1,421 @@
+use std::io::{BufWriter, Write};
+
+use clap::{arg, builder::RangedU64ValueParser, value_parser, ArgMatches, Command};
+use sigalign::{
+    Aligner, Reference, StrandError,
+    algorithms::{
+        Algorithm,
+        Local, LocalWithChunk, LocalWithLimit,
+        SemiGlobal, SemiGlobalWithChunk, SemiGlobalWithLimit,
+    },
+    results::{AlignmentOrder, QueryAlignment},
+};
+use sigalign_utils::sequence_reader::SequenceFileReader;
+
+use crate::io::{create_output_file, load_reference, open_output, open_sequence_input, STDIO};
+use crate::{error_msg, Result};
+
+mod output;
+use output::{OutputFormat, ResultWriter};
+mod checkpoint;
+use checkpoint::{reopen_output, Checkpointer};
+
+pub struct AlignApp;
+
+#[derive(Debug)]
+struct Config {
+    reference_path: String,
+    query_path: String,
+    output_path: String,
+    overwrite: bool,
+    mmap: bool,
+    format: OutputFormat,
+    // None to be decided by the reference
+    strand: Option<Strand>,
+    num_threads: usize,
+    batch_size: usize,
+    // Checkpoint
+    checkpoint_path: Option<String>,
+    checkpoint_interval: u64,
+    resume: bool,
+    // Algorithm
+    mismatch_penalty: u32,
+    gap_open_penalty: u32,
+    gap_extend_penalty: u32,
+    minimum_length: u32,
+    maximum_penalty_per_length: f32,
+    semi_global: bool,
+    limit: Option<u32>,
+    chunk: Option<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strand {
+    Forward,
+    Reverse,
+    Both,
+}
+
+/// Query record read from FASTA or FASTQ.
+#[derive(Default)]
+pub struct QueryRecord {
+    pub id: String,
+    pub sequence: Vec<u8>,
+}
+
+/// Result of a strand of the query.
+pub struct StrandAlignment {
+    pub is_forward: bool,
+    pub query_alignment: QueryAlignment,
+}
+
+impl AlignApp {
+    pub fn get_command() -> Command {
+        Command::new("align")
+            .about("Align FASTA or FASTQ queries to the reference")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(-r --reference <FILE> "Reference file built by 'index' ('-' for the standard input)")
+                    .required(true),
+            )
+            .arg(
+                arg!([QUERY] "FASTA or FASTQ file of queries; can be compressed, '-' for the standard input")
+                    .default_value(STDIO),
+            )
+            .arg(
+                arg!(-l --"min-length" <INT> "Minimum length of alignment (MinL)")
+                    .value_parser(value_parser!(u32))
+                    .required(true),
+            )
+            .arg(
+                arg!(-p --"max-penalty" <FLOAT> "Maximum penalty per length of alignment (MaxP)")
+                    .value_parser(value_parser!(f32))
+                    .required(true),
+            )
+            .arg(
+                arg!(-x --mismatch <INT> "Mismatch penalty")
+                    .value_parser(value_parser!(u32))
+                    .default_value("4"),
+            )
+            .arg(
+                arg!(-g --"gap-open" <INT> "Gap-open penalty")
+                    .value_parser(value_parser!(u32))
+                    .default_value("6"),
+            )
+            .arg(
+                arg!(-e --"gap-extend" <INT> "Gap-extend penalty")
+                    .value_parser(value_parser!(u32))
+                    .default_value("2"),
+            )
+            .arg(arg!(--"semi-global" "Semi-global alignment, instead of local alignment"))
+            .arg(
+                arg!(--limit <INT> "Stop after this number of alignments per query (not guaranteed to be optimal)")
+                    .value_parser(value_parser!(u32)),
+            )
+            .arg(
+                arg!(--chunk <SIZE> "Align the query in chunks of SIZE sliding by STEP (for long queries)")
+                    .value_names(["SIZE", "STEP"])
+                    .num_args(2)
+                    .value_parser(value_parser!(u32))
+                    .conflicts_with("limit"),
+            )
+            .arg(
+                arg!(-s --strand <STRAND> "Strand of the queries to align (default: both, or forward for protein)")
+                    .value_parser(["forward", "reverse", "both"]),
+            )
+            .arg(
+                arg!(-f --format <FORMAT> "Output format")
+                    .value_parser(["sam", "paf", "tsv", "json"])
+                    .default_value("sam"),
+            )
+            .arg(arg!(-o --output <FILE> "Output file ('-' for the standard output)").default_value(STDIO))
+            .arg(arg!(-w --overwrite "Overwrite the existing output file"))
+            .arg(
+                arg!(-t --threads <INT> "Number of threads (0 for all cores)")
+                    .value_parser(value_parser!(usize))
+                    .default_value("1"),
+            )
+            .arg(
+                arg!(--"batch-size" <INT> "Number of queries aligned at once")
+                    .value_parser(RangedU64ValueParser::<usize>::new().range(1..))
+                    .default_value("4096"),
+            )
+            .arg(arg!(--mmap "Borrow the sequences and the index from the memory-mapped reference file (must not be modified while aligning)"))
+            .arg(
+                arg!(--checkpoint <FILE> "Save the progress periodically to resume the interrupted alignment (requires the output file)"),
+            )
+            .arg(
+                arg!(--"checkpoint-interval" <INT> "Number of queries written between the checkpoints")
+                    .value_parser(RangedU64ValueParser::<u64>::new().range(1..))
+                    .default_value("100000")
+                    .requires("checkpoint"),
+            )
+            .arg(
+                arg!(--resume "Resume from the checkpoint with the same settings (starts from the beginning if not saved yet)")
+                    .requires("checkpoint"),
+            )
+    }
+    pub fn run(matches: &ArgMatches) -> Result<()> {
+        let config = Config::from_matches(matches)?;
+        config.align_with_algorithm()
+    }
+}
+
+impl Config {
+    fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        let reference_path = matches.get_one::<String>("reference").cloned().expect("Required argument");
+        let query_path = matches.get_one::<String>("QUERY").cloned().expect("Default value");
+        if reference_path == STDIO && query_path == STDIO {
+            error_msg!("The reference and the queries cannot be both from the standard input")
+        }
+        let format = match matches.get_one::<String>("format").map(|x| x.as_str()) {
+            Some("paf") => OutputFormat::Paf,
+            Some("tsv") => OutputFormat::Tsv,
+            Some("json") => OutputFormat::Json,
+            _ => OutputFormat::Sam,
+        };
+        let strand = matches.get_one::<String>("strand").map(|x| match x.as_str() {
+            "forward" => Strand::Forward,
+            "reverse" => Strand::Reverse,
+            _ => Strand::Both,
+        });
+        let num_threads = match *matches.get_one::<usize>("threads").expect("Default value") {
+            0 => std::thread::available_parallelism().map_or(1, |x| x.get()),
+            v => v,
+        };
+        let output_path = matches.get_one::<String>("output").cloned().expect("Default value");
+        let checkpoint_path = matches.get_one::<String>("checkpoint").cloned();
+        if checkpoint_path.is_some() && output_path == STDIO {
+            error_msg!("The checkpoint requires the output file (use '--output')")
+        }
+        let chunk = matches.get_many::<u32>("chunk").map(|mut values| {
+            let size = *values.next().expect("Two values");
+            let step = *values.next().expect("Two values");
+            (size, step)
+        });
+
+        Ok(Self {
+            reference_path,
+            query_path,
+            output_path,
+            overwrite: matches.get_flag("overwrite"),
+            mmap: matches.get_flag("mmap"),
+            format,
+            strand,
+            num_threads,
+            batch_size: *matches.get_one::<usize>("batch-size").expect("Default value"),
+            checkpoint_path,
+            checkpoint_interval: *matches.get_one::<u64>("checkpoint-interval").expect("Default value"),
+            resume: matches.get_flag("resume"),
+            mismatch_penalty: *matches.get_one::<u32>("mismatch").expect("Default value"),
+            gap_open_penalty: *matches.get_one::<u32>("gap-open").expect("Default value"),
+            gap_extend_penalty: *matches.get_one::<u32>("gap-extend").expect("Default value"),
+            minimum_length: *matches.get_one::<u32>("min-length").expect("Required argument"),
+            maximum_penalty_per_length: *matches.get_one::<f32>("max-penalty").expect("Required argument"),
+            semi_global: matches.get_flag("semi-global"),
+            limit: matches.get_one::<u32>("limit").copied(),
+            chunk,
+        })
+    }
+    fn align_with_algorithm(&self) -> Result<()> {
+        let (px, po, pe) = (self.mismatch_penalty, self.gap_open_penalty, self.gap_extend_penalty);
+        let (minl, maxp) = (self.minimum_length, self.maximum_penalty_per_length);
+        match (self.semi_global, self.limit, self.chunk) {
+            (false, None, None) => self.align(Local::new(px, po, pe, minl, maxp)?),
+            (false, Some(limit), _) => self.align(LocalWithLimit::new(px, po, pe, minl, maxp, limit)?),
+            (false, None, Some((size, step))) => {
+                self.align(LocalWithChunk::new(px, po, pe, minl, maxp, size, step)?)
+            },
+            (true, None, None) => self.align(SemiGlobal::new(px, po, pe, minl, maxp)?),
+            (true, Some(limit), _) => self.align(SemiGlobalWithLimit::new(px, po, pe, minl, maxp, limit)?),
+            (true, None, Some((size, step))) => {
+                self.align(SemiGlobalWithChunk::new(px, po, pe, minl, maxp, size, step)?)
+            },
+        }
+    }
+    fn align<A: Algorithm>(&self, algorithm: A) -> Result<()> {
+        let reference = load_reference(&self.reference_path, self.mmap)?;
+        let strand = match self.strand {
+            Some(strand) => strand,
+            None if reference.has_complementary_strand() => Strand::Both,
+            None => Strand::Forward,
+        };
+        if strand != Strand::Forward && !reference.has_complementary_strand() {
+            error_msg!("The reference has no complementary strand (use '--strand forward')")
+        }
+        let mut query_reader = open_sequence_input(&self.query_path)?;
+
+        let aligner = Aligner::new(algorithm);
+        let mapping_quality_estimator = aligner.mapping_quality_estimator();
+        let mut aligners = vec![aligner; self.num_threads];
+
+        let Some(checkpoint_path) = &self.checkpoint_path else {
+            let mut result_writer = ResultWriter::new(
+                open_output(&self.output_path, self.overwrite)?,
+                self.format,
+                mapping_quality_estimator,
+            );
+            result_writer.write_header(&reference)?;
+            self.align_queries(
+                &mut aligners, &mut query_reader, &reference, strand, &mut result_writer, 0,
+                |_, _| Ok(()),
+            )?;
+            result_writer.finish()?;
+            return Ok(());
+        };
+
+        let mut checkpointer = Checkpointer::new(
+            checkpoint_path,
+            self.settings(strand),
+            self.checkpoint_interval,
+        );
+        let checkpoint = if self.resume { checkpointer.load()? } else { None };
+        let (output_file, num_written_queries) = match &checkpoint {
+            Some(checkpoint) => (reopen_output(&self.output_path, checkpoint)?, checkpoint.num_queries),
+            // With `--resume`, the output interrupted before the first checkpoint is started over.
+            None => (create_output_file(&self.output_path, self.overwrite || self.resume)?, 0),
+        };
+        let mut result_writer = ResultWriter::new(
+            BufWriter::new(output_file),
+            self.format,
+            mapping_quality_estimator,
+        );
+        if checkpoint.is_none() {
+            result_writer.write_header(&reference)?;
+        }
+        skip_records(&mut query_reader, num_written_queries)?;
+        let num_written_queries = self.align_queries(
+            &mut aligners, &mut query_reader, &reference, strand, &mut result_writer, num_written_queries,
+            |writer, num_written_queries| checkpointer.save_if_due(writer, num_written_queries),
+        )?;
+        let mut writer = result_writer.finish()?;
+        checkpointer.save(&mut writer, num_written_queries)?;
+        Ok(())
+    }
+    /// Align the remaining queries in batches.
+    ///  - `on_batch` is called with the total number of written queries after each batch.
+    ///  - Returns the total number of written queries.
+    #[allow(clippy::too_many_arguments)]
+    fn align_queries<A: Algorithm, W: Write>(
+        &self,
+        aligners: &mut [Aligner<A>],
+        query_reader: &mut SequenceFileReader,
+        reference: &Reference,
+        strand: Strand,
+        result_writer: &mut ResultWriter<W>,
+        mut num_written_queries: u64,
+        mut on_batch: impl FnMut(&mut W, u64) -> Result<()>,
+    ) -> Result<u64> {
+        let mut records: Vec<QueryRecord> = Vec::new();
+        loop {
+            let num_records = read_records(query_reader, &mut records, self.batch_size)?;
+            if num_records == 0 {
+                break;
+            }
+            let records = &records[..num_records];
+            let strand_alignments = align_records_in_parallel(aligners, records, reference, strand)?;
+            for (record, strand_alignments) in records.iter().zip(strand_alignments) {
+                result_writer.write_record(record, &strand_alignments, reference)?;
+            }
+            num_written_queries += num_records as u64;
+            on_batch(result_writer.get_mut(), num_written_queries)?;
+        }
+        Ok(num_written_queries)
+    }
+    /// Settings changing the output, to be matched on resume.
+    fn settings(&self, strand: Strand) -> String {
+        format!(
+            "reference={} query={} format={:?} strand={:?} penalties={},{},{} \
+            min_length={} max_penalty={} semi_global={} limit={:?} chunk={:?}",
+            self.reference_path, self.query_path, self.format, strand,
+            self.mismatch_penalty, self.gap_open_penalty, self.gap_extend_penalty,
+            self.minimum_length, self.maximum_penalty_per_length, self.semi_global, self.limit, self.chunk,
+        )
+    }
+}
+
+/// Fill the `records` with the next records, reusing their buffers.
+///  - Returns the number of records read, 0 at the end of the input.
+fn read_records(
+    query_reader: &mut SequenceFileReader,
+    records: &mut Vec<QueryRecord>,
+    batch_size: usize,
+) -> Result<usize> {
+    let mut num_records = 0;
+    while num_records < batch_size {
+        if records.len() == num_records {
+            records.push(QueryRecord::default());
+        }
+        let record = &mut records[num_records];
+        match query_reader.read_record(&mut record.sequence, &mut record.id) {
+            Some(result) => result?,
+            None => break,
+        }
+        num_records += 1;
+    }
+    Ok(num_records)
+}
+
+/// Skip the records already written before the checkpoint.
+fn skip_records(query_reader: &mut SequenceFileReader, num_records: u64) -> Result<()> {
+    let mut record = QueryRecord::default();
+    for _ in 0..num_records {
+        match query_reader.read_record(&mut record.sequence, &mut record.id) {
+            Some(result) => result?,
+            None => error_msg!("The queries end before the checkpoint ({} queries)", num_records),
+        }
+    }
+    Ok(())
+}
+
+/// Align the records by the `aligners`, preserving the order of the records.
+fn align_records_in_parallel<A: Algorithm>(
+    aligners: &mut [Aligner<A>],
+    records: &[QueryRecord],
+    reference: &Reference,
+    strand: Strand,
+) -> Result<Vec<Vec<StrandAlignment>>> {
+    let chunk_size = records.len().div_ceil(aligners.len()).max(1);
+    if aligners.len() == 1 || records.len() == 1 {
+        return Ok(align_records(&mut aligners[0], records, reference, strand)?);
+    }
+    let chunk_results = std::thread::scope(|scope| {
+        let handles: Vec<_> = records.chunks(chunk_size).zip(aligners.iter_mut()).map(|(chunk, aligner)| {
+            scope.spawn(move || align_records(aligner, chunk, reference, strand))
+        }).collect();
+        handles.into_iter()
+            .map(|handle| handle.join().expect("Alignment thread panicked"))
+            .collect::<Vec<_>>()
+    });
+    let mut strand_alignments = Vec::with_capacity(records.len());
+    for chunk_result in chunk_results {
+        strand_alignments.extend(chunk_result?);
+    }
+    Ok(strand_alignments)
+}
+
+/// The alignments are sorted by the penalty to make the output deterministic.
+fn align_records<A: Algorithm>(
+    aligner: &mut Aligner<A>,
+    records: &[QueryRecord],
+    reference: &Reference,
+    strand: Strand,
+) -> Result<Vec<Vec<StrandAlignment>>, StrandError> {
+    records.iter().map(|record| {
+        let mut strand_alignments = Vec::with_capacity(2);
+        if strand != Strand::Reverse {
+            strand_alignments.push(StrandAlignment {
+                is_forward: true,
+                query_alignment: aligner.align(&record.sequence, reference).sorted(AlignmentOrder::Penalty),
+            });
+        }
+        if strand != Strand::Forward {
+            let reversed = reference.reverse_complement(&record.sequence)?;
+            strand_alignments.push(StrandAlignment {
+                is_forward: false,
+                query_alignment: aligner.align(&reversed, reference).sorted(AlignmentOrder::Penalty),
+            });
+        }
+        Ok(strand_alignments)
+    }).collect()
+}
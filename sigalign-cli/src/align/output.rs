@@ -0,0 +1,247 @@
+//! Writers of the alignment results.
+//!  - SAM: minimal records without the sequence and quality, with the estimated MAPQ.
+//!    Only the best record of a query is primary, and the others are secondary (0x100).
+//!  - PAF: query positions on the forward strand of the query, with the extended CIGAR (`cg:Z`).
+//!  - TSV: one alignment per line with the header; query positions on the aligned strand.
+//!  - JSON: one JSON object per strand of the query with any alignment (JSON Lines).
+//!    The keys are fixed regardless of the `short_key` feature of `sigalign`.
+//!  - MAPQ of SAM and PAF is estimated over the alignments of both strands of a query together.
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+use sigalign::{
+    Reference,
+    results::{Alignment, AlignmentOperation, AlignmentStats, MappingQualityEstimator, QueryAlignment},
+    utils::formatter::SamFormatter,
+};
+
+use super::{QueryRecord, StrandAlignment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Sam,
+    Paf,
+    Tsv,
+    Json,
+}
+
+const TSV_HEADER: &str = "read\tstrand\ttarget_index\ttarget_label\tpenalty\tlength\t\
+query_start\tquery_end\ttarget_start\ttarget_end\tcigar\tidentity\n";
+// MAPQ for the unavailable estimation
+const MISSING_MAPQ: u8 = 255;
+
+pub struct ResultWriter<W: Write> {
+    writer: W,
+    format: OutputFormat,
+    mapping_quality_estimator: MappingQualityEstimator,
+    sam_formatter: SamFormatter,
+    itoa_buffer: itoa::Buffer,
+}
+
+impl<W: Write> ResultWriter<W> {
+    pub fn new(
+        writer: W,
+        format: OutputFormat,
+        mapping_quality_estimator: MappingQualityEstimator,
+    ) -> Self {
+        Self {
+            writer,
+            format,
+            sam_formatter: SamFormatter::new()
+                .with_mapping_quality_estimator(mapping_quality_estimator.clone()),
+            mapping_quality_estimator,
+            itoa_buffer: itoa::Buffer::new(),
+        }
+    }
+    pub fn write_header(&mut self, reference: &Reference) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Sam => {
+                self.sam_formatter.write_hd_header(&mut self.writer)?;
+                for target_index in 0..reference.get_num_targets() {
+                    self.sam_formatter.write_sq_header(
+                        &mut self.writer,
+                        reference.get_label_str(target_index).unwrap_or_default(),
+                        &reference.get_sequence_length(target_index).unwrap_or_default(),
+                    )?;
+                }
+                writeln!(
+                    self.writer,
+                    "@PG\tID:sigalign\tPN:sigalign\tVN:{}",
+                    env!("CARGO_PKG_VERSION"),
+                )
+            },
+            OutputFormat::Tsv => self.writer.write_all(TSV_HEADER.as_bytes()),
+            OutputFormat::Paf | OutputFormat::Json => Ok(()),
+        }
+    }
+    pub fn write_record(
+        &mut self,
+        record: &QueryRecord,
+        strand_alignments: &[StrandAlignment],
+        reference: &Reference,
+    ) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Sam => {
+                let strand = |is_forward: bool| strand_alignments.iter()
+                    .find(|x| x.is_forward == is_forward)
+                    .map(|x| &x.query_alignment);
+                self.sam_formatter.write_read_alignment(
+                    &mut self.writer,
+                    strand(true),
+                    strand(false),
+                    &record.id,
+                    record.sequence.len() as u32,
+                    reference,
+                )
+            },
+            OutputFormat::Paf => self.write_paf(record, strand_alignments, reference),
+            OutputFormat::Tsv | OutputFormat::Json => {
+                for strand_alignment in strand_alignments {
+                    if strand_alignment.query_alignment.0.is_empty() {
+                        continue;
+                    }
+                    if self.format == OutputFormat::Tsv {
+                        self.write_tsv(record, strand_alignment, reference)?;
+                    } else {
+                        self.write_json(record, strand_alignment, reference)?;
+                    }
+                }
+                Ok(())
+            },
+        }
+    }
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+    /// Flush and return the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_paf(
+        &mut self,
+        record: &QueryRecord,
+        strand_alignments: &[StrandAlignment],
+        reference: &Reference,
+    ) -> io::Result<()> {
+        let query_length = record.sequence.len() as u32;
+        let records: Vec<(bool, u32, &Alignment)> = strand_alignments.iter().flat_map(|strand_alignment| {
+            iter_alignments(&strand_alignment.query_alignment)
+                .map(move |(target_index, alignment)| (strand_alignment.is_forward, target_index, alignment))
+        }).collect();
+        // Estimated on the query positions of the forward strand, as the columns below
+        let mut mapping_qualities = self.mapping_quality_estimator.estimate_on_both_strands(
+            records.iter().map(|(is_forward, _, alignment)| (*is_forward, *alignment)),
+            query_length,
+        ).into_iter();
+        for (is_forward, target_index, alignment) in records {
+            let stats = AlignmentStats::from_operations(&alignment.operations);
+            let (query_start, query_end) = if is_forward {
+                alignment.position.query
+            } else {
+                (query_length - alignment.position.query.1, query_length - alignment.position.query.0)
+            };
+            // (1-4) Query name, length, start, end
+            self.write_field(record.id.as_bytes())?;
+            self.write_integer(query_length)?;
+            self.write_integer(query_start)?;
+            self.write_integer(query_end)?;
+            // (5) Strand
+            self.write_field(if is_forward { b"+" } else { b"-" })?;
+            // (6-9) Target name, length, start, end
+            self.write_field(reference.get_label_str(target_index).unwrap_or_default().as_bytes())?;
+            self.write_integer(reference.get_sequence_length(target_index).unwrap_or_default())?;
+            self.write_integer(alignment.position.target.0)?;
+            self.write_integer(alignment.position.target.1)?;
+            // (10-12) Matches, alignment block length, MAPQ
+            self.write_integer(stats.matches)?;
+            self.write_integer(stats.alignment_length())?;
+            self.write_integer(mapping_qualities.next().unwrap_or(MISSING_MAPQ))?;
+            // Tags: edit distance and CIGAR
+            self.writer.write_all(b"NM:i:")?;
+            self.write_integer(stats.mismatches + stats.insertions + stats.deletions)?;
+            self.writer.write_all(b"cg:Z:")?;
+            self.writer.write_all(alignment.to_cigar().as_bytes())?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+    fn write_tsv(
+        &mut self,
+        record: &QueryRecord,
+        strand_alignment: &StrandAlignment,
+        reference: &Reference,
+    ) -> io::Result<()> {
+        for (target_index, alignment) in iter_alignments(&strand_alignment.query_alignment) {
+            self.write_field(record.id.as_bytes())?;
+            self.write_field(if strand_alignment.is_forward { b"+" } else { b"-" })?;
+            self.write_integer(target_index)?;
+            self.write_field(reference.get_label_str(target_index).unwrap_or_default().as_bytes())?;
+            self.write_integer(alignment.penalty)?;
+            self.write_integer(alignment.length)?;
+            self.write_integer(alignment.position.query.0)?;
+            self.write_integer(alignment.position.query.1)?;
+            self.write_integer(alignment.position.target.0)?;
+            self.write_integer(alignment.position.target.1)?;
+            self.write_field(alignment.to_cigar().as_bytes())?;
+            writeln!(self.writer, "{:.4}", alignment.identity())?;
+        }
+        Ok(())
+    }
+    fn write_json(
+        &mut self,
+        record: &QueryRecord,
+        strand_alignment: &StrandAlignment,
+        reference: &Reference,
+    ) -> io::Result<()> {
+        let result: Vec<Value> = strand_alignment.query_alignment.0.iter().map(|target_alignment| json!({
+            "index": target_alignment.index,
+            "label": reference.get_label_str(target_alignment.index).unwrap_or_default(),
+            "alignments": target_alignment.alignments.iter().map(alignment_to_json).collect::<Vec<_>>(),
+        })).collect();
+        let value = json!({
+            "read": record.id,
+            "is_forward": strand_alignment.is_forward,
+            "result": result,
+        });
+        serde_json::to_writer(&mut self.writer, &value)?;
+        self.writer.write_all(b"\n")
+    }
+
+    // Field followed by a tab
+    fn write_field(&mut self, field: &[u8]) -> io::Result<()> {
+        self.writer.write_all(field)?;
+        self.writer.write_all(b"\t")
+    }
+    fn write_integer<I: itoa::Integer>(&mut self, value: I) -> io::Result<()> {
+        self.writer.write_all(self.itoa_buffer.format(value).as_bytes())?;
+        self.writer.write_all(b"\t")
+    }
+}
+
+fn iter_alignments(query_alignment: &QueryAlignment) -> impl Iterator<Item = (u32, &Alignment)> {
+    query_alignment.0.iter().flat_map(|target_alignment| {
+        target_alignment.alignments.iter().map(move |alignment| (target_alignment.index, alignment))
+    })
+}
+
+fn alignment_to_json(alignment: &Alignment) -> Value {
+    json!({
+        "penalty": alignment.penalty,
+        "length": alignment.length,
+        "position": {
+            "query": alignment.position.query,
+            "target": alignment.position.target,
+        },
+        "operations": alignment.operations.iter().map(|operations| json!({
+            "operation": match operations.operation {
+                AlignmentOperation::Match => "Match",
+                AlignmentOperation::Subst => "Subst",
+                AlignmentOperation::Insertion => "Insertion",
+                AlignmentOperation::Deletion => "Deletion",
+            },
+            "count": operations.count,
+        })).collect::<Vec<_>>(),
+    })
+}
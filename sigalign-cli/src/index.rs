@@ -0,0 +1,108 @@
+use std::io::Write;
+
+use clap::{arg, ArgMatches, Command};
+use sigalign::{Alphabet, ReferenceBuilder};
+
+use crate::io::{open_input, open_output, STDIO};
+use crate::{error_msg, Result};
+
+pub struct IndexApp;
+
+#[derive(Debug)]
+struct Config {
+    input_paths: Vec<String>,
+    output_path: String,
+    overwrite: bool,
+    metadata_path: Option<String>,
+    keep_case: bool,
+    ignored_bases: Option<String>,
+    alphabet: Option<Alphabet>,
+    strict_alphabet: bool,
+    soft_masking: bool,
+    keep_metadata: bool,
+}
+
+impl IndexApp {
+    pub fn get_command() -> Command {
+        Command::new("index")
+            .about("Build the reference file from FASTA")
+            .arg_required_else_help(true)
+            .arg(
+                arg!([INPUT] ... "FASTA file(s); can be compressed, '-' for the standard input")
+                    .default_value(STDIO),
+            )
+            .arg(
+                arg!(-o --output <FILE> "Reference file to write ('-' for the standard output)")
+                    .required(true),
+            )
+            .arg(arg!(-w --overwrite "Overwrite the existing output file"))
+            .arg(arg!(-m --metadata <TSV> "Tab-separated table of the target attributes (header: label, keys...)"))
+            .arg(arg!(--"keep-case" "Keep the lowercase letters, instead of converting them to uppercase"))
+            .arg(arg!(-n --"ignore-bases" <BASES> "Bases never matched to any other bases (e.g., N)"))
+            .arg(
+                arg!(-a --alphabet <NAME> "Alphabet to normalize the residues to (default: used as they are)")
+                    .value_parser(["dna", "dna_iupac", "rna", "protein"]),
+            )
+            .arg(arg!(--"strict-alphabet" "Fail if any residue is not in the alphabet, instead of masking it"))
+            .arg(arg!(--"soft-masking" "Exclude the lowercase regions from the seeding"))
+            .arg(arg!(--"keep-metadata" "Keep the FASTA descriptions as the metadata of targets"))
+    }
+    pub fn run(matches: &ArgMatches) -> Result<()> {
+        let config = Config::from_matches(matches)?;
+        config.build_and_save_reference()
+    }
+}
+
+impl Config {
+    fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        let input_paths: Vec<String> = matches
+            .get_many::<String>("INPUT")
+            .map(|x| x.cloned().collect())
+            .unwrap_or_default();
+        let output_path = matches.get_one::<String>("output").cloned().expect("Required argument");
+        if input_paths.iter().filter(|x| *x == STDIO).count() > 1 {
+            error_msg!("The standard input can be used only once")
+        }
+        let alphabet = matches.get_one::<String>("alphabet").map(|name| {
+            Alphabet::from_name(name).expect("Checked by the parser")
+        });
+
+        Ok(Self {
+            input_paths,
+            output_path,
+            overwrite: matches.get_flag("overwrite"),
+            metadata_path: matches.get_one::<String>("metadata").cloned(),
+            keep_case: matches.get_flag("keep-case"),
+            ignored_bases: matches.get_one::<String>("ignore-bases").cloned(),
+            alphabet,
+            strict_alphabet: matches.get_flag("strict-alphabet"),
+            soft_masking: matches.get_flag("soft-masking"),
+            keep_metadata: matches.get_flag("keep-metadata"),
+        })
+    }
+    fn build_and_save_reference(&self) -> Result<()> {
+        let mut builder = ReferenceBuilder::new()
+            .set_uppercase(!self.keep_case)
+            .set_strict_alphabet(self.strict_alphabet)
+            .set_soft_masking(self.soft_masking)
+            .set_keep_metadata(self.keep_metadata);
+        if let Some(ignored_bases) = &self.ignored_bases {
+            builder = builder.ignore_bases(ignored_bases.as_bytes());
+        }
+        if let Some(alphabet) = self.alphabet {
+            builder = builder.set_alphabet(alphabet);
+        }
+        for input_path in &self.input_paths {
+            builder = builder.add_fasta(open_input(input_path)?)?;
+        }
+        if let Some(metadata_path) = &self.metadata_path {
+            builder = builder.add_metadata_tsv_file(metadata_path)?;
+        }
+        let reference = builder.build()?;
+
+        let mut writer = open_output(&self.output_path, self.overwrite)?;
+        reference.save_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+//! Inputs and outputs from the files or the standard streams.
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+
+use anyhow::{Context, Result};
+use sigalign::Reference;
+use sigalign_utils::sequence_reader::{
+    open_decompressed_file, open_decompressed_reader, open_sequence_file, SequenceFileReader,
+};
+
+use crate::error_msg;
+
+/// Path for the standard input or output.
+pub const STDIO: &str = "-";
+
+/// Buffered writer to the file, or to the standard output for `-`.
+///  - The existing file is not overwritten unless `overwrite`.
+pub fn open_output(path: &str, overwrite: bool) -> Result<Box<dyn Write>> {
+    if path == STDIO {
+        return Ok(Box::new(BufWriter::new(io::stdout().lock())));
+    }
+    Ok(Box::new(BufWriter::new(create_output_file(path, overwrite)?)))
+}
+
+/// Create the output file.
+///  - The existing file is not overwritten unless `overwrite`.
+pub fn create_output_file(path: &str, overwrite: bool) -> Result<File> {
+    let file = if overwrite {
+        File::create(path)
+    } else {
+        OpenOptions::new().write(true).create_new(true).open(path)
+    };
+    match file {
+        Ok(file) => Ok(file),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            error_msg!("Output file ({}) already exists (use --overwrite)", path)
+        },
+        Err(e) => Err(e).with_context(|| format!("Failed to create the output file ({})", path)),
+    }
+}
+
+/// Reader of the file, or of the standard input for `-`.
+///  - The compression is detected from the contents and decoded.
+pub fn open_input(path: &str) -> Result<Box<dyn Read + Send>> {
+    let reader = if path == STDIO {
+        open_decompressed_reader(io::stdin())
+    } else {
+        open_decompressed_file(path)
+    };
+    reader.with_context(|| format!("Failed to open the input ({})", path))
+}
+
+/// FASTA or FASTQ reader of the file, or of the standard input for `-`.
+///  - The compression and the format are detected from the contents.
+pub fn open_sequence_input(path: &str) -> Result<SequenceFileReader> {
+    let reader = if path == STDIO {
+        SequenceFileReader::new(io::stdin())
+    } else {
+        open_sequence_file(path)
+    };
+    reader.with_context(|| format!("Failed to open the sequence input ({})", path))
+}
+
+/// Load the reference from the file, or from the standard input for `-`.
+///  - With `mmap`, the sequences and the index are borrowed from the memory-mapped file.
+pub fn load_reference(path: &str, mmap: bool) -> Result<Reference> {
+    let reference = if path == STDIO {
+        if mmap {
+            error_msg!("The reference from the standard input cannot be memory-mapped")
+        }
+        Reference::load_from(BufReader::new(io::stdin().lock()))
+    } else if mmap {
+        // Safety: the reference file is not modified by this process,
+        //  and the users are informed not to modify it in the help.
+        unsafe { Reference::load_from_mapped_file(path) }
+    } else {
+        let file = File::open(path).with_context(|| format!("Failed to open the reference ({})", path))?;
+        Reference::load_from(BufReader::new(file))
+    };
+    reference.with_context(|| format!("Failed to load the reference ({})", path))
+}
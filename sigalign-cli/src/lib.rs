@@ -0,0 +1,62 @@
+/*!
+Command-line tool of SigAlign.
+
+- `index`: Build the reference file from FASTA.
+- `align`: Align FASTA or FASTQ queries to the reference.
+- `view`: Print the targets of the reference.
+- `stats`: Print the summary of the reference.
+
+The inputs and outputs can be the standard streams (`-`), so that the commands fit in the pipelines.
+*/
+use std::ffi::OsString;
+
+use anyhow::{bail as error_msg, Result};
+use clap::Command;
+
+mod io;
+mod index;
+use index::IndexApp;
+mod align;
+use align::AlignApp;
+mod view;
+use view::ViewApp;
+mod stats;
+use stats::StatsApp;
+
+pub struct Application;
+
+impl Application {
+    pub fn get_command() -> Command {
+        Command::new("sigalign")
+            .bin_name("sigalign")
+            .version(env!("CARGO_PKG_VERSION"))
+            .author("baku4 <bahkhun@gmail.com>")
+            .about("Similarity-guided alignment with explicit cutoffs")
+            .arg_required_else_help(true)
+            .propagate_version(true)
+            .subcommand_required(true)
+            .subcommand(IndexApp::get_command().display_order(1))
+            .subcommand(AlignApp::get_command().display_order(2))
+            .subcommand(ViewApp::get_command().display_order(3))
+            .subcommand(StatsApp::get_command().display_order(4))
+    }
+    /// Run with the arguments of the process.
+    pub fn run() -> Result<()> {
+        Self::run_from(std::env::args_os())
+    }
+    /// Run with the `args`, including the binary name.
+    pub fn run_from<I, T>(args: I) -> Result<()> where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::get_command().get_matches_from(args);
+
+        match matches.subcommand() {
+            Some(("index", sub_matches)) => IndexApp::run(sub_matches),
+            Some(("align", sub_matches)) => AlignApp::run(sub_matches),
+            Some(("view", sub_matches)) => ViewApp::run(sub_matches),
+            Some(("stats", sub_matches)) => StatsApp::run(sub_matches),
+            _ => unreachable!(),
+        }
+    }
+}
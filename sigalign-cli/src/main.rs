@@ -0,0 +1,16 @@
+use std::io::ErrorKind;
+
+use sigalign_cli::Application;
+
+fn main() {
+    Application::run().unwrap_or_else(|e| {
+        // The reader of the output is closed (e.g., `| head`)
+        if let Some(io_error) = e.downcast_ref::<std::io::Error>() {
+            if io_error.kind() == ErrorKind::BrokenPipe {
+                std::process::exit(0);
+            }
+        }
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    });
+}
@@ -0,0 +1,113 @@
+use std::io::Write;
+
+use clap::{arg, ArgMatches, Command};
+use serde_json::Value;
+use sigalign::Reference;
+
+use crate::io::{load_reference, open_output, STDIO};
+use crate::Result;
+
+pub struct StatsApp;
+
+#[derive(Debug)]
+struct Config {
+    reference_path: String,
+    output_path: String,
+    overwrite: bool,
+    as_json: bool,
+}
+
+impl StatsApp {
+    pub fn get_command() -> Command {
+        Command::new("stats")
+            .about("Print the summary of the reference and the length distribution of the targets")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(<REFERENCE> "Reference file built by 'index' ('-' for the standard input)"),
+            )
+            .arg(arg!(--json "Print as a JSON object, instead of the key-value lines"))
+            .arg(arg!(-o --output <FILE> "Output file ('-' for the standard output)").default_value(STDIO))
+            .arg(arg!(-w --overwrite "Overwrite the existing output file"))
+    }
+    pub fn run(matches: &ArgMatches) -> Result<()> {
+        let config = Config {
+            reference_path: matches.get_one::<String>("REFERENCE").cloned().expect("Required argument"),
+            output_path: matches.get_one::<String>("output").cloned().expect("Default value"),
+            overwrite: matches.get_flag("overwrite"),
+            as_json: matches.get_flag("json"),
+        };
+        config.print_stats()
+    }
+}
+
+impl Config {
+    fn print_stats(&self) -> Result<()> {
+        let reference = load_reference(&self.reference_path, false)?;
+        let stats = collect_stats(&reference);
+        let mut writer = open_output(&self.output_path, self.overwrite)?;
+
+        if self.as_json {
+            // Written manually to keep the order of the keys
+            writer.write_all(b"{\n")?;
+            for (index, (key, value)) in stats.iter().enumerate() {
+                let separator = if index + 1 == stats.len() { "" } else { "," };
+                writeln!(writer, "  \"{}\": {}{}", key, value, separator)?;
+            }
+            writer.write_all(b"}\n")?;
+        } else {
+            for (key, value) in stats {
+                match value {
+                    Value::String(value) => writeln!(writer, "{}\t{}", key, value)?,
+                    Value::Null => writeln!(writer, "{}\t-", key)?,
+                    value => writeln!(writer, "{}\t{}", key, value)?,
+                }
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Ordered (key, value) of the statistics.
+fn collect_stats(reference: &Reference) -> Vec<(&'static str, Value)> {
+    let summary = reference.get_summary();
+    let mut lengths: Vec<u32> = (0..summary.num_targets)
+        .map(|target_index| reference.get_sequence_length(target_index).unwrap_or_default())
+        .collect();
+    lengths.sort_unstable();
+
+    let mut stats = Vec::new();
+    let mut insert = |key: &'static str, value: Value| stats.push((key, value));
+    insert("num_targets", summary.num_targets.into());
+    insert("total_length", summary.total_length.into());
+    insert("min_length", lengths.first().copied().into());
+    insert("max_length", lengths.last().copied().into());
+    insert("mean_length", if lengths.is_empty() {
+        Value::Null
+    } else {
+        (summary.total_length as f64 / lengths.len() as f64).into()
+    });
+    insert("n50", n50(&lengths).into());
+    insert("alphabet", summary.alphabet.map(|x| x.as_str()).into());
+    insert("pattern_index_type", summary.pattern_index_type.into());
+    insert("bits_per_character", summary.bits_per_character.into());
+    insert("num_soft_masked_bases", summary.num_soft_masked_bases.into());
+    insert("has_target_metadata", summary.has_target_metadata.into());
+    if let Some(build_options) = &summary.build_options {
+        insert("uppercase", build_options.uppercase.into());
+        insert("strict_alphabet", build_options.strict_alphabet.into());
+        insert("ignored_bases", String::from_utf8_lossy(&build_options.ignored_bases).into_owned().into());
+    }
+    insert("estimated_size_in_bytes", summary.estimated_size_in_bytes.into());
+    stats
+}
+
+/// Length of the target at which the half of the total length is covered by the longer targets.
+fn n50(sorted_lengths: &[u32]) -> Option<u32> {
+    let total_length: u64 = sorted_lengths.iter().map(|&x| x as u64).sum();
+    let mut covered_length = 0;
+    sorted_lengths.iter().rev().find(|&&length| {
+        covered_length += length as u64;
+        covered_length * 2 >= total_length
+    }).copied()
+}
@@ -0,0 +1,126 @@
+use std::io::Write;
+
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
+use sigalign::Reference;
+
+use crate::io::{load_reference, open_output, STDIO};
+use crate::Result;
+
+pub struct ViewApp;
+
+#[derive(Debug)]
+struct Config {
+    reference_path: String,
+    output_path: String,
+    overwrite: bool,
+    mmap: bool,
+    // None for all targets
+    labels: Option<Vec<String>>,
+    as_fasta: bool,
+    line_width: usize,
+}
+
+impl ViewApp {
+    pub fn get_command() -> Command {
+        Command::new("view")
+            .about("Print the targets of the reference as a table (index, label, length, description) or FASTA")
+            .arg_required_else_help(true)
+            .arg(
+                arg!(<REFERENCE> "Reference file built by 'index' ('-' for the standard input)"),
+            )
+            .arg(arg!(--fasta "Print the sequences in FASTA"))
+            .arg(
+                arg!(-t --target <LABEL> "Print only the targets with the label (can be repeated)")
+                    .action(ArgAction::Append),
+            )
+            .arg(
+                arg!(--"line-width" <INT> "Number of residues per line of FASTA (0 for no wrapping)")
+                    .value_parser(value_parser!(usize))
+                    .default_value("80"),
+            )
+            .arg(arg!(-o --output <FILE> "Output file ('-' for the standard output)").default_value(STDIO))
+            .arg(arg!(-w --overwrite "Overwrite the existing output file"))
+            .arg(arg!(--mmap "Borrow the sequences and the index from the memory-mapped reference file"))
+    }
+    pub fn run(matches: &ArgMatches) -> Result<()> {
+        let config = Config::from_matches(matches);
+        config.view()
+    }
+}
+
+impl Config {
+    fn from_matches(matches: &ArgMatches) -> Self {
+        Self {
+            reference_path: matches.get_one::<String>("REFERENCE").cloned().expect("Required argument"),
+            output_path: matches.get_one::<String>("output").cloned().expect("Default value"),
+            overwrite: matches.get_flag("overwrite"),
+            mmap: matches.get_flag("mmap"),
+            labels: matches.get_many::<String>("target").map(|x| x.cloned().collect()),
+            as_fasta: matches.get_flag("fasta"),
+            line_width: *matches.get_one::<usize>("line-width").expect("Default value"),
+        }
+    }
+    fn view(&self) -> Result<()> {
+        let reference = load_reference(&self.reference_path, self.mmap)?;
+        let mut writer = open_output(&self.output_path, self.overwrite)?;
+
+        if !self.as_fasta {
+            writer.write_all(b"index\tlabel\tlength\tdescription\n")?;
+        }
+        let target_indices = (0..reference.get_num_targets()).filter(|&target_index| {
+            match &self.labels {
+                Some(labels) => {
+                    let label = reference.get_label_str(target_index).unwrap_or_default();
+                    labels.iter().any(|x| x == label)
+                },
+                None => true,
+            }
+        });
+        for target_index in target_indices {
+            if self.as_fasta {
+                write_fasta_record(&mut writer, &reference, target_index, self.line_width)?;
+            } else {
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}\t{}",
+                    target_index,
+                    reference.get_label_str(target_index).unwrap_or_default(),
+                    reference.get_sequence_length(target_index).unwrap_or_default(),
+                    get_description(&reference, target_index),
+                )?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn write_fasta_record(
+    writer: &mut impl Write,
+    reference: &Reference,
+    target_index: u32,
+    line_width: usize,
+) -> Result<()> {
+    writer.write_all(b">")?;
+    writer.write_all(reference.get_label_str(target_index).unwrap_or_default().as_bytes())?;
+    let description = get_description(reference, target_index);
+    if !description.is_empty() {
+        writer.write_all(b" ")?;
+        writer.write_all(description.as_bytes())?;
+    }
+    writer.write_all(b"\n")?;
+    let sequence = reference.get_sequence(target_index).unwrap_or_default();
+    let line_width = if line_width == 0 { sequence.len().max(1) } else { line_width };
+    for line in sequence.chunks(line_width) {
+        writer.write_all(line)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn get_description(reference: &Reference, target_index: u32) -> &str {
+    reference
+        .get_target_metadata(target_index)
+        .map(|metadata| metadata.description.as_str())
+        .unwrap_or_default()
+}
@@ -0,0 +1,299 @@
+//! Run the `sigalign` binary with the generated sequences.
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const BIN: &str = env!("CARGO_BIN_EXE_sigalign");
+const ALIGN_OPTIONS: [&str; 4] = ["-l", "50", "-p", "0.1"];
+
+fn run(args: &[&str], stdin: Option<&[u8]>) -> Output {
+    let mut child = Command::new(BIN)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut child_stdin = child.stdin.take().unwrap();
+    if let Some(stdin) = stdin {
+        // The binary can exit before reading all (e.g., invalid input)
+        match child_stdin.write_all(stdin) {
+            Err(err) if err.kind() == std::io::ErrorKind::BrokenPipe => {},
+            result => result.unwrap(),
+        }
+    }
+    drop(child_stdin);
+    child.wait_with_output().unwrap()
+}
+fn run_ok(args: &[&str], stdin: Option<&[u8]>) -> Vec<u8> {
+    let output = run(args, stdin);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    output.stdout
+}
+
+fn random_sequence(length: usize, seed: &mut u64) -> Vec<u8> {
+    (0..length).map(|_| {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        b"ACGT"[(*seed >> 33) as usize % 4]
+    }).collect()
+}
+fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|&x| match x {
+        b'A' => b'T', b'C' => b'G', b'G' => b'C', _ => b'A',
+    }).collect()
+}
+
+struct TestFiles {
+    dir: PathBuf,
+    reference_fasta: Vec<u8>,
+    query_fasta: Vec<u8>,
+}
+
+impl TestFiles {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("sigalign-cli-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut seed = 7;
+        let targets: Vec<Vec<u8>> = (0..3).map(|_| random_sequence(1000, &mut seed)).collect();
+        let mut reference_fasta = Vec::new();
+        for (index, target) in targets.iter().enumerate() {
+            writeln!(reference_fasta, ">target_{} description {}", index, index).unwrap();
+            reference_fasta.extend_from_slice(target);
+            reference_fasta.push(b'\n');
+        }
+        let mut query_fasta = Vec::new();
+        for index in 0..30 {
+            let target = &targets[index % 3];
+            let start = (index * 23) % 800;
+            let mut query = target[start..start + 150].to_vec();
+            query[75] = if query[75] == b'A' { b'C' } else { b'A' };
+            if index % 2 == 1 {
+                query = reverse_complement(&query);
+            }
+            writeln!(query_fasta, ">query_{}", index).unwrap();
+            query_fasta.extend_from_slice(&query);
+            query_fasta.push(b'\n');
+        }
+        std::fs::write(dir.join("reference.fa"), &reference_fasta).unwrap();
+        std::fs::write(dir.join("query.fa"), &query_fasta).unwrap();
+        Self { dir, reference_fasta, query_fasta }
+    }
+    fn path(&self, name: &str) -> String {
+        self.dir.join(name).to_str().unwrap().to_string()
+    }
+    fn index(&self) -> String {
+        let reference = self.path("reference.sa");
+        run_ok(&["index", &self.path("reference.fa"), "-o", &reference, "--keep-metadata", "-w"], None);
+        reference
+    }
+}
+impl Drop for TestFiles {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[test]
+fn index_from_stdin_is_same_as_from_file() {
+    let files = TestFiles::new("index");
+    let reference = files.index();
+    let from_stdin = run_ok(&["index", "-o", "-", "--keep-metadata"], Some(&files.reference_fasta));
+    assert_eq!(std::fs::read(&reference).unwrap(), from_stdin);
+
+    // Not overwritten without the flag
+    let output = run(&["index", &files.path("reference.fa"), "-o", &reference], None);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn view_and_stats_of_reference() {
+    let files = TestFiles::new("view");
+    let reference = files.index();
+
+    let table = String::from_utf8(run_ok(&["view", &reference], None)).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "index\tlabel\tlength\tdescription");
+    assert_eq!(lines[2], "1\ttarget_1\t1000\tdescription 1");
+    assert_eq!(lines.len(), 4);
+
+    let fasta = run_ok(&["view", &reference, "--fasta", "--line-width", "0"], None);
+    assert_eq!(fasta, files.reference_fasta);
+    let fasta = run_ok(&["view", &reference, "--fasta", "-t", "target_2"], None);
+    assert!(fasta.starts_with(b">target_2 description 2\n"));
+    assert_eq!(fasta.split(|&x| x == b'\n').filter(|x| !x.is_empty()).count(), 1 + 13);
+
+    let stats = String::from_utf8(run_ok(&["stats", &reference], None)).unwrap();
+    assert!(stats.contains("num_targets\t3\n"));
+    assert!(stats.contains("total_length\t3000\n"));
+    assert!(stats.contains("has_target_metadata\ttrue\n"));
+    let stats_json = run_ok(&["stats", &reference, "--json"], None);
+    let stats_json: serde_json::Value = serde_json::from_slice(&stats_json).unwrap();
+    assert_eq!(stats_json["n50"], 1000);
+}
+
+#[test]
+fn align_in_all_formats() {
+    let files = TestFiles::new("align");
+    let reference = files.index();
+    let query = files.path("query.fa");
+    let align = |extra: &[&str]| -> String {
+        let mut args = vec!["align", "-r", &reference, &query];
+        args.extend_from_slice(&ALIGN_OPTIONS);
+        args.extend_from_slice(extra);
+        String::from_utf8(run_ok(&args, None)).unwrap()
+    };
+
+    // TSV: every query is aligned once on its strand
+    let tsv = align(&["-f", "tsv"]);
+    let records: Vec<Vec<&str>> = tsv.lines().skip(1).map(|x| x.split('\t').collect()).collect();
+    assert_eq!(records.len(), 30);
+    for record in &records {
+        let index: usize = record[0].trim_start_matches("query_").parse().unwrap();
+        assert_eq!(record[1], if index % 2 == 0 { "+" } else { "-" });
+        assert_eq!(record[3], format!("target_{}", index % 3));
+        assert_eq!(record[4], "4");
+        assert_eq!(record[10], "75=1X74=");
+    }
+    // Same results with the threads and the standard input
+    let mut args = vec!["align", "-r", &reference, "-f", "tsv", "-t", "3", "--batch-size", "4"];
+    args.extend_from_slice(&ALIGN_OPTIONS);
+    assert_eq!(String::from_utf8(run_ok(&args, Some(&files.query_fasta))).unwrap(), tsv);
+
+    // SAM
+    let sam = align(&[]);
+    assert!(sam.starts_with("@HD\tVN:1.6"));
+    assert_eq!(sam.lines().filter(|x| x.starts_with("@SQ")).count(), 3);
+    let record = sam.lines().find(|x| x.starts_with("query_1\t")).unwrap();
+    assert_eq!(record.split('\t').nth(1), Some("16"));
+
+    // PAF: query positions are on the forward strand
+    let paf = align(&["-f", "paf"]);
+    assert_eq!(paf.lines().count(), 30);
+    let record: Vec<&str> = paf.lines().find(|x| x.starts_with("query_1\t")).unwrap().split('\t').collect();
+    assert_eq!(&record[1..5], &["150", "0", "150", "-"]);
+    assert_eq!(record[9], "149");
+    assert_eq!(record[13], "cg:Z:75=1X74=");
+
+    // JSON Lines
+    let json = align(&["-f", "json", "--strand", "forward"]);
+    assert_eq!(json.lines().count(), 15);
+    for line in json.lines() {
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(value["is_forward"], true);
+        assert_eq!(value["result"][0]["alignments"][0]["penalty"], 4);
+    }
+}
+
+#[test]
+fn both_strands_of_query_are_ranked_together() {
+    let files = TestFiles::new("strands");
+    // `target_1` is the reverse complement of `target_0` with one more substitution
+    let mut seed = 11;
+    let target_0 = random_sequence(1000, &mut seed);
+    let mut target_1 = reverse_complement(&target_0);
+    target_1[799] = if target_1[799] == b'A' { b'C' } else { b'A' };
+    let mut query = target_0[100..250].to_vec();
+    query[75] = if query[75] == b'A' { b'C' } else { b'A' };
+    let reference_fasta = [
+        b">target_0\n".as_slice(), &target_0, b"\n>target_1\n", &target_1, b"\n",
+    ].concat();
+    std::fs::write(files.path("reference.fa"), reference_fasta).unwrap();
+    let reference = files.index();
+    let query_fasta = [b">query\n".as_slice(), &query, b"\n"].concat();
+    let align = |format: &str| {
+        let mut args = vec!["align", "-r", &reference, "-f", format];
+        args.extend_from_slice(&ALIGN_OPTIONS);
+        String::from_utf8(run_ok(&args, Some(&query_fasta))).unwrap()
+    };
+
+    // (target, flag, MAPQ)
+    let sam = align("sam");
+    let mut sam_records: Vec<(&str, &str, &str)> = sam.lines().filter(|x| !x.starts_with('@')).map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        (fields[2], fields[1], fields[4])
+    }).collect();
+    sam_records.sort();
+    assert_eq!(sam_records.len(), 2);
+    // Only the best is primary
+    assert_eq!((sam_records[0].0, sam_records[0].1), ("target_0", "0"));
+    assert_eq!((sam_records[1].0, sam_records[1].1), ("target_1", "272"));
+
+    // MAPQ of PAF is the same as SAM
+    let paf = align("paf");
+    let mut paf_records: Vec<(&str, &str)> = paf.lines().map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        (fields[5], fields[11])
+    }).collect();
+    paf_records.sort();
+    let sam_mapqs: Vec<(&str, &str)> = sam_records.iter().map(|(target, _, mapq)| (*target, *mapq)).collect();
+    assert_eq!(paf_records, sam_mapqs);
+}
+
+#[test]
+fn align_with_invalid_inputs() {
+    let files = TestFiles::new("invalid");
+    let reference = files.index();
+
+    let mut args = vec!["align", "-r", "-", "-"];
+    args.extend_from_slice(&ALIGN_OPTIONS);
+    assert!(!run(&args, None).status.success());
+
+    let mut args = vec!["align", "-r", &reference];
+    args.extend_from_slice(&ALIGN_OPTIONS);
+    let output = run(&args, Some(b"not a sequence"));
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Not a FASTA or FASTQ"));
+
+    // Protein reference has no reverse strand
+    let protein = files.path("protein.sa");
+    run_ok(&["index", "-a", "protein", "-o", &protein], Some(b">p\nMKVLAAGIVGLLLAAQPAMAQVQLVESGGGLVQPGGSLRLSCAAS\n"));
+    let args = ["align", "-r", &protein, "-l", "20", "-p", "0.1", "-s", "both"];
+    assert!(!run(&args, Some(b">q\nMKVLAAGIVGLLLAAQPAMAQVQLV\n")).status.success());
+    let args = ["align", "-r", &protein, "-l", "20", "-p", "0.1", "-f", "tsv"];
+    let tsv = run_ok(&args, Some(b">q\nMKVLAAGIVGLLLAAQPAMAQVQLV\n"));
+    assert_eq!(tsv.split(|&x| x == b'\n').filter(|x| !x.is_empty()).count(), 2);
+}
+
+#[test]
+fn resume_from_checkpoint() {
+    let files = TestFiles::new("checkpoint");
+    let reference = files.index();
+    let query = files.path("query.fa");
+    let output = files.path("result.sam");
+    let checkpoint = files.path("result.checkpoint");
+    let align = |extra: &[&str]| {
+        let mut args = vec!["align", "-r", &reference, &query, "-o", &output, "--batch-size", "4"];
+        args.extend_from_slice(&ALIGN_OPTIONS);
+        args.extend_from_slice(extra);
+        run_ok(&args, None);
+    };
+    align(&["-w"]);
+    let expected = std::fs::read(&output).unwrap();
+
+    // Interrupted after the first 10 queries, with the partially written result
+    let first_queries: Vec<&[u8]> = files.query_fasta.split(|&x| x == b'\n').take(20).collect();
+    std::fs::write(&query, first_queries.join(&b'\n')).unwrap();
+    align(&["-w", "--checkpoint", &checkpoint, "--checkpoint-interval", "8"]);
+    let contents = std::fs::read_to_string(&checkpoint).unwrap();
+    assert!(contents.contains("num_queries\t10\n"));
+    std::fs::write(&query, &files.query_fasta).unwrap();
+    std::fs::OpenOptions::new().append(true).open(&output).unwrap().write_all(b"partial").unwrap();
+
+    align(&["--checkpoint", &checkpoint, "--resume"]);
+    assert_eq!(std::fs::read(&output).unwrap(), expected);
+    // Nothing left
+    align(&["--checkpoint", &checkpoint, "--resume"]);
+    assert_eq!(std::fs::read(&output).unwrap(), expected);
+
+    // Other settings
+    let mut args = vec!["align", "-r", &reference, &query, "-o", &output, "--checkpoint", &checkpoint, "--resume"];
+    args.extend_from_slice(&["-l", "60", "-p", "0.1"]);
+    assert!(!run(&args, None).status.success());
+
+    // Interrupted before the first checkpoint: started over without `--overwrite`
+    std::fs::remove_file(&checkpoint).unwrap();
+    std::fs::write(&output, b"partial").unwrap();
+    align(&["--checkpoint", &checkpoint, "--resume"]);
+    assert_eq!(std::fs::read(&output).unwrap(), expected);
+}
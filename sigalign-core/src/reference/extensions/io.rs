@@ -3,7 +3,8 @@ use super::{
     PatternIndex,
     SequenceStorage,
 };
-use std::io::{Write, Read, Error};
+use std::io::{Write, Read, Error, ErrorKind};
+use std::sync::Arc;
 
 /// Save and load the structure
 pub trait Serialize {
@@ -52,8 +53,62 @@ impl<I, S> EstimateSize for Reference<I, S> where
     S: SequenceStorage + EstimateSize,
 {
     fn serialized_size(&self) -> usize {
-        (self.target_boundaries.len() * std::mem::size_of::<u32>())
+        self.target_boundaries.to_be_saved_size()
         + self.sequence_storage.serialized_size()
         + self.pattern_index.serialized_size()
     }
 }
+
+/// Bytes shared by the structures loaded from them (e.g., memory-mapped file).
+pub type SharedBytes = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// Load the structure from the shared bytes, borrowing the buffers instead of copying them if possible.
+pub trait LoadFromShared: Serialize {
+    /// Load from `bytes[offset..]`, and return the structure with the offset right after it.
+    fn load_from_shared(bytes: &SharedBytes, offset: usize) -> Result<(Self, usize), Error> where
+        Self: Sized;
+}
+
+/// `LoadFromShared` by copying: load with `Serialize` from the slice of the shared bytes.
+pub fn load_from_shared_by_copy<T: Serialize>(bytes: &SharedBytes, offset: usize) -> Result<(T, usize), Error> {
+    let whole: &[u8] = (**bytes).as_ref();
+    let Some(mut remained) = whole.get(offset..) else {
+        return Err(ErrorKind::UnexpectedEof.into())
+    };
+    let structure = T::load_from(&mut remained)?;
+    Ok((structure, whole.len() - remained.len()))
+}
+
+impl<I, S> LoadFromShared for Reference<I, S> where
+    I: PatternIndex + LoadFromShared,
+    S: SequenceStorage + LoadFromShared,
+{
+    fn load_from_shared(bytes: &SharedBytes, offset: usize) -> Result<(Self, usize), Error> {
+        let (target_boundaries, offset) = load_from_shared_by_copy::<TargetBoundaries>(bytes, offset)?;
+        let (pattern_index, offset) = I::load_from_shared(bytes, offset)?;
+        let (sequence_storage, offset) = S::load_from_shared(bytes, offset)?;
+        Ok((
+            Self {
+                target_boundaries: target_boundaries.0,
+                pattern_index,
+                sequence_storage,
+            },
+            offset,
+        ))
+    }
+}
+
+struct TargetBoundaries(Vec<u32>);
+impl Serialize for TargetBoundaries {
+    fn save_to<W>(&self, writer: W) -> Result<(), Error> where
+        W: Write
+    {
+        self.0.save_to(writer)
+    }
+    fn load_from<R>(reader: R) -> Result<Self, Error> where
+        R: Read,
+        Self: Sized
+    {
+        Ok(Self(Vec::load_from(reader)?))
+    }
+}
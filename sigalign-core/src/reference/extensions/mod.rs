@@ -13,6 +13,9 @@ mod io;
 pub use io::{
     Serialize,
     EstimateSize,
+    SharedBytes,
+    LoadFromShared,
+    load_from_shared_by_copy,
 };
 mod label;
 pub use label::{LabelStorage, LabelRefStorage};
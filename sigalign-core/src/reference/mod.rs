@@ -51,7 +51,22 @@ impl<I, S> Reference<I, S> where
         sequence_storage: S,
         pattern_index_option: I::Option,
     ) -> Result<Self, I::BuildError> {
-        let (concatenated_sequence, target_boundaries) = sequence_storage.get_concatenated_sequence_with_boundaries_of_targets();
+        Self::new_with_modified_index(sequence_storage, pattern_index_option, |_, _| {})
+    }
+    /// Same as `new`, but the pattern index is built from the concatenated sequence
+    /// modified by `modify_sequence_for_index` (with the boundaries of targets).
+    ///  - The modified positions can be excluded from the pattern locating
+    ///    by replacing them with a character that never appears in the query,
+    ///    while they remain in the `SequenceStorage` for the extension.
+    pub fn new_with_modified_index<F>(
+        sequence_storage: S,
+        pattern_index_option: I::Option,
+        modify_sequence_for_index: F,
+    ) -> Result<Self, I::BuildError> where
+        F: FnOnce(&mut [u8], &[u32]),
+    {
+        let (mut concatenated_sequence, target_boundaries) = sequence_storage.get_concatenated_sequence_with_boundaries_of_targets();
+        modify_sequence_for_index(&mut concatenated_sequence, &target_boundaries);
         let pattern_index = I::new(concatenated_sequence, pattern_index_option)?;
 
         Ok(Self {
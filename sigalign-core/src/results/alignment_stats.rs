@@ -0,0 +1,143 @@
+use super::{
+    TargetAlignment,
+    Alignment,
+    AlignmentOperations,
+    AlignmentOperation,
+};
+
+/// Summary of the alignment derived from the operations.
+///
+/// With the gap-affine penalties used by SigAlign, the penalty of the alignment is
+/// `mismatches * px + gap_opens * po + gap_extensions * pe`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AlignmentStats {
+    /// Number of matched bases.
+    pub matches: u32,
+    /// Number of substituted bases.
+    pub mismatches: u32,
+    /// Number of bases only in the query.
+    pub insertions: u32,
+    /// Number of bases only in the target.
+    pub deletions: u32,
+    /// Number of gaps (consecutive insertions or deletions).
+    pub gap_opens: u32,
+    /// Number of gap bases. Every gap base is charged the extension penalty.
+    pub gap_extensions: u32,
+}
+
+impl AlignmentStats {
+    pub fn from_operations(operations: &[AlignmentOperations]) -> Self {
+        let mut stats = Self::default();
+        let mut previous_operation = None;
+        operations.iter().for_each(|operations| {
+            match operations.operation {
+                AlignmentOperation::Match => stats.matches += operations.count,
+                AlignmentOperation::Subst => stats.mismatches += operations.count,
+                AlignmentOperation::Insertion | AlignmentOperation::Deletion => {
+                    if operations.operation == AlignmentOperation::Insertion {
+                        stats.insertions += operations.count;
+                    } else {
+                        stats.deletions += operations.count;
+                    }
+                    if previous_operation != Some(&operations.operation) {
+                        stats.gap_opens += 1;
+                    }
+                    stats.gap_extensions += operations.count;
+                },
+            }
+            previous_operation = Some(&operations.operation);
+        });
+        stats
+    }
+    /// Number of columns in the alignment.
+    pub fn alignment_length(&self) -> u32 {
+        self.matches + self.mismatches + self.insertions + self.deletions
+    }
+    /// Ratio of the matched bases to the columns of the alignment (0.0 ~ 1.0).
+    pub fn identity(&self) -> f64 {
+        ratio(self.matches, self.alignment_length())
+    }
+    /// Penalty calculated with the given penalties.
+    pub fn penalty(&self, mismatch_penalty: u32, gap_open_penalty: u32, gap_extend_penalty: u32) -> u32 {
+        self.mismatches * mismatch_penalty
+        + self.gap_opens * gap_open_penalty
+        + self.gap_extensions * gap_extend_penalty
+    }
+}
+
+impl std::ops::AddAssign for AlignmentStats {
+    fn add_assign(&mut self, other: Self) {
+        self.matches += other.matches;
+        self.mismatches += other.mismatches;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+        self.gap_opens += other.gap_opens;
+        self.gap_extensions += other.gap_extensions;
+    }
+}
+impl std::iter::Sum for AlignmentStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        let mut stats = Self::default();
+        iter.for_each(|other| stats += other);
+        stats
+    }
+}
+
+impl Alignment {
+    pub fn stats(&self) -> AlignmentStats {
+        AlignmentStats::from_operations(&self.operations)
+    }
+    /// Ratio of the matched bases to the columns of the alignment (0.0 ~ 1.0).
+    pub fn identity(&self) -> f64 {
+        self.stats().identity()
+    }
+    /// Ratio of the aligned query to the whole query (0.0 ~ 1.0).
+    pub fn query_coverage(&self, query_length: u32) -> f64 {
+        ratio(self.position.query.1 - self.position.query.0, query_length)
+    }
+    /// Ratio of the aligned target to the whole target (0.0 ~ 1.0).
+    pub fn target_coverage(&self, target_length: u32) -> f64 {
+        ratio(self.position.target.1 - self.position.target.0, target_length)
+    }
+}
+
+impl TargetAlignment {
+    /// Sum of the statistics of all alignments.
+    pub fn stats(&self) -> AlignmentStats {
+        self.alignments.iter().map(|alignment| alignment.stats()).sum()
+    }
+    /// Ratio of the query covered by any alignment (0.0 ~ 1.0).
+    ///  - Overlapped regions are counted once.
+    pub fn query_coverage(&self, query_length: u32) -> f64 {
+        let intervals = self.alignments.iter().map(|x| x.position.query).collect();
+        ratio(length_of_union(intervals), query_length)
+    }
+    /// Ratio of the target covered by any alignment (0.0 ~ 1.0).
+    ///  - Overlapped regions are counted once.
+    pub fn target_coverage(&self, target_length: u32) -> f64 {
+        let intervals = self.alignments.iter().map(|x| x.position.target).collect();
+        ratio(length_of_union(intervals), target_length)
+    }
+}
+
+fn length_of_union(mut intervals: Vec<(u32, u32)>) -> u32 {
+    intervals.sort_unstable();
+    let mut length = 0;
+    let mut covered_until = 0;
+    intervals.into_iter().for_each(|(start, end)| {
+        let start = start.max(covered_until);
+        if end > start {
+            length += end - start;
+            covered_until = end;
+        }
+    });
+    length
+}
+#[inline]
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
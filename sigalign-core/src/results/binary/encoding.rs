@@ -0,0 +1,204 @@
+use std::io::{Read, Write, Error, ErrorKind};
+
+use super::super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+    AlignmentPosition,
+    AlignmentOperations,
+    AlignmentOperation,
+};
+
+/// Encode and decode the structure in the compact binary format.
+pub trait BinaryEncode: Sized {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error>;
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error>;
+}
+
+/// Top-level structure that can be written as a record of the binary stream.
+pub trait BinaryRecord: BinaryEncode {
+    /// Tag written in the stream header to prevent reading a stream as the wrong type.
+    const RECORD_TYPE: u8;
+}
+
+/// Write `value` as LEB128 variable-length integer.
+#[inline]
+pub fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> Result<(), Error> {
+    let mut buffer = [0u8; 10];
+    let mut size = 0;
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer[size] = byte;
+            size += 1;
+            break;
+        } else {
+            buffer[size] = byte | 0x80;
+            size += 1;
+        }
+    }
+    writer.write_all(&buffer[..size])
+}
+/// Read LEB128 variable-length integer.
+#[inline]
+pub fn read_varint<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    let mut byte = [0u8; 1];
+    loop {
+        reader.read_exact(&mut byte)?;
+        let low_bits = (byte[0] & 0x7F) as u64;
+        if shift == 63 && low_bits > 1 {
+            return Err(invalid_data("Variable-length integer overflows u64"));
+        }
+        value |= low_bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err(invalid_data("Variable-length integer is too long"));
+        }
+    }
+}
+
+#[inline]
+fn read_varint_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    let value = read_varint(reader)?;
+    u32::try_from(value).map_err(|_| invalid_data("Value overflows u32"))
+}
+pub(super) fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+// Primitives
+impl BinaryEncode for u32 {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        write_varint(writer, *self as u64)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        read_varint_u32(reader)
+    }
+}
+impl BinaryEncode for String {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        write_varint(writer, self.len() as u64)?;
+        writer.write_all(self.as_bytes())
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = read_varint(reader)?;
+        let mut bytes = Vec::new();
+        reader.take(len).read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != len {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        String::from_utf8(bytes).map_err(|_| invalid_data("String is not valid UTF-8"))
+    }
+}
+impl<T: BinaryEncode> BinaryEncode for Vec<T> {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        write_varint(writer, self.len() as u64)?;
+        for item in self.iter() {
+            item.encode_to(writer)?;
+        }
+        Ok(())
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let len = read_varint(reader)?;
+        // Do not trust the length for the allocation: the input can be corrupted.
+        let mut vec = Vec::with_capacity(len.min(1024) as usize);
+        for _ in 0..len {
+            vec.push(T::decode_from(reader)?);
+        }
+        Ok(vec)
+    }
+}
+
+// Results
+impl BinaryEncode for QueryAlignment {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.0.encode_to(writer)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(Self(Vec::decode_from(reader)?))
+    }
+}
+impl BinaryRecord for QueryAlignment {
+    const RECORD_TYPE: u8 = 1;
+}
+impl BinaryEncode for TargetAlignment {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.index.encode_to(writer)?;
+        self.alignments.encode_to(writer)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let index = u32::decode_from(reader)?;
+        let alignments = Vec::decode_from(reader)?;
+        Ok(Self { index, alignments })
+    }
+}
+impl BinaryEncode for Alignment {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.penalty.encode_to(writer)?;
+        self.length.encode_to(writer)?;
+        self.position.encode_to(writer)?;
+        self.operations.encode_to(writer)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let penalty = u32::decode_from(reader)?;
+        let length = u32::decode_from(reader)?;
+        let position = AlignmentPosition::decode_from(reader)?;
+        let operations = Vec::decode_from(reader)?;
+        Ok(Self { penalty, length, position, operations })
+    }
+}
+impl BinaryEncode for AlignmentPosition {
+    // Ends are stored as the span from the start, which is usually much smaller.
+    //  - The span wraps around, so that any position (even with the end smaller than the start)
+    //    is encoded without error and decoded as it was.
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let query_span = self.query.1.wrapping_sub(self.query.0);
+        let target_span = self.target.1.wrapping_sub(self.target.0);
+        self.query.0.encode_to(writer)?;
+        query_span.encode_to(writer)?;
+        self.target.0.encode_to(writer)?;
+        target_span.encode_to(writer)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let query_start = u32::decode_from(reader)?;
+        let query_span = u32::decode_from(reader)?;
+        let target_start = u32::decode_from(reader)?;
+        let target_span = u32::decode_from(reader)?;
+        let query_end = query_start.wrapping_add(query_span);
+        let target_end = target_start.wrapping_add(target_span);
+        Ok(Self {
+            query: (query_start, query_end),
+            target: (target_start, target_end),
+        })
+    }
+}
+impl BinaryEncode for AlignmentOperations {
+    // Packed into one integer: the lower 2 bits are the operation.
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        let code = match self.operation {
+            AlignmentOperation::Match => 0,
+            AlignmentOperation::Subst => 1,
+            AlignmentOperation::Deletion => 2,
+            AlignmentOperation::Insertion => 3,
+        };
+        write_varint(writer, ((self.count as u64) << 2) | code)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let packed = read_varint(reader)?;
+        let operation = match packed & 0b11 {
+            0 => AlignmentOperation::Match,
+            1 => AlignmentOperation::Subst,
+            2 => AlignmentOperation::Deletion,
+            _ => AlignmentOperation::Insertion,
+        };
+        let count = u32::try_from(packed >> 2)
+            .map_err(|_| invalid_data("Operation count overflows u32"))?;
+        Ok(Self { operation, count })
+    }
+}
@@ -0,0 +1,74 @@
+/*!
+Compact binary encoding of the alignment results.
+
+JSON is convenient to inspect, but too large and slow for checkpointing millions of results.
+The binary encoding packs:
+- Positions and penalties as LEB128 variable-length integers (end positions are stored as the span from the start).
+- Each `AlignmentOperations` into one variable-length integer (`count << 2 | operation`).
+
+## Streams
+A stream starts with a header and is followed by length-prefixed records:
+```text
+[ magic: "SGRS" ][ version: u8 ][ record type: u8 ]
+[ payload length: varint ][ payload ] ... (repeated)
+```
+- `BinaryResultWriter` writes the header (unless appending to an existing stream) and records.
+- `BinaryResultReader` checks the header and yields records lazily, so files can be re-read without loading them whole.
+- Because each record is length-prefixed, a truncated record is reported as an error instead of being silently dropped.
+
+Usage:
+```rust
+use sigalign_core::results::{
+    QueryAlignment,
+    binary::{BinaryResultWriter, BinaryResultReader},
+};
+
+let results = vec![QueryAlignment(Vec::new()); 3];
+
+let mut buffer = Vec::new();
+let mut writer = BinaryResultWriter::new(&mut buffer).unwrap();
+for result in results.iter() {
+    writer.write_record(result).unwrap();
+}
+writer.finish().unwrap();
+
+let reader = BinaryResultReader::<_, QueryAlignment>::new(&buffer[..]).unwrap();
+assert_eq!(reader.count(), 3);
+```
+*/
+
+mod encoding;
+pub use encoding::{
+    BinaryEncode,
+    BinaryRecord,
+    write_varint,
+    read_varint,
+};
+mod stream;
+
+/// Encode the value to a new buffer.
+///  - Never fails: the encoding of the results returns an error only from the writer,
+///    and writing to `Vec<u8>` never fails.
+pub fn encode_to_vec<T: BinaryEncode>(value: &T) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    value.encode_to(&mut buffer).expect("Writing to Vec<u8> never fails");
+    buffer
+}
+/// Decode the value from the bytes, which must be consumed entirely.
+pub fn decode_whole_bytes<T: BinaryEncode>(mut bytes: &[u8]) -> Result<T, std::io::Error> {
+    let decoded = T::decode_from(&mut bytes)?;
+    if bytes.is_empty() {
+        Ok(decoded)
+    } else {
+        Err(std::io::ErrorKind::InvalidData.into())
+    }
+}
+
+pub use stream::{
+    BinaryResultWriter,
+    BinaryResultReader,
+    QueryAlignmentWriter,
+    QueryAlignmentReader,
+    STREAM_MAGIC,
+    STREAM_VERSION,
+};
@@ -0,0 +1,210 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Write, Seek, SeekFrom, Error, ErrorKind};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use super::super::QueryAlignment;
+use super::encoding::{BinaryRecord, write_varint, read_varint, invalid_data};
+
+/// Magic bytes at the start of every binary result stream.
+pub const STREAM_MAGIC: [u8; 4] = *b"SGRS";
+/// Version of the binary encoding.
+pub const STREAM_VERSION: u8 = 1;
+const HEADER_SIZE: u64 = 6;
+
+/// Writer of a binary result stream.
+pub struct BinaryResultWriter<W: Write, T: BinaryRecord> {
+    writer: W,
+    record_buffer: Vec<u8>,
+    _record_type: PhantomData<T>,
+}
+/// Reader of a binary result stream.
+pub struct BinaryResultReader<R: Read, T: BinaryRecord> {
+    reader: R,
+    record_buffer: Vec<u8>,
+    _record_type: PhantomData<T>,
+}
+
+/// Writer of `QueryAlignment` stream.
+pub type QueryAlignmentWriter<W> = BinaryResultWriter<W, QueryAlignment>;
+/// Reader of `QueryAlignment` stream.
+pub type QueryAlignmentReader<R> = BinaryResultReader<R, QueryAlignment>;
+
+impl<W: Write, T: BinaryRecord> BinaryResultWriter<W, T> {
+    /// Start a new stream by writing the header.
+    pub fn new(mut writer: W) -> Result<Self, Error> {
+        write_header::<_, T>(&mut writer)?;
+        Ok(Self::appending(writer))
+    }
+    /// Continue a stream that already has the header.
+    /// The caller is responsible for the `writer` being positioned at the end of a valid stream.
+    pub fn appending(writer: W) -> Self {
+        Self {
+            writer,
+            record_buffer: Vec::new(),
+            _record_type: PhantomData,
+        }
+    }
+    /// Write one record.
+    pub fn write_record(&mut self, record: &T) -> Result<(), Error> {
+        self.record_buffer.clear();
+        record.encode_to(&mut self.record_buffer)?;
+        write_varint(&mut self.writer, self.record_buffer.len() as u64)?;
+        self.writer.write_all(&self.record_buffer)
+    }
+    /// Flush and return the inner writer.
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<T: BinaryRecord> BinaryResultWriter<BufWriter<File>, T> {
+    /// Open the file to append records.
+    /// - If the file is empty or does not exist, the header is written.
+    /// - If the file ends with an incomplete record (e.g., the previous run was killed while writing),
+    ///   the incomplete record is truncated, so that the file stays readable.
+    pub fn append_to_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        let file_size = file.metadata()?.len();
+        if file_size == 0 {
+            let mut writer = BufWriter::new(file);
+            write_header::<_, T>(&mut writer)?;
+            return Ok(Self::appending(writer));
+        }
+
+        let end_of_valid_records = {
+            let mut reader = BufReader::new(&mut file);
+            read_header::<_, T>(&mut reader)?;
+            let mut offset = HEADER_SIZE;
+            loop {
+                let payload_size = match read_payload_size(&mut reader) {
+                    Ok(Some(v)) => v,
+                    Ok(None) => break,
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+                    Err(e) => return Err(e),
+                };
+                let record_end = offset
+                    + varint_size(payload_size)
+                    + payload_size;
+                if record_end > file_size {
+                    break;
+                }
+                reader.seek_relative(payload_size as i64)?;
+                offset = record_end;
+            }
+            offset
+        };
+        if end_of_valid_records != file_size {
+            file.set_len(end_of_valid_records)?;
+        }
+        file.seek(SeekFrom::Start(end_of_valid_records))?;
+        Ok(Self::appending(BufWriter::new(file)))
+    }
+}
+
+impl<R: Read, T: BinaryRecord> BinaryResultReader<R, T> {
+    /// Start reading a stream by checking the header.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        read_header::<_, T>(&mut reader)?;
+        Ok(Self {
+            reader,
+            record_buffer: Vec::new(),
+            _record_type: PhantomData,
+        })
+    }
+    /// Read the next record. `None` at the end of the stream.
+    pub fn read_record(&mut self) -> Option<Result<T, Error>> {
+        let payload_size = match read_payload_size(&mut self.reader) {
+            Ok(Some(v)) => v,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(self.read_payload(payload_size))
+    }
+    fn read_payload(&mut self, payload_size: u64) -> Result<T, Error> {
+        self.record_buffer.clear();
+        (&mut self.reader).take(payload_size).read_to_end(&mut self.record_buffer)?;
+        if self.record_buffer.len() as u64 != payload_size {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "Record is truncated"));
+        }
+        let mut payload = &self.record_buffer[..];
+        let record = T::decode_from(&mut payload)?;
+        if !payload.is_empty() {
+            return Err(invalid_data("Record has trailing bytes"));
+        }
+        Ok(record)
+    }
+    /// Return the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read, T: BinaryRecord> Iterator for BinaryResultReader<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record()
+    }
+}
+
+fn write_header<W: Write, T: BinaryRecord>(writer: &mut W) -> Result<(), Error> {
+    writer.write_all(&STREAM_MAGIC)?;
+    writer.write_all(&[STREAM_VERSION, T::RECORD_TYPE])
+}
+fn read_header<R: Read, T: BinaryRecord>(reader: &mut R) -> Result<(), Error> {
+    let mut header = [0u8; HEADER_SIZE as usize];
+    reader.read_exact(&mut header).map_err(|e| {
+        if e.kind() == ErrorKind::UnexpectedEof {
+            invalid_data("Not a SigAlign binary result stream")
+        } else {
+            e
+        }
+    })?;
+    if header[..4] != STREAM_MAGIC {
+        return Err(invalid_data("Not a SigAlign binary result stream"));
+    }
+    if header[4] != STREAM_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Unsupported version of binary result stream: {}", header[4]),
+        ));
+    }
+    if header[5] != T::RECORD_TYPE {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Stream contains other type of records (expected {}, found {})", T::RECORD_TYPE, header[5]),
+        ));
+    }
+    Ok(())
+}
+/// `None` if the stream ends cleanly before the record.
+fn read_payload_size<R: Read>(reader: &mut R) -> Result<Option<u64>, Error> {
+    let mut first_byte = [0u8; 1];
+    loop {
+        match reader.read(&mut first_byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    if first_byte[0] & 0x80 == 0 {
+        Ok(Some(first_byte[0] as u64))
+    } else {
+        let rest = read_varint(reader)?;
+        let value = rest.checked_shl(7)
+            .filter(|v| v >> 7 == rest)
+            .ok_or_else(|| invalid_data("Record size overflows u64"))?;
+        Ok(Some(value | (first_byte[0] & 0x7F) as u64))
+    }
+}
+fn varint_size(mut value: u64) -> u64 {
+    let mut size = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        size += 1;
+    }
+    size
+}
@@ -0,0 +1,100 @@
+use thiserror::Error;
+
+use super::{
+    Alignment,
+    AlignmentOperations,
+    AlignmentOperation,
+};
+
+/// Error to parse the CIGAR string.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum CigarError {
+    #[error("Operation '{0}' is not supported. Only '=', 'X', 'I' and 'D' are allowed.")]
+    UnsupportedOperation(char),
+    #[error("Operation '{0}' has no count.")]
+    MissingCount(char),
+    #[error("Count {0} has no operation.")]
+    MissingOperation(String),
+    #[error("Count of operation is zero or overflows u32.")]
+    InvalidCount,
+}
+
+/// Translation between the alignment operations and the extended CIGAR string (e.g., `10=1X2I5=`).
+///
+/// Since SigAlign distinguishes matches from substitutions, `M` is not used.
+pub trait Cigar: Sized {
+    fn to_cigar(&self) -> String;
+    fn from_cigar(cigar: &str) -> Result<Self, CigarError>;
+}
+
+impl Cigar for Vec<AlignmentOperations> {
+    fn to_cigar(&self) -> String {
+        let mut cigar = String::with_capacity(self.len() * 4);
+        self.iter().for_each(|operations| {
+            cigar.push_str(&operations.count.to_string());
+            cigar.push(operations.operation.to_cigar_code() as char);
+        });
+        cigar
+    }
+    fn from_cigar(cigar: &str) -> Result<Self, CigarError> {
+        let mut operations: Vec<AlignmentOperations> = Vec::new();
+        let mut count_start = 0;
+        for (index, chr) in cigar.char_indices() {
+            if chr.is_ascii_digit() {
+                continue;
+            }
+            let operation = if chr.is_ascii() {
+                AlignmentOperation::from_cigar_code(chr as u8)
+            } else {
+                None
+            }.ok_or(CigarError::UnsupportedOperation(chr))?;
+            let count_str = &cigar[count_start..index];
+            if count_str.is_empty() {
+                return Err(CigarError::MissingCount(chr));
+            }
+            let count: u32 = count_str.parse().map_err(|_| CigarError::InvalidCount)?;
+            if count == 0 {
+                return Err(CigarError::InvalidCount);
+            }
+            // Merge the same consecutive operations
+            match operations.last_mut() {
+                Some(last) if last.operation == operation => {
+                    last.count = last.count.checked_add(count).ok_or(CigarError::InvalidCount)?;
+                },
+                _ => operations.push(AlignmentOperations { operation, count }),
+            }
+            count_start = index + 1;
+        }
+        if count_start != cigar.len() {
+            return Err(CigarError::MissingOperation(cigar[count_start..].to_string()));
+        }
+        Ok(operations)
+    }
+}
+
+impl AlignmentOperation {
+    /// Operation code of the extended CIGAR.
+    pub fn to_cigar_code(&self) -> u8 {
+        match self {
+            Self::Match => b'=',
+            Self::Subst => b'X',
+            Self::Insertion => b'I',
+            Self::Deletion => b'D',
+        }
+    }
+    pub fn from_cigar_code(code: u8) -> Option<Self> {
+        match code {
+            b'=' => Some(Self::Match),
+            b'X' => Some(Self::Subst),
+            b'I' => Some(Self::Insertion),
+            b'D' => Some(Self::Deletion),
+            _ => None,
+        }
+    }
+}
+
+impl Alignment {
+    pub fn to_cigar(&self) -> String {
+        self.operations.to_cigar()
+    }
+}
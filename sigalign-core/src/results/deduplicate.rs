@@ -1,22 +1,60 @@
 use std::cmp::Ordering;
 
-use ahash::AHashSet;
+use ahash::AHashMap;
 
 use super::{
     QueryAlignment,
     TargetAlignment,
     Alignment,
-    AlignmentOperation, AlignmentPosition,
+    AlignmentOperation,
+    AlignmentOrder,
 };
 
+/// Criterion to decide that an alignment is a duplicate of the already kept one.
+///  - The fractions are clamped into (0, 1]: values above 1 and NaN are treated as 1,
+///    and values not above 0 as any overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeduplicationStrategy {
+    /// Share at least one connected (Match or Subst) base pair position.
+    SharedPath,
+    /// Overlapped query region is at least this fraction of the query region of the alignment.
+    QueryOverlap(f64),
+    /// Overlapped target region is at least this fraction of the target region of the alignment.
+    TargetOverlap(f64),
+    /// Overlapped regions are at least this fraction of the regions of *both* alignments,
+    /// in both the query and the target.
+    ReciprocalOverlap(f64),
+}
+
+/// Which alignment is kept first when alignments are duplicated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeduplicationPriority {
+    /// Longer query region first. Ties are broken by the query start.
+    Longest,
+    /// Longer query region first. Ties are broken by lower penalty.
+    LongestWithLowerPenalty,
+    /// Lower penalty first. Ties are broken by longer query region.
+    LowerPenalty,
+    /// Lower penalty per length first, in the same order as `AlignmentOrder::PenaltyPerLength`.
+    LowerPenaltyPerLength,
+}
+
 impl QueryAlignment {
     /// Deduplicate the alignments by connected (Match or Subst) base pairs positions.
     pub fn deduplicated(self) -> Self {
-        let mut paths = AHashSet::new();
+        self.deduplicated_with(DeduplicationStrategy::SharedPath, DeduplicationPriority::Longest)
+    }
+    /// Deduplicate the alignments of each target with the strategy.
+    pub fn deduplicated_with(
+        self,
+        strategy: DeduplicationStrategy,
+        priority: DeduplicationPriority,
+    ) -> Self {
+        let mut kept_regions = KeptRegions::default();
 
         Self(
             self.0.into_iter().map(|v| {
-                v.deduplicated_with_paths_buffer(&mut paths)
+                v.deduplicated_with_buffer(strategy, priority, &mut kept_regions)
             }).collect()
         )
     }
@@ -24,76 +62,206 @@ impl QueryAlignment {
 
 impl TargetAlignment {
     pub fn deduplicated(self) -> Self {
-        let mut paths = AHashSet::new();
-        self.deduplicated_with_paths_buffer(&mut paths)
+        self.deduplicated_with(DeduplicationStrategy::SharedPath, DeduplicationPriority::Longest)
+    }
+    pub fn deduplicated_with(
+        self,
+        strategy: DeduplicationStrategy,
+        priority: DeduplicationPriority,
+    ) -> Self {
+        let mut kept_regions = KeptRegions::default();
+        self.deduplicated_with_buffer(strategy, priority, &mut kept_regions)
     }
-    fn deduplicated_with_paths_buffer(mut self, paths: &mut AHashSet<(u32, u32)>) -> Self {
-        paths.clear();
+    fn deduplicated_with_buffer(
+        mut self,
+        strategy: DeduplicationStrategy,
+        priority: DeduplicationPriority,
+        kept_regions: &mut KeptRegions,
+    ) -> Self {
+        kept_regions.clear();
+        let strategy = strategy.clamped();
 
         self.alignments.sort_unstable_by(|a, b| {
-            cmp_alignment_by_query_position(a, b)
+            priority.cmp(a, b)
         });
 
         let temporary_vec = std::mem::take(&mut self.alignments);
-        // - Same as
-        // let temporary_vec = std::mem::replace(
-        //     &mut self.alignments,
-        //     Vec::new(),
-        // );
-        // TODO: Which is better?
-        
         self.alignments = temporary_vec.into_iter().filter(|v| {
-            let path = v.get_path();
-            if paths.is_disjoint(&path) {
-                paths.extend(path);
-                true
-            } else {
+            if kept_regions.is_duplicated(v, strategy) {
                 false
+            } else {
+                kept_regions.add(v, strategy);
+                true
             }
         }).collect();
         self
     }
 }
 
-fn cmp_alignment_by_query_position(
-    a: &Alignment,
-    b: &Alignment,
-) -> Ordering {
-    b.position.get_query_length().cmp(&a.position.get_query_length())
-        .then(a.position.query.0.cmp(&b.position.query.0))
+impl DeduplicationPriority {
+    fn cmp(&self, a: &Alignment, b: &Alignment) -> Ordering {
+        let by_length = b.query_length().cmp(&a.query_length());
+        match self {
+            Self::Longest => {
+                by_length
+                    .then(a.position.query.0.cmp(&b.position.query.0))
+            },
+            Self::LongestWithLowerPenalty => {
+                by_length
+                    .then(a.penalty.cmp(&b.penalty))
+                    .then(a.position.query.0.cmp(&b.position.query.0))
+            },
+            Self::LowerPenalty => {
+                a.penalty.cmp(&b.penalty)
+                    .then(by_length)
+                    .then(a.position.query.0.cmp(&b.position.query.0))
+            },
+            Self::LowerPenaltyPerLength => {
+                AlignmentOrder::PenaltyPerLength.cmp(a, b)
+            },
+        }
+    }
+}
+
+impl DeduplicationStrategy {
+    fn clamped(self) -> Self {
+        let clamp = |fraction: f64| {
+            if fraction.is_nan() || fraction > 1.0 {
+                1.0
+            } else if fraction <= 0.0 {
+                f64::MIN_POSITIVE
+            } else {
+                fraction
+            }
+        };
+        match self {
+            Self::SharedPath => Self::SharedPath,
+            Self::QueryOverlap(fraction) => Self::QueryOverlap(clamp(fraction)),
+            Self::TargetOverlap(fraction) => Self::TargetOverlap(clamp(fraction)),
+            Self::ReciprocalOverlap(fraction) => Self::ReciprocalOverlap(clamp(fraction)),
+        }
+    }
+}
+
+/// Regions of the kept alignments.
+///  - For `SharedPath`, the runs of connected base pairs are stored per diagonal
+///    instead of every position, so that the memory is proportional to the number of operations.
+#[derive(Default)]
+struct KeptRegions {
+    // Diagonal (target - query) -> sorted, disjoint query intervals
+    runs_by_diagonal: AHashMap<i64, Vec<(u32, u32)>>,
+    // (query, target) regions of the kept alignments
+    regions: Vec<((u32, u32), (u32, u32))>,
+}
+
+impl KeptRegions {
+    fn clear(&mut self) {
+        self.runs_by_diagonal.clear();
+        self.regions.clear();
+    }
+    fn is_duplicated(&self, alignment: &Alignment, strategy: DeduplicationStrategy) -> bool {
+        let query = alignment.position.query;
+        let target = alignment.position.target;
+        match strategy {
+            DeduplicationStrategy::SharedPath => {
+                alignment.connected_runs().any(|(diagonal, run)| {
+                    self.runs_by_diagonal.get(&diagonal).is_some_and(|kept_runs| {
+                        // The first kept run ending after the start of this run
+                        let idx = kept_runs.partition_point(|kept_run| kept_run.1 <= run.0);
+                        idx < kept_runs.len() && kept_runs[idx].0 < run.1
+                    })
+                })
+            },
+            DeduplicationStrategy::QueryOverlap(fraction) => {
+                self.regions.iter().any(|(kept_query, _)| {
+                    overlap_fraction(query, *kept_query) >= fraction
+                })
+            },
+            DeduplicationStrategy::TargetOverlap(fraction) => {
+                self.regions.iter().any(|(_, kept_target)| {
+                    overlap_fraction(target, *kept_target) >= fraction
+                })
+            },
+            DeduplicationStrategy::ReciprocalOverlap(fraction) => {
+                self.regions.iter().any(|(kept_query, kept_target)| {
+                    overlap_fraction(query, *kept_query) >= fraction
+                    && overlap_fraction(*kept_query, query) >= fraction
+                    && overlap_fraction(target, *kept_target) >= fraction
+                    && overlap_fraction(*kept_target, target) >= fraction
+                })
+            },
+        }
+    }
+    fn add(&mut self, alignment: &Alignment, strategy: DeduplicationStrategy) {
+        match strategy {
+            DeduplicationStrategy::SharedPath => {
+                alignment.connected_runs().for_each(|(diagonal, run)| {
+                    let kept_runs = self.runs_by_diagonal.entry(diagonal).or_default();
+                    let idx = kept_runs.partition_point(|kept_run| kept_run.0 < run.0);
+                    kept_runs.insert(idx, run);
+                });
+            },
+            _ => {
+                self.regions.push((alignment.position.query, alignment.position.target));
+            },
+        }
+    }
 }
 
-impl AlignmentPosition {
-    fn get_query_length(&self) -> u32 {
-        self.query.1 - self.query.0
+/// Fraction of `region` overlapped with `other`.
+fn overlap_fraction(region: (u32, u32), other: (u32, u32)) -> f64 {
+    let length = region.1 - region.0;
+    if length == 0 {
+        return 0.0;
+    }
+    let start = region.0.max(other.0);
+    let end = region.1.min(other.1);
+    if end > start {
+        (end - start) as f64 / length as f64
+    } else {
+        0.0
     }
 }
 
 impl Alignment {
-    fn get_path(&self) -> AHashSet<(u32, u32)> {
-        let (mut query_index, mut target_index) = {
-            let query_index = self.position.query.0;
-            let target_index = self.position.target.0;
-            (query_index, target_index)
-        };
-        let mut paths = AHashSet::new();
-        self.operations.iter().for_each(|operation| {
-            match operation.operation {
-                AlignmentOperation::Match | AlignmentOperation::Subst => {
-                    for _ in 0..operation.count {
-                        paths.insert((query_index, target_index));
-                        query_index += 1;
-                        target_index += 1;
-                    }
-                },
-                AlignmentOperation::Deletion => {
-                    target_index += operation.count;
-                },
-                AlignmentOperation::Insertion => {
-                    query_index += operation.count;
-                },
+    fn query_length(&self) -> u32 {
+        self.position.query.1 - self.position.query.0
+    }
+    /// Runs of connected (Match or Subst) base pairs as (diagonal, query interval).
+    fn connected_runs(&self) -> impl Iterator<Item = (i64, (u32, u32))> + '_ {
+        let mut query_index = self.position.query.0;
+        let mut target_index = self.position.target.0;
+        let mut operations = self.operations.iter().peekable();
+        std::iter::from_fn(move || {
+            loop {
+                let operation = operations.next()?;
+                match operation.operation {
+                    AlignmentOperation::Match | AlignmentOperation::Subst => {
+                        let diagonal = target_index as i64 - query_index as i64;
+                        let start = query_index;
+                        query_index += operation.count;
+                        target_index += operation.count;
+                        // Merge the following Match or Subst on the same diagonal
+                        while let Some(next) = operations.peek() {
+                            match next.operation {
+                                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                                    query_index += next.count;
+                                    target_index += next.count;
+                                    operations.next();
+                                },
+                                _ => break,
+                            }
+                        }
+                        return Some((diagonal, (start, query_index)));
+                    },
+                    AlignmentOperation::Deletion => {
+                        target_index += operation.count;
+                    },
+                    AlignmentOperation::Insertion => {
+                        query_index += operation.count;
+                    },
+                }
             }
-        });
-        paths
+        })
     }
 }
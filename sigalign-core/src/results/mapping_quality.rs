@@ -0,0 +1,164 @@
+use super::{
+    QueryAlignment,
+    Alignment,
+};
+
+/// Estimator of the mapping quality (MAPQ) of the alignments of one query.
+///
+/// SigAlign returns every alignment satisfying the cutoff, so a query from a repeat has many hits.
+/// The mapping quality is estimated for each alignment by comparing it with the *competitors*:
+/// the other alignments covering mostly the same region of the query.
+///  - No competitor: the maximum mapping quality.
+///  - A competitor has lower penalty: 0 (the alignment is secondary).
+///  - Competitors with the same penalty: `-10 * log10(1 - 1/n)` for the `n` equally good hits (3 for two hits).
+///  - Otherwise: proportional to the penalty gap to the second best, counted in mismatches.
+///
+/// Then the mapping quality is scaled down for the alignments shorter than `full_confidence_length`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MappingQualityEstimator {
+    mismatch_penalty: u32,
+    max_mapping_quality: u8,
+    phred_per_mismatch: f64,
+    min_query_overlap: f64,
+    full_confidence_length: u32,
+}
+
+impl MappingQualityEstimator {
+    /// Create an estimator with the mismatch penalty used in the alignment.
+    pub fn new(mismatch_penalty: u32) -> Self {
+        Self {
+            mismatch_penalty: mismatch_penalty.max(1),
+            max_mapping_quality: 60,
+            phred_per_mismatch: 10.0,
+            min_query_overlap: 0.5,
+            full_confidence_length: 100,
+        }
+    }
+    /// Maximum mapping quality (default: 60).
+    pub fn max_mapping_quality(mut self, max_mapping_quality: u8) -> Self {
+        self.max_mapping_quality = max_mapping_quality.min(254);
+        self
+    }
+    /// Mapping quality added per one mismatch of penalty gap to the second best (default: 10.0).
+    pub fn phred_per_mismatch(mut self, phred_per_mismatch: f64) -> Self {
+        self.phred_per_mismatch = phred_per_mismatch;
+        self
+    }
+    /// Fraction of the query region to be overlapped to be a competitor (default: 0.5).
+    pub fn min_query_overlap(mut self, min_query_overlap: f64) -> Self {
+        self.min_query_overlap = min_query_overlap;
+        self
+    }
+    /// Alignments shorter than this length get proportionally lower mapping quality (default: 100).
+    pub fn full_confidence_length(mut self, full_confidence_length: u32) -> Self {
+        self.full_confidence_length = full_confidence_length;
+        self
+    }
+
+    /// Estimate the mapping qualities of all alignments of one query, in the order of the iterator.
+    ///  - The alignments must be on the same strand of the query.
+    ///    Use `estimate_on_both_strands` for the alignments of both strands.
+    pub fn estimate<'a, I>(&self, alignments: I) -> Vec<u8> where
+        I: IntoIterator<Item = &'a Alignment>,
+    {
+        let alignments: Vec<((u32, u32), &Alignment)> = alignments.into_iter()
+            .map(|alignment| (alignment.position.query, alignment))
+            .collect();
+        self.estimate_with_query_regions(&alignments)
+    }
+    /// Estimate the mapping qualities of the alignments of both strands of one query,
+    /// in the order of the iterator of (`is_forward`, alignment).
+    ///  - The query positions of the reverse strand are on the reverse complement of the query,
+    ///    so they are flipped with the `query_length` to be compared with the forward strand.
+    pub fn estimate_on_both_strands<'a, I>(&self, alignments: I, query_length: u32) -> Vec<u8> where
+        I: IntoIterator<Item = (bool, &'a Alignment)>,
+    {
+        let alignments: Vec<((u32, u32), &Alignment)> = alignments.into_iter()
+            .map(|(is_forward, alignment)| {
+                let (start, end) = alignment.position.query;
+                let query_region = if is_forward {
+                    (start, end)
+                } else {
+                    (query_length.saturating_sub(end), query_length.saturating_sub(start))
+                };
+                (query_region, alignment)
+            })
+            .collect();
+        self.estimate_with_query_regions(&alignments)
+    }
+    // (query region on the forward strand, alignment)
+    fn estimate_with_query_regions(&self, alignments: &[((u32, u32), &Alignment)]) -> Vec<u8> {
+        alignments.iter().enumerate().map(|(index, (query_region, alignment))| {
+            self.estimate_one(index, *query_region, alignment, alignments)
+        }).collect()
+    }
+    fn estimate_one(
+        &self,
+        index: usize,
+        query_region: (u32, u32),
+        alignment: &Alignment,
+        alignments: &[((u32, u32), &Alignment)],
+    ) -> u8 {
+        let mut best_other_penalty = None;
+        let mut equally_good_count = 1;
+        alignments.iter().enumerate().for_each(|(other_index, (other_query_region, other))| {
+            if other_index == index || !self.is_competitor(query_region, *other_query_region) {
+                return;
+            }
+            if other.penalty == alignment.penalty {
+                equally_good_count += 1;
+            }
+            best_other_penalty = Some(match best_other_penalty {
+                Some(penalty) if penalty <= other.penalty => penalty,
+                _ => other.penalty,
+            });
+        });
+
+        let max_mapping_quality = self.max_mapping_quality as f64;
+        let mapping_quality = match best_other_penalty {
+            None => max_mapping_quality,
+            Some(best_other_penalty) => {
+                if best_other_penalty < alignment.penalty {
+                    return 0;
+                } else if equally_good_count > 1 {
+                    let probability_of_wrong = 1.0 - 1.0 / equally_good_count as f64;
+                    (-10.0 * probability_of_wrong.log10()).min(max_mapping_quality)
+                } else {
+                    let gap_in_mismatches = (best_other_penalty - alignment.penalty) as f64
+                        / self.mismatch_penalty as f64;
+                    (self.phred_per_mismatch * gap_in_mismatches).min(max_mapping_quality)
+                }
+            },
+        };
+        let length_factor = if self.full_confidence_length == 0 {
+            1.0
+        } else {
+            (alignment.length as f64 / self.full_confidence_length as f64).min(1.0)
+        };
+        (mapping_quality * length_factor).round() as u8
+    }
+    fn is_competitor(&self, query_region: (u32, u32), other_query_region: (u32, u32)) -> bool {
+        let (start, end) = query_region;
+        let length = end.saturating_sub(start);
+        if length == 0 {
+            return false;
+        }
+        let overlap_start = start.max(other_query_region.0);
+        let overlap_end = end.min(other_query_region.1);
+        overlap_end > overlap_start
+            && (overlap_end - overlap_start) as f64 / length as f64 >= self.min_query_overlap
+    }
+}
+
+impl QueryAlignment {
+    /// Mapping qualities in the same structure as the alignments (`[target][alignment]`).
+    pub fn mapping_qualities(&self, estimator: &MappingQualityEstimator) -> Vec<Vec<u8>> {
+        let flat = estimator.estimate(
+            self.0.iter().flat_map(|target_alignment| target_alignment.alignments.iter())
+        );
+        let mut flat = flat.into_iter();
+        self.0.iter().map(|target_alignment| {
+            flat.by_ref().take(target_alignment.alignments.len()).collect()
+        }).collect()
+    }
+}
@@ -106,7 +106,18 @@ pub enum AlignmentOperation {
 }
 
 mod to_json;
+pub mod binary;
+mod to_binary;
 
 // Features
 mod count_alignments;
 mod deduplicate;
+pub use deduplicate::{DeduplicationStrategy, DeduplicationPriority};
+mod alignment_stats;
+pub use alignment_stats::AlignmentStats;
+mod cigar;
+pub use cigar::{Cigar, CigarError};
+mod sort_and_filter;
+pub use sort_and_filter::AlignmentOrder;
+mod mapping_quality;
+pub use mapping_quality::MappingQualityEstimator;
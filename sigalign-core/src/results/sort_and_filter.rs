@@ -0,0 +1,213 @@
+use std::cmp::Ordering;
+
+use ahash::{AHashMap, AHashSet};
+
+use super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+};
+
+/// Order of the alignments used in sorting and ranking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlignmentOrder {
+    /// Lower penalty first. Ties are broken by longer length.
+    Penalty,
+    /// Lower penalty per length first. Ties are broken by longer length.
+    PenaltyPerLength,
+    /// Smaller query start first.
+    QueryPosition,
+    /// Smaller target start first.
+    TargetPosition,
+}
+
+impl AlignmentOrder {
+    /// Compare two alignments. The alignment that comes first is `Less`.
+    pub fn cmp(&self, a: &Alignment, b: &Alignment) -> Ordering {
+        let by_order = match self {
+            Self::Penalty => {
+                a.penalty.cmp(&b.penalty)
+                    .then(b.length.cmp(&a.length))
+            },
+            Self::PenaltyPerLength => {
+                // a.penalty / a.length <> b.penalty / b.length
+                (a.penalty as u64 * b.length as u64).cmp(&(b.penalty as u64 * a.length as u64))
+                    .then(b.length.cmp(&a.length))
+            },
+            Self::QueryPosition => {
+                a.position.query.cmp(&b.position.query)
+                    .then(a.position.target.cmp(&b.position.target))
+            },
+            Self::TargetPosition => {
+                a.position.target.cmp(&b.position.target)
+                    .then(a.position.query.cmp(&b.position.query))
+            },
+        };
+        // To make the result deterministic
+        by_order
+            .then(a.position.query.cmp(&b.position.query))
+            .then(a.position.target.cmp(&b.position.target))
+            .then(a.penalty.cmp(&b.penalty))
+    }
+}
+
+impl QueryAlignment {
+    /// Keep the alignments that satisfy the predicate.
+    ///  - The predicate takes the index of target and the alignment.
+    ///  - Targets without alignments are removed.
+    pub fn filtered<F>(self, mut predicate: F) -> Self where
+        F: FnMut(u32, &Alignment) -> bool,
+    {
+        Self(
+            self.0.into_iter().filter_map(|target_alignment| {
+                let index = target_alignment.index;
+                let target_alignment = target_alignment.filtered(|alignment| predicate(index, alignment));
+                if target_alignment.alignments.is_empty() {
+                    None
+                } else {
+                    Some(target_alignment)
+                }
+            }).collect()
+        )
+    }
+    pub fn filtered_by_penalty(self, max_penalty: u32) -> Self {
+        self.filtered(|_, alignment| alignment.penalty <= max_penalty)
+    }
+    pub fn filtered_by_length(self, min_length: u32) -> Self {
+        self.filtered(|_, alignment| alignment.length >= min_length)
+    }
+    /// `min_identity` is the ratio (0.0 ~ 1.0) of the matched bases to the columns of the alignment.
+    pub fn filtered_by_identity(self, min_identity: f64) -> Self {
+        self.filtered(|_, alignment| alignment.identity() >= min_identity)
+    }
+    /// Keep the alignments to the given targets.
+    pub fn filtered_by_targets(self, target_indices: &[u32]) -> Self {
+        let target_indices: AHashSet<u32> = target_indices.iter().copied().collect();
+        self.filtered(|index, _| target_indices.contains(&index))
+    }
+    /// Sort the alignments in each target, and the targets by their first alignment.
+    ///  - For `TargetPosition`, the targets are sorted by their index.
+    pub fn sorted(mut self, order: AlignmentOrder) -> Self {
+        self.0.iter_mut().for_each(|target_alignment| {
+            target_alignment.sort(order);
+        });
+        self.sort_targets(order);
+        self
+    }
+    /// Keep at most `k` best alignments for each target.
+    pub fn best_k_per_target(mut self, k: usize, order: AlignmentOrder) -> Self {
+        self.0.iter_mut().for_each(|target_alignment| {
+            target_alignment.sort(order);
+            target_alignment.alignments.truncate(k);
+        });
+        self.0.retain(|target_alignment| !target_alignment.alignments.is_empty());
+        self.sort_targets(order);
+        self
+    }
+    /// Keep the best alignments so that at most `k` alignments cover each position of the query.
+    ///  - The alignments are compared across all targets.
+    ///  - An alignment is kept, if the number of the kept alignments overlapping its query region is less than `k`.
+    pub fn best_k_per_query_region(self, k: usize, order: AlignmentOrder) -> Self {
+        let mut candidates: Vec<(u32, Alignment)> = self.0.into_iter().flat_map(|target_alignment| {
+            let index = target_alignment.index;
+            target_alignment.alignments.into_iter().map(move |alignment| (index, alignment))
+        }).collect();
+        candidates.sort_by(|(index_a, a), (index_b, b)| {
+            order.cmp(a, b).then(index_a.cmp(index_b))
+        });
+
+        let mut kept_query_regions: Vec<(u32, u32)> = Vec::new();
+        let mut kept: Vec<(u32, Alignment)> = Vec::new();
+        candidates.into_iter().for_each(|(index, alignment)| {
+            let (start, end) = alignment.position.query;
+            let overlapped_count = kept_query_regions.iter().filter(|(kept_start, kept_end)| {
+                start < *kept_end && *kept_start < end
+            }).count();
+            if overlapped_count < k {
+                kept_query_regions.push((start, end));
+                kept.push((index, alignment));
+            }
+        });
+
+        // Group by target in the order of the best alignment
+        let mut target_alignments: Vec<TargetAlignment> = Vec::new();
+        let mut order_of_target: AHashMap<u32, usize> = AHashMap::new();
+        kept.into_iter().for_each(|(index, alignment)| {
+            let position = *order_of_target.entry(index).or_insert_with(|| {
+                target_alignments.push(TargetAlignment { index, alignments: Vec::new() });
+                target_alignments.len() - 1
+            });
+            target_alignments[position].alignments.push(alignment);
+        });
+        Self(target_alignments)
+    }
+    /// Merge the alignments of the other result (e.g., the results of the forward and reverse passes).
+    ///  - The alignments of the same target are collected into one `TargetAlignment`.
+    ///  - Identical alignments are kept only once.
+    ///  - The strand of the query is not recorded: keep the results separately if it is needed.
+    pub fn merged(mut self, other: Self) -> Self {
+        let mut position_of_target: AHashMap<u32, usize> = self.0.iter().enumerate().map(|(position, target_alignment)| {
+            (target_alignment.index, position)
+        }).collect();
+        other.0.into_iter().for_each(|target_alignment| {
+            match position_of_target.get(&target_alignment.index) {
+                Some(&position) => {
+                    let existing = &mut self.0[position].alignments;
+                    let mut seen: AHashSet<Alignment> = existing.iter().cloned().collect();
+                    target_alignment.alignments.into_iter().for_each(|alignment| {
+                        if !seen.contains(&alignment) {
+                            seen.insert(alignment.clone());
+                            existing.push(alignment);
+                        }
+                    });
+                },
+                None => {
+                    position_of_target.insert(target_alignment.index, self.0.len());
+                    self.0.push(target_alignment);
+                },
+            }
+        });
+        self
+    }
+    fn sort_targets(&mut self, order: AlignmentOrder) {
+        match order {
+            AlignmentOrder::TargetPosition => {
+                self.0.sort_by_key(|target_alignment| target_alignment.index);
+            },
+            _ => {
+                // Alignments in targets are already sorted
+                self.0.sort_by(|a, b| {
+                    match (a.alignments.first(), b.alignments.first()) {
+                        (Some(first_a), Some(first_b)) => order.cmp(first_a, first_b),
+                        (Some(_), None) => Ordering::Less,
+                        (None, Some(_)) => Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    }.then(a.index.cmp(&b.index))
+                });
+            },
+        }
+    }
+}
+
+impl TargetAlignment {
+    /// Keep the alignments that satisfy the predicate.
+    pub fn filtered<F>(mut self, predicate: F) -> Self where
+        F: FnMut(&Alignment) -> bool,
+    {
+        self.alignments = self.alignments.into_iter().filter(predicate).collect();
+        self
+    }
+    pub fn sorted(mut self, order: AlignmentOrder) -> Self {
+        self.sort(order);
+        self
+    }
+    /// Keep at most `k` best alignments.
+    pub fn best_k(mut self, k: usize, order: AlignmentOrder) -> Self {
+        self.sort(order);
+        self.alignments.truncate(k);
+        self
+    }
+    fn sort(&mut self, order: AlignmentOrder) {
+        self.alignments.sort_by(|a, b| order.cmp(a, b));
+    }
+}
@@ -0,0 +1,27 @@
+/// Implement `to_binary`, `write_as_binary` and `from_binary` with `BinaryEncode`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_translate_between_binary {
+    ( $st: ident ) => {
+        impl $st {
+            pub fn to_binary(&self) -> Vec<u8> {
+                $crate::results::binary::encode_to_vec(self)
+            }
+            pub fn write_as_binary<W: std::io::Write>(&self, mut writer: W) -> Result<(), std::io::Error> {
+                $crate::results::binary::BinaryEncode::encode_to(self, &mut writer)
+            }
+            pub fn from_binary(bytes: &[u8]) -> Result<Self, std::io::Error> {
+                $crate::results::binary::decode_whole_bytes(bytes)
+            }
+        }
+    };
+}
+
+use super::{
+    QueryAlignment,
+    TargetAlignment,
+    Alignment,
+};
+impl_translate_between_binary!(QueryAlignment);
+impl_translate_between_binary!(TargetAlignment);
+impl_translate_between_binary!(Alignment);
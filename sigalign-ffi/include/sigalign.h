@@ -0,0 +1,248 @@
+/* C API of SigAlign. Generated by cbindgen from `sigalign-ffi`; do not edit by hand. */
+
+#ifndef SIGALIGN_H
+#define SIGALIGN_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Result of the fallible functions.
+typedef enum SigalignStatus {
+  SIGALIGN_STATUS_OK = 0,
+  // A required pointer is NULL.
+  SIGALIGN_STATUS_NULL_POINTER = 1,
+  // Invalid value of the argument (e.g., parameters of the aligner, non UTF-8 string).
+  SIGALIGN_STATUS_INVALID_ARGUMENT = 2,
+  // Failed to read or write the file.
+  SIGALIGN_STATUS_IO = 3,
+  // Failed to build the reference.
+  SIGALIGN_STATUS_BUILD = 4,
+  // Failed to load the reference (e.g., not a reference file, or corrupted).
+  SIGALIGN_STATUS_LOAD = 5,
+  // Unexpected internal error. Please report it as a bug.
+  SIGALIGN_STATUS_PANIC = 99,
+} SigalignStatus;
+
+// Aligner with the penalties, the cutoffs and the algorithm.
+typedef struct SigalignAligner SigalignAligner;
+
+// Reference for the alignment.
+typedef struct SigalignReference SigalignReference;
+
+// Builder of `SigalignReference`.
+typedef struct SigalignReferenceBuilder SigalignReferenceBuilder;
+
+// Alignments of a query.
+typedef struct SigalignResult SigalignResult;
+
+// Options to select the algorithm of the aligner. NULL options are the same as all zeros (local alignment).
+typedef struct SigalignAlignerOptions {
+  // Semi-global alignment, instead of local alignment.
+  bool semi_global;
+  // Stop after this number of alignments per query. 0 for no limit.
+  uint32_t limit;
+  // Align the query in chunks of this size. 0 for no chunk.
+  uint32_t chunk_size;
+  // Step between the chunks. Used with `chunk_size`.
+  uint32_t chunk_step;
+} SigalignAlignerOptions;
+
+// An alignment of the query to a target.
+//  - The positions are 0-based and half-open ([start, end)).
+typedef struct SigalignAlignment {
+  uint32_t target_index;
+  uint32_t penalty;
+  uint32_t length;
+  uint32_t query_start;
+  uint32_t query_end;
+  uint32_t target_start;
+  uint32_t target_end;
+  // CIGAR string with `=` and `X`. Owned by the result; valid until the result is freed.
+  const char *cigar;
+} SigalignAlignment;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Message of the error of the last failed call in the current thread, or NULL if no call has failed.
+// The string is owned by the library, and is valid until the next failed call in the same thread.
+const char *sigalign_last_error_message(void);
+
+// Make a new builder with the default options.
+//  - All bases are converted to uppercase.
+//  - No bases are ignored.
+//  - The alphabet is not validated, and DNA is assumed.
+SigalignReferenceBuilder *sigalign_reference_builder_new(void);
+
+// Convert all bases to uppercase when building.
+//
+// # Safety
+// `builder` must be a valid handle.
+SigalignStatus sigalign_reference_builder_set_uppercase(SigalignReferenceBuilder *builder,
+                                                        bool uppercase);
+
+// Add the bases that never match to any other bases (e.g., "N").
+//
+// # Safety
+// `builder` must be a valid handle, and `bases` a NUL-terminated string.
+SigalignStatus sigalign_reference_builder_ignore_bases(SigalignReferenceBuilder *builder,
+                                                       const char *bases);
+
+// Set the alphabet of the sequences by the name (e.g., "DNA", "RNA", "PROTEIN"), to validate the sequences.
+//
+// # Safety
+// `builder` must be a valid handle, and `name` a NUL-terminated string.
+SigalignStatus sigalign_reference_builder_set_alphabet(SigalignReferenceBuilder *builder,
+                                                       const char *name);
+
+// Add a target sequence with the label.
+//
+// # Safety
+// `builder` must be a valid handle, `label` a NUL-terminated string, and `sequence` valid for `sequence_len` bytes.
+SigalignStatus sigalign_reference_builder_add_target(SigalignReferenceBuilder *builder,
+                                                     const char *label,
+                                                     const uint8_t *sequence,
+                                                     size_t sequence_len);
+
+// Add the targets in the FASTA formatted bytes.
+//
+// # Safety
+// `builder` must be a valid handle, and `data` valid for `data_len` bytes.
+SigalignStatus sigalign_reference_builder_add_fasta(SigalignReferenceBuilder *builder,
+                                                    const uint8_t *data,
+                                                    size_t data_len);
+
+// Add the targets in the FASTA file (can be compressed).
+//
+// # Safety
+// `builder` must be a valid handle, and `path` a NUL-terminated string.
+SigalignStatus sigalign_reference_builder_add_fasta_file(SigalignReferenceBuilder *builder,
+                                                         const char *path);
+
+// Build the reference to `out`.
+// The builder is consumed and freed, whether the build succeeds or not.
+//
+// # Safety
+// `builder` must be a valid handle, not used after this call, and `out` a valid pointer.
+SigalignStatus sigalign_reference_builder_build(SigalignReferenceBuilder *builder,
+                                                SigalignReference **out);
+
+// Free the builder that is not built.
+//
+// # Safety
+// `builder` must be NULL or a valid handle, not used after this call.
+void sigalign_reference_builder_free(SigalignReferenceBuilder *builder);
+
+// Load the reference from the file saved by `sigalign_reference_save_file`.
+//
+// # Safety
+// `path` must be a NUL-terminated string, and `out` a valid pointer.
+SigalignStatus sigalign_reference_load_file(const char *path, SigalignReference **out);
+
+// Load the reference from the bytes of the reference file.
+//
+// # Safety
+// `data` must be valid for `data_len` bytes, and `out` a valid pointer.
+SigalignStatus sigalign_reference_load_bytes(const uint8_t *data,
+                                             size_t data_len,
+                                             SigalignReference **out);
+
+// Save the reference to the file.
+//
+// # Safety
+// `reference` must be a valid handle, and `path` a NUL-terminated string.
+SigalignStatus sigalign_reference_save_file(const SigalignReference *reference, const char *path);
+
+// Number of the targets. 0 if `reference` is NULL.
+//
+// # Safety
+// `reference` must be NULL or a valid handle.
+uint32_t sigalign_reference_num_targets(const SigalignReference *reference);
+
+// Total length of the target sequences. 0 if `reference` is NULL.
+//
+// # Safety
+// `reference` must be NULL or a valid handle.
+uint32_t sigalign_reference_total_length(const SigalignReference *reference);
+
+// Label of the target to `out`. The string must be freed by `sigalign_string_free`.
+//
+// # Safety
+// `reference` must be a valid handle, and `out` a valid pointer.
+SigalignStatus sigalign_reference_get_label(const SigalignReference *reference,
+                                            uint32_t target_index,
+                                            char **out);
+
+// Free the reference.
+//
+// # Safety
+// `reference` must be NULL or a valid handle, not used after this call.
+void sigalign_reference_free(SigalignReference *reference);
+
+// Make a new aligner to `out`.
+//  - Penalties: mismatch (`px`, > 0), gap-open (`po`) and gap-extend (`pe`, > 0).
+//  - Cutoffs: minimum aligned length (`minl`) and maximum penalty per length (`maxp`).
+//
+// # Safety
+// `options` must be NULL or a valid pointer, and `out` a valid pointer.
+SigalignStatus sigalign_aligner_new(uint32_t px,
+                                    uint32_t po,
+                                    uint32_t pe,
+                                    uint32_t minl,
+                                    float maxp,
+                                    const SigalignAlignerOptions *options,
+                                    SigalignAligner **out);
+
+// Free the aligner.
+//
+// # Safety
+// `aligner` must be NULL or a valid handle, not used after this call.
+void sigalign_aligner_free(SigalignAligner *aligner);
+
+// Align the query to the reference, and write the result to `out`.
+// The alignments are sorted by the penalty, and the result must be freed by `sigalign_result_free`.
+//
+// # Safety
+// `aligner` and `reference` must be valid handles, `query` valid for `query_len` bytes, and `out` a valid pointer.
+SigalignStatus sigalign_aligner_align(SigalignAligner *aligner,
+                                      const SigalignReference *reference,
+                                      const uint8_t *query,
+                                      size_t query_len,
+                                      SigalignResult **out);
+
+// Number of the alignments in the result. 0 if `result` is NULL.
+//
+// # Safety
+// `result` must be NULL or a valid handle.
+size_t sigalign_result_num_alignments(const SigalignResult *result);
+
+// Write the alignment at `index` (< `sigalign_result_num_alignments`) to `out`.
+//
+// # Safety
+// `result` must be a valid handle, and `out` a valid pointer.
+SigalignStatus sigalign_result_get_alignment(const SigalignResult *result,
+                                             size_t index,
+                                             SigalignAlignment *out);
+
+// Free the result. The CIGAR strings of its alignments are freed together.
+//
+// # Safety
+// `result` must be NULL or a valid handle, not used after this call.
+void sigalign_result_free(SigalignResult *result);
+
+// Version of the library (e.g., "0.1.0"). Static; never freed.
+const char *sigalign_version(void);
+
+// Free the string returned by this library (e.g., `sigalign_reference_get_label`).
+//
+// # Safety
+// `string` must be NULL or a string returned by this library, not freed yet.
+void sigalign_string_free(char *string);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SIGALIGN_H */
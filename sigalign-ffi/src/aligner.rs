@@ -0,0 +1,154 @@
+use sigalign::{
+    algorithms::{
+        Local, LocalWithChunk, LocalWithLimit, ParamsError, SemiGlobal, SemiGlobalWithChunk, SemiGlobalWithLimit,
+    },
+    results::QueryAlignment,
+    Aligner, Reference,
+};
+
+use crate::{
+    bytes_from_ptr,
+    error::{as_mut, as_ref, check_out, run, write_out, Error, SigalignStatus},
+    reference::SigalignReference,
+    result::SigalignResult,
+};
+
+/// Options to select the algorithm of the aligner. NULL options are the same as all zeros (local alignment).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SigalignAlignerOptions {
+    /// Semi-global alignment, instead of local alignment.
+    pub semi_global: bool,
+    /// Stop after this number of alignments per query. 0 for no limit.
+    pub limit: u32,
+    /// Align the query in chunks of this size. 0 for no chunk.
+    pub chunk_size: u32,
+    /// Step between the chunks. Used with `chunk_size`.
+    pub chunk_step: u32,
+}
+
+enum AlignerWrapper {
+    Local(Aligner<Local>),
+    LocalWithLimit(Aligner<LocalWithLimit>),
+    LocalWithChunk(Aligner<LocalWithChunk>),
+    SemiGlobal(Aligner<SemiGlobal>),
+    SemiGlobalWithLimit(Aligner<SemiGlobalWithLimit>),
+    SemiGlobalWithChunk(Aligner<SemiGlobalWithChunk>),
+}
+
+/// Aligner with the penalties, the cutoffs and the algorithm.
+pub struct SigalignAligner {
+    inner: AlignerWrapper,
+}
+
+fn params_error(error: ParamsError) -> Error {
+    Error::invalid_argument(error.to_string())
+}
+
+impl AlignerWrapper {
+    fn new(
+        px: u32,
+        po: u32,
+        pe: u32,
+        minl: u32,
+        maxp: f32,
+        options: SigalignAlignerOptions,
+    ) -> Result<Self, Error> {
+        let limit = (options.limit != 0).then_some(options.limit);
+        let chunk = (options.chunk_size != 0).then_some((options.chunk_size, options.chunk_step));
+        let wrapper = match (options.semi_global, limit, chunk) {
+            (_, Some(_), Some(_)) => {
+                return Err(Error::invalid_argument("'limit' and 'chunk_size' cannot be used together"));
+            },
+            (false, None, None) => Self::Local(Aligner::new(
+                Local::new(px, po, pe, minl, maxp).map_err(params_error)?
+            )),
+            (false, Some(limit), None) => Self::LocalWithLimit(Aligner::new(
+                LocalWithLimit::new(px, po, pe, minl, maxp, limit).map_err(params_error)?
+            )),
+            (false, None, Some((size, step))) => Self::LocalWithChunk(Aligner::new(
+                LocalWithChunk::new(px, po, pe, minl, maxp, size, step).map_err(params_error)?
+            )),
+            (true, None, None) => Self::SemiGlobal(Aligner::new(
+                SemiGlobal::new(px, po, pe, minl, maxp).map_err(params_error)?
+            )),
+            (true, Some(limit), None) => Self::SemiGlobalWithLimit(Aligner::new(
+                SemiGlobalWithLimit::new(px, po, pe, minl, maxp, limit).map_err(params_error)?
+            )),
+            (true, None, Some((size, step))) => Self::SemiGlobalWithChunk(Aligner::new(
+                SemiGlobalWithChunk::new(px, po, pe, minl, maxp, size, step).map_err(params_error)?
+            )),
+        };
+        Ok(wrapper)
+    }
+    fn align(&mut self, query: &[u8], reference: &Reference) -> QueryAlignment {
+        match self {
+            Self::Local(v) => v.align(query, reference),
+            Self::LocalWithLimit(v) => v.align(query, reference),
+            Self::LocalWithChunk(v) => v.align(query, reference),
+            Self::SemiGlobal(v) => v.align(query, reference),
+            Self::SemiGlobalWithLimit(v) => v.align(query, reference),
+            Self::SemiGlobalWithChunk(v) => v.align(query, reference),
+        }
+    }
+}
+
+/// Make a new aligner to `out`.
+///  - Penalties: mismatch (`px`, > 0), gap-open (`po`) and gap-extend (`pe`, > 0).
+///  - Cutoffs: minimum aligned length (`minl`) and maximum penalty per length (`maxp`).
+///
+/// # Safety
+/// `options` must be NULL or a valid pointer, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_aligner_new(
+    px: u32,
+    po: u32,
+    pe: u32,
+    minl: u32,
+    maxp: f32,
+    options: *const SigalignAlignerOptions,
+    out: *mut *mut SigalignAligner,
+) -> SigalignStatus {
+    run(|| {
+        check_out(out)?;
+        let options = options.as_ref().copied().unwrap_or_default();
+        let inner = AlignerWrapper::new(px, po, pe, minl, maxp, options)?;
+        write_out(out, Box::into_raw(Box::new(SigalignAligner { inner })));
+        Ok(())
+    })
+}
+
+/// Free the aligner.
+///
+/// # Safety
+/// `aligner` must be NULL or a valid handle, not used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_aligner_free(aligner: *mut SigalignAligner) {
+    if !aligner.is_null() {
+        drop(Box::from_raw(aligner));
+    }
+}
+
+/// Align the query to the reference, and write the result to `out`.
+/// The alignments are sorted by the penalty, and the result must be freed by `sigalign_result_free`.
+///
+/// # Safety
+/// `aligner` and `reference` must be valid handles, `query` valid for `query_len` bytes, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_aligner_align(
+    aligner: *mut SigalignAligner,
+    reference: *const SigalignReference,
+    query: *const u8,
+    query_len: usize,
+    out: *mut *mut SigalignResult,
+) -> SigalignStatus {
+    run(|| {
+        let aligner = as_mut(aligner, "aligner")?;
+        let reference = as_ref(reference, "reference")?;
+        let query = bytes_from_ptr(query, query_len, "query")?;
+        check_out(out)?;
+        let query_alignment = aligner.inner.align(query, &reference.inner);
+        write_out(out, Box::into_raw(Box::new(SigalignResult::new(query_alignment))));
+        Ok(())
+    })
+}
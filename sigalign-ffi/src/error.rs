@@ -0,0 +1,105 @@
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+/// Result of the fallible functions.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigalignStatus {
+    Ok = 0,
+    /// A required pointer is NULL.
+    NullPointer = 1,
+    /// Invalid value of the argument (e.g., parameters of the aligner, non UTF-8 string).
+    InvalidArgument = 2,
+    /// Failed to read or write the file.
+    Io = 3,
+    /// Failed to build the reference.
+    Build = 4,
+    /// Failed to load the reference (e.g., not a reference file, or corrupted).
+    Load = 5,
+    /// Unexpected internal error. Please report it as a bug.
+    Panic = 99,
+}
+
+pub(crate) struct Error {
+    status: SigalignStatus,
+    message: String,
+}
+
+impl Error {
+    pub(crate) fn new(status: SigalignStatus, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+    pub(crate) fn null_pointer(name: &str) -> Self {
+        Self::new(SigalignStatus::NullPointer, format!("'{}' is NULL", name))
+    }
+    pub(crate) fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(SigalignStatus::InvalidArgument, message)
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Interior NUL bytes cannot be in the C string
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|x| *x.borrow_mut() = Some(message));
+}
+
+/// Message of the error of the last failed call in the current thread, or NULL if no call has failed.
+/// The string is owned by the library, and is valid until the next failed call in the same thread.
+#[no_mangle]
+pub extern "C" fn sigalign_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|x| match x.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Run the body of the function. Errors and panics are converted to the status.
+pub(crate) fn run<F>(body: F) -> SigalignStatus where
+    F: FnOnce() -> Result<(), Error>,
+{
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => SigalignStatus::Ok,
+        Ok(Err(error)) => {
+            set_last_error(error.message);
+            error.status
+        },
+        Err(panic) => {
+            let message = panic.downcast_ref::<&str>().map(|x| x.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Unknown panic".to_string());
+            set_last_error(format!("Internal error: {}", message));
+            SigalignStatus::Panic
+        },
+    }
+}
+
+/// Write the value to the output pointer.
+pub(crate) unsafe fn write_out<T>(out: *mut T, value: T) {
+    // `out` is checked before doing the work
+    out.write(value);
+}
+
+pub(crate) fn check_out<T>(out: *mut T) -> Result<(), Error> {
+    if out.is_null() {
+        Err(Error::null_pointer("out"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Borrow the object of the handle.
+pub(crate) unsafe fn as_ref<'a, T>(ptr: *const T, name: &str) -> Result<&'a T, Error> {
+    ptr.as_ref().ok_or_else(|| Error::null_pointer(name))
+}
+
+pub(crate) unsafe fn as_mut<'a, T>(ptr: *mut T, name: &str) -> Result<&'a mut T, Error> {
+    ptr.as_mut().ok_or_else(|| Error::null_pointer(name))
+}
@@ -0,0 +1,113 @@
+/*!
+C API of SigAlign, for the tools not written in Rust.
+
+- All objects are opaque handles, created by the `*_new`, `*_build` or `*_load_*` functions and released by the matching `*_free` function.
+- Fallible functions return a `SigalignStatus`. On failure, `sigalign_last_error_message` describes the error of the last failed call in the current thread.
+- The handles are not synchronized. Use one `SigalignAligner` per thread; a `SigalignReference` can be shared by the threads once built.
+- The header (`include/sigalign.h`) is generated by `cbindgen` with `cbindgen.toml`.
+
+Example:
+```c
+SigalignReferenceBuilder *builder = sigalign_reference_builder_new();
+sigalign_reference_builder_add_fasta_file(builder, "reference.fa");
+SigalignReference *reference = NULL;
+if (sigalign_reference_builder_build(builder, &reference) != SIGALIGN_STATUS_OK) {
+    fprintf(stderr, "%s\n", sigalign_last_error_message());
+}
+
+SigalignAligner *aligner = NULL;
+sigalign_aligner_new(4, 6, 2, 50, 0.2f, NULL, &aligner);
+
+SigalignResult *result = NULL;
+sigalign_aligner_align(aligner, reference, query, query_len, &result);
+SigalignAlignment alignment;
+for (size_t i = 0; i < sigalign_result_num_alignments(result); i++) {
+    sigalign_result_get_alignment(result, i, &alignment);
+    printf("%u\t%u\t%s\n", alignment.target_index, alignment.penalty, alignment.cigar);
+}
+
+sigalign_result_free(result);
+sigalign_aligner_free(aligner);
+sigalign_reference_free(reference);
+```
+*/
+use std::ffi::{c_char, CStr, CString};
+
+mod error;
+mod reference;
+mod aligner;
+mod result;
+
+pub use error::{SigalignStatus, sigalign_last_error_message};
+pub use reference::{
+    SigalignReferenceBuilder,
+    SigalignReference,
+    sigalign_reference_builder_new,
+    sigalign_reference_builder_set_uppercase,
+    sigalign_reference_builder_ignore_bases,
+    sigalign_reference_builder_set_alphabet,
+    sigalign_reference_builder_add_target,
+    sigalign_reference_builder_add_fasta,
+    sigalign_reference_builder_add_fasta_file,
+    sigalign_reference_builder_build,
+    sigalign_reference_builder_free,
+    sigalign_reference_load_file,
+    sigalign_reference_load_bytes,
+    sigalign_reference_save_file,
+    sigalign_reference_num_targets,
+    sigalign_reference_total_length,
+    sigalign_reference_get_label,
+    sigalign_reference_free,
+};
+pub use aligner::{
+    SigalignAligner,
+    SigalignAlignerOptions,
+    sigalign_aligner_new,
+    sigalign_aligner_free,
+    sigalign_aligner_align,
+};
+pub use result::{
+    SigalignResult,
+    SigalignAlignment,
+    sigalign_result_num_alignments,
+    sigalign_result_get_alignment,
+    sigalign_result_free,
+};
+
+/// Version of the library (e.g., "0.1.0"). Static; never freed.
+#[no_mangle]
+pub extern "C" fn sigalign_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Free the string returned by this library (e.g., `sigalign_reference_get_label`).
+///
+/// # Safety
+/// `string` must be NULL or a string returned by this library, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+// Borrow the NUL-terminated UTF-8 string
+unsafe fn str_from_ptr<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, error::Error> {
+    if ptr.is_null() {
+        return Err(error::Error::null_pointer(name));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        error::Error::invalid_argument(format!("'{}' is not a valid UTF-8 string", name))
+    })
+}
+
+// Borrow the bytes. NULL is allowed for the empty bytes.
+unsafe fn bytes_from_ptr<'a>(ptr: *const u8, len: usize, name: &str) -> Result<&'a [u8], error::Error> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(error::Error::null_pointer(name))
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
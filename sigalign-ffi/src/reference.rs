@@ -0,0 +1,310 @@
+use std::{
+    ffi::{c_char, CString},
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+};
+
+use sigalign::{
+    Alphabet, Reference, ReferenceBuilder, ReferenceBuildError, ReferenceLoadError,
+};
+
+use crate::{
+    bytes_from_ptr, str_from_ptr,
+    error::{as_mut, as_ref, check_out, run, write_out, Error, SigalignStatus},
+};
+
+/// Builder of `SigalignReference`.
+pub struct SigalignReferenceBuilder {
+    // `None` after a failed call consumed the builder
+    inner: Option<ReferenceBuilder>,
+}
+
+/// Reference for the alignment.
+pub struct SigalignReference {
+    pub(crate) inner: Reference,
+}
+
+fn build_error(error: ReferenceBuildError) -> Error {
+    let status = match error {
+        ReferenceBuildError::IoError(_) | ReferenceBuildError::OpenFileError(_) => SigalignStatus::Io,
+        _ => SigalignStatus::Build,
+    };
+    Error::new(status, error.to_string())
+}
+fn load_error(error: ReferenceLoadError) -> Error {
+    let status = match error {
+        ReferenceLoadError::IoError(_) => SigalignStatus::Io,
+        _ => SigalignStatus::Load,
+    };
+    Error::new(status, error.to_string())
+}
+fn io_error(error: std::io::Error, path: &str) -> Error {
+    Error::new(SigalignStatus::Io, format!("{}: {}", path, error))
+}
+
+// Apply the configuration to the builder.
+//  - The arguments are validated before, so that the invalid ones leave the builder usable.
+//  - Only the failure of `update` (e.g., reading the file), which consumes the builder, makes it unusable.
+unsafe fn update_builder<F>(builder: *mut SigalignReferenceBuilder, update: F) -> Result<(), Error> where
+    F: FnOnce(ReferenceBuilder) -> Result<ReferenceBuilder, Error>,
+{
+    let builder = as_mut(builder, "builder")?;
+    let inner = builder.inner.take().ok_or_else(|| {
+        Error::invalid_argument("The builder is no longer usable after a failed call")
+    })?;
+    builder.inner = Some(update(inner)?);
+    Ok(())
+}
+
+/* Builder */
+/// Make a new builder with the default options.
+///  - All bases are converted to uppercase.
+///  - No bases are ignored.
+///  - The alphabet is not validated, and DNA is assumed.
+#[no_mangle]
+pub extern "C" fn sigalign_reference_builder_new() -> *mut SigalignReferenceBuilder {
+    Box::into_raw(Box::new(SigalignReferenceBuilder { inner: Some(ReferenceBuilder::new()) }))
+}
+
+/// Convert all bases to uppercase when building.
+///
+/// # Safety
+/// `builder` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_set_uppercase(
+    builder: *mut SigalignReferenceBuilder,
+    uppercase: bool,
+) -> SigalignStatus {
+    run(|| update_builder(builder, |x| Ok(x.set_uppercase(uppercase))))
+}
+
+/// Add the bases that never match to any other bases (e.g., "N").
+///
+/// # Safety
+/// `builder` must be a valid handle, and `bases` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_ignore_bases(
+    builder: *mut SigalignReferenceBuilder,
+    bases: *const c_char,
+) -> SigalignStatus {
+    run(|| {
+        let bases = str_from_ptr(bases, "bases")?;
+        update_builder(builder, |x| Ok(x.ignore_bases(bases.as_bytes())))
+    })
+}
+
+/// Set the alphabet of the sequences by the name (e.g., "DNA", "RNA", "PROTEIN"), to validate the sequences.
+///
+/// # Safety
+/// `builder` must be a valid handle, and `name` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_set_alphabet(
+    builder: *mut SigalignReferenceBuilder,
+    name: *const c_char,
+) -> SigalignStatus {
+    run(|| {
+        let name = str_from_ptr(name, "name")?;
+        let alphabet = Alphabet::from_name(name).ok_or_else(|| {
+            Error::invalid_argument(format!("Unknown alphabet: {}", name))
+        })?;
+        update_builder(builder, |x| Ok(x.set_alphabet(alphabet)))
+    })
+}
+
+/// Add a target sequence with the label.
+///
+/// # Safety
+/// `builder` must be a valid handle, `label` a NUL-terminated string, and `sequence` valid for `sequence_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_add_target(
+    builder: *mut SigalignReferenceBuilder,
+    label: *const c_char,
+    sequence: *const u8,
+    sequence_len: usize,
+) -> SigalignStatus {
+    run(|| {
+        let label = str_from_ptr(label, "label")?;
+        let sequence = bytes_from_ptr(sequence, sequence_len, "sequence")?;
+        update_builder(builder, |x| Ok(x.add_target(label, sequence)))
+    })
+}
+
+/// Add the targets in the FASTA formatted bytes.
+///
+/// # Safety
+/// `builder` must be a valid handle, and `data` valid for `data_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_add_fasta(
+    builder: *mut SigalignReferenceBuilder,
+    data: *const u8,
+    data_len: usize,
+) -> SigalignStatus {
+    run(|| {
+        let data = bytes_from_ptr(data, data_len, "data")?;
+        update_builder(builder, |x| x.add_fasta(data).map_err(build_error))
+    })
+}
+
+/// Add the targets in the FASTA file (can be compressed).
+///
+/// # Safety
+/// `builder` must be a valid handle, and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_add_fasta_file(
+    builder: *mut SigalignReferenceBuilder,
+    path: *const c_char,
+) -> SigalignStatus {
+    run(|| {
+        let path = str_from_ptr(path, "path")?;
+        update_builder(builder, |x| x.add_fasta_file(path).map_err(build_error))
+    })
+}
+
+/// Build the reference to `out`.
+/// The builder is consumed and freed, whether the build succeeds or not.
+///
+/// # Safety
+/// `builder` must be a valid handle, not used after this call, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_build(
+    builder: *mut SigalignReferenceBuilder,
+    out: *mut *mut SigalignReference,
+) -> SigalignStatus {
+    run(|| {
+        if builder.is_null() {
+            return Err(Error::null_pointer("builder"));
+        }
+        let builder = Box::from_raw(builder);
+        check_out(out)?;
+        let inner = builder.inner.ok_or_else(|| {
+            Error::invalid_argument("The builder is no longer usable after a failed call")
+        })?;
+        let reference = inner.build().map_err(build_error)?;
+        write_out(out, Box::into_raw(Box::new(SigalignReference { inner: reference })));
+        Ok(())
+    })
+}
+
+/// Free the builder that is not built.
+///
+/// # Safety
+/// `builder` must be NULL or a valid handle, not used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_builder_free(builder: *mut SigalignReferenceBuilder) {
+    if !builder.is_null() {
+        drop(Box::from_raw(builder));
+    }
+}
+
+/* Reference */
+/// Load the reference from the file saved by `sigalign_reference_save_file`.
+///
+/// # Safety
+/// `path` must be a NUL-terminated string, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_load_file(
+    path: *const c_char,
+    out: *mut *mut SigalignReference,
+) -> SigalignStatus {
+    run(|| {
+        let path = str_from_ptr(path, "path")?;
+        check_out(out)?;
+        let file = File::open(path).map_err(|e| io_error(e, path))?;
+        let reference = Reference::load_from(BufReader::new(file)).map_err(load_error)?;
+        write_out(out, Box::into_raw(Box::new(SigalignReference { inner: reference })));
+        Ok(())
+    })
+}
+
+/// Load the reference from the bytes of the reference file.
+///
+/// # Safety
+/// `data` must be valid for `data_len` bytes, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_load_bytes(
+    data: *const u8,
+    data_len: usize,
+    out: *mut *mut SigalignReference,
+) -> SigalignStatus {
+    run(|| {
+        let data = bytes_from_ptr(data, data_len, "data")?;
+        check_out(out)?;
+        let reference = Reference::load_from(data).map_err(load_error)?;
+        write_out(out, Box::into_raw(Box::new(SigalignReference { inner: reference })));
+        Ok(())
+    })
+}
+
+/// Save the reference to the file.
+///
+/// # Safety
+/// `reference` must be a valid handle, and `path` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_save_file(
+    reference: *const SigalignReference,
+    path: *const c_char,
+) -> SigalignStatus {
+    run(|| {
+        let reference = as_ref(reference, "reference")?;
+        let path = str_from_ptr(path, "path")?;
+        let file = File::create(path).map_err(|e| io_error(e, path))?;
+        let mut writer = BufWriter::new(file);
+        reference.inner.save_to(&mut writer).map_err(|e| io_error(e, path))?;
+        writer.flush().map_err(|e| io_error(e, path))?;
+        Ok(())
+    })
+}
+
+/// Number of the targets. 0 if `reference` is NULL.
+///
+/// # Safety
+/// `reference` must be NULL or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_num_targets(reference: *const SigalignReference) -> u32 {
+    reference.as_ref().map(|x| x.inner.get_num_targets()).unwrap_or(0)
+}
+
+/// Total length of the target sequences. 0 if `reference` is NULL.
+///
+/// # Safety
+/// `reference` must be NULL or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_total_length(reference: *const SigalignReference) -> u32 {
+    reference.as_ref().map(|x| x.inner.get_total_length()).unwrap_or(0)
+}
+
+/// Label of the target to `out`. The string must be freed by `sigalign_string_free`.
+///
+/// # Safety
+/// `reference` must be a valid handle, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_get_label(
+    reference: *const SigalignReference,
+    target_index: u32,
+    out: *mut *mut c_char,
+) -> SigalignStatus {
+    run(|| {
+        let reference = as_ref(reference, "reference")?;
+        check_out(out)?;
+        let label = reference.inner.get_label_str(target_index).ok_or_else(|| {
+            Error::invalid_argument(format!("Target index {} is out of range", target_index))
+        })?;
+        let label = CString::new(label).map_err(|_| {
+            Error::invalid_argument("The label contains a NUL byte")
+        })?;
+        write_out(out, label.into_raw());
+        Ok(())
+    })
+}
+
+/// Free the reference.
+///
+/// # Safety
+/// `reference` must be NULL or a valid handle, not used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_reference_free(reference: *mut SigalignReference) {
+    if !reference.is_null() {
+        drop(Box::from_raw(reference));
+    }
+}
+
@@ -0,0 +1,94 @@
+use std::ffi::{c_char, CString};
+
+use sigalign::results::{Alignment, AlignmentOrder, QueryAlignment};
+
+use crate::error::{as_ref, check_out, run, write_out, Error, SigalignStatus};
+
+/// Alignments of a query.
+pub struct SigalignResult {
+    alignments: Vec<(u32, Alignment)>,
+    cigars: Vec<CString>,
+}
+
+/// An alignment of the query to a target.
+///  - The positions are 0-based and half-open ([start, end)).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SigalignAlignment {
+    pub target_index: u32,
+    pub penalty: u32,
+    pub length: u32,
+    pub query_start: u32,
+    pub query_end: u32,
+    pub target_start: u32,
+    pub target_end: u32,
+    /// CIGAR string with `=` and `X`. Owned by the result; valid until the result is freed.
+    pub cigar: *const c_char,
+}
+
+impl SigalignResult {
+    pub(crate) fn new(query_alignment: QueryAlignment) -> Self {
+        let alignments: Vec<(u32, Alignment)> = query_alignment.sorted(AlignmentOrder::Penalty).0
+            .into_iter()
+            .flat_map(|target_alignment| {
+                let index = target_alignment.index;
+                target_alignment.alignments.into_iter().map(move |alignment| (index, alignment))
+            })
+            .collect();
+        // CIGAR strings never have NUL bytes
+        let cigars = alignments.iter()
+            .map(|(_, alignment)| CString::new(alignment.to_cigar()).unwrap_or_default())
+            .collect();
+        Self { alignments, cigars }
+    }
+}
+
+/// Number of the alignments in the result. 0 if `result` is NULL.
+///
+/// # Safety
+/// `result` must be NULL or a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_result_num_alignments(result: *const SigalignResult) -> usize {
+    result.as_ref().map(|x| x.alignments.len()).unwrap_or(0)
+}
+
+/// Write the alignment at `index` (< `sigalign_result_num_alignments`) to `out`.
+///
+/// # Safety
+/// `result` must be a valid handle, and `out` a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_result_get_alignment(
+    result: *const SigalignResult,
+    index: usize,
+    out: *mut SigalignAlignment,
+) -> SigalignStatus {
+    run(|| {
+        let result = as_ref(result, "result")?;
+        check_out(out)?;
+        let (target_index, alignment) = result.alignments.get(index).ok_or_else(|| {
+            Error::invalid_argument(format!("Alignment index {} is out of range", index))
+        })?;
+        write_out(out, SigalignAlignment {
+            target_index: *target_index,
+            penalty: alignment.penalty,
+            length: alignment.length,
+            query_start: alignment.position.query.0,
+            query_end: alignment.position.query.1,
+            target_start: alignment.position.target.0,
+            target_end: alignment.position.target.1,
+            cigar: result.cigars[index].as_ptr(),
+        });
+        Ok(())
+    })
+}
+
+/// Free the result. The CIGAR strings of its alignments are freed together.
+///
+/// # Safety
+/// `result` must be NULL or a valid handle, not used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn sigalign_result_free(result: *mut SigalignResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
@@ -0,0 +1,190 @@
+//! The C harness (`tests/c`) is run with `make test`.
+use std::ffi::{CStr, CString};
+use std::ptr;
+
+use sigalign_ffi::*;
+
+fn random_sequence(length: usize, seed: &mut u64) -> Vec<u8> {
+    (0..length).map(|_| {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        b"ACGT"[(*seed >> 33) as usize % 4]
+    }).collect()
+}
+
+// Three targets of 1000 bp, and a query from the second one with a mismatch
+fn build_reference() -> (*mut SigalignReference, Vec<u8>) {
+    let mut seed = 11;
+    let targets: Vec<Vec<u8>> = (0..3).map(|_| random_sequence(1000, &mut seed)).collect();
+    let mut query = targets[1][200..400].to_vec();
+    query[100] = if query[100] == b'A' { b'C' } else { b'A' };
+
+    let mut reference = ptr::null_mut();
+    unsafe {
+        let builder = sigalign_reference_builder_new();
+        for (index, target) in targets.iter().enumerate() {
+            let label = CString::new(format!("target_{}", index)).unwrap();
+            let status = sigalign_reference_builder_add_target(builder, label.as_ptr(), target.as_ptr(), target.len());
+            assert_eq!(status, SigalignStatus::Ok);
+        }
+        assert_eq!(sigalign_reference_builder_build(builder, &mut reference), SigalignStatus::Ok);
+    }
+    (reference, query)
+}
+
+fn last_error_message() -> String {
+    let message = sigalign_last_error_message();
+    assert!(!message.is_null());
+    unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string()
+}
+
+// (target_index, penalty, query_position, target_position, cigar)
+type FlatAlignment = (u32, u32, (u32, u32), (u32, u32), String);
+
+unsafe fn align(
+    aligner: *mut SigalignAligner,
+    reference: *const SigalignReference,
+    query: &[u8],
+) -> Vec<FlatAlignment> {
+    let mut result = ptr::null_mut();
+    assert_eq!(sigalign_aligner_align(aligner, reference, query.as_ptr(), query.len(), &mut result), SigalignStatus::Ok);
+    let alignments = (0..sigalign_result_num_alignments(result)).map(|index| {
+        let mut alignment = std::mem::zeroed::<SigalignAlignment>();
+        assert_eq!(sigalign_result_get_alignment(result, index, &mut alignment), SigalignStatus::Ok);
+        (
+            alignment.target_index,
+            alignment.penalty,
+            (alignment.query_start, alignment.query_end),
+            (alignment.target_start, alignment.target_end),
+            CStr::from_ptr(alignment.cigar).to_str().unwrap().to_string(),
+        )
+    }).collect();
+    sigalign_result_free(result);
+    alignments
+}
+
+#[test]
+fn build_reference_and_align() {
+    let (reference, query) = build_reference();
+    unsafe {
+        assert_eq!(sigalign_reference_num_targets(reference), 3);
+        assert_eq!(sigalign_reference_total_length(reference), 3000);
+        let mut label = ptr::null_mut();
+        assert_eq!(sigalign_reference_get_label(reference, 1, &mut label), SigalignStatus::Ok);
+        assert_eq!(CStr::from_ptr(label).to_str().unwrap(), "target_1");
+        sigalign_string_free(label);
+
+        let mut aligner = ptr::null_mut();
+        assert_eq!(sigalign_aligner_new(4, 6, 2, 50, 0.1, ptr::null(), &mut aligner), SigalignStatus::Ok);
+        assert_eq!(
+            align(aligner, reference, &query),
+            vec![(1, 4, (0, 200), (200, 400), "100=1X99=".to_string())],
+        );
+        sigalign_aligner_free(aligner);
+
+        // All algorithms find the same alignment
+        let options = [
+            SigalignAlignerOptions { semi_global: false, limit: 1, chunk_size: 0, chunk_step: 0 },
+            SigalignAlignerOptions { semi_global: false, limit: 0, chunk_size: 100, chunk_step: 50 },
+            SigalignAlignerOptions { semi_global: true, limit: 0, chunk_size: 0, chunk_step: 0 },
+            SigalignAlignerOptions { semi_global: true, limit: 1, chunk_size: 0, chunk_step: 0 },
+            SigalignAlignerOptions { semi_global: true, limit: 0, chunk_size: 100, chunk_step: 50 },
+        ];
+        for options in options {
+            let mut aligner = ptr::null_mut();
+            assert_eq!(sigalign_aligner_new(4, 6, 2, 50, 0.1, &options, &mut aligner), SigalignStatus::Ok);
+            let alignments = align(aligner, reference, &query);
+            assert!(alignments.iter().any(|x| x.0 == 1 && x.1 == 4), "{:?}", options);
+            sigalign_aligner_free(aligner);
+        }
+        sigalign_reference_free(reference);
+    }
+}
+
+#[test]
+fn save_and_load_reference() {
+    let (reference, query) = build_reference();
+    let path = std::env::temp_dir().join(format!("sigalign_ffi_{}.sigref", std::process::id()));
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+    unsafe {
+        assert_eq!(sigalign_reference_save_file(reference, c_path.as_ptr()), SigalignStatus::Ok);
+        let mut from_file = ptr::null_mut();
+        assert_eq!(sigalign_reference_load_file(c_path.as_ptr(), &mut from_file), SigalignStatus::Ok);
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut from_bytes = ptr::null_mut();
+        assert_eq!(sigalign_reference_load_bytes(bytes.as_ptr(), bytes.len(), &mut from_bytes), SigalignStatus::Ok);
+
+        let mut aligner = ptr::null_mut();
+        assert_eq!(sigalign_aligner_new(4, 6, 2, 50, 0.1, ptr::null(), &mut aligner), SigalignStatus::Ok);
+        let original = align(aligner, reference, &query);
+        assert_eq!(align(aligner, from_file, &query), original);
+        assert_eq!(align(aligner, from_bytes, &query), original);
+
+        sigalign_aligner_free(aligner);
+        sigalign_reference_free(from_bytes);
+        sigalign_reference_free(from_file);
+        sigalign_reference_free(reference);
+    }
+}
+
+#[test]
+fn errors_are_reported_with_messages() {
+    let (reference, _) = build_reference();
+    unsafe {
+        let mut aligner = ptr::null_mut();
+        assert_eq!(sigalign_aligner_new(0, 6, 2, 50, 0.1, ptr::null(), &mut aligner), SigalignStatus::InvalidArgument);
+        assert!(aligner.is_null());
+        assert!(!last_error_message().is_empty());
+        let options = SigalignAlignerOptions { semi_global: false, limit: 1, chunk_size: 100, chunk_step: 50 };
+        assert_eq!(sigalign_aligner_new(4, 6, 2, 50, 0.1, &options, &mut aligner), SigalignStatus::InvalidArgument);
+        assert!(last_error_message().contains("together"));
+        assert_eq!(sigalign_aligner_new(4, 6, 2, 50, 0.1, ptr::null(), ptr::null_mut()), SigalignStatus::NullPointer);
+
+        let mut loaded = ptr::null_mut();
+        let not_exist = CString::new("not_exist.sigref").unwrap();
+        assert_eq!(sigalign_reference_load_file(not_exist.as_ptr(), &mut loaded), SigalignStatus::Io);
+        assert!(last_error_message().contains("not_exist.sigref"));
+        let not_reference = b"not a reference file";
+        assert_eq!(sigalign_reference_load_bytes(not_reference.as_ptr(), not_reference.len(), &mut loaded), SigalignStatus::Load);
+        assert!(loaded.is_null());
+
+        let mut label = ptr::null_mut();
+        assert_eq!(sigalign_reference_get_label(reference, 3, &mut label), SigalignStatus::InvalidArgument);
+        assert_eq!(sigalign_reference_get_label(ptr::null(), 0, &mut label), SigalignStatus::NullPointer);
+        assert_eq!(sigalign_reference_num_targets(ptr::null()), 0);
+
+        // The invalid arguments leave the builder usable
+        let builder = sigalign_reference_builder_new();
+        let invalid = CString::new(vec![0xFF, 0xFE]).unwrap();
+        assert_eq!(sigalign_reference_builder_ignore_bases(builder, invalid.as_ptr()), SigalignStatus::InvalidArgument);
+        let unknown = CString::new("unknown").unwrap();
+        assert_eq!(sigalign_reference_builder_set_alphabet(builder, unknown.as_ptr()), SigalignStatus::InvalidArgument);
+        assert_eq!(sigalign_reference_builder_add_fasta_file(builder, ptr::null()), SigalignStatus::NullPointer);
+        assert_eq!(sigalign_reference_builder_add_target(builder, invalid.as_ptr(), b"ACGT".as_ptr(), 4), SigalignStatus::InvalidArgument);
+        assert_eq!(sigalign_reference_builder_set_uppercase(builder, true), SigalignStatus::Ok);
+        // The builder is not usable after it failed to read the file
+        let not_exist = CString::new("not_exist.fa").unwrap();
+        assert_eq!(sigalign_reference_builder_add_fasta_file(builder, not_exist.as_ptr()), SigalignStatus::Io);
+        assert_eq!(sigalign_reference_builder_set_uppercase(builder, true), SigalignStatus::InvalidArgument);
+        assert!(last_error_message().contains("no longer usable"));
+        sigalign_reference_builder_free(builder);
+
+        sigalign_reference_free(reference);
+    }
+}
+
+#[test]
+fn header_declares_all_functions() {
+    let header = include_str!("../include/sigalign.h");
+    let source_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut num_functions = 0;
+    for entry in std::fs::read_dir(source_dir).unwrap() {
+        let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+        for line in source.lines().filter(|x| x.contains("extern \"C\" fn ")) {
+            let name = line.split("fn ").nth(1).unwrap().split('(').next().unwrap();
+            assert!(header.contains(&format!(" {}(", name)) || header.contains(&format!("*{}(", name)), "{} is not in the header", name);
+            num_functions += 1;
+        }
+    }
+    assert_eq!(header.matches(" sigalign_").count() + header.matches("*sigalign_").count(), num_functions);
+}
@@ -0,0 +1,168 @@
+/* Test harness of the C API. Run with `make test` in this directory. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "sigalign.h"
+
+static int failures = 0;
+
+#define CHECK(condition)                                                    \
+    do {                                                                    \
+        if (!(condition)) {                                                 \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+                    #condition);                                            \
+            failures++;                                                     \
+        }                                                                   \
+    } while (0)
+
+#define CHECK_OK(call)                                                      \
+    do {                                                                    \
+        SigalignStatus status_ = (call);                                    \
+        if (status_ != SIGALIGN_STATUS_OK) {                                \
+            fprintf(stderr, "%s:%d: %s failed (%d): %s\n", __FILE__,        \
+                    __LINE__, #call, (int)status_,                          \
+                    sigalign_last_error_message());                         \
+            exit(1);                                                        \
+        }                                                                   \
+    } while (0)
+
+#define TARGET_LENGTH 1000
+
+static void random_sequence(char *buffer, size_t length, uint64_t *seed) {
+    static const char bases[] = "ACGT";
+    for (size_t i = 0; i < length; i++) {
+        *seed = *seed * 6364136223846793005ULL + 1442695040888963407ULL;
+        buffer[i] = bases[(*seed >> 33) % 4];
+    }
+    buffer[length] = '\0';
+}
+
+/* Three targets, and a query from the second one with a mismatch */
+static SigalignReference *build_reference(char *query) {
+    char targets[3][TARGET_LENGTH + 1];
+    uint64_t seed = 11;
+    char fasta[3 * (TARGET_LENGTH + 32)] = "";
+    for (int i = 0; i < 3; i++) {
+        random_sequence(targets[i], TARGET_LENGTH, &seed);
+        char header[32];
+        snprintf(header, sizeof(header), ">target_%d\n", i);
+        strcat(fasta, header);
+        strcat(fasta, targets[i]);
+        strcat(fasta, "\n");
+    }
+    memcpy(query, targets[1] + 200, 200);
+    query[200] = '\0';
+    query[100] = query[100] == 'A' ? 'C' : 'A';
+
+    SigalignReferenceBuilder *builder = sigalign_reference_builder_new();
+    CHECK(builder != NULL);
+    CHECK_OK(sigalign_reference_builder_ignore_bases(builder, "N"));
+    CHECK_OK(sigalign_reference_builder_add_fasta(builder, (const uint8_t *)fasta, strlen(fasta)));
+    SigalignReference *reference = NULL;
+    CHECK_OK(sigalign_reference_builder_build(builder, &reference));
+    return reference;
+}
+
+static void test_align(const SigalignReference *reference, const char *query) {
+    SigalignAligner *aligner = NULL;
+    CHECK_OK(sigalign_aligner_new(4, 6, 2, 50, 0.1f, NULL, &aligner));
+
+    SigalignResult *result = NULL;
+    CHECK_OK(sigalign_aligner_align(aligner, reference, (const uint8_t *)query, strlen(query), &result));
+    CHECK(sigalign_result_num_alignments(result) == 1);
+
+    SigalignAlignment alignment;
+    CHECK_OK(sigalign_result_get_alignment(result, 0, &alignment));
+    CHECK(alignment.target_index == 1);
+    CHECK(alignment.penalty == 4);
+    CHECK(alignment.length == 200);
+    CHECK(alignment.query_start == 0 && alignment.query_end == 200);
+    CHECK(alignment.target_start == 200 && alignment.target_end == 400);
+    CHECK(strcmp(alignment.cigar, "100=1X99=") == 0);
+    CHECK(sigalign_result_get_alignment(result, 1, &alignment) == SIGALIGN_STATUS_INVALID_ARGUMENT);
+
+    sigalign_result_free(result);
+    sigalign_aligner_free(aligner);
+
+    /* Semi-global with limit */
+    SigalignAlignerOptions options = { .semi_global = true, .limit = 1 };
+    CHECK_OK(sigalign_aligner_new(4, 6, 2, 50, 0.1f, &options, &aligner));
+    CHECK_OK(sigalign_aligner_align(aligner, reference, (const uint8_t *)query, strlen(query), &result));
+    CHECK(sigalign_result_num_alignments(result) == 1);
+    sigalign_result_free(result);
+    sigalign_aligner_free(aligner);
+}
+
+static void test_save_and_load(const SigalignReference *reference, const char *query) {
+    const char *path = "test_sigalign.sigref";
+    CHECK_OK(sigalign_reference_save_file(reference, path));
+
+    SigalignReference *loaded = NULL;
+    CHECK_OK(sigalign_reference_load_file(path, &loaded));
+    remove(path);
+    CHECK(sigalign_reference_num_targets(loaded) == 3);
+    CHECK(sigalign_reference_total_length(loaded) == 3 * TARGET_LENGTH);
+
+    char *label = NULL;
+    CHECK_OK(sigalign_reference_get_label(loaded, 2, &label));
+    CHECK(strcmp(label, "target_2") == 0);
+    sigalign_string_free(label);
+
+    SigalignAligner *aligner = NULL;
+    SigalignResult *result = NULL;
+    CHECK_OK(sigalign_aligner_new(4, 6, 2, 50, 0.1f, NULL, &aligner));
+    CHECK_OK(sigalign_aligner_align(aligner, loaded, (const uint8_t *)query, strlen(query), &result));
+    CHECK(sigalign_result_num_alignments(result) == 1);
+    sigalign_result_free(result);
+    sigalign_aligner_free(aligner);
+    sigalign_reference_free(loaded);
+}
+
+static void test_errors(const SigalignReference *reference) {
+    SigalignAligner *aligner = NULL;
+    /* Mismatch penalty must be positive */
+    CHECK(sigalign_aligner_new(0, 6, 2, 50, 0.1f, NULL, &aligner) == SIGALIGN_STATUS_INVALID_ARGUMENT);
+    CHECK(aligner == NULL);
+    CHECK(sigalign_last_error_message() != NULL);
+    SigalignAlignerOptions options = { .limit = 1, .chunk_size = 100, .chunk_step = 50 };
+    CHECK(sigalign_aligner_new(4, 6, 2, 50, 0.1f, &options, &aligner) == SIGALIGN_STATUS_INVALID_ARGUMENT);
+
+    SigalignReference *loaded = NULL;
+    CHECK(sigalign_reference_load_file("not_exist.sigref", &loaded) == SIGALIGN_STATUS_IO);
+    const uint8_t not_reference[] = "not a reference";
+    CHECK(sigalign_reference_load_bytes(not_reference, sizeof(not_reference), &loaded) == SIGALIGN_STATUS_LOAD);
+    CHECK(loaded == NULL);
+
+    char *label = NULL;
+    CHECK(sigalign_reference_get_label(reference, 3, &label) == SIGALIGN_STATUS_INVALID_ARGUMENT);
+    CHECK(sigalign_reference_get_label(NULL, 0, &label) == SIGALIGN_STATUS_NULL_POINTER);
+
+    /* The invalid arguments leave the builder usable */
+    SigalignReferenceBuilder *builder = sigalign_reference_builder_new();
+    CHECK(sigalign_reference_builder_set_alphabet(builder, "unknown") == SIGALIGN_STATUS_INVALID_ARGUMENT);
+    CHECK(sigalign_reference_builder_set_uppercase(builder, true) == SIGALIGN_STATUS_OK);
+
+    /* The builder is not usable after it failed to read the file */
+    CHECK(sigalign_reference_builder_add_fasta_file(builder, "not_exist.fa") == SIGALIGN_STATUS_IO);
+    CHECK(sigalign_reference_builder_set_uppercase(builder, true) == SIGALIGN_STATUS_INVALID_ARGUMENT);
+    CHECK(sigalign_reference_builder_build(builder, &loaded) == SIGALIGN_STATUS_INVALID_ARGUMENT);
+}
+
+int main(void) {
+    printf("sigalign %s\n", sigalign_version());
+    char query[201];
+    SigalignReference *reference = build_reference(query);
+
+    test_align(reference, query);
+    test_save_and_load(reference, query);
+    test_errors(reference);
+
+    sigalign_reference_free(reference);
+    if (failures > 0) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return 1;
+    }
+    printf("All checks passed\n");
+    return 0;
+}
@@ -28,6 +28,36 @@ pub struct DynamicLfiOption {
     pub lookup_table_max_bytes_size: u64,
     pub use_safe_guard: bool,
 }
+impl DynamicLfi {
+    /// Bits per character of the index (2 to 5), chosen by the number of characters.
+    pub fn bits_per_character(&self) -> u32 {
+        match self {
+            Self::B2(_) => 2,
+            Self::B3(_) => 3,
+            Self::B4(_) => 4,
+            Self::B5(_) => 5,
+        }
+    }
+    /// `true` if the index is borrowed from the shared bytes (e.g., memory-mapped file).
+    pub fn is_shared(&self) -> bool {
+        match self {
+            Self::B2(v) => v.is_shared(),
+            Self::B3(v) => v.is_shared(),
+            Self::B4(v) => v.is_shared(),
+            Self::B5(v) => v.is_shared(),
+        }
+    }
+    /// Name of the type of the index (e.g., `Lfi32B2V64`).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::B2(_) => "Lfi32B2V64",
+            Self::B3(_) => "Lfi32B3V64",
+            Self::B4(_) => "Lfi32B4V64",
+            Self::B5(_) => "Lfi32B5V64",
+        }
+    }
+}
+
 impl DynamicLfiOption {
     fn to_lfi_option(self) -> LfiOption {
         LfiOption {
@@ -86,6 +116,8 @@ impl PatternIndex for DynamicLfi {
 use sigalign_core::reference::extensions::{
     Serialize,
     EstimateSize,
+    SharedBytes,
+    LoadFromShared,
 };
 //  - Serialize
 use crate::core::{EndianType, WriteBytesExt, ReadBytesExt};
@@ -155,6 +187,39 @@ impl DynamicLfi {
     // LtFmIndexPosition32Block5Vector64: 6a2427ab
     const B5_MAGIC_NUMBER: u64 = 1780754347;
 }
+//  - LoadFromShared
+//    The buffers of the index are borrowed from the shared bytes.
+impl LoadFromShared for DynamicLfi {
+    fn load_from_shared(bytes: &SharedBytes, offset: usize) -> Result<(Self, usize), std::io::Error> {
+        let whole: &[u8] = (**bytes).as_ref();
+        let Some(mut reader) = whole.get(offset..) else {
+            return Err(std::io::ErrorKind::UnexpectedEof.into())
+        };
+        let magic_number = reader.read_u64::<EndianType>()?;
+        let offset = offset + std::mem::size_of::<u64>();
+        match magic_number {
+            Self::B2_MAGIC_NUMBER => {
+                let (inner, offset) = Lfi32B2V64::load_from_shared(bytes, offset)?;
+                Ok((Self::B2(inner), offset))
+            },
+            Self::B3_MAGIC_NUMBER => {
+                let (inner, offset) = Lfi32B3V64::load_from_shared(bytes, offset)?;
+                Ok((Self::B3(inner), offset))
+            },
+            Self::B4_MAGIC_NUMBER => {
+                let (inner, offset) = Lfi32B4V64::load_from_shared(bytes, offset)?;
+                Ok((Self::B4(inner), offset))
+            },
+            Self::B5_MAGIC_NUMBER => {
+                let (inner, offset) = Lfi32B5V64::load_from_shared(bytes, offset)?;
+                Ok((Self::B5(inner), offset))
+            },
+            _ => {
+                Err((std::io::ErrorKind::InvalidData).into())
+            },
+        }
+    }
+}
 //  - EstimateSize
 impl EstimateSize for DynamicLfi {
     fn serialized_size(&self) -> usize {
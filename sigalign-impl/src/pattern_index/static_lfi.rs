@@ -20,7 +20,26 @@ pub type Lfi32B5V64 = StaticLfi<blocks::Block5<u64>>;
 /// (The maximum length of one sequence is u32::MAX)
 #[derive(Clone)]
 pub struct StaticLfi<B: Block<u32>> {
-    inner: LtFmIndex<u32, B>,
+    inner: LfiInner<B>,
+}
+
+/// Index of `StaticLfi`.
+///  - `Owned`: allocated in memory.
+///  - `Shared`: borrowed from the shared bytes (e.g., memory-mapped file).
+#[derive(Clone)]
+enum LfiInner<B: Block<u32>> {
+    Owned(LtFmIndex<u32, B>),
+    Shared(SharedLfi<B>),
+}
+
+mod shared;
+use shared::SharedLfi;
+
+impl<B: Block<u32>> StaticLfi<B> {
+    /// `true` if the index is borrowed from the shared bytes (e.g., memory-mapped file).
+    pub fn is_shared(&self) -> bool {
+        matches!(self.inner, LfiInner::Shared(_))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -80,12 +99,15 @@ impl <B: Block<u32>> PatternIndex for StaticLfi<B> {
             option.suffix_array_sampling_ratio as u32,
             lookup_table_kmer_size,
         ) {
-            Ok(v) => Ok(Self { inner: v }),
+            Ok(v) => Ok(Self { inner: LfiInner::Owned(v) }),
             Err(err) => Err(Self::BuildError::InvalidOption(format!("{}", err))),
         }
     }
     fn get_sorted_positions(&self, pattern: &[u8]) -> Vec<u32> {
-        let mut positions = self.inner.locate(pattern);
+        let mut positions = match &self.inner {
+            LfiInner::Owned(v) => v.locate(pattern),
+            LfiInner::Shared(v) => v.locate(pattern),
+        };
         positions.sort_unstable();
         positions
     }
@@ -126,13 +148,18 @@ pub enum LfiBuildError {
 use sigalign_core::reference::extensions::{
     Serialize,
     EstimateSize,
+    SharedBytes,
+    LoadFromShared,
 };
 //  - Serialize
 impl<B: Block<u32>> Serialize for StaticLfi<B> {
     fn save_to<W>(&self, mut writer: W) -> Result<(), std::io::Error> where
         W: std::io::Write
     {
-        self.inner.save_to(&mut writer)?;
+        match &self.inner {
+            LfiInner::Owned(v) => v.save_to(&mut writer)?,
+            LfiInner::Shared(v) => writer.write_all(v.as_bytes())?,
+        }
         Ok(())
     }
     fn load_from<R>(mut reader: R) -> Result<Self, std::io::Error> where
@@ -140,12 +167,23 @@ impl<B: Block<u32>> Serialize for StaticLfi<B> {
         Self: Sized
     {
         let inner = LtFmIndex::load_from(&mut reader)?;
-        Ok(Self { inner })
+        Ok(Self { inner: LfiInner::Owned(inner) })
+    }
+}
+//  - LoadFromShared
+//    The buffers of the index are borrowed from the shared bytes.
+impl<B: Block<u32>> LoadFromShared for StaticLfi<B> {
+    fn load_from_shared(bytes: &SharedBytes, offset: usize) -> Result<(Self, usize), std::io::Error> {
+        let (inner, offset) = SharedLfi::new(bytes, offset)?;
+        Ok((Self { inner: LfiInner::Shared(inner) }, offset))
     }
 }
 //  - EstimateSize
 impl<B: Block<u32>> EstimateSize for StaticLfi<B> {
     fn serialized_size(&self) -> usize {
-        self.inner.encoded_len()
+        match &self.inner {
+            LfiInner::Owned(v) => v.encoded_len(),
+            LfiInner::Shared(v) => v.as_bytes().len(),
+        }
     }
 }
@@ -0,0 +1,266 @@
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+
+use lt_fm_index::Block;
+use sigalign_core::reference::extensions::SharedBytes;
+
+use crate::core::{EndianType, ReadBytesExt};
+
+/// `LtFmIndex` borrowed from the shared bytes (e.g., memory-mapped file).
+///  - The suffix array, the lookup table, the rank checkpoints and the BWT blocks
+///    are read from the bytes saved by `LtFmIndex::save_to` without being copied.
+///  - The values are read unaligned, since the offsets of the buffers depend on their lengths.
+#[derive(Clone)]
+pub(super) struct SharedLfi<B: Block<u32>> {
+    bytes: SharedBytes,
+    start: usize,
+    end: usize,
+    chr_idx_table: [u8; 256],
+    suffix_array_sampling_ratio: u32,
+    suffix_array: usize,
+    kmer_size: u32,
+    count_table: Vec<u32>,
+    kmer_count_table: usize,
+    multiplier: Vec<usize>,
+    primary_index: u32,
+    chr_count: u32,
+    rank_checkpoints: usize,
+    blocks: usize,
+    _block: PhantomData<B>,
+}
+
+impl<B: Block<u32>> SharedLfi<B> {
+    /// Parse `bytes[offset..]` in the layout of `LtFmIndex::save_to`,
+    /// and return the index with the offset right after it.
+    pub fn new(bytes: &SharedBytes, offset: usize) -> Result<(Self, usize), Error> {
+        let whole: &[u8] = (**bytes).as_ref();
+        let mut reader = Reader {
+            whole,
+            offset,
+        };
+        // text_len
+        reader.read_u64()?;
+        // chr_idx_table
+        let mut chr_idx_table = [0; 256];
+        chr_idx_table.copy_from_slice(reader.skip(256)?);
+        // suffix_array
+        let suffix_array_sampling_ratio = reader.read_u64()? as u32;
+        if suffix_array_sampling_ratio == 0 {
+            return Err(ErrorKind::InvalidData.into())
+        }
+        let suffix_array = reader.skip_vec(4)?;
+        // count_array
+        let kmer_size = reader.read_u32()?;
+        let count_table = reader.read_u32_vec()?;
+        let kmer_count_table = reader.skip_vec(4)?;
+        let multiplier = reader.read_usize_vec()?;
+        // bwm
+        let primary_index = reader.read_u64()? as u32;
+        let chr_count = reader.read_u32()?;
+        let rank_checkpoints = reader.skip_vec(4)?;
+        let blocks = reader.skip_vec(std::mem::size_of::<B>())?;
+
+        let end = reader.offset;
+        Ok((
+            Self {
+                bytes: bytes.clone(),
+                start: offset,
+                end,
+                chr_idx_table,
+                suffix_array_sampling_ratio,
+                suffix_array,
+                kmer_size,
+                count_table,
+                kmer_count_table,
+                multiplier,
+                primary_index,
+                chr_count,
+                rank_checkpoints,
+                blocks,
+                _block: PhantomData,
+            },
+            end,
+        ))
+    }
+    /// The bytes saved by `LtFmIndex::save_to`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.whole()[self.start..self.end]
+    }
+
+    // Same as `LtFmIndex::locate`
+    pub fn locate(&self, pattern: &[u8]) -> Vec<u32> {
+        let pos_range = self.get_pos_range(pattern);
+        self.get_locations(pos_range)
+    }
+
+    fn whole(&self) -> &[u8] {
+        (*self.bytes).as_ref()
+    }
+    fn u32_at(&self, buffer: usize, index: usize) -> u32 {
+        let start = buffer + index * 4;
+        u32::from_ne_bytes(self.whole()[start..start + 4].try_into().unwrap())
+    }
+    fn block_at(&self, index: usize) -> B {
+        let size = std::mem::size_of::<B>();
+        let start = self.blocks + index * size;
+        let block_bytes = &self.whole()[start..start + size];
+        // SAFETY: `Block` is `Pod`, and the length of the slice is the size of `B`.
+        unsafe { std::ptr::read_unaligned(block_bytes.as_ptr() as *const B) }
+    }
+    fn idx_of(&self, chr: u8) -> u8 {
+        self.chr_idx_table[chr as usize]
+    }
+
+    // Count array
+    fn get_initial_pos_range_and_idx_of_pattern(&self, pattern: &[u8]) -> ((u32, u32), usize) {
+        let pattern_len = pattern.len();
+        if pattern_len < self.kmer_size as usize {
+            let start_idx = self.get_idx_of_kmer_count_table(pattern);
+            let gap_btw_unsearched_kmer = self.multiplier[pattern_len - 1] - 1;
+            let end_idx = start_idx + gap_btw_unsearched_kmer;
+
+            let pos_range = (
+                self.u32_at(self.kmer_count_table, start_idx - 1),
+                self.u32_at(self.kmer_count_table, end_idx),
+            );
+            (pos_range, 0)
+        } else {
+            let sliced_pattern = &pattern[pattern_len - self.kmer_size as usize..];
+            let start_idx = self.get_idx_of_kmer_count_table(sliced_pattern);
+
+            let pos_range = (
+                self.u32_at(self.kmer_count_table, start_idx - 1),
+                self.u32_at(self.kmer_count_table, start_idx),
+            );
+            (pos_range, pattern_len - self.kmer_size as usize)
+        }
+    }
+    fn get_idx_of_kmer_count_table(&self, sliced_pattern: &[u8]) -> usize {
+        sliced_pattern.iter().zip(self.multiplier.iter())
+            .map(|(&chr, &mul_of_pos)| {
+                (self.idx_of(chr) + 1) as usize * mul_of_pos
+            }).sum()
+    }
+
+    // Burrows-Wheeler matrix
+    fn get_next_rank(&self, mut pos: u32, chridx: u8) -> u32 {
+        if pos < self.primary_index {
+            pos += 1;
+        }
+        let quot = pos / B::BLOCK_LEN;
+        let rem = pos % B::BLOCK_LEN;
+
+        let rank_idx = quot * self.chr_count + chridx as u32;
+        let rank_precount = self.u32_at(self.rank_checkpoints, rank_idx as usize);
+        if rem == 0 {
+            rank_precount
+        } else {
+            rank_precount + self.block_at(quot as usize).get_remain_count_of(rem, chridx)
+        }
+    }
+    fn get_pre_rank_and_chridx(&self, mut pos: u32) -> Option<(u32, u8)> {
+        if pos == self.primary_index.wrapping_sub(1) {
+            return None;
+        } else if pos < self.primary_index {
+            pos += 1;
+        }
+        let quot = pos / B::BLOCK_LEN;
+        let rem = pos % B::BLOCK_LEN;
+
+        let block = self.block_at(quot as usize);
+        let chridx = block.get_chridx_of(rem);
+
+        let rank_idx = quot * self.chr_count + chridx as u32;
+        let rank_precount = self.u32_at(self.rank_checkpoints, rank_idx as usize);
+        if rem == 0 {
+            Some((rank_precount, chridx))
+        } else {
+            Some((rank_precount + block.get_remain_count_of(rem, chridx), chridx))
+        }
+    }
+
+    // Locate
+    fn get_pos_range(&self, pattern: &[u8]) -> (u32, u32) {
+        let (mut pos_range, mut idx) = self.get_initial_pos_range_and_idx_of_pattern(pattern);
+        // LF mapping
+        while pos_range.0 < pos_range.1 && idx > 0 {
+            idx -= 1;
+            pos_range = self.next_pos_range(pos_range, pattern[idx]);
+        }
+        pos_range
+    }
+    fn next_pos_range(&self, pos_range: (u32, u32), chr: u8) -> (u32, u32) {
+        let chridx = self.idx_of(chr);
+        let precount = self.count_table[chridx as usize];
+        let start_rank = self.get_next_rank(pos_range.0, chridx);
+        let end_rank = self.get_next_rank(pos_range.1, chridx);
+        (precount + start_rank, precount + end_rank)
+    }
+    fn get_locations(&self, pos_range: (u32, u32)) -> Vec<u32> {
+        let mut locations = Vec::with_capacity((pos_range.1 - pos_range.0) as usize);
+
+        'each_pos: for mut pos in pos_range.0..pos_range.1 {
+            let mut offset = 0;
+            while pos % self.suffix_array_sampling_ratio != 0 {
+                match self.get_pre_rank_and_chridx(pos) {
+                    Some((rank, chridx)) => {
+                        pos = self.count_table[chridx as usize] + rank;
+                    },
+                    None => { // if position == pidx
+                        locations.push(offset);
+                        continue 'each_pos;
+                    },
+                }
+                offset += 1;
+            }
+            let location = self.u32_at(
+                self.suffix_array,
+                (pos / self.suffix_array_sampling_ratio) as usize,
+            ) + offset;
+            locations.push(location);
+        }
+        locations
+    }
+}
+
+/// Reader of the bytes saved by `capwriter`, keeping the offsets of the buffers.
+struct Reader<'a> {
+    whole: &'a [u8],
+    offset: usize,
+}
+impl<'a> Reader<'a> {
+    fn skip(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        let skipped = self.offset.checked_add(length)
+            .and_then(|end| self.whole.get(self.offset..end))
+            .ok_or(Error::from(ErrorKind::UnexpectedEof))?;
+        self.offset += length;
+        Ok(skipped)
+    }
+    fn read_u32(&mut self) -> Result<u32, Error> {
+        self.skip(4)?.read_u32::<EndianType>()
+    }
+    fn read_u64(&mut self) -> Result<u64, Error> {
+        self.skip(8)?.read_u64::<EndianType>()
+    }
+    /// Skip the `Vec` and return the offset of its elements.
+    fn skip_vec(&mut self, element_size: usize) -> Result<usize, Error> {
+        let length = usize::try_from(self.read_u64()?)
+            .map_err(|_| Error::from(ErrorKind::InvalidData))?;
+        let buffer = self.offset;
+        self.skip(length.checked_mul(element_size).ok_or(Error::from(ErrorKind::InvalidData))?)?;
+        Ok(buffer)
+    }
+    fn read_u32_vec(&mut self) -> Result<Vec<u32>, Error> {
+        let start = self.skip_vec(4)?;
+        Ok(self.whole[start..self.offset].chunks_exact(4).map(|x| {
+            u32::from_ne_bytes(x.try_into().unwrap())
+        }).collect())
+    }
+    fn read_usize_vec(&mut self) -> Result<Vec<usize>, Error> {
+        let size = std::mem::size_of::<usize>();
+        let start = self.skip_vec(size)?;
+        Ok(self.whole[start..self.offset].chunks_exact(size).map(|x| {
+            usize::from_ne_bytes(x.try_into().unwrap())
+        }).collect())
+    }
+}
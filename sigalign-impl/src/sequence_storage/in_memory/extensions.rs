@@ -5,11 +5,13 @@ use capwriter::{Save, Load};
 use sigalign_core::reference::extensions::{
     Serialize,
     EstimateSize,
+    SharedBytes,
+    LoadFromShared,
     LabelStorage,
     LabelRefStorage,
 };
 use crate::core::{EndianType, ReadBytesExt, WriteBytesExt};
-use super::InMemoryStorage;
+use super::{InMemoryStorage, SequenceBytes};
 
 //  - Serialize
 impl Serialize for InMemoryStorage {
@@ -17,7 +19,7 @@ impl Serialize for InMemoryStorage {
         W: Write
     {
         writer.write_u64::<EndianType>(self.target_count as u64)?;
-        self.concatenated_sequence.save_to(&mut writer)?;
+        (&*self.concatenated_sequence).save_to(&mut writer)?;
         self.sequence_index.save_to(&mut writer)?;
         self.concatenated_label.as_bytes().save_to(&mut writer)?;
         self.label_index.save_to(&mut writer)?;
@@ -37,7 +39,7 @@ impl Serialize for InMemoryStorage {
         let label_index = Vec::load_from(&mut reader)?;
         Ok(Self {
             target_count,
-            concatenated_sequence,
+            concatenated_sequence: SequenceBytes::Owned(concatenated_sequence),
             sequence_index,
             concatenated_label,
             label_index,
@@ -45,13 +47,57 @@ impl Serialize for InMemoryStorage {
     }
 }
 
+//  - LoadFromShared
+//    The concatenated sequence is borrowed from the shared bytes.
+impl LoadFromShared for InMemoryStorage {
+    fn load_from_shared(bytes: &SharedBytes, offset: usize) -> Result<(Self, usize), Error> {
+        let whole: &[u8] = (**bytes).as_ref();
+        let Some(mut reader) = whole.get(offset..) else {
+            return Err(ErrorKind::UnexpectedEof.into())
+        };
+        let target_count = reader.read_u64::<EndianType>()? as usize;
+        // Same as the length of `Vec` saved by `capwriter`
+        #[cfg(target_pointer_width = "32")]
+        let sequence_length = reader.read_u32::<EndianType>()? as usize;
+        #[cfg(target_pointer_width = "64")]
+        let sequence_length = reader.read_u64::<EndianType>()? as usize;
+        if reader.len() < sequence_length {
+            return Err(ErrorKind::UnexpectedEof.into())
+        }
+        let sequence_start = whole.len() - reader.len();
+        let concatenated_sequence = SequenceBytes::Shared {
+            bytes: bytes.clone(),
+            start: sequence_start,
+            end: sequence_start + sequence_length,
+        };
+        reader = &reader[sequence_length..];
+
+        let sequence_index = Vec::load_from(&mut reader)?;
+        let concatenated_label = match String::from_utf8(Vec::<u8>::load_from(&mut reader)?) {
+            Ok(v) => v,
+            Err(_) => return Err(ErrorKind::InvalidData.into()),
+        };
+        let label_index = Vec::load_from(&mut reader)?;
+        Ok((
+            Self {
+                target_count,
+                concatenated_sequence,
+                sequence_index,
+                concatenated_label,
+                label_index,
+            },
+            whole.len() - reader.len(),
+        ))
+    }
+}
+
 //  - EstimateSize
 impl EstimateSize for InMemoryStorage {
     fn serialized_size(&self) -> usize {
         // target_count
         std::mem::size_of::<u64>()
         // concatenated_sequence
-        + self.concatenated_sequence.to_be_saved_size()
+        + (&*self.concatenated_sequence).to_be_saved_size()
         // sequence_index
         + self.sequence_index.to_be_saved_size()
         // concatenated_label
@@ -6,16 +6,25 @@ use sigalign_core::reference::{
 };
 use sigalign_utils::sequence_reader::{
     SeqRecord, IdRecord,
+    ReadError, ReadErrorKind,
     fasta::FastaReader,
     decompress::get_gzip_decoder,
 };
 
+fn invalid_id_error<R: Read>(fasta_reader: &FastaReader<R>, err: Utf8Error) -> ReadError {
+    ReadError::new(
+        ReadErrorKind::InvalidId(err),
+        fasta_reader.record_number(),
+        fasta_reader.line_number(),
+    )
+}
+
 // TODO: Debug impl manually
 /// `SequenceStorage` that stores sequences in memory.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InMemoryStorage {
     target_count: usize,
-    concatenated_sequence: Vec<u8>,
+    concatenated_sequence: SequenceBytes,
     sequence_index: Vec<usize>,
     concatenated_label: String,
     label_index: Vec<usize>,
@@ -75,7 +84,7 @@ impl InMemoryStorage {
     pub fn new() -> Self {
         Self {
             target_count: 0,
-            concatenated_sequence: Vec::new(),
+            concatenated_sequence: SequenceBytes::new(),
             sequence_index: vec![0],
             concatenated_label: String::new(),
             label_index: vec![0],
@@ -87,22 +96,51 @@ impl InMemoryStorage {
         sequence: &[u8],
     ) {
         self.target_count += 1;
-        self.concatenated_sequence.extend_from_slice(sequence);
+        self.concatenated_sequence.to_mut().extend_from_slice(sequence);
         self.sequence_index.push(self.concatenated_sequence.len());
         self.concatenated_label.push_str(label);
         self.label_index.push(self.concatenated_label.len());
     }
-    pub fn add_fasta<R: Read>(&mut self, reader: R) -> Result<(), Utf8Error> {
+    pub fn add_fasta<R: Read>(&mut self, reader: R) -> Result<(), ReadError> {
         let mut fasta_reader = FastaReader::new(reader);
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record?;
             self.target_count += 1;
-            record.extend_seq_buf(&mut self.concatenated_sequence);
+            record.extend_seq_buf(self.concatenated_sequence.to_mut());
             self.sequence_index.push(self.concatenated_sequence.len());
-            record.extend_id_string(&mut self.concatenated_label)?;
+            if let Err(err) = record.extend_id_string(&mut self.concatenated_label) {
+                return Err(invalid_id_error(&fasta_reader, err));
+            }
             self.label_index.push(self.concatenated_label.len());
         }
         Ok(())
     }
+    /// Same as `add_fasta`, but the descriptions of the headers are pushed to `descriptions`.
+    ///  - Empty string if the header has no description.
+    pub fn add_fasta_with_descriptions<R: Read>(
+        &mut self,
+        reader: R,
+        descriptions: &mut Vec<String>,
+    ) -> Result<(), ReadError> {
+        let mut fasta_reader = FastaReader::new(reader);
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record?;
+            let description = match record.desc_str() {
+                Some(Ok(v)) => v.to_string(),
+                Some(Err(err)) => return Err(invalid_id_error(&fasta_reader, err)),
+                None => String::new(),
+            };
+            self.target_count += 1;
+            record.extend_seq_buf(self.concatenated_sequence.to_mut());
+            self.sequence_index.push(self.concatenated_sequence.len());
+            if let Err(err) = record.extend_id_string(&mut self.concatenated_label) {
+                return Err(invalid_id_error(&fasta_reader, err));
+            }
+            self.label_index.push(self.concatenated_label.len());
+            descriptions.push(description);
+        }
+        Ok(())
+    }
     /// Get filled storages
     /// Each storage has a total length of at most `max_length`
     /// !If one record is longer than `max_length`, it will be in a storage of its own
@@ -110,14 +148,15 @@ impl InMemoryStorage {
         &mut self,
         reader: R,
         max_length: u32,
-    ) -> Result<Vec<Self>, Utf8Error> {
+    ) -> Result<Vec<Self>, ReadError> {
         let mut filled_storages = Vec::new();
 
         let mut fasta_reader = FastaReader::new(reader);
         let mut current_seq_length = self.get_total_length();
         let mut seq_buffer = Vec::new();
         
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record?;
             record.extend_seq_buf(&mut seq_buffer);
             let new_seq_length = seq_buffer.len() as u32;
 
@@ -132,21 +171,26 @@ impl InMemoryStorage {
             // Add record to current storage
             current_seq_length += new_seq_length;
             self.target_count += 1;
-            self.concatenated_sequence.append(&mut seq_buffer);
+            self.concatenated_sequence.to_mut().append(&mut seq_buffer);
             self.sequence_index.push(self.concatenated_sequence.len());
-            record.extend_id_string(&mut self.concatenated_label)?;
+            if let Err(err) = record.extend_id_string(&mut self.concatenated_label) {
+                return Err(invalid_id_error(&fasta_reader, err));
+            }
             self.label_index.push(self.concatenated_label.len());
         }
         Ok(filled_storages)
     }
-    pub fn add_gzip_fasta<R: Read>(&mut self, reader: R) -> Result<(), Utf8Error> {
+    pub fn add_gzip_fasta<R: Read>(&mut self, reader: R) -> Result<(), ReadError> {
         let decomp_reader = get_gzip_decoder(reader);
         let mut fasta_reader = FastaReader::new(decomp_reader);
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record?;
             self.target_count += 1;
-            record.extend_seq_buf(&mut self.concatenated_sequence);
+            record.extend_seq_buf(self.concatenated_sequence.to_mut());
             self.sequence_index.push(self.concatenated_sequence.len());
-            record.extend_id_string(&mut self.concatenated_label)?;
+            if let Err(err) = record.extend_id_string(&mut self.concatenated_label) {
+                return Err(invalid_id_error(&fasta_reader, err));
+            }
             self.label_index.push(self.concatenated_label.len());
         }
         Ok(())
@@ -154,7 +198,7 @@ impl InMemoryStorage {
     pub fn merge(&mut self, other: Self) {
         let Self {
             target_count: other_target_count,
-            concatenated_sequence: mut other_combined_sequence,
+            concatenated_sequence: other_combined_sequence,
             sequence_index: other_sequence_index,
             concatenated_label: other_combined_label,
             label_index: other_label_index,
@@ -162,7 +206,7 @@ impl InMemoryStorage {
         // record_count
         self.target_count += other_target_count;
         // concatenated_sequence
-        self.concatenated_sequence.append(&mut other_combined_sequence);
+        self.concatenated_sequence.to_mut().extend_from_slice(&other_combined_sequence);
         // sequence_index
         let last_seq_idx = *self.sequence_index.last().unwrap();
         self.sequence_index.reserve(other_target_count);
@@ -204,6 +248,33 @@ impl InMemoryStorage {
         self.concatenated_label = String::new();
         self.label_index = vec![0; self.target_count+1];
     }
+    /// Get the lowercase regions of each target as (start, end) in the target
+    pub fn get_lowercase_regions(&self) -> Vec<Vec<(u32, u32)>> {
+        self.sequence_index.windows(2).map(|boundary| {
+            let (target_start, target_end) = (boundary[0], boundary[1]);
+            let mut regions = Vec::new();
+            let mut region_start = None;
+            for (position, base) in self.concatenated_sequence[target_start..target_end].iter().enumerate() {
+                match (base.is_ascii_lowercase(), region_start) {
+                    (true, None) => region_start = Some(position as u32),
+                    (false, Some(start)) => {
+                        regions.push((start, position as u32));
+                        region_start = None;
+                    },
+                    _ => {},
+                }
+            }
+            if let Some(start) = region_start {
+                regions.push((start, (target_end - target_start) as u32));
+            }
+            regions
+        }).collect()
+    }
+    /// `true` if the sequences are borrowed from the shared bytes (e.g., memory-mapped file).
+    ///  - The sequences are copied when modified.
+    pub fn is_sequence_shared(&self) -> bool {
+        self.concatenated_sequence.is_shared()
+    }
     /// Set sequence to uppercase
     /// !Cannot be undone
     pub fn set_sequences_to_uppercase(&mut self) {
@@ -219,6 +290,11 @@ impl InMemoryStorage {
         bases_to_change.iter().for_each(|v| {
             byte_mapper[*v as usize] = target_base;
         });
+        self.map_bases(&byte_mapper);
+    }
+    /// Convert all bases with the `byte_mapper` (indexed by the base)
+    /// !Cannot be undone
+    pub fn map_bases(&mut self, byte_mapper: &[u8; 256]) {
         self.concatenated_sequence.iter_mut().for_each(|v| {
             *v = byte_mapper[*v as usize];
         });
@@ -235,3 +311,5 @@ impl InMemoryBuffer {
 }
 
 mod extensions;
+mod sequence_bytes;
+use sequence_bytes::SequenceBytes;
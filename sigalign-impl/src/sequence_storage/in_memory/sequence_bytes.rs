@@ -0,0 +1,64 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use sigalign_core::reference::extensions::SharedBytes;
+
+/// Concatenated sequence of `InMemoryStorage`.
+///  - `Owned`: allocated in memory.
+///  - `Shared`: borrowed from the shared bytes (e.g., memory-mapped file).
+///    It is copied to `Owned` when modified (by `to_mut` or `deref_mut`).
+#[derive(Clone)]
+pub(super) enum SequenceBytes {
+    Owned(Vec<u8>),
+    Shared {
+        bytes: SharedBytes,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl SequenceBytes {
+    pub fn new() -> Self {
+        Self::Owned(Vec::new())
+    }
+    pub fn to_mut(&mut self) -> &mut Vec<u8> {
+        if let Self::Shared { .. } = self {
+            *self = Self::Owned(self.to_vec());
+        }
+        match self {
+            Self::Owned(v) => v,
+            Self::Shared { .. } => unreachable!(),
+        }
+    }
+    pub fn is_shared(&self) -> bool {
+        matches!(self, Self::Shared { .. })
+    }
+}
+
+impl Deref for SequenceBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Owned(v) => v,
+            Self::Shared { bytes, start, end } => &(**bytes).as_ref()[*start..*end],
+        }
+    }
+}
+impl DerefMut for SequenceBytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.to_mut()
+    }
+}
+
+impl fmt::Debug for SequenceBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+impl PartialEq for SequenceBytes {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+impl Eq for SequenceBytes {}
@@ -0,0 +1,7 @@
+# The classes are defined in the extension module.
+from .sigalign import *  # noqa: F403
+from .sigalign import results
+
+# The submodule of the extension is registered to be imported as `sigalign.results`.
+import sys as _sys
+_sys.modules[__name__ + ".results"] = results
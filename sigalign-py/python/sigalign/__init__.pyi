@@ -0,0 +1,271 @@
+# Type stubs of the `sigalign` package.
+#  - Generated from the pyo3 signatures of the built module by `python scripts/check_stubs.py --write`.
+#  - Only the annotations and the code outside the classes of the module are written by hand.
+from types import TracebackType
+from typing import Any, Iterable, Literal, Sequence, final
+
+from typing_extensions import Buffer
+
+from . import results
+
+_Bytes = str | bytes
+# Sequence as str, bytes, or 1D buffer of 1-byte items (e.g., NumPy `uint8` array)
+_SequenceInput = str | Buffer
+_Target = _SequenceInput | tuple[str, _SequenceInput] | list[str | _SequenceInput]
+_Alphabet = Literal["dna", "dna_iupac", "rna", "protein"]
+
+@final
+class Reference:
+    @classmethod
+    def from_iterable(
+        cls,
+        targets: Iterable[_Target],
+        set_uppercase: bool = False,
+        bases_to_ignore: str = "",
+        alphabet: _Alphabet | None = None,
+        soft_masking: bool = False,
+        keep_metadata: bool = False,
+        metadata_file: str | None = None,
+        symbols: str | None = None,
+    ) -> Reference: ...
+    @classmethod
+    def from_fasta(
+        cls,
+        fasta: _Bytes,
+        set_uppercase: bool = False,
+        bases_to_ignore: str = "",
+        alphabet: _Alphabet | None = None,
+        soft_masking: bool = False,
+        keep_metadata: bool = False,
+        metadata_file: str | None = None,
+    ) -> Reference: ...
+    @classmethod
+    def from_fasta_file(
+        cls,
+        file_path: str,
+        set_uppercase: bool = False,
+        bases_to_ignore: str = "",
+        alphabet: _Alphabet | None = None,
+        soft_masking: bool = False,
+        keep_metadata: bool = False,
+        metadata_file: str | None = None,
+    ) -> Reference: ...
+    @property
+    def num_targets(self) -> int: ...
+    @property
+    def estimated_size(self) -> int: ...
+    @property
+    def alphabet(self) -> str | None: ...
+    @property
+    def is_soft_masked(self) -> bool: ...
+    @property
+    def total_length(self) -> int: ...
+    @property
+    def is_memory_mapped(self) -> bool: ...
+    def get_sequence(self, target_index: int) -> str: ...
+    def get_label(self, target_index: int) -> str: ...
+    def get_description(self, target_index: int) -> str | None: ...
+    def get_attributes(self, target_index: int) -> dict[str, str]: ...
+    def get_soft_masked_regions(self, target_index: int) -> list[tuple[int, int]]: ...
+    def count_soft_masked_bases(
+        self,
+        target_index: int,
+        target_start: int,
+        target_end: int,
+    ) -> int: ...
+    def get_summary(self) -> dict[str, Any]: ...
+    def save_to_file(self, file_path: str, overwrite: bool = False) -> None: ...
+    @classmethod
+    def load_from_file(cls, file_path: str, mmap: bool = False) -> Reference: ...
+    def to_bytes(self) -> bytes: ...
+    @classmethod
+    def from_bytes(cls, data: bytes) -> Reference: ...
+    @classmethod
+    def verify_file(cls, file_path: str) -> dict[str, Any]: ...
+    def recommend_parameters(
+        self,
+        queries: Iterable[_Bytes],
+        px: int,
+        po: int,
+        pe: int,
+        throughput_constants: Sequence[float] | None = None,
+    ) -> list[dict[str, float]]: ...
+    def __reduce__(self) -> tuple[Any, tuple[bytes]]: ...
+    def __copy__(self) -> Reference: ...
+    def __deepcopy__(self, _memo: Any) -> Reference: ...
+
+@final
+class ReferenceBuilder:
+    def __init__(self) -> None: ...
+    def set_uppercase(self, uppercase: bool) -> ReferenceBuilder: ...
+    def ignore_bases(self, bases: str) -> ReferenceBuilder: ...
+    def reset_ignore_bases(self) -> ReferenceBuilder: ...
+    def set_alphabet(self, alphabet: _Alphabet) -> ReferenceBuilder: ...
+    def set_strict_alphabet(self, strict: bool) -> ReferenceBuilder: ...
+    def set_soft_masking(self, soft_masking: bool) -> ReferenceBuilder: ...
+    def set_keep_metadata(self, keep_metadata: bool) -> ReferenceBuilder: ...
+    def add_target(
+        self,
+        label: str,
+        sequence: _SequenceInput,
+        symbols: str | None = None,
+    ) -> ReferenceBuilder: ...
+    def add_targets(
+        self,
+        targets: Iterable[_Target],
+        symbols: str | None = None,
+    ) -> ReferenceBuilder: ...
+    def add_fasta(self, fasta: _Bytes) -> ReferenceBuilder: ...
+    def add_fasta_file(self, file_path: str) -> ReferenceBuilder: ...
+    def add_metadata_tsv(self, tsv: _Bytes) -> ReferenceBuilder: ...
+    def add_metadata_tsv_file(self, file_path: str) -> ReferenceBuilder: ...
+    def build(self) -> Reference: ...
+
+@final
+class Aligner:
+    def __init__(
+        self,
+        px: int,
+        po: int,
+        pe: int,
+        minl: int,
+        maxp: float,
+        use_local_mode: bool = True,
+        use_limit: int | None = None,
+        use_chunk: tuple[int, int] | None = None,
+    ) -> None: ...
+    @property
+    def px(self) -> int: ...
+    @property
+    def po(self) -> int: ...
+    @property
+    def pe(self) -> int: ...
+    @property
+    def minl(self) -> int: ...
+    @property
+    def maxp(self) -> float: ...
+    @property
+    def pattern_size(self) -> int: ...
+    @property
+    def algorithm(self) -> str: ...
+    @property
+    def is_local_mode(self) -> bool: ...
+    @property
+    def limitation(self) -> int | None: ...
+    @property
+    def chunk(self) -> tuple[int, int] | None: ...
+    def align_query(
+        self,
+        query: _SequenceInput,
+        reference: Reference,
+        with_label: bool = False,
+        target_indices: Sequence[int] | None = None,
+        symbols: str | None = None,
+    ) -> results.QueryAlignment: ...
+    def align_queries(
+        self,
+        queries: Buffer | Sequence[_SequenceInput],
+        reference: Reference,
+        lengths: Sequence[int] | None = None,
+        symbols: str | None = None,
+        with_label: bool = False,
+        num_threads: int = 1,
+    ) -> list[results.QueryAlignment]: ...
+    def align_fasta_file(
+        self,
+        file_path: str,
+        reference: Reference,
+        with_label: bool = False,
+        with_reverse_complementary: bool = False,
+        allow_interrupt: bool = False,
+        num_threads: int = 1,
+    ) -> results.FastaAlignment: ...
+    def align_fasta(
+        self,
+        fasta: _Bytes,
+        reference: Reference,
+        with_label: bool = False,
+        with_reverse_complementary: bool = False,
+        allow_interrupt: bool = False,
+        num_threads: int = 1,
+    ) -> results.FastaAlignment: ...
+    def align_fastq_file(
+        self,
+        file_path: str,
+        reference: Reference,
+        with_label: bool = False,
+        with_reverse_complementary: bool = False,
+        allow_interrupt: bool = False,
+        quality_trimming: tuple[int, int] | None = None,
+        adapters: Sequence[str] | None = None,
+        min_adapter_overlap: int = 5,
+        masking_quality: int | None = None,
+        num_threads: int = 1,
+    ) -> results.FastaAlignment: ...
+    def align_fastq(
+        self,
+        fastq: _Bytes,
+        reference: Reference,
+        with_label: bool = False,
+        with_reverse_complementary: bool = False,
+        allow_interrupt: bool = False,
+        quality_trimming: tuple[int, int] | None = None,
+        adapters: Sequence[str] | None = None,
+        min_adapter_overlap: int = 5,
+        masking_quality: int | None = None,
+        num_threads: int = 1,
+    ) -> results.FastaAlignment: ...
+    def iter_align_fasta(
+        self,
+        file_path: str,
+        reference: Reference,
+        with_label: bool = False,
+        with_reverse_complementary: bool = False,
+        num_threads: int = 1,
+        buffer_size: int = 1024,
+    ) -> ReadAlignmentIterator: ...
+    def iter_align_fastq(
+        self,
+        file_path: str,
+        reference: Reference,
+        with_label: bool = False,
+        with_reverse_complementary: bool = False,
+        quality_trimming: tuple[int, int] | None = None,
+        adapters: Sequence[str] | None = None,
+        min_adapter_overlap: int = 5,
+        masking_quality: int | None = None,
+        num_threads: int = 1,
+        buffer_size: int = 1024,
+    ) -> ReadAlignmentIterator: ...
+    def align_paired_fastq_file(
+        self,
+        file_path: str,
+        reference: Reference,
+        second_file_path: str | None = None,
+        min_insert_size: int = 0,
+        max_insert_size: int = 1000,
+        orientation: Literal["fr", "rf", "ff"] = "fr",
+        with_label: bool = False,
+        allow_interrupt: bool = False,
+        quality_trimming: tuple[int, int] | None = None,
+        adapters: Sequence[str] | None = None,
+        min_adapter_overlap: int = 5,
+        masking_quality: int | None = None,
+    ) -> list[results.ReadPairAlignment]: ...
+    def __reduce__(self) -> tuple[type[Aligner], tuple[Any, ...]]: ...
+    def __copy__(self) -> Aligner: ...
+    def __deepcopy__(self, _memo: Any) -> Aligner: ...
+
+# Returned by `Aligner.iter_align_fasta` and `Aligner.iter_align_fastq`.
+@final
+class ReadAlignmentIterator:
+    def __iter__(self) -> ReadAlignmentIterator: ...
+    def __next__(self) -> results.ReadAlignment: ...
+    def close(self) -> None: ...
+    def __enter__(self) -> ReadAlignmentIterator: ...
+    def __exit__(
+        self,
+        _exc_type: type[BaseException] | None,
+        _exc_value: BaseException | None,
+        _traceback: TracebackType | None,
+    ) -> bool: ...
@@ -0,0 +1,189 @@
+# Type stubs of the `sigalign.results` submodule.
+#  - Generated from the pyo3 signatures of the built module by `python scripts/check_stubs.py --write`.
+#  - Only the annotations and the code outside the classes of the module are written by hand.
+from typing import Any, Iterator, Literal, Sequence, final
+
+_FlatTargetAlignment = tuple[int, str | None, int, int, int, int, int, int, str]
+_FlatReadAlignment = tuple[str, bool, int, str | None, int, int, int, int, int, int, str]
+
+@final
+class FastaAlignment:
+    def __init__(self, read_alignments: Sequence[ReadAlignment]) -> None: ...
+    def to_list(self) -> list[ReadAlignment]: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+    def to_rows(self) -> list[_FlatReadAlignment]: ...
+    def num_alignments(self) -> int: ...
+    def __arrow_c_array__(
+        self,
+        requested_schema: object | None = None,
+    ) -> tuple[object, object]: ...
+    def to_arrow(self) -> Any: ...
+    def to_pandas(self) -> Any: ...
+    def to_polars(self) -> Any: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[ReadAlignment]: ...
+
+@final
+class ReadAlignment:
+    def __init__(self, read: str, result: QueryAlignment, is_forward: bool = True) -> None: ...
+    @property
+    def read(self) -> str: ...
+    @property
+    def is_forward(self) -> bool: ...
+    @property
+    def result(self) -> QueryAlignment: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+    def to_rows(self) -> list[_FlatReadAlignment]: ...
+    def num_alignments(self) -> int: ...
+
+@final
+class QueryAlignment:
+    def __init__(self, target_alignments: Sequence[TargetAlignment]) -> None: ...
+    def to_list(self) -> list[TargetAlignment]: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+    def to_rows(self) -> list[_FlatTargetAlignment]: ...
+    def num_alignments(self) -> int: ...
+    def __arrow_c_array__(
+        self,
+        requested_schema: object | None = None,
+    ) -> tuple[object, object]: ...
+    def to_arrow(self) -> Any: ...
+    def to_pandas(self) -> Any: ...
+    def to_polars(self) -> Any: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[TargetAlignment]: ...
+
+@final
+class TargetAlignment:
+    def __init__(
+        self,
+        index: int,
+        alignments: Sequence[Alignment],
+        label: str | None = None,
+    ) -> None: ...
+    @property
+    def index(self) -> int: ...
+    @property
+    def label(self) -> str | None: ...
+    @property
+    def alignments(self) -> list[Alignment]: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+    def to_rows(self) -> list[_FlatTargetAlignment]: ...
+    def num_alignments(self) -> int: ...
+    def __arrow_c_array__(
+        self,
+        requested_schema: object | None = None,
+    ) -> tuple[object, object]: ...
+    def to_arrow(self) -> Any: ...
+    def to_pandas(self) -> Any: ...
+    def to_polars(self) -> Any: ...
+
+@final
+class Alignment:
+    def __init__(
+        self,
+        penalty: int,
+        length: int,
+        query_position: tuple[int, int],
+        target_position: tuple[int, int],
+        operations: Sequence[AlignmentOperations],
+    ) -> None: ...
+    @property
+    def penalty(self) -> int: ...
+    @property
+    def length(self) -> int: ...
+    @property
+    def query_position(self) -> tuple[int, int]: ...
+    @property
+    def target_position(self) -> tuple[int, int]: ...
+    @property
+    def operations(self) -> list[AlignmentOperations]: ...
+    def to_cigar(self) -> str: ...
+    def stats(self) -> AlignmentStats: ...
+    def query_coverage(self, query_length: int) -> float: ...
+    def target_coverage(self, target_length: int) -> float: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+
+@final
+class AlignmentStats:
+    @property
+    def matches(self) -> int: ...
+    @property
+    def mismatches(self) -> int: ...
+    @property
+    def insertions(self) -> int: ...
+    @property
+    def deletions(self) -> int: ...
+    @property
+    def gap_opens(self) -> int: ...
+    @property
+    def gap_extensions(self) -> int: ...
+    @property
+    def identity(self) -> float: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+
+@final
+class AlignmentOperations:
+    def __init__(self, operation: AlignmentOperation, count: int) -> None: ...
+    @property
+    def operation(self) -> AlignmentOperation: ...
+    @property
+    def count(self) -> int: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+
+@final
+class AlignmentOperation:
+    Match: AlignmentOperation
+    Subst: AlignmentOperation
+    Insertion: AlignmentOperation
+    Deletion: AlignmentOperation
+    def __init__(
+        self,
+        chr: Literal["M", "Match", "S", "Subst", "I", "Insertion", "D", "Deletion"],
+    ) -> None: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+
+@final
+class ReadPairAlignment:
+    @property
+    def read(self) -> str: ...
+    @property
+    def first(self) -> list[MateAlignment]: ...
+    @property
+    def second(self) -> list[MateAlignment]: ...
+    @property
+    def concordant_pairs(self) -> list[ConcordantPair]: ...
+    def best_pair(self) -> ConcordantPair | None: ...
+    def is_concordant(self) -> bool: ...
+    def to_json(self) -> str: ...
+    def to_json_pretty(self) -> str: ...
+
+@final
+class MateAlignment:
+    @property
+    def target_index(self) -> int: ...
+    @property
+    def target_label(self) -> str | None: ...
+    @property
+    def is_forward(self) -> bool: ...
+    @property
+    def alignment(self) -> Alignment: ...
+
+@final
+class ConcordantPair:
+    @property
+    def first(self) -> int: ...
+    @property
+    def second(self) -> int: ...
+    @property
+    def insert_size(self) -> int: ...
+    @property
+    def penalty(self) -> int: ...
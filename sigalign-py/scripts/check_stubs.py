@@ -0,0 +1,217 @@
+"""Generate the stubs of the package (`python/sigalign/*.pyi`) from the signatures of the built module.
+
+Usage (after `maturin develop`):
+    python scripts/check_stubs.py           # Check that the stubs are up to date
+    python scripts/check_stubs.py --write   # Regenerate the stubs
+
+The members of the classes and the parameter names and default values are
+taken from the module (`__text_signature__` generated by pyo3).
+pyo3 signatures have no types, so the annotations are kept from the current
+stub by the name, and the new ones are `Any` to be filled in by hand.
+Everything outside the classes of the module (imports, aliases, comments and
+the classes not exported by the module) is also kept as it is.
+Each stub file is generated from the module of the same name
+(`__init__.pyi` from `sigalign`, and `results.pyi` from `sigalign.results`).
+"""
+import ast
+import difflib
+import inspect
+import json
+import sys
+from pathlib import Path
+
+import sigalign
+import sigalign.results
+
+PACKAGE_PATH = Path(__file__).resolve().parent.parent / "python" / "sigalign"
+# Stub file -> module
+STUBS = {
+    "__init__.pyi": sigalign,
+    "results.pyi": sigalign.results,
+}
+# Members written even if they start with "_"
+DUNDERS = {
+    "__arrow_c_array__", "__reduce__", "__copy__", "__deepcopy__",
+    "__len__", "__iter__", "__next__", "__enter__", "__exit__",
+}
+MAX_LINE_LENGTH = 100
+INDENT = "    "
+
+
+def runtime_classes(module):
+    return {
+        name: obj for name, obj in vars(module).items()
+        if isinstance(obj, type)
+    }
+
+
+def stub_members(tree, source):
+    """Annotations and order of the members in the stub.
+     - (class name, member name) -> (annotations of the parameters, return annotation)
+     - class name -> member names
+    """
+    annotations = {}
+    orders = {}
+
+    def text(node):
+        return ast.get_source_segment(source, node)
+
+    for node in tree.body:
+        if not isinstance(node, ast.ClassDef):
+            continue
+        path = node.name
+        orders[path] = []
+        for item in node.body:
+            if isinstance(item, ast.FunctionDef):
+                arguments = item.args.args + item.args.kwonlyargs
+                annotations[(path, item.name)] = (
+                    {
+                        arg.arg: text(arg.annotation)
+                        for arg in arguments if arg.annotation is not None
+                    },
+                    text(item.returns) if item.returns is not None else None,
+                )
+                orders[path].append(item.name)
+            elif isinstance(item, ast.AnnAssign):
+                annotations[(path, item.target.id)] = ({}, text(item.annotation))
+                orders[path].append(item.target.id)
+
+    return annotations, orders
+
+
+def format_default(value):
+    if isinstance(value, str):
+        return json.dumps(value)
+    return repr(value)
+
+
+def runtime_parameters(function):
+    # Without `self` or `cls`
+    return list(inspect.signature(function).parameters.values())[1:]
+
+
+def format_function(name, first, runtime, known, indent):
+    parameter_annotations, returns = known
+    parameters = [first]
+    for parameter in runtime:
+        text = parameter.name
+        annotation = parameter_annotations.get(parameter.name, "Any")
+        text += f": {annotation}"
+        if parameter.default is not inspect.Parameter.empty:
+            text += f" = {format_default(parameter.default)}"
+        parameters.append(text)
+    returns = returns or "Any"
+    one_line = f"{indent}def {name}({', '.join(parameters)}) -> {returns}: ..."
+    if len(one_line) <= MAX_LINE_LENGTH:
+        return [one_line]
+    return (
+        [f"{indent}def {name}("]
+        + [f"{indent}{INDENT}{parameter}," for parameter in parameters]
+        + [f"{indent}) -> {returns}: ..."]
+    )
+
+
+def format_class(cls, annotations, order):
+    path = cls.__name__
+    member_indent = INDENT
+
+    def known(name):
+        return annotations.get((path, name), ({}, None))
+
+    members = {}
+    # Constants (e.g., variants of the enum)
+    for name, value in vars(cls).items():
+        if isinstance(value, cls):
+            members[name] = [f"{member_indent}{name}: {known(name)[1] or 'Any'}"]
+    if getattr(cls, "__text_signature__", None) is not None:
+        parameter_annotations, _ = known("__init__")
+        members["__init__"] = format_function(
+            "__init__", "self", inspect.signature(cls).parameters.values(),
+            (parameter_annotations, "None"), member_indent,
+        )
+    properties = []
+    for name, member in vars(cls).items():
+        if name.startswith("_") and name not in DUNDERS:
+            continue
+        kind = type(member).__name__
+        if kind == "getset_descriptor":
+            properties.append(name)
+            members[name] = [
+                f"{member_indent}@property",
+                f"{member_indent}def {name}(self) -> {known(name)[1] or 'Any'}: ...",
+            ]
+        elif kind == "classmethod_descriptor":
+            members[name] = [f"{member_indent}@classmethod"] + format_function(
+                name, "cls", runtime_parameters(member), known(name), member_indent,
+            )
+        elif kind in ("method_descriptor", "wrapper_descriptor"):
+            members[name] = format_function(
+                name, "self", runtime_parameters(member), known(name), member_indent,
+            )
+
+    # In the order of the stub, and the new members follow.
+    #  - The order of the properties at runtime is not fixed.
+    names = [name for name in order if name in members]
+    names += [name for name in members if name not in names and name not in properties]
+    names += sorted(name for name in properties if name not in names)
+    lines = ["@final", f"class {cls.__name__}:"]
+    for name in names:
+        lines += members[name]
+    return lines
+
+
+def node_start(node):
+    return min([node.lineno] + [decorator.lineno for decorator in node.decorator_list])
+
+
+def generate(source, module):
+    """Replace the classes of the module in the stub."""
+    tree = ast.parse(source)
+    annotations, orders = stub_members(tree, source)
+    lines = source.splitlines()
+    classes = runtime_classes(module)
+    output = []
+    written = set()
+    cursor = 0
+    for node in tree.body:
+        if not isinstance(node, ast.ClassDef) or node.name not in classes:
+            continue
+        output.extend(lines[cursor:node_start(node) - 1])
+        output.extend(format_class(classes[node.name], annotations, orders[node.name]))
+        written.add(node.name)
+        cursor = node.end_lineno
+    output.extend(lines[cursor:])
+    # New classes are appended
+    for name, cls in classes.items():
+        if name not in written:
+            output.append("")
+            output.extend(format_class(cls, annotations, []))
+    return "\n".join(output) + "\n"
+
+
+def main():
+    to_write = "--write" in sys.argv[1:]
+    outdated = False
+    for file_name, module in STUBS.items():
+        path = PACKAGE_PATH / file_name
+        source = path.read_text()
+        generated = generate(source, module)
+        if to_write:
+            path.write_text(generated)
+            print(f"{file_name} is written.")
+        elif generated != source:
+            sys.stdout.writelines(difflib.unified_diff(
+                source.splitlines(keepends=True), generated.splitlines(keepends=True),
+                fromfile=file_name, tofile="generated",
+            ))
+            outdated = True
+    if to_write:
+        return
+    if outdated:
+        print("Stubs are outdated: run with `--write`.")
+        sys.exit(1)
+    print("Stubs match the module.")
+
+
+if __name__ == "__main__":
+    main()
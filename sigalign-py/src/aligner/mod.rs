@@ -1,16 +1,28 @@
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyString};
+use pyo3::types::{PyBytes, PyString, PyType};
+
+use sigalign::results::{PairOrientation, PairingOptions};
+use sigalign_utils::sequence_manipulation::preprocessing::QueryPreprocessor;
+use sigalign_utils::sequence_reader::{fasta::FastaReader, fastq::FastqReader, open_decompressed_file};
 
 use crate::{
     reference::PyReference,
-    results::{PyFastaAlignment, PyQueryAlignment},
+    results::{PyFastaAlignment, PyQueryAlignment, PyReadPairAlignment},
+    sequence_input::{extract_sequence, extract_sequence_batch, Symbols},
 };
 
 mod wrapper_for_algorithm;
-use wrapper_for_algorithm::AlignerWrapper;
+use wrapper_for_algorithm::{AlignerWrapper, map_open_err};
+mod streaming;
+pub use streaming::ReadAlignmentStream;
+use streaming::RecordReader;
+
+// Symbol for the masked bases. Not in the reference, so never matched.
+const MASK_SYMBOL: u8 = b'#';
 
-#[pyclass(name = "Aligner")]
+#[pyclass(name = "Aligner", module = "sigalign")]
+#[derive(Clone)]
 pub struct PyAligner {
     pub aligner_wrapper: AlignerWrapper,
     pub limitation_holder: Option<u32>,
@@ -90,55 +102,125 @@ impl PyAligner {
         self.chunk_holder
     }
 
+    // Pickled as the arguments of the constructor
+    #[allow(clippy::type_complexity)]
+    fn __reduce__<'py>(
+        slf: &Bound<'py, Self>,
+    ) -> (Bound<'py, PyType>, (u32, u32, u32, u32, f32, bool, Option<u32>, Option<(u32, u32)>)) {
+        let this = slf.borrow();
+        (slf.get_type(), (
+            this.get_mismatch_penalty(),
+            this.get_gap_open_penalty(),
+            this.get_gap_extend_penalty(),
+            this.get_minimum_length(),
+            this.get_maximum_penalty_per_length(),
+            this.is_local_mode(),
+            this.limitation_holder,
+            this.chunk_holder,
+        ))
+    }
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+    fn __deepcopy__(&self, _memo: &Bound<PyAny>) -> Self {
+        self.clone()
+    }
+
     // Alignments
+    /// The query is str, bytes, or an object supporting the buffer protocol (e.g., NumPy `uint8` array).
+    ///  - With `target_indices`, only the targets in the list are searched.
+    ///  - With `symbols`, the integer codes are decoded to the residues (e.g., "ACGT" for 0–3).
     #[pyo3(signature = (
         query,
         reference,
         with_label=false,
+        target_indices=None,
+        symbols=None,
     ))]
     fn align_query(
         &mut self,
         query: &Bound<PyAny>,
         reference: &PyReference,
         with_label: bool,
+        target_indices: Option<Vec<u32>>,
+        symbols: Option<&str>,
     ) -> PyResult<PyQueryAlignment> {
-        let query_bytes = if query.is_instance_of::<PyString>() {
-            query.downcast::<PyString>()?.to_str()?.as_bytes()
-        } else if query.is_instance_of::<PyBytes>() {
-            query.downcast::<PyBytes>()?.as_bytes()
-        } else {
-            return Err(PyValueError::new_err(
-                "The input must be either a string or bytes.",
-            ));
-        };
+        let symbols = Symbols::from_option(symbols)?;
+        let query_bytes = extract_sequence(query, symbols.as_ref())?;
+
+        if let Some(target_indices) = &target_indices {
+            let num_targets = reference.as_ref().get_num_targets();
+            if target_indices.iter().any(|&target_index| target_index >= num_targets) {
+                return Err(PyValueError::new_err("Target index is out of bound."));
+            }
+        }
 
         let py_query_alignment =
             self.aligner_wrapper
-                .align_query(query_bytes, reference, with_label);
+                .align_query(&query_bytes, reference, with_label, target_indices.as_deref());
         Ok(py_query_alignment)
     }
+    /// Align the batch of queries, returning the list of results in the same order.
+    ///  - `queries` is a 2D buffer of the padded rows (e.g., NumPy `uint8` array), or a list of sequences.
+    ///  - With `lengths`, each query is truncated to the length to remove the padding.
+    ///  - With `symbols`, the integer codes are decoded to the residues (e.g., "ACGT" for 0–3).
+    #[pyo3(signature = (
+        queries,
+        reference,
+        lengths=None,
+        symbols=None,
+        with_label=false,
+        num_threads=1,
+    ))]
+    fn align_queries(
+        &mut self,
+        py: Python<'_>,
+        queries: &Bound<PyAny>,
+        reference: &PyReference,
+        lengths: Option<Vec<usize>>,
+        symbols: Option<&str>,
+        with_label: bool,
+        num_threads: usize,
+    ) -> PyResult<Vec<PyQueryAlignment>> {
+        let symbols = Symbols::from_option(symbols)?;
+        let queries = extract_sequence_batch(queries, lengths.as_deref(), symbols.as_ref())?;
+
+        let num_threads = resolve_num_threads(num_threads).min(queries.len()).max(1);
+        let mut workers = self.aligner_wrapper.clone_workers(num_threads);
+        Ok(py.allow_threads(|| self.aligner_wrapper.align_queries(
+            &mut workers,
+            &queries,
+            reference,
+            with_label,
+        )))
+    }
     #[pyo3(signature = (
         file_path,
         reference,
         with_label=false,
         with_reverse_complementary=false,
         allow_interrupt=false,
+        num_threads=1,
     ))]
     fn align_fasta_file(
         &mut self,
+        py: Python<'_>,
         file_path: &str,
         reference: &PyReference,
         with_label: bool,
         with_reverse_complementary: bool,
         allow_interrupt: bool,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
-        self.aligner_wrapper.align_fasta_file(
+        let num_threads = resolve_num_threads(num_threads);
+        py.allow_threads(|| self.aligner_wrapper.align_fasta_file(
             reference,
             file_path,
             with_label,
             with_reverse_complementary,
             allow_interrupt,
-        )
+            num_threads,
+        ))
     }
     #[pyo3(signature = (
         fasta,
@@ -146,14 +228,17 @@ impl PyAligner {
         with_label=false,
         with_reverse_complementary=false,
         allow_interrupt=false,
+        num_threads=1,
     ))]
     fn align_fasta(
         &mut self,
+        py: Python<'_>,
         fasta: &Bound<PyAny>,
         reference: &PyReference,
         with_label: bool,
         with_reverse_complementary: bool,
         allow_interrupt: bool,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
         let fasta_bytes = if fasta.is_instance_of::<PyString>() {
             fasta.downcast::<PyString>()?.to_str()?.as_bytes()
@@ -165,13 +250,15 @@ impl PyAligner {
             ));
         };
 
-        self.aligner_wrapper.align_fasta_bytes(
+        let num_threads = resolve_num_threads(num_threads);
+        py.allow_threads(|| self.aligner_wrapper.align_fasta_bytes(
             reference,
             fasta_bytes,
             with_label,
             with_reverse_complementary,
             allow_interrupt,
-        )
+            num_threads,
+        ))
     }
     #[pyo3(signature = (
         file_path,
@@ -179,22 +266,37 @@ impl PyAligner {
         with_label=false,
         with_reverse_complementary=false,
         allow_interrupt=false,
+        quality_trimming=None,
+        adapters=None,
+        min_adapter_overlap=5,
+        masking_quality=None,
+        num_threads=1,
     ))]
     fn align_fastq_file(
         &mut self,
+        py: Python<'_>,
         file_path: &str,
         reference: &PyReference,
         with_label: bool,
         with_reverse_complementary: bool,
         allow_interrupt: bool,
+        quality_trimming: Option<(usize, u8)>,
+        adapters: Option<Vec<String>>,
+        min_adapter_overlap: usize,
+        masking_quality: Option<u8>,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
-        self.aligner_wrapper.align_fastq_file(
+        let preprocessor = build_preprocessor(quality_trimming, adapters, min_adapter_overlap, masking_quality);
+        let num_threads = resolve_num_threads(num_threads);
+        py.allow_threads(|| self.aligner_wrapper.align_fastq_file(
             reference,
             file_path,
             with_label,
             with_reverse_complementary,
+            preprocessor.as_ref(),
             allow_interrupt,
-        )
+            num_threads,
+        ))
     }
     #[pyo3(signature = (
         fastq,
@@ -202,14 +304,25 @@ impl PyAligner {
         with_label=false,
         with_reverse_complementary=false,
         allow_interrupt=false,
+        quality_trimming=None,
+        adapters=None,
+        min_adapter_overlap=5,
+        masking_quality=None,
+        num_threads=1,
     ))]
     fn align_fastq(
         &mut self,
+        py: Python<'_>,
         fastq: &Bound<PyAny>,
         reference: &PyReference,
         with_label: bool,
         with_reverse_complementary: bool,
         allow_interrupt: bool,
+        quality_trimming: Option<(usize, u8)>,
+        adapters: Option<Vec<String>>,
+        min_adapter_overlap: usize,
+        masking_quality: Option<u8>,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
         let fastq_bytes = if fastq.is_instance_of::<PyString>() {
             fastq.downcast::<PyString>()?.to_str()?.as_bytes()
@@ -221,12 +334,176 @@ impl PyAligner {
             ));
         };
 
-        self.aligner_wrapper.align_fastq_bytes(
+        let preprocessor = build_preprocessor(quality_trimming, adapters, min_adapter_overlap, masking_quality);
+        let num_threads = resolve_num_threads(num_threads);
+        py.allow_threads(|| self.aligner_wrapper.align_fastq_bytes(
             reference,
             fastq_bytes,
             with_label,
             with_reverse_complementary,
+            preprocessor.as_ref(),
+            allow_interrupt,
+            num_threads,
+        ))
+    }
+    /// Iterate over the alignments of the records in the FASTA file (can be gzipped) lazily,
+    /// instead of keeping all results in memory.
+    ///  - At most `buffer_size` records are aligned at once.
+    #[pyo3(signature = (
+        file_path,
+        reference,
+        with_label=false,
+        with_reverse_complementary=false,
+        num_threads=1,
+        buffer_size=1024,
+    ))]
+    fn iter_align_fasta(
+        &self,
+        file_path: &str,
+        reference: Py<PyReference>,
+        with_label: bool,
+        with_reverse_complementary: bool,
+        num_threads: usize,
+        buffer_size: usize,
+    ) -> PyResult<ReadAlignmentStream> {
+        let fasta_reader = FastaReader::new(open_decompressed_file(file_path).map_err(map_open_err)?);
+        Ok(ReadAlignmentStream::new(
+            &self.aligner_wrapper,
+            reference,
+            RecordReader::Fasta(fasta_reader),
+            buffer_size,
+            resolve_num_threads(num_threads),
+            with_label,
+            with_reverse_complementary,
+        ))
+    }
+    /// Iterate over the alignments of the records in the FASTQ file (can be gzipped) lazily,
+    /// instead of keeping all results in memory.
+    ///  - At most `buffer_size` records are aligned at once.
+    #[pyo3(signature = (
+        file_path,
+        reference,
+        with_label=false,
+        with_reverse_complementary=false,
+        quality_trimming=None,
+        adapters=None,
+        min_adapter_overlap=5,
+        masking_quality=None,
+        num_threads=1,
+        buffer_size=1024,
+    ))]
+    fn iter_align_fastq(
+        &self,
+        file_path: &str,
+        reference: Py<PyReference>,
+        with_label: bool,
+        with_reverse_complementary: bool,
+        quality_trimming: Option<(usize, u8)>,
+        adapters: Option<Vec<String>>,
+        min_adapter_overlap: usize,
+        masking_quality: Option<u8>,
+        num_threads: usize,
+        buffer_size: usize,
+    ) -> PyResult<ReadAlignmentStream> {
+        let fastq_reader = FastqReader::new(open_decompressed_file(file_path).map_err(map_open_err)?);
+        let preprocessor = build_preprocessor(quality_trimming, adapters, min_adapter_overlap, masking_quality);
+        Ok(ReadAlignmentStream::new(
+            &self.aligner_wrapper,
+            reference,
+            RecordReader::Fastq(fastq_reader, preprocessor),
+            buffer_size,
+            resolve_num_threads(num_threads),
+            with_label,
+            with_reverse_complementary,
+        ))
+    }
+    /// Align paired-end reads in two FASTQ files, or in one interleaved file
+    /// if `second_file_path` is not given.
+    ///  - `orientation`: "fr", "rf", or "ff".
+    #[pyo3(signature = (
+        file_path,
+        reference,
+        second_file_path=None,
+        min_insert_size=0,
+        max_insert_size=1000,
+        orientation="fr",
+        with_label=false,
+        allow_interrupt=false,
+        quality_trimming=None,
+        adapters=None,
+        min_adapter_overlap=5,
+        masking_quality=None,
+    ))]
+    fn align_paired_fastq_file(
+        &mut self,
+        file_path: &str,
+        reference: &PyReference,
+        second_file_path: Option<&str>,
+        min_insert_size: u32,
+        max_insert_size: u32,
+        orientation: &str,
+        with_label: bool,
+        allow_interrupt: bool,
+        quality_trimming: Option<(usize, u8)>,
+        adapters: Option<Vec<String>>,
+        min_adapter_overlap: usize,
+        masking_quality: Option<u8>,
+    ) -> PyResult<Vec<PyReadPairAlignment>> {
+        let orientation = match orientation {
+            "fr" => PairOrientation::ForwardReverse,
+            "rf" => PairOrientation::ReverseForward,
+            "ff" => PairOrientation::ForwardForward,
+            _ => return Err(PyValueError::new_err(
+                "The orientation must be one of 'fr', 'rf', or 'ff'.",
+            )),
+        };
+        if min_insert_size > max_insert_size {
+            return Err(PyValueError::new_err(
+                "The minimum insert size must not exceed the maximum.",
+            ));
+        }
+        let pairing_options = PairingOptions::new(orientation, min_insert_size, max_insert_size);
+        let preprocessor = build_preprocessor(quality_trimming, adapters, min_adapter_overlap, masking_quality);
+        self.aligner_wrapper.align_paired_fastq_file(
+            reference,
+            file_path,
+            second_file_path,
+            &pairing_options,
+            preprocessor.as_ref(),
+            with_label,
             allow_interrupt,
         )
     }
 }
+
+/// `0` uses all available cores.
+fn resolve_num_threads(num_threads: usize) -> usize {
+    if num_threads == 0 {
+        std::thread::available_parallelism().map_or(1, |x| x.get())
+    } else {
+        num_threads
+    }
+}
+
+/// `None` if no preprocessing step is set.
+///  - `quality_trimming`: (window size, minimum mean quality) of the sliding window.
+///  - `masking_quality`: bases lower than this are masked.
+fn build_preprocessor(
+    quality_trimming: Option<(usize, u8)>,
+    adapters: Option<Vec<String>>,
+    min_adapter_overlap: usize,
+    masking_quality: Option<u8>,
+) -> Option<QueryPreprocessor> {
+    let mut preprocessor = QueryPreprocessor::new();
+    if let Some((window_size, min_quality)) = quality_trimming {
+        preprocessor = preprocessor.with_sliding_window_trimming(window_size, min_quality);
+    }
+    if let Some(adapters) = adapters {
+        let adapters = adapters.into_iter().map(|x| x.to_ascii_uppercase().into_bytes()).collect();
+        preprocessor = preprocessor.with_adapters(adapters, min_adapter_overlap);
+    }
+    if let Some(min_quality) = masking_quality {
+        preprocessor = preprocessor.with_quality_masking(min_quality, MASK_SYMBOL);
+    }
+    if preprocessor.is_empty() { None } else { Some(preprocessor) }
+}
@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::io::Read;
+
+use pyo3::prelude::*;
+use sigalign_utils::{
+    sequence_reader::{fasta::FastaReader, fastq::FastqReader},
+    sequence_manipulation::preprocessing::QueryPreprocessor,
+};
+
+use crate::reference::PyReference;
+use crate::results::PyReadAlignment;
+use super::wrapper_for_algorithm::{
+    AlignerWrapper, QueryRecord, read_fasta_records, read_fastq_records,
+};
+
+pub enum RecordReader {
+    Fasta(FastaReader<Box<dyn Read + Send>>),
+    Fastq(FastqReader<Box<dyn Read + Send>>, Option<QueryPreprocessor>),
+}
+
+impl RecordReader {
+    fn read_records(&mut self, records: &mut Vec<QueryRecord>, batch_size: usize) -> PyResult<()> {
+        match self {
+            Self::Fasta(reader) => read_fasta_records(reader, records, batch_size),
+            Self::Fastq(reader, preprocessor) => {
+                read_fastq_records(reader, preprocessor.as_ref(), records, batch_size)
+            },
+        }
+    }
+}
+
+/// Iterator yielding `ReadAlignment` of the records in the file lazily.
+///  - At most `buffer_size` records are read and aligned at once.
+///  - The file is closed when the iterator is exhausted, closed, or dropped.
+/// Not in Rust library.
+#[pyclass(name = "ReadAlignmentIterator", module = "sigalign")]
+pub struct ReadAlignmentStream {
+    aligner: AlignerWrapper,
+    workers: Vec<AlignerWrapper>,
+    reference: Py<PyReference>,
+    // None after the end of the file
+    reader: Option<RecordReader>,
+    records: Vec<QueryRecord>,
+    buffer: VecDeque<PyReadAlignment>,
+    buffer_size: usize,
+    with_label: bool,
+    with_reverse_complementary: bool,
+}
+
+#[pymethods]
+impl ReadAlignmentStream {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PyReadAlignment>> {
+        loop {
+            if let Some(read_alignment) = slf.buffer.pop_front() {
+                return Ok(Some(read_alignment));
+            }
+            if slf.reader.is_none() {
+                return Ok(None);
+            }
+            let Self {
+                aligner, workers, reference, reader, records, buffer,
+                buffer_size, with_label, with_reverse_complementary,
+            } = &mut *slf;
+            let reference = reference.borrow(py);
+            let reference = reference.as_ref();
+            let result = py.allow_threads(|| -> PyResult<()> {
+                records.clear();
+                reader.as_mut().unwrap().read_records(records, *buffer_size)?;
+                let mut py_read_alignments = Vec::new();
+                aligner.align_batch(
+                    workers,
+                    records,
+                    reference,
+                    *with_label,
+                    *with_reverse_complementary,
+                    &mut py_read_alignments,
+                )?;
+                buffer.extend(py_read_alignments);
+                Ok(())
+            });
+            if result.is_err() || records.is_empty() {
+                *reader = None;
+            }
+            result?;
+        }
+    }
+    /// Stop the iteration and close the file.
+    fn close(&mut self) {
+        self.reader = None;
+        self.buffer.clear();
+    }
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    fn __exit__(
+        &mut self,
+        _exc_type: &Bound<'_, PyAny>,
+        _exc_value: &Bound<'_, PyAny>,
+        _traceback: &Bound<'_, PyAny>,
+    ) -> bool {
+        self.close();
+        false
+    }
+}
+
+impl ReadAlignmentStream {
+    pub fn new(
+        aligner: &AlignerWrapper,
+        reference: Py<PyReference>,
+        reader: RecordReader,
+        buffer_size: usize,
+        num_threads: usize,
+        with_label: bool,
+        with_reverse_complementary: bool,
+    ) -> Self {
+        let buffer_size = buffer_size.max(1);
+        Self {
+            aligner: aligner.clone(),
+            workers: aligner.clone_workers(num_threads),
+            reference,
+            reader: Some(reader),
+            records: Vec::with_capacity(buffer_size),
+            buffer: VecDeque::new(),
+            buffer_size,
+            with_label,
+            with_reverse_complementary,
+        }
+    }
+}
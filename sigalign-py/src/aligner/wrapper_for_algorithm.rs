@@ -1,29 +1,29 @@
 use std::io::Read;
 
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
 use sigalign::algorithms::Algorithm;
 use sigalign::{
     algorithms::{
         Local, LocalWithChunk, LocalWithLimit, SemiGlobal, SemiGlobalWithChunk, SemiGlobalWithLimit,
     },
-    results::QueryAlignment,
-    Aligner, Reference,
+    results::{QueryAlignment, PairingOptions},
+    Aligner, Reference, StrandError,
 };
 use sigalign_utils::{
         sequence_reader::{
         fasta::FastaReader, fastq::FastqReader, IdRecord, SeqRecord,
+        ReadError, ReadErrorKind, open_decompressed_file, OpenSequenceFileError,
+        PairedFastqReader, ReadPair,
     },
-    sequence_manipulation::reverse_complementary::{
-        reverse_complement_of_dna_sequence,
-        reverse_complement_of_dna_sequence_in_place,
-    },
+    sequence_manipulation::preprocessing::{QueryPreprocessor, TrimmedRange},
 };
 use sigalign_utils::sequence_reader::{IdRefRecord, SeqRefRecord};
 
 use crate::reference::PyReference;
-use crate::results::{PyFastaAlignment, PyQueryAlignment, PyReadAlignment};
+use crate::results::{PyFastaAlignment, PyQueryAlignment, PyReadAlignment, PyReadPairAlignment};
 
+#[derive(Clone)]
 pub enum AlignerWrapper {
     Local(Aligner<Local>),
     LocalWithLimit(Aligner<LocalWithLimit>),
@@ -33,9 +33,27 @@ pub enum AlignerWrapper {
     SemiGlobalWithChunk(Aligner<SemiGlobalWithChunk>),
 }
 
+// Records aligned by each thread in a batch. Signals are checked after each batch.
+const RECORDS_PER_THREAD_IN_BATCH: usize = 64;
+
 fn map_params_err(err: sigalign::algorithms::ParamsError) -> PyErr {
     PyValueError::new_err(err.to_string())
 }
+pub(super) fn map_open_err(err: OpenSequenceFileError) -> PyErr {
+    match err {
+        OpenSequenceFileError::Io(err) => PyErr::from(err),
+        _ => PyValueError::new_err(err.to_string()),
+    }
+}
+fn map_strand_err(err: StrandError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+fn map_read_err(err: ReadError) -> PyErr {
+    match err.kind() {
+        ReadErrorKind::Io(_) => PyIOError::new_err(err.to_string()),
+        _ => PyValueError::new_err(err.to_string()),
+    }
+}
 
 impl AlignerWrapper {
     /*
@@ -187,18 +205,19 @@ impl AlignerWrapper {
         query: &[u8],
         reference: &PyReference,
         with_label: bool,
+        target_indices: Option<&[u32]>,
     ) -> PyQueryAlignment {
         let reference = reference.as_ref();
         let query_alignment = match self {
-            AlignerWrapper::Local(v) => align_query_with_core_aligner(v, query, reference),
-            AlignerWrapper::LocalWithLimit(v) => align_query_with_core_aligner(v, query, reference),
-            AlignerWrapper::LocalWithChunk(v) => align_query_with_core_aligner(v, query, reference),
-            AlignerWrapper::SemiGlobal(v) => align_query_with_core_aligner(v, query, reference),
+            AlignerWrapper::Local(v) => align_query_with_core_aligner(v, query, reference, target_indices),
+            AlignerWrapper::LocalWithLimit(v) => align_query_with_core_aligner(v, query, reference, target_indices),
+            AlignerWrapper::LocalWithChunk(v) => align_query_with_core_aligner(v, query, reference, target_indices),
+            AlignerWrapper::SemiGlobal(v) => align_query_with_core_aligner(v, query, reference, target_indices),
             AlignerWrapper::SemiGlobalWithLimit(v) => {
-                align_query_with_core_aligner(v, query, reference)
+                align_query_with_core_aligner(v, query, reference, target_indices)
             }
             AlignerWrapper::SemiGlobalWithChunk(v) => {
-                align_query_with_core_aligner(v, query, reference)
+                align_query_with_core_aligner(v, query, reference, target_indices)
             }
         };
         if with_label {
@@ -208,6 +227,32 @@ impl AlignerWrapper {
             PyQueryAlignment::from(query_alignment)
         }
     }
+    /// Align the queries with this aligner and `workers`, preserving the order of the queries.
+    pub fn align_queries(
+        &mut self,
+        workers: &mut [AlignerWrapper],
+        queries: &[Vec<u8>],
+        reference: &PyReference,
+        with_label: bool,
+    ) -> Vec<PyQueryAlignment> {
+        let chunk_size = queries.len().div_ceil(workers.len() + 1).max(1);
+        let mut chunks = queries.chunks(chunk_size);
+        let first_chunk = chunks.next().unwrap_or_default();
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = chunks.zip(workers.iter_mut()).map(|(chunk, worker)| {
+                scope.spawn(move || -> Vec<PyQueryAlignment> {
+                    chunk.iter().map(|query| worker.align_query(query, reference, with_label, None)).collect()
+                })
+            }).collect();
+            let mut query_alignments: Vec<PyQueryAlignment> = first_chunk.iter().map(|query| {
+                self.align_query(query, reference, with_label, None)
+            }).collect();
+            for handle in handles {
+                query_alignments.extend(handle.join().expect("Alignment thread panicked"));
+            }
+            query_alignments
+        })
+    }
 
     // - For fasta
     pub fn align_fasta_file(
@@ -217,23 +262,17 @@ impl AlignerWrapper {
         with_label: bool,
         with_reverse_complementary: bool,
         checking_signals: bool,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
-        let mut fasta_reader = FastaReader::from_path(file_path)?;
-        if checking_signals {
-            self.align_fasta_with_checking_signals(
-                reference,
-                &mut fasta_reader,
-                with_label,
-                with_reverse_complementary,
-            )
-        } else {
-            self.align_fasta_without_checking_signals(
-                reference,
-                &mut fasta_reader,
-                with_label,
-                with_reverse_complementary,
-            )
-        }
+        let mut fasta_reader = FastaReader::new(open_decompressed_file(file_path).map_err(map_open_err)?);
+        self.align_records(
+            reference.as_ref(),
+            |records, batch_size| read_fasta_records(&mut fasta_reader, records, batch_size),
+            with_label,
+            with_reverse_complementary,
+            checking_signals,
+            num_threads,
+        )
     }
     pub fn align_fasta_bytes(
         &mut self,
@@ -242,289 +281,211 @@ impl AlignerWrapper {
         with_label: bool,
         with_reverse_complementary: bool,
         checking_signals: bool,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
         let mut fasta_reader = FastaReader::new(fasta_bytes);
-        if checking_signals {
-            self.align_fasta_with_checking_signals(
-                reference,
-                &mut fasta_reader,
-                with_label,
-                with_reverse_complementary,
-            )
-        } else {
-            self.align_fasta_without_checking_signals(
-                reference,
-                &mut fasta_reader,
-                with_label,
-                with_reverse_complementary,
-            )
-        }
+        self.align_records(
+            reference.as_ref(),
+            |records, batch_size| read_fasta_records(&mut fasta_reader, records, batch_size),
+            with_label,
+            with_reverse_complementary,
+            checking_signals,
+            num_threads,
+        )
     }
-    fn align_fasta_without_checking_signals<R: Read>(
+    // - For fastq
+    pub fn align_fastq_file(
         &mut self,
         reference: &PyReference,
-        fasta_reader: &mut FastaReader<R>,
+        file_path: &str,
         with_label: bool,
         with_reverse_complementary: bool,
+        preprocessor: Option<&QueryPreprocessor>,
+        checking_signals: bool,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
-        match self {
-            AlignerWrapper::Local(v) => Ok(align_fasta_with_core_aligner(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::LocalWithLimit(v) => Ok(align_fasta_with_core_aligner(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::LocalWithChunk(v) => Ok(align_fasta_with_core_aligner(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::SemiGlobal(v) => Ok(align_fasta_with_core_aligner(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::SemiGlobalWithLimit(v) => Ok(align_fasta_with_core_aligner(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::SemiGlobalWithChunk(v) => Ok(align_fasta_with_core_aligner(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-        }
+        let mut fastq_reader = FastqReader::new(open_decompressed_file(file_path).map_err(map_open_err)?);
+        self.align_records(
+            reference.as_ref(),
+            |records, batch_size| read_fastq_records(&mut fastq_reader, preprocessor, records, batch_size),
+            with_label,
+            with_reverse_complementary,
+            checking_signals,
+            num_threads,
+        )
     }
-    fn align_fasta_with_checking_signals<R: Read>(
+    pub fn align_fastq_bytes(
         &mut self,
         reference: &PyReference,
-        fasta_reader: &mut FastaReader<R>,
+        fastq_bytes: &[u8],
         with_label: bool,
         with_reverse_complementary: bool,
+        preprocessor: Option<&QueryPreprocessor>,
+        checking_signals: bool,
+        num_threads: usize,
     ) -> PyResult<PyFastaAlignment> {
-        match self {
-            AlignerWrapper::Local(v) => align_fasta_with_core_aligner_checking_signals(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            ),
-            AlignerWrapper::LocalWithLimit(v) => align_fasta_with_core_aligner_checking_signals(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            ),
-            AlignerWrapper::LocalWithChunk(v) => align_fasta_with_core_aligner_checking_signals(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            ),
-            AlignerWrapper::SemiGlobal(v) => align_fasta_with_core_aligner_checking_signals(
-                v,
-                fasta_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            ),
-            AlignerWrapper::SemiGlobalWithLimit(v) => {
-                align_fasta_with_core_aligner_checking_signals(
-                    v,
-                    fasta_reader,
-                    reference.as_ref(),
-                    with_label,
-                    with_reverse_complementary,
-                )
-            }
-            AlignerWrapper::SemiGlobalWithChunk(v) => {
-                align_fasta_with_core_aligner_checking_signals(
-                    v,
-                    fasta_reader,
-                    reference.as_ref(),
-                    with_label,
-                    with_reverse_complementary,
-                )
-            }
-        }
+        let mut fastq_reader = FastqReader::new(fastq_bytes);
+        self.align_records(
+            reference.as_ref(),
+            |records, batch_size| read_fastq_records(&mut fastq_reader, preprocessor, records, batch_size),
+            with_label,
+            with_reverse_complementary,
+            checking_signals,
+            num_threads,
+        )
     }
-    // - For fastq
-    pub fn align_fastq_file(
+    // - For records
+    /// Align the records batch by batch, preserving the order of the records.
+    ///  - Each batch is aligned by this aligner and its `num_threads - 1` clones.
+    ///  - `read_batch` fills the records up to the batch size. No record is read at the end.
+    fn align_records<F>(
         &mut self,
-        reference: &PyReference,
-        file_path: &str,
+        reference: &Reference,
+        mut read_batch: F,
         with_label: bool,
         with_reverse_complementary: bool,
         checking_signals: bool,
-    ) -> PyResult<PyFastaAlignment> {
-        let mut fastq_reader = FastqReader::from_path(file_path)?;
-        if checking_signals {
-            self.align_fastq_with_checking_signals(
-                reference,
-                &mut fastq_reader,
-                with_label,
-                with_reverse_complementary,
-            )
-        } else {
-            self.align_fastq_without_checking_signals(
+        num_threads: usize,
+    ) -> PyResult<PyFastaAlignment> where
+        F: FnMut(&mut Vec<QueryRecord>, usize) -> PyResult<()>,
+    {
+        let num_threads = num_threads.max(1);
+        let mut workers = self.clone_workers(num_threads);
+        let batch_size = num_threads * RECORDS_PER_THREAD_IN_BATCH;
+
+        let mut py_read_alignments = Vec::new();
+        let mut records = Vec::with_capacity(batch_size);
+        loop {
+            records.clear();
+            read_batch(&mut records, batch_size)?;
+            if records.is_empty() {
+                break;
+            }
+            self.align_batch(
+                &mut workers,
+                &records,
                 reference,
-                &mut fastq_reader,
                 with_label,
                 with_reverse_complementary,
-            )
+                &mut py_read_alignments,
+            )?;
+            if checking_signals {
+                Python::with_gil(|py| py.check_signals())?;
+            }
         }
+        Ok(PyFastaAlignment(py_read_alignments))
     }
-    pub fn align_fastq_bytes(
+    /// Clones of this aligner for the other threads.
+    pub fn clone_workers(&self, num_threads: usize) -> Vec<AlignerWrapper> {
+        (1..num_threads).map(|_| self.clone()).collect()
+    }
+    /// Align the records with this aligner and `workers`, preserving the order of the records.
+    ///  - The records are split into chunks for each aligner.
+    pub fn align_batch(
         &mut self,
-        reference: &PyReference,
-        fastq_bytes: &[u8],
+        workers: &mut [AlignerWrapper],
+        records: &[QueryRecord],
+        reference: &Reference,
         with_label: bool,
         with_reverse_complementary: bool,
-        checking_signals: bool,
-    ) -> PyResult<PyFastaAlignment> {
-        let mut fastq_reader = FastqReader::new(fastq_bytes);
-        if checking_signals {
-            self.align_fastq_with_checking_signals(
-                reference,
-                &mut fastq_reader,
-                with_label,
-                with_reverse_complementary,
-            )
-        } else {
-            self.align_fastq_without_checking_signals(
-                reference,
-                &mut fastq_reader,
-                with_label,
-                with_reverse_complementary,
-            )
+        py_read_alignments: &mut Vec<PyReadAlignment>,
+    ) -> PyResult<()> {
+        if workers.is_empty() {
+            for record in records {
+                self.align_record(record, reference, with_label, with_reverse_complementary, py_read_alignments)?;
+            }
+            return Ok(());
+        }
+        let chunk_size = records.len().div_ceil(workers.len() + 1).max(1);
+        let mut chunks = records.chunks(chunk_size);
+        let first_chunk = chunks.next().unwrap_or_default();
+        let chunk_results = std::thread::scope(|scope| {
+            let handles: Vec<_> = chunks.zip(workers.iter_mut()).map(|(chunk, worker)| {
+                scope.spawn(move || -> PyResult<Vec<PyReadAlignment>> {
+                    let mut chunk_result = Vec::new();
+                    for record in chunk {
+                        worker.align_record(record, reference, with_label, with_reverse_complementary, &mut chunk_result)?;
+                    }
+                    Ok(chunk_result)
+                })
+            }).collect();
+            let mut first_result = Vec::new();
+            let first_result = first_chunk.iter().try_for_each(|record| {
+                self.align_record(record, reference, with_label, with_reverse_complementary, &mut first_result)
+            }).map(|_| first_result);
+            std::iter::once(first_result).chain(
+                handles.into_iter().map(|handle| handle.join().expect("Alignment thread panicked"))
+            ).collect::<Vec<_>>()
+        });
+        for chunk_result in chunk_results {
+            py_read_alignments.extend(chunk_result?);
         }
+        Ok(())
     }
-    fn align_fastq_without_checking_signals<R: Read>(
+    fn align_record(
         &mut self,
-        reference: &PyReference,
-        fastq_reader: &mut FastqReader<R>,
+        record: &QueryRecord,
+        reference: &Reference,
         with_label: bool,
         with_reverse_complementary: bool,
-    ) -> PyResult<PyFastaAlignment> {
+        py_read_alignments: &mut Vec<PyReadAlignment>,
+    ) -> PyResult<()> {
         match self {
-            AlignerWrapper::Local(v) => Ok(align_fastq_with_core_aligner(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::LocalWithLimit(v) => Ok(align_fastq_with_core_aligner(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::LocalWithChunk(v) => Ok(align_fastq_with_core_aligner(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::SemiGlobal(v) => Ok(align_fastq_with_core_aligner(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::SemiGlobalWithLimit(v) => Ok(align_fastq_with_core_aligner(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
-            AlignerWrapper::SemiGlobalWithChunk(v) => Ok(align_fastq_with_core_aligner(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
-            )),
+            AlignerWrapper::Local(v) => align_record_with_core_aligner(
+                v, record, reference, with_label, with_reverse_complementary, py_read_alignments,
+            ),
+            AlignerWrapper::LocalWithLimit(v) => align_record_with_core_aligner(
+                v, record, reference, with_label, with_reverse_complementary, py_read_alignments,
+            ),
+            AlignerWrapper::LocalWithChunk(v) => align_record_with_core_aligner(
+                v, record, reference, with_label, with_reverse_complementary, py_read_alignments,
+            ),
+            AlignerWrapper::SemiGlobal(v) => align_record_with_core_aligner(
+                v, record, reference, with_label, with_reverse_complementary, py_read_alignments,
+            ),
+            AlignerWrapper::SemiGlobalWithLimit(v) => align_record_with_core_aligner(
+                v, record, reference, with_label, with_reverse_complementary, py_read_alignments,
+            ),
+            AlignerWrapper::SemiGlobalWithChunk(v) => align_record_with_core_aligner(
+                v, record, reference, with_label, with_reverse_complementary, py_read_alignments,
+            ),
         }
     }
-    fn align_fastq_with_checking_signals<R: Read>(
+    // - For paired FASTQ
+    pub fn align_paired_fastq_file(
         &mut self,
         reference: &PyReference,
-        fastq_reader: &mut FastqReader<R>,
+        file_path: &str,
+        second_file_path: Option<&str>,
+        pairing_options: &PairingOptions,
+        preprocessor: Option<&QueryPreprocessor>,
         with_label: bool,
-        with_reverse_complementary: bool,
-    ) -> PyResult<PyFastaAlignment> {
+        checking_signals: bool,
+    ) -> PyResult<Vec<PyReadPairAlignment>> {
+        let mut paired_reader = match second_file_path {
+            Some(second_file_path) => PairedFastqReader::from_paths(file_path, second_file_path),
+            None => PairedFastqReader::interleaved_from_path(file_path),
+        }.map_err(map_open_err)?;
+        let reference = reference.as_ref();
         match self {
-            AlignerWrapper::Local(v) => align_fastq_with_core_aligner_checking_signals(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
+            AlignerWrapper::Local(v) => align_read_pairs_with_core_aligner(
+                v, &mut paired_reader, reference, pairing_options, preprocessor, with_label, checking_signals,
             ),
-            AlignerWrapper::LocalWithLimit(v) => align_fastq_with_core_aligner_checking_signals(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
+            AlignerWrapper::LocalWithLimit(v) => align_read_pairs_with_core_aligner(
+                v, &mut paired_reader, reference, pairing_options, preprocessor, with_label, checking_signals,
             ),
-            AlignerWrapper::LocalWithChunk(v) => align_fastq_with_core_aligner_checking_signals(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
+            AlignerWrapper::LocalWithChunk(v) => align_read_pairs_with_core_aligner(
+                v, &mut paired_reader, reference, pairing_options, preprocessor, with_label, checking_signals,
             ),
-            AlignerWrapper::SemiGlobal(v) => align_fastq_with_core_aligner_checking_signals(
-                v,
-                fastq_reader,
-                reference.as_ref(),
-                with_label,
-                with_reverse_complementary,
+            AlignerWrapper::SemiGlobal(v) => align_read_pairs_with_core_aligner(
+                v, &mut paired_reader, reference, pairing_options, preprocessor, with_label, checking_signals,
+            ),
+            AlignerWrapper::SemiGlobalWithLimit(v) => align_read_pairs_with_core_aligner(
+                v, &mut paired_reader, reference, pairing_options, preprocessor, with_label, checking_signals,
+            ),
+            AlignerWrapper::SemiGlobalWithChunk(v) => align_read_pairs_with_core_aligner(
+                v, &mut paired_reader, reference, pairing_options, preprocessor, with_label, checking_signals,
             ),
-            AlignerWrapper::SemiGlobalWithLimit(v) => {
-                align_fastq_with_core_aligner_checking_signals(
-                    v,
-                    fastq_reader,
-                    reference.as_ref(),
-                    with_label,
-                    with_reverse_complementary,
-                )
-            }
-            AlignerWrapper::SemiGlobalWithChunk(v) => {
-                align_fastq_with_core_aligner_checking_signals(
-                    v,
-                    fastq_reader,
-                    reference.as_ref(),
-                    with_label,
-                    with_reverse_complementary,
-                )
-            }
         }
     }
 }
@@ -536,204 +497,163 @@ fn align_query_with_core_aligner<A: Algorithm>(
     aligner: &mut Aligner<A>,
     query: &[u8],
     reference: &Reference,
+    target_indices: Option<&[u32]>,
 ) -> QueryAlignment {
-    aligner.align(query, reference)
+    match target_indices {
+        Some(target_indices) => aligner.align_to_targets(query, reference, target_indices),
+        None => aligner.align(query, reference),
+    }
+}
+// - For records of FASTA and FASTQ
+/// Query to align, read from the FASTA or FASTQ record.
+pub struct QueryRecord {
+    label: String,
+    query: Vec<u8>,
+    // Only for the preprocessed FASTQ record
+    trimmed_range: Option<TrimmedRange>,
 }
-// - For FASTA
 #[inline]
-fn align_fasta_with_core_aligner<A: Algorithm, R: Read>(
+fn align_record_with_core_aligner<A: Algorithm>(
     aligner: &mut Aligner<A>,
-    fasta_reader: &mut FastaReader<R>,
+    record: &QueryRecord,
     reference: &Reference,
     with_label: bool,
     with_reverse_complementary: bool,
-) -> PyFastaAlignment {
-    let mut py_read_alignments = Vec::new();
-
-    let mut query_buffer = Vec::new();
-    let mut label_buffer = String::new();
-    while let Some(mut record) = fasta_reader.next() {
-        query_buffer.clear();
-        label_buffer.clear();
-        record.extend_seq_buf(&mut query_buffer);
-        record.extend_id_string(&mut label_buffer).unwrap();
-
-        let query_alignment = aligner.align(&query_buffer, reference);
-        let py_query_alignment = if with_label {
+    py_read_alignments: &mut Vec<PyReadAlignment>,
+) -> PyResult<()> {
+    let to_py_query_alignment = |query_alignment: QueryAlignment| {
+        if with_label {
             let labeled_query_alignment = reference.label_query_alignment(query_alignment);
             PyQueryAlignment::from(labeled_query_alignment)
         } else {
             PyQueryAlignment::from(query_alignment)
-        };
-        let py_read_alignment = PyReadAlignment {
-            read: label_buffer.clone(),
-            is_forward: true,
-            result: py_query_alignment,
-        };
-        py_read_alignments.push(py_read_alignment);
-
-        if with_reverse_complementary {
-            reverse_complement_of_dna_sequence_in_place(&mut query_buffer);
-            let query_alignment = aligner.align(&query_buffer, reference);
-            let py_query_alignmnet = if with_label {
-                let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-                PyQueryAlignment::from(labeled_query_alignment)
-            } else {
-                PyQueryAlignment::from(query_alignment)
-            };
-            let py_read_alignment = PyReadAlignment {
-                read: label_buffer.clone(),
-                is_forward: false,
-                result: py_query_alignmnet,
-            };
-            py_read_alignments.push(py_read_alignment);
         }
+    };
+    let query_alignment = match &record.trimmed_range {
+        Some(trimmed_range) => aligner.align_preprocessed(&record.query, trimmed_range, reference),
+        None => aligner.align(&record.query, reference),
+    };
+    py_read_alignments.push(PyReadAlignment {
+        read: record.label.clone(),
+        is_forward: true,
+        result: to_py_query_alignment(query_alignment),
+    });
+
+    if with_reverse_complementary {
+        let reversed = reference.reverse_complement(&record.query).map_err(map_strand_err)?;
+        let query_alignment = match &record.trimmed_range {
+            Some(trimmed_range) => aligner.align_preprocessed(&reversed, &trimmed_range.reverse(), reference),
+            None => aligner.align(&reversed, reference),
+        };
+        py_read_alignments.push(PyReadAlignment {
+            read: record.label.clone(),
+            is_forward: false,
+            result: to_py_query_alignment(query_alignment),
+        });
     }
-    PyFastaAlignment(py_read_alignments)
+    Ok(())
 }
-#[inline]
-fn align_fasta_with_core_aligner_checking_signals<A: Algorithm, R: Read>(
-    aligner: &mut Aligner<A>,
+pub fn read_fasta_records<R: Read>(
     fasta_reader: &mut FastaReader<R>,
-    reference: &Reference,
-    with_label: bool,
-    with_reverse_complementary: bool,
-) -> PyResult<PyFastaAlignment> {
-    Python::with_gil(|py| -> PyResult<PyFastaAlignment> {
-        let mut py_read_alignments = Vec::new();
-
-        let mut query_buffer = Vec::new();
-        let mut label_buffer = String::new();
-        while let Some(mut record) = fasta_reader.next() {
-            query_buffer.clear();
-            label_buffer.clear();
-            record.extend_seq_buf(&mut query_buffer);
-            record.extend_id_string(&mut label_buffer).unwrap();
-
-            let query_alignment = aligner.align(&query_buffer, reference);
-            let py_query_alignment = if with_label {
-                let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-                PyQueryAlignment::from(labeled_query_alignment)
-            } else {
-                PyQueryAlignment::from(query_alignment)
-            };
-            let py_read_alignment = PyReadAlignment {
-                read: label_buffer.clone(),
-                is_forward: true,
-                result: py_query_alignment,
-            };
-            py_read_alignments.push(py_read_alignment);
-
-            if with_reverse_complementary {
-                reverse_complement_of_dna_sequence_in_place(&mut query_buffer);
-                let query_alignment = aligner.align(&query_buffer, reference);
-                let py_query_alignmnet = if with_label {
-                    let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-                    PyQueryAlignment::from(labeled_query_alignment)
-                } else {
-                    PyQueryAlignment::from(query_alignment)
-                };
-                let py_read_alignment = PyReadAlignment {
-                    read: label_buffer.clone(),
-                    is_forward: false,
-                    result: py_query_alignmnet,
-                };
-                py_read_alignments.push(py_read_alignment);
-            }
-            py.check_signals()?;
+    records: &mut Vec<QueryRecord>,
+    batch_size: usize,
+) -> PyResult<()> {
+    while records.len() < batch_size {
+        let Some(record) = fasta_reader.next() else {
+            break;
+        };
+        let mut record = record.map_err(map_read_err)?;
+        let mut query = Vec::new();
+        let mut label = String::new();
+        record.extend_seq_buf(&mut query);
+        if let Err(err) = record.extend_id_string(&mut label) {
+            return Err(map_read_err(invalid_id_error(fasta_reader, err)));
         }
-        Ok(PyFastaAlignment(py_read_alignments))
-    })
+        records.push(QueryRecord { label, query, trimmed_range: None });
+    }
+    Ok(())
 }
-// - For FASTQ
-#[inline]
-fn align_fastq_with_core_aligner<A: Algorithm, R: Read>(
-    aligner: &mut Aligner<A>,
+fn invalid_id_error<R: Read>(fasta_reader: &FastaReader<R>, err: std::str::Utf8Error) -> ReadError {
+    ReadError::new(
+        ReadErrorKind::InvalidId(err),
+        fasta_reader.record_number(),
+        fasta_reader.line_number(),
+    )
+}
+pub fn read_fastq_records<R: Read>(
     fastq_reader: &mut FastqReader<R>,
-    reference: &Reference,
-    with_label: bool,
-    with_reverse_complementary: bool,
-) -> PyFastaAlignment {
-    let mut py_read_alignments = Vec::new();
-
-    while let Some(record) = fastq_reader.next() {
-        let query_alignment = aligner.align(record.seq(), reference);
-        let py_query_alignment = if with_label {
-            let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-            PyQueryAlignment::from(labeled_query_alignment)
-        } else {
-            PyQueryAlignment::from(query_alignment)
+    preprocessor: Option<&QueryPreprocessor>,
+    records: &mut Vec<QueryRecord>,
+    batch_size: usize,
+) -> PyResult<()> {
+    while records.len() < batch_size {
+        let Some(record) = fastq_reader.next() else {
+            break;
         };
-        let py_read_alignment = PyReadAlignment {
-            read: String::from_utf8_lossy(record.id()).to_string(),
-            is_forward: true,
-            result: py_query_alignment,
+        let record = record.map_err(map_read_err)?;
+        let label = String::from_utf8_lossy(record.id()).to_string();
+        let (query, trimmed_range) = match preprocessor {
+            Some(preprocessor) => {
+                let mut preprocessed = Vec::new();
+                let trimmed_range = preprocessor.preprocess(record.seq(), Some(record.qual()), &mut preprocessed);
+                (preprocessed, trimmed_range)
+            },
+            None => (record.seq().to_vec(), TrimmedRange::full(record.seq().len() as u32)),
         };
-        py_read_alignments.push(py_read_alignment);
-
-        if with_reverse_complementary {
-            let reversed = reverse_complement_of_dna_sequence(record.seq());
-            let query_alignment = aligner.align(&reversed, reference);
-            let py_query_alignmnet = if with_label {
-                let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-                PyQueryAlignment::from(labeled_query_alignment)
-            } else {
-                PyQueryAlignment::from(query_alignment)
-            };
-            let py_read_alignment = PyReadAlignment {
-                read: String::from_utf8_lossy(record.id()).to_string(),
-                is_forward: false,
-                result: py_query_alignmnet,
-            };
-            py_read_alignments.push(py_read_alignment);
-        }
+        records.push(QueryRecord { label, query, trimmed_range: Some(trimmed_range) });
     }
-    PyFastaAlignment(py_read_alignments)
+    Ok(())
 }
+// - For paired FASTQ
 #[inline]
-fn align_fastq_with_core_aligner_checking_signals<A: Algorithm, R: Read>(
+fn align_read_pairs_with_core_aligner<A: Algorithm, R: Read>(
     aligner: &mut Aligner<A>,
-    fastq_reader: &mut FastqReader<R>,
+    paired_reader: &mut PairedFastqReader<R>,
     reference: &Reference,
+    pairing_options: &PairingOptions,
+    preprocessor: Option<&QueryPreprocessor>,
     with_label: bool,
-    with_reverse_complementary: bool,
-) -> PyResult<PyFastaAlignment> {
-    Python::with_gil(|py| -> PyResult<PyFastaAlignment> {
-        let mut py_read_alignments = Vec::new();
+    checking_signals: bool,
+) -> PyResult<Vec<PyReadPairAlignment>> {
+    let mut read_pair = ReadPair::default();
+    let mut preprocessed_buffers = (Vec::new(), Vec::new());
+    let mut py_read_pair_alignments = Vec::new();
 
-        while let Some(record) = fastq_reader.next() {
-            let query_alignment = aligner.align(record.seq(), reference);
-            let py_query_alignment = if with_label {
-                let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-                PyQueryAlignment::from(labeled_query_alignment)
-            } else {
-                PyQueryAlignment::from(query_alignment)
-            };
-            let py_read_alignment = PyReadAlignment {
-                read: String::from_utf8_lossy(record.id()).to_string(),
-                is_forward: true,
-                result: py_query_alignment,
-            };
-            py_read_alignments.push(py_read_alignment);
-
-            if with_reverse_complementary {
-                let reversed = reverse_complement_of_dna_sequence(record.seq());
-                let query_alignment = aligner.align(&reversed, reference);
-                let py_query_alignmnet = if with_label {
-                    let labeled_query_alignment = reference.label_query_alignment(query_alignment);
-                    PyQueryAlignment::from(labeled_query_alignment)
-                } else {
-                    PyQueryAlignment::from(query_alignment)
-                };
-                let py_read_alignment = PyReadAlignment {
-                    read: String::from_utf8_lossy(record.id()).to_string(),
-                    is_forward: false,
-                    result: py_query_alignmnet,
-                };
-                py_read_alignments.push(py_read_alignment);
-            }
-            py.check_signals()?;
+    while let Some(result) = paired_reader.read_pair(&mut read_pair) {
+        result.map_err(map_read_err)?;
+        let read_pair_alignment = match preprocessor {
+            Some(preprocessor) => {
+                let (first, second) = &mut preprocessed_buffers;
+                let first_trimmed_range = preprocessor.preprocess(
+                    &read_pair.first_sequence, Some(&read_pair.first_quality), first,
+                );
+                let second_trimmed_range = preprocessor.preprocess(
+                    &read_pair.second_sequence, Some(&read_pair.second_quality), second,
+                );
+                aligner.align_preprocessed_read_pair(
+                    first,
+                    &first_trimmed_range,
+                    second,
+                    &second_trimmed_range,
+                    reference,
+                    pairing_options,
+                )
+            },
+            None => aligner.align_read_pair(
+                &read_pair.first_sequence,
+                &read_pair.second_sequence,
+                reference,
+                pairing_options,
+            ),
+        }.map_err(map_strand_err)?;
+        py_read_pair_alignments.push(PyReadPairAlignment::new(
+            read_pair.name().to_string(),
+            read_pair_alignment,
+            if with_label { Some(reference) } else { None },
+        ));
+        if checking_signals {
+            Python::with_gil(|py| py.check_signals())?;
         }
-        Ok(PyFastaAlignment(py_read_alignments))
-    })
+    }
+    Ok(py_read_pair_alignments)
 }
@@ -3,15 +3,18 @@ use pyo3::prelude::*;
 mod aligner;
 mod reference;
 mod results;
+mod sequence_input;
 
-use aligner::PyAligner;
-use reference::PyReference;
+use aligner::{PyAligner, ReadAlignmentStream};
+use reference::{PyReference, PyReferenceBuilder};
 use results::register_results_module_as_submodule;
 
 #[pymodule]
 fn sigalign(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyReference>()?;
+    m.add_class::<PyReferenceBuilder>()?;
     m.add_class::<PyAligner>()?;
+    m.add_class::<ReadAlignmentStream>()?;
     register_results_module_as_submodule(m)?;
     Ok(())
 }
@@ -0,0 +1,168 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+
+use sigalign::{Alphabet, ReferenceBuilder};
+
+use crate::sequence_input::{extract_sequence, Symbols};
+
+use super::{
+    PyReference,
+    add_targets_from_iterable_to_builder,
+    add_fasta_bytes_to_builder, add_fasta_file_to_builder,
+};
+
+/// Builder for `Reference` with the same options as the Rust `ReferenceBuilder`.
+///  - Methods return the builder itself, so that they can be chained.
+///  - The builder is consumed by `build`.
+#[pyclass(name = "ReferenceBuilder", module = "sigalign")]
+pub struct PyReferenceBuilder {
+    // None after `build` or a failed method
+    inner: Option<ReferenceBuilder>,
+}
+
+#[pymethods]
+impl PyReferenceBuilder {
+    #[new]
+    fn py_new() -> Self {
+        Self {
+            inner: Some(ReferenceBuilder::new()),
+        }
+    }
+
+    /* Configuration */
+    /// Set all letters to uppercase when building (default: True).
+    fn set_uppercase(mut slf: PyRefMut<'_, Self>, uppercase: bool) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|builder| Ok(builder.set_uppercase(uppercase)))?;
+        Ok(slf)
+    }
+    /// Set the bases that never match to any other bases (e.g., "N").
+    fn ignore_bases<'py>(mut slf: PyRefMut<'py, Self>, bases: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.update(|builder| Ok(builder.ignore_bases(bases.as_bytes())))?;
+        Ok(slf)
+    }
+    /// Reset the bases to ignore.
+    fn reset_ignore_bases(mut slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|builder| Ok(builder.reset_ignore_bases()))?;
+        Ok(slf)
+    }
+    /// Set the alphabet of the sequences: 'dna', 'dna_iupac', 'rna', or 'protein'.
+    fn set_alphabet<'py>(mut slf: PyRefMut<'py, Self>, alphabet: &str) -> PyResult<PyRefMut<'py, Self>> {
+        let alphabet = Alphabet::from_name(alphabet).ok_or_else(|| PyValueError::new_err(
+            "The alphabet must be one of 'dna', 'dna_iupac', 'rna', or 'protein'.",
+        ))?;
+        slf.update(|builder| Ok(builder.set_alphabet(alphabet)))?;
+        Ok(slf)
+    }
+    /// Fail to build if any residue is not in the alphabet, instead of masking it.
+    fn set_strict_alphabet(mut slf: PyRefMut<'_, Self>, strict: bool) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|builder| Ok(builder.set_strict_alphabet(strict)))?;
+        Ok(slf)
+    }
+    /// Treat lowercase regions as soft-masked (excluded from the seeding).
+    fn set_soft_masking(mut slf: PyRefMut<'_, Self>, soft_masking: bool) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|builder| Ok(builder.set_soft_masking(soft_masking)))?;
+        Ok(slf)
+    }
+    /// Keep the FASTA descriptions as the metadata of targets.
+    fn set_keep_metadata(mut slf: PyRefMut<'_, Self>, keep_metadata: bool) -> PyResult<PyRefMut<'_, Self>> {
+        slf.update(|builder| Ok(builder.set_keep_metadata(keep_metadata)))?;
+        Ok(slf)
+    }
+
+    /* Add Sequences */
+    /// Add a target. The sequence is str, bytes, or an object supporting the buffer protocol.
+    ///  - With `symbols`, the integer codes are decoded to the residues (e.g., "ACGT" for 0–3).
+    #[pyo3(signature = (label, sequence, symbols=None))]
+    fn add_target<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        label: &str,
+        sequence: &Bound<'py, PyAny>,
+        symbols: Option<&str>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let symbols = Symbols::from_option(symbols)?;
+        let sequence = extract_sequence(sequence, symbols.as_ref())?;
+        slf.update(|builder| Ok(builder.add_target(label, &sequence)))?;
+        Ok(slf)
+    }
+    /// Add targets from `iterable` of sequences or tuples (label, sequence).
+    #[pyo3(signature = (targets, symbols=None))]
+    fn add_targets<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        targets: &Bound<'py, PyAny>,
+        symbols: Option<&str>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let symbols = Symbols::from_option(symbols)?;
+        slf.update(|builder| add_targets_from_iterable_to_builder(builder, targets, symbols.as_ref()))?;
+        Ok(slf)
+    }
+    /// Add targets from FASTA (str or bytes).
+    fn add_fasta<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        fasta: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let fasta_bytes = as_bytes(fasta)?;
+        slf.update(|builder| add_fasta_bytes_to_builder(builder, fasta_bytes))?;
+        Ok(slf)
+    }
+    /// Add targets from FASTA file. The compressed file is decoded automatically.
+    fn add_fasta_file<'py>(mut slf: PyRefMut<'py, Self>, file_path: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.update(|builder| add_fasta_file_to_builder(builder, file_path))?;
+        Ok(slf)
+    }
+
+    /* Add Metadata */
+    /// Add attributes of targets from the tab-separated table (str or bytes).
+    ///  - The first line is the header: the first column is the label, and the others are the keys.
+    fn add_metadata_tsv<'py>(
+        mut slf: PyRefMut<'py, Self>,
+        tsv: &Bound<'py, PyAny>,
+    ) -> PyResult<PyRefMut<'py, Self>> {
+        let tsv_bytes = as_bytes(tsv)?;
+        slf.update(|builder| {
+            builder.add_metadata_tsv(tsv_bytes).map_err(|e| PyValueError::new_err(format!("{e}")))
+        })?;
+        Ok(slf)
+    }
+    /// Add attributes of targets from the tab-separated file.
+    fn add_metadata_tsv_file<'py>(mut slf: PyRefMut<'py, Self>, file_path: &str) -> PyResult<PyRefMut<'py, Self>> {
+        slf.update(|builder| {
+            builder.add_metadata_tsv_file(file_path).map_err(|e| PyValueError::new_err(format!("{e}")))
+        })?;
+        Ok(slf)
+    }
+
+    /// Finish building `Reference`. The builder cannot be used after this.
+    fn build(&mut self) -> PyResult<PyReference> {
+        let builder = self.take()?;
+        PyReference::from_builder(builder)
+    }
+}
+
+impl PyReferenceBuilder {
+    fn take(&mut self) -> PyResult<ReferenceBuilder> {
+        self.inner.take().ok_or_else(|| PyValueError::new_err(
+            "The builder is consumed by `build` or a failed method.",
+        ))
+    }
+    // The builder is dropped if `f` fails, as the Rust builder is consumed.
+    fn update<F>(&mut self, f: F) -> PyResult<()> where
+        F: FnOnce(ReferenceBuilder) -> PyResult<ReferenceBuilder>,
+    {
+        let builder = self.take()?;
+        self.inner = Some(f(builder)?);
+        Ok(())
+    }
+}
+
+fn as_bytes<'a>(value: &'a Bound<'_, PyAny>) -> PyResult<&'a [u8]> {
+    if value.is_instance_of::<PyString>() {
+        Ok(value.downcast::<PyString>()?.to_str()?.as_bytes())
+    } else if value.is_instance_of::<PyBytes>() {
+        Ok(value.downcast::<PyBytes>()?.as_bytes())
+    } else {
+        Err(PyValueError::new_err(
+            "The input must be either a string or bytes.",
+        ))
+    }
+}
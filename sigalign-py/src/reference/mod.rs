@@ -2,14 +2,22 @@ use pyo3::exceptions::{
     PyFileExistsError, PyFileNotFoundError, PyOSError, PyTypeError, PyValueError,
 };
 use pyo3::prelude::*;
-use pyo3::types::{PyBytes, PyList, PySequence, PyString, PyTuple, PyType};
+use pyo3::types::{PyBytes, PyDict, PyList, PySequence, PyString, PyTuple, PyType};
 
-use sigalign::{Reference, ReferenceBuilder};
+use sigalign::{Alphabet, Reference, ReferenceBuilder};
+use sigalign::utils::recommend_parameters::ParameterRecommender;
+use sigalign_utils::sequence_reader::{open_decompressed_file, OpenSequenceFileError};
 
+use crate::sequence_input::{extract_sequence, Symbols};
+
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::path::Path;
 
-#[pyclass(name = "Reference")]
+mod builder;
+pub use builder::PyReferenceBuilder;
+
+#[pyclass(name = "Reference", module = "sigalign")]
 pub struct PyReference {
     pub inner: Reference,
 }
@@ -23,26 +31,41 @@ impl AsRef<Reference> for PyReference {
 #[pymethods]
 impl PyReference {
     #[classmethod]
-    #[pyo3(signature = (targets, set_uppercase=false, bases_to_ignore=""))]
+    /// Each target is a sequence or a tuple (label, sequence).
+    ///  - The sequence is str, bytes, or an object supporting the buffer protocol (e.g., NumPy `uint8` array).
+    ///  - With `symbols`, the integer codes are decoded to the residues (e.g., "ACGT" for 0–3).
+    #[pyo3(signature = (targets, set_uppercase=false, bases_to_ignore="", alphabet=None, soft_masking=false, keep_metadata=false, metadata_file=None, symbols=None))]
     fn from_iterable(
         _cls: &Bound<PyType>,
         targets: &Bound<PyAny>,
         set_uppercase: bool,
         bases_to_ignore: &str,
+        alphabet: Option<&str>,
+        soft_masking: bool,
+        keep_metadata: bool,
+        metadata_file: Option<&str>,
+        symbols: Option<&str>,
     ) -> PyResult<Self> {
-        let mut reference_builder = Self::new_configured_builder(set_uppercase, bases_to_ignore);
+        let symbols = Symbols::from_option(symbols)?;
+        let mut reference_builder = Self::new_configured_builder(
+            set_uppercase, bases_to_ignore, alphabet, soft_masking, keep_metadata, metadata_file,
+        )?;
 
-        reference_builder = add_targets_from_iterable_to_builder(reference_builder, targets)?;
+        reference_builder = add_targets_from_iterable_to_builder(reference_builder, targets, symbols.as_ref())?;
 
         Self::from_builder(reference_builder)
     }
     #[classmethod]
-    #[pyo3(signature = (fasta, set_uppercase=false, bases_to_ignore=""))]
+    #[pyo3(signature = (fasta, set_uppercase=false, bases_to_ignore="", alphabet=None, soft_masking=false, keep_metadata=false, metadata_file=None))]
     fn from_fasta(
         _cls: &Bound<PyType>,
         fasta: &Bound<PyAny>,
         set_uppercase: bool,
         bases_to_ignore: &str,
+        alphabet: Option<&str>,
+        soft_masking: bool,
+        keep_metadata: bool,
+        metadata_file: Option<&str>,
     ) -> PyResult<Self> {
         let fasta_bytes = if fasta.is_instance_of::<PyString>() {
             fasta.downcast::<PyString>()?.to_str()?.as_bytes()
@@ -54,21 +77,29 @@ impl PyReference {
             ));
         };
 
-        let mut reference_builder = Self::new_configured_builder(set_uppercase, bases_to_ignore);
+        let mut reference_builder = Self::new_configured_builder(
+            set_uppercase, bases_to_ignore, alphabet, soft_masking, keep_metadata, metadata_file,
+        )?;
 
         reference_builder = add_fasta_bytes_to_builder(reference_builder, fasta_bytes)?;
 
         Self::from_builder(reference_builder)
     }
     #[classmethod]
-    #[pyo3(signature = (file_path, set_uppercase=false, bases_to_ignore=""))]
+    #[pyo3(signature = (file_path, set_uppercase=false, bases_to_ignore="", alphabet=None, soft_masking=false, keep_metadata=false, metadata_file=None))]
     fn from_fasta_file(
         _cls: &Bound<PyType>,
         file_path: &Bound<PyAny>,
         set_uppercase: bool,
         bases_to_ignore: &str,
+        alphabet: Option<&str>,
+        soft_masking: bool,
+        keep_metadata: bool,
+        metadata_file: Option<&str>,
     ) -> PyResult<Self> {
-        let mut reference_builder = Self::new_configured_builder(set_uppercase, bases_to_ignore);
+        let mut reference_builder = Self::new_configured_builder(
+            set_uppercase, bases_to_ignore, alphabet, soft_masking, keep_metadata, metadata_file,
+        )?;
 
         let file_path = file_path.downcast::<PyString>()?.to_str()?;
         reference_builder = add_fasta_file_to_builder(reference_builder, file_path)?;
@@ -84,6 +115,16 @@ impl PyReference {
     fn get_estimated_size(&self) -> PyResult<usize> {
         Ok(self.inner.get_estimated_size_in_bytes())
     }
+    /// `None` if the reference is built without the alphabet.
+    #[getter(alphabet)]
+    fn get_alphabet(&self) -> Option<&'static str> {
+        self.inner.get_alphabet().map(|x| x.as_str())
+    }
+    /// `True` if the lowercase regions are soft-masked.
+    #[getter(is_soft_masked)]
+    fn is_soft_masked(&self) -> bool {
+        self.inner.get_soft_masked_regions().is_some()
+    }
     #[getter(total_length)]
     fn get_total_length(&self) -> PyResult<u32> {
         Ok(self.inner.get_total_length())
@@ -101,6 +142,32 @@ impl PyReference {
             None => Err(PyValueError::new_err("Target index is out of bound.")),
         }
     }
+    /// FASTA description of the target. `None` if the reference is built without the metadata.
+    fn get_description(&self, target_index: u32) -> PyResult<Option<String>> {
+        self.check_target_index(target_index)?;
+        Ok(self.inner.get_target_metadata(target_index).map(|x| x.description.clone()))
+    }
+    /// Attributes of the target as a dict. Empty if the reference is built without the metadata.
+    fn get_attributes(&self, target_index: u32) -> PyResult<BTreeMap<String, String>> {
+        self.check_target_index(target_index)?;
+        Ok(self.inner.get_target_metadata(target_index).map(|x| x.attributes.clone()).unwrap_or_default())
+    }
+    /// Soft-masked regions of the target as a list of (start, end).
+    fn get_soft_masked_regions(&self, target_index: u32) -> PyResult<Vec<(u32, u32)>> {
+        match self.inner.get_soft_masked_regions() {
+            Some(regions) => match regions.of_target(target_index) {
+                Some(v) => Ok(v.to_vec()),
+                None => Err(PyValueError::new_err("Target index is out of bound.")),
+            },
+            None => Ok(Vec::new()),
+        }
+    }
+    /// Number of the soft-masked bases in the range (target_start, target_end) of the target.
+    fn count_soft_masked_bases(&self, target_index: u32, target_start: u32, target_end: u32) -> u32 {
+        self.inner.get_soft_masked_regions().map_or(0, |regions| {
+            regions.count_in_range(target_index, (target_start, target_end))
+        })
+    }
 
     #[pyo3(signature = (file_path, overwrite=false))]
     fn save_to_file(&self, file_path: &str, overwrite: bool) -> PyResult<()> {
@@ -123,9 +190,24 @@ impl PyReference {
             ))),
         }
     }
+    /// Load the reference from the file.
+    ///  - With `mmap`, the sequences and the pattern index are borrowed from the memory-mapped file
+    ///    without checking the checksum of the whole file (use `verify_file`).
+    ///    The file must not be modified while the reference is alive.
     #[classmethod]
-    fn load_from_file(_cls: &Bound<PyType>, file_path: &Bound<PyAny>) -> PyResult<Self> {
+    #[pyo3(signature = (file_path, mmap=false))]
+    fn load_from_file(_cls: &Bound<PyType>, file_path: &Bound<PyAny>, mmap: bool) -> PyResult<Self> {
         let file_path = file_path.downcast::<PyString>()?.to_str()?;
+        if mmap {
+            // Safety: documented to the caller
+            let inner = unsafe { Reference::load_from_mapped_file(file_path) }.map_err(|e| {
+                PyTypeError::new_err(format!(
+                    "Failed to load a valid reference from file '{}'. Error: {}",
+                    file_path, e
+                ))
+            })?;
+            return Ok(Self { inner })
+        }
         let file = match File::open(file_path) {
             Ok(v) => v,
             Err(e) => {
@@ -148,16 +230,170 @@ impl PyReference {
 
         Ok(Self { inner })
     }
+    /// Serialize the reference to bytes, in the same format as `save_to_file`.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buffer = Vec::with_capacity(self.inner.get_estimated_size_in_bytes());
+        self.inner.save_to(&mut buffer).map_err(|e| {
+            PyOSError::new_err(format!("Failed to serialize the reference. Error: {}", e))
+        })?;
+        Ok(PyBytes::new_bound(py, &buffer))
+    }
+    /// Load the reference from the bytes of `to_bytes`.
+    #[classmethod]
+    fn from_bytes(_cls: &Bound<PyType>, data: &[u8]) -> PyResult<Self> {
+        let inner = Reference::load_from(data).map_err(|e| {
+            PyValueError::new_err(format!("Failed to load a valid reference from bytes. Error: {}", e))
+        })?;
+        Ok(Self { inner })
+    }
+    // Pickled as the serialized bytes (the memory-mapped reference is unpickled as loaded in memory)
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyBytes>,))> {
+        let from_bytes = slf.get_type().getattr("from_bytes")?;
+        let bytes = slf.borrow().to_bytes(slf.py())?;
+        Ok((from_bytes, (bytes,)))
+    }
+    // Reference is immutable, so the shallow copy is itself.
+    fn __copy__(slf: Py<Self>) -> Py<Self> {
+        slf
+    }
+    fn __deepcopy__(&self, _memo: &Bound<PyAny>) -> PyResult<Self> {
+        let mut buffer = Vec::new();
+        self.inner.save_to(&mut buffer).map_err(|e| PyOSError::new_err(e.to_string()))?;
+        let inner = Reference::load_from(&buffer[..]).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+    /// Validate the reference file without fully loading it, and get the header as a dict.
+    #[classmethod]
+    fn verify_file<'py>(cls: &Bound<'py, PyType>, file_path: &str) -> PyResult<Bound<'py, PyDict>> {
+        let header = Reference::verify(file_path).map_err(|e| {
+            PyValueError::new_err(format!(
+                "Invalid reference file '{}'. Error: {}",
+                file_path, e
+            ))
+        })?;
+        let dict = PyDict::new_bound(cls.py());
+        dict.set_item("format_version", header.format_version)?;
+        dict.set_item("num_targets", header.num_targets)?;
+        dict.set_item("total_length", header.total_length)?;
+        dict.set_item("alphabet", header.alphabet.map(|x| x.as_str()))?;
+        dict.set_item("sections", header.sections)?;
+        Ok(dict)
+    }
+    /// Recommend the cutoffs (minl, maxp) from the statistics of this reference and the sample queries.
+    ///  - Returns the list of candidates (dict), from the most sensitive to the fastest.
+    #[pyo3(signature = (queries, px, po, pe, throughput_constants=None))]
+    fn recommend_parameters<'py>(
+        &self,
+        py: Python<'py>,
+        queries: &Bound<'py, PyAny>,
+        px: u32,
+        po: u32,
+        pe: u32,
+        throughput_constants: Option<Vec<f32>>,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let mut recommender = ParameterRecommender::new(&self.inner, px, po, pe);
+        for query in queries.iter()? {
+            let query = query?;
+            let query_bytes = if query.is_instance_of::<PyString>() {
+                query.downcast::<PyString>()?.to_str()?.as_bytes().to_vec()
+            } else if query.is_instance_of::<PyBytes>() {
+                query.downcast::<PyBytes>()?.as_bytes().to_vec()
+            } else {
+                return Err(PyValueError::new_err(
+                    "The queries must be either strings or bytes.",
+                ));
+            };
+            recommender = recommender.add_query(&query_bytes);
+        }
+        if let Some(throughput_constants) = throughput_constants {
+            recommender = recommender.set_throughput_constants(throughput_constants);
+        }
+        let recommendation = recommender.recommend().map_err(|e| {
+            PyValueError::new_err(e.to_string())
+        })?;
+        recommendation.candidates.iter().map(|candidate| {
+            let dict = PyDict::new_bound(py);
+            dict.set_item("throughput_constant", candidate.throughput_constant)?;
+            dict.set_item("minl", candidate.min_length)?;
+            dict.set_item("maxp", candidate.max_penalty)?;
+            dict.set_item("pattern_size", candidate.pattern_size)?;
+            dict.set_item("random_anchors_per_pattern", candidate.random_anchors_per_pattern)?;
+            dict.set_item("observed_anchors_per_pattern", candidate.observed_anchors_per_pattern)?;
+            dict.set_item("anchors_per_query", candidate.anchors_per_query)?;
+            dict.set_item("max_divergence", candidate.max_divergence)?;
+            dict.set_item("relative_cost", candidate.relative_cost)?;
+            Ok(dict)
+        }).collect()
+    }
+    /// Summary of the reference as a dict, including the type of the pattern index.
+    fn get_summary<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let summary = self.inner.get_summary();
+        let dict = PyDict::new_bound(py);
+        dict.set_item("num_targets", summary.num_targets)?;
+        dict.set_item("total_length", summary.total_length)?;
+        dict.set_item("alphabet", summary.alphabet.map(|x| x.as_str()))?;
+        dict.set_item("is_soft_masked", summary.num_soft_masked_bases.is_some())?;
+        dict.set_item("num_soft_masked_bases", summary.num_soft_masked_bases)?;
+        dict.set_item("has_metadata", summary.has_target_metadata)?;
+        dict.set_item("pattern_index_type", summary.pattern_index_type)?;
+        dict.set_item("bits_per_character", summary.bits_per_character)?;
+        dict.set_item("is_memory_mapped", summary.is_sequence_shared && summary.is_pattern_index_shared)?;
+        dict.set_item("estimated_size", summary.estimated_size_in_bytes)?;
+        match summary.build_options {
+            Some(build_options) => {
+                let options = PyDict::new_bound(py);
+                options.set_item("uppercase", build_options.uppercase)?;
+                options.set_item("strict_alphabet", build_options.strict_alphabet)?;
+                options.set_item("ignored_bases", String::from_utf8_lossy(&build_options.ignored_bases))?;
+                dict.set_item("build_options", options)?;
+            },
+            None => dict.set_item("build_options", py.None())?,
+        }
+        Ok(dict)
+    }
+    /// `True` if the sequences and the pattern index are borrowed from the memory-mapped file.
+    #[getter]
+    fn is_memory_mapped(&self) -> bool {
+        self.inner.is_sequence_shared() && self.inner.is_pattern_index_shared()
+    }
 }
 
 impl PyReference {
-    fn new_configured_builder(set_uppercase: bool, bases_to_ignore: &str) -> ReferenceBuilder {
+    fn check_target_index(&self, target_index: u32) -> PyResult<()> {
+        if target_index < self.inner.get_num_targets() {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err("Target index is out of bound."))
+        }
+    }
+    fn new_configured_builder(
+        set_uppercase: bool,
+        bases_to_ignore: &str,
+        alphabet: Option<&str>,
+        soft_masking: bool,
+        keep_metadata: bool,
+        metadata_file: Option<&str>,
+    ) -> PyResult<ReferenceBuilder> {
         let mut reference_builder = ReferenceBuilder::new();
         if !bases_to_ignore.is_empty() {
             reference_builder = reference_builder.ignore_bases(bases_to_ignore.as_bytes());
         }
-        reference_builder = reference_builder.set_uppercase(set_uppercase);
-        reference_builder
+        reference_builder = reference_builder
+            .set_uppercase(set_uppercase)
+            .set_soft_masking(soft_masking)
+            .set_keep_metadata(keep_metadata);
+        if let Some(metadata_file) = metadata_file {
+            reference_builder = reference_builder
+                .add_metadata_tsv_file(metadata_file)
+                .map_err(|e| PyValueError::new_err(format!("{e}")))?;
+        }
+        if let Some(alphabet) = alphabet {
+            let alphabet = Alphabet::from_name(alphabet).ok_or_else(|| PyValueError::new_err(
+                "The alphabet must be one of 'dna', 'dna_iupac', 'rna', or 'protein'.",
+            ))?;
+            reference_builder = reference_builder.set_alphabet(alphabet);
+        }
+        Ok(reference_builder)
     }
     fn from_builder(reference_builder: ReferenceBuilder) -> PyResult<Self> {
         let reference = match reference_builder.build() {
@@ -175,11 +411,12 @@ impl PyReference {
 fn add_targets_from_iterable_to_builder(
     mut reference_builder: ReferenceBuilder,
     targets: &Bound<PyAny>,
+    symbols: Option<&Symbols>,
 ) -> PyResult<ReferenceBuilder> {
     let py_iterator = targets.iter()?;
     for value in py_iterator {
         let target = value?;
-        reference_builder = add_target_to_builder(reference_builder, target)?;
+        reference_builder = add_target_to_builder(reference_builder, target, symbols)?;
     }
     Ok(reference_builder)
 }
@@ -188,34 +425,22 @@ fn add_targets_from_iterable_to_builder(
 fn add_target_to_builder(
     mut reference_builder: ReferenceBuilder,
     target: Bound<PyAny>,
+    symbols: Option<&Symbols>,
 ) -> PyResult<ReferenceBuilder> {
-    if target.is_instance_of::<PyString>() {
-        let sequence = target.downcast::<PyString>()?.to_str()?.as_bytes();
-        reference_builder = reference_builder.add_target("", sequence);
-    } else if target.is_instance_of::<PyBytes>() {
-        let sequence = target.downcast::<PyBytes>()?.as_bytes();
-        reference_builder = reference_builder.add_target("", sequence);
-    } else if target.is_instance_of::<PyTuple>() || target.is_instance_of::<PyList>() {
+    if target.is_instance_of::<PyTuple>() || target.is_instance_of::<PyList>() {
         let py_sequence = target.downcast_into::<PySequence>().unwrap();
         if py_sequence.len()? == 2 {
             let first = py_sequence.get_item(0)?;
             let label = first.downcast::<PyString>()?.to_str()?;
             let second = py_sequence.get_item(1)?;
-            let sequence = if second.is_instance_of::<PyString>() {
-                second.downcast::<PyString>()?.to_str()?.as_bytes()
-            } else if second.is_instance_of::<PyBytes>() {
-                second.downcast::<PyBytes>()?.as_bytes()
-            } else {
-                return Err(PyTypeError::new_err("Each target must either be a sequence string or a list/tuple with length of 2 (containing label and sequence)."));
-            };
-            reference_builder = reference_builder.add_target(label, sequence);
+            let sequence = extract_sequence(&second, symbols)?;
+            reference_builder = reference_builder.add_target(label, &sequence);
         } else {
-            return Err(PyValueError::new_err("Each target must either be a sequence string or a list/tuple with length of 2 (containing label and sequence)."));
+            return Err(PyValueError::new_err("Each target must either be a sequence or a list/tuple with length of 2 (containing label and sequence)."));
         }
     } else {
-        return Err(PyTypeError::new_err(
-            "Targets must be of type str, bytes, tuple, or list",
-        ));
+        let sequence = extract_sequence(&target, symbols)?;
+        reference_builder = reference_builder.add_target("", &sequence);
     }
     Ok(reference_builder)
 }
@@ -236,12 +461,18 @@ fn add_fasta_file_to_builder(
     mut reference_builder: ReferenceBuilder,
     file_path: &str,
 ) -> PyResult<ReferenceBuilder> {
-    let file = File::open(file_path).map_err(|e| {
+    let file = open_decompressed_file(file_path).map_err(|e| {
         let msg = format!("{e}");
-        PyFileNotFoundError::new_err(msg)
+        match e {
+            OpenSequenceFileError::Io(_) => PyFileNotFoundError::new_err(msg),
+            _ => PyValueError::new_err(msg),
+        }
     })?;
     reference_builder = reference_builder
         .add_fasta(file)
-        .map_err(|_| PyValueError::new_err("Invalid FASTA record."))?;
+        .map_err(|e| {
+            let msg = format!("{e}");
+            PyValueError::new_err(msg)
+        })?;
     Ok(reference_builder)
 }
@@ -0,0 +1,546 @@
+//! Export of the results as the Arrow `RecordBatch` through the Arrow C data interface.
+//!  - https://arrow.apache.org/docs/format/CDataInterface.html
+//!  - https://arrow.apache.org/docs/format/CDataInterface/PyCapsuleInterface.html
+//!
+//! The buffers are built once in Rust, and their ownership is passed to the consumer (e.g., pyarrow).
+use std::any::Any;
+use std::ffi::{c_char, c_void, CString};
+use std::ptr;
+
+use pyo3::prelude::*;
+use pyo3::types::PyCapsule;
+use sigalign::results::{AlignmentOperations, AlignmentStats};
+
+use super::{
+    PyFastaAlignment, PyQueryAlignment, PyTargetAlignment, PyAlignment,
+    to_flat_result::operations_to_cigars,
+};
+
+const ARROW_FLAG_NULLABLE: i64 = 2;
+
+#[repr(C)]
+pub struct FFI_ArrowSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut FFI_ArrowSchema,
+    dictionary: *mut FFI_ArrowSchema,
+    release: Option<unsafe extern "C" fn(*mut FFI_ArrowSchema)>,
+    private_data: *mut c_void,
+}
+
+#[repr(C)]
+pub struct FFI_ArrowArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut FFI_ArrowArray,
+    dictionary: *mut FFI_ArrowArray,
+    release: Option<unsafe extern "C" fn(*mut FFI_ArrowArray)>,
+    private_data: *mut c_void,
+}
+
+// Owned by the capsule until the consumer moves it
+unsafe impl Send for FFI_ArrowSchema {}
+unsafe impl Send for FFI_ArrowArray {}
+
+struct SchemaPrivateData {
+    _format: CString,
+    _name: CString,
+    children: Vec<*mut FFI_ArrowSchema>,
+}
+unsafe extern "C" fn release_schema(schema: *mut FFI_ArrowSchema) {
+    if schema.is_null() || (*schema).release.is_none() {
+        return;
+    }
+    let private_data = Box::from_raw((*schema).private_data as *mut SchemaPrivateData);
+    for child in private_data.children {
+        if let Some(release) = (*child).release {
+            release(child);
+        }
+        drop(Box::from_raw(child));
+    }
+    (*schema).release = None;
+}
+
+struct ArrayPrivateData {
+    _buffers: Vec<Box<dyn Any>>,
+    buffer_pointers: Vec<*const c_void>,
+    children: Vec<*mut FFI_ArrowArray>,
+}
+unsafe extern "C" fn release_array(array: *mut FFI_ArrowArray) {
+    if array.is_null() || (*array).release.is_none() {
+        return;
+    }
+    let private_data = Box::from_raw((*array).private_data as *mut ArrayPrivateData);
+    for &child in &private_data.children {
+        if let Some(release) = (*child).release {
+            release(child);
+        }
+        drop(Box::from_raw(child));
+    }
+    drop(private_data);
+    (*array).release = None;
+}
+
+// Destructors of the capsules: released if not moved by the consumer
+fn drop_schema(mut schema: FFI_ArrowSchema) {
+    unsafe { release_schema(&mut schema) }
+}
+fn drop_array(mut array: FFI_ArrowArray) {
+    unsafe { release_array(&mut array) }
+}
+
+fn new_schema(format: &str, name: &str, flags: i64, children: Vec<FFI_ArrowSchema>) -> FFI_ArrowSchema {
+    let format = CString::new(format).unwrap();
+    let name = CString::new(name).unwrap();
+    let mut private_data = Box::new(SchemaPrivateData {
+        children: children.into_iter().map(|x| Box::into_raw(Box::new(x))).collect(),
+        _format: format,
+        _name: name,
+    });
+    FFI_ArrowSchema {
+        format: private_data._format.as_ptr(),
+        name: private_data._name.as_ptr(),
+        metadata: ptr::null(),
+        flags,
+        n_children: private_data.children.len() as i64,
+        children: private_data.children.as_mut_ptr(),
+        dictionary: ptr::null_mut(),
+        release: Some(release_schema),
+        private_data: Box::into_raw(private_data) as *mut c_void,
+    }
+}
+
+/// `buffer_pointers` must point to the data owned by `buffers` (null for the absent validity).
+fn new_array(
+    length: usize,
+    null_count: usize,
+    buffers: Vec<Box<dyn Any>>,
+    buffer_pointers: Vec<*const c_void>,
+    children: Vec<FFI_ArrowArray>,
+) -> FFI_ArrowArray {
+    let mut private_data = Box::new(ArrayPrivateData {
+        _buffers: buffers,
+        buffer_pointers,
+        children: children.into_iter().map(|x| Box::into_raw(Box::new(x))).collect(),
+    });
+    FFI_ArrowArray {
+        length: length as i64,
+        null_count: null_count as i64,
+        offset: 0,
+        n_buffers: private_data.buffer_pointers.len() as i64,
+        n_children: private_data.children.len() as i64,
+        buffers: private_data.buffer_pointers.as_mut_ptr(),
+        children: private_data.children.as_mut_ptr(),
+        dictionary: ptr::null_mut(),
+        release: Some(release_array),
+        private_data: Box::into_raw(private_data) as *mut c_void,
+    }
+}
+
+enum Column {
+    UInt32(Vec<u32>),
+    Float64(Vec<f64>),
+    // Bit-packed values
+    Boolean(Vec<u8>),
+    // Large UTF-8 with i64 offsets, not to overflow on many alignments.
+    // (offsets, data, validity bitmap if nullable)
+    LargeUtf8(Vec<i64>, Vec<u8>, Option<Vec<u8>>),
+}
+
+impl Column {
+    fn format(&self) -> &'static str {
+        match self {
+            Self::UInt32(_) => "I",
+            Self::Float64(_) => "g",
+            Self::Boolean(_) => "b",
+            Self::LargeUtf8(..) => "U",
+        }
+    }
+    fn is_nullable(&self) -> bool {
+        matches!(self, Self::LargeUtf8(_, _, Some(_)))
+    }
+    fn into_array(self, length: usize) -> FFI_ArrowArray {
+        match self {
+            Self::UInt32(values) => {
+                let pointer = values.as_ptr() as *const c_void;
+                new_array(length, 0, vec![Box::new(values)], vec![ptr::null(), pointer], Vec::new())
+            },
+            Self::Float64(values) => {
+                let pointer = values.as_ptr() as *const c_void;
+                new_array(length, 0, vec![Box::new(values)], vec![ptr::null(), pointer], Vec::new())
+            },
+            Self::Boolean(bits) => {
+                let pointer = bits.as_ptr() as *const c_void;
+                new_array(length, 0, vec![Box::new(bits)], vec![ptr::null(), pointer], Vec::new())
+            },
+            Self::LargeUtf8(offsets, data, validity) => {
+                let (null_count, validity_pointer) = match &validity {
+                    Some(bits) => (
+                        length - count_set_bits(bits, length),
+                        bits.as_ptr() as *const c_void,
+                    ),
+                    None => (0, ptr::null()),
+                };
+                let buffer_pointers = vec![
+                    validity_pointer,
+                    offsets.as_ptr() as *const c_void,
+                    data.as_ptr() as *const c_void,
+                ];
+                new_array(
+                    length,
+                    null_count,
+                    vec![Box::new(offsets), Box::new(data), Box::new(validity)],
+                    buffer_pointers,
+                    Vec::new(),
+                )
+            },
+        }
+    }
+}
+
+fn set_bit(bits: &mut Vec<u8>, index: usize, value: bool) {
+    if index / 8 >= bits.len() {
+        bits.push(0);
+    }
+    if value {
+        bits[index / 8] |= 1 << (index % 8);
+    }
+}
+fn count_set_bits(bits: &[u8], length: usize) -> usize {
+    (0..length).filter(|&index| bits[index / 8] & (1 << (index % 8)) != 0).count()
+}
+fn push_str(offsets: &mut Vec<i64>, data: &mut Vec<u8>, value: &str) {
+    data.extend_from_slice(value.as_bytes());
+    offsets.push(data.len() as i64);
+}
+
+/// Columns of the flat alignments, with the read columns for `FastaAlignment`.
+struct AlignmentColumns {
+    length: usize,
+    with_read: bool,
+    read: (Vec<i64>, Vec<u8>),
+    is_forward: Vec<u8>,
+    target_index: Vec<u32>,
+    target_label: (Vec<i64>, Vec<u8>, Vec<u8>),
+    penalty: Vec<u32>,
+    alignment_length: Vec<u32>,
+    query_start: Vec<u32>,
+    query_end: Vec<u32>,
+    target_start: Vec<u32>,
+    target_end: Vec<u32>,
+    cigar: (Vec<i64>, Vec<u8>),
+    identity: Vec<f64>,
+}
+
+impl AlignmentColumns {
+    fn new(with_read: bool) -> Self {
+        Self {
+            length: 0,
+            with_read,
+            read: (vec![0], Vec::new()),
+            is_forward: Vec::new(),
+            target_index: Vec::new(),
+            target_label: (vec![0], Vec::new(), Vec::new()),
+            penalty: Vec::new(),
+            alignment_length: Vec::new(),
+            query_start: Vec::new(),
+            query_end: Vec::new(),
+            target_start: Vec::new(),
+            target_end: Vec::new(),
+            cigar: (vec![0], Vec::new()),
+            identity: Vec::new(),
+        }
+    }
+    fn push_target_alignment(&mut self, read: Option<(&str, bool)>, target_alignment: &PyTargetAlignment) {
+        for alignment in &target_alignment.alignments {
+            if let Some((read, is_forward)) = read {
+                push_str(&mut self.read.0, &mut self.read.1, read);
+                set_bit(&mut self.is_forward, self.length, is_forward);
+            }
+            self.target_index.push(target_alignment.index);
+            let (offsets, data, validity) = &mut self.target_label;
+            set_bit(validity, self.length, target_alignment.label.is_some());
+            push_str(offsets, data, target_alignment.label.as_deref().unwrap_or_default());
+            self.push_alignment(alignment);
+            self.length += 1;
+        }
+    }
+    fn push_alignment(&mut self, alignment: &PyAlignment) {
+        self.penalty.push(alignment.penalty);
+        self.alignment_length.push(alignment.length);
+        self.query_start.push(alignment.query_position.0);
+        self.query_end.push(alignment.query_position.1);
+        self.target_start.push(alignment.target_position.0);
+        self.target_end.push(alignment.target_position.1);
+        push_str(&mut self.cigar.0, &mut self.cigar.1, &operations_to_cigars(&alignment.operations));
+        let operations: Vec<AlignmentOperations> = alignment.operations
+            .iter()
+            .map(|op| AlignmentOperations::from(op.clone()))
+            .collect();
+        self.identity.push(AlignmentStats::from_operations(&operations).identity());
+    }
+    fn into_named_columns(self) -> Vec<(&'static str, Column)> {
+        let mut columns = Vec::new();
+        if self.with_read {
+            columns.push(("read", Column::LargeUtf8(self.read.0, self.read.1, None)));
+            columns.push(("is_forward", Column::Boolean(self.is_forward)));
+        }
+        let (offsets, data, validity) = self.target_label;
+        columns.extend([
+            ("target_index", Column::UInt32(self.target_index)),
+            ("target_label", Column::LargeUtf8(offsets, data, Some(validity))),
+            ("penalty", Column::UInt32(self.penalty)),
+            ("length", Column::UInt32(self.alignment_length)),
+            ("query_start", Column::UInt32(self.query_start)),
+            ("query_end", Column::UInt32(self.query_end)),
+            ("target_start", Column::UInt32(self.target_start)),
+            ("target_end", Column::UInt32(self.target_end)),
+            ("cigar", Column::LargeUtf8(self.cigar.0, self.cigar.1, None)),
+            ("identity", Column::Float64(self.identity)),
+        ]);
+        columns
+    }
+    /// (Schema, array) of the struct array representing the `RecordBatch`.
+    fn into_ffi(self) -> (FFI_ArrowSchema, FFI_ArrowArray) {
+        let length = self.length;
+        let (child_schemas, child_arrays): (Vec<_>, Vec<_>) = self.into_named_columns()
+            .into_iter()
+            .map(|(name, column)| {
+                let flags = if column.is_nullable() { ARROW_FLAG_NULLABLE } else { 0 };
+                let schema = new_schema(column.format(), name, flags, Vec::new());
+                (schema, column.into_array(length))
+            })
+            .unzip();
+        (
+            new_schema("+s", "", 0, child_schemas),
+            new_array(length, 0, Vec::new(), vec![ptr::null()], child_arrays),
+        )
+    }
+    fn into_py_capsules(self, py: Python<'_>) -> PyResult<(Bound<'_, PyCapsule>, Bound<'_, PyCapsule>)> {
+        let (schema, array) = self.into_ffi();
+        let schema_capsule = PyCapsule::new_bound_with_destructor(
+            py,
+            schema,
+            Some(CString::new("arrow_schema").unwrap()),
+            |schema, _| drop_schema(schema),
+        )?;
+        let array_capsule = PyCapsule::new_bound_with_destructor(
+            py,
+            array,
+            Some(CString::new("arrow_array").unwrap()),
+            |array, _| drop_array(array),
+        )?;
+        Ok((schema_capsule, array_capsule))
+    }
+}
+
+/// Results that can be exported as the Arrow `RecordBatch`.
+pub trait ToArrowCapsules {
+    /// (`arrow_schema`, `arrow_array`) capsules of the struct array.
+    fn to_arrow_capsules<'py>(&self, py: Python<'py>) -> PyResult<(Bound<'py, PyCapsule>, Bound<'py, PyCapsule>)>;
+}
+
+/// `pyarrow.RecordBatch` of the object implementing `__arrow_c_array__` (pyarrow >= 14 is required).
+pub fn to_pyarrow_record_batch<'py>(results: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    results.py().import_bound("pyarrow")?.getattr("record_batch")?.call1((results,))
+}
+/// `pandas.DataFrame` converted from the `pyarrow.RecordBatch`.
+pub fn to_pandas_data_frame<'py>(results: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    to_pyarrow_record_batch(results)?.call_method0("to_pandas")
+}
+/// `polars.DataFrame` converted from the `pyarrow.RecordBatch`.
+pub fn to_polars_data_frame<'py>(results: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let record_batch = to_pyarrow_record_batch(results)?;
+    results.py().import_bound("polars")?.getattr("from_arrow")?.call1((record_batch,))
+}
+
+/// `#[pymethods]` of the results with the methods to export as Arrow:
+/// `__arrow_c_array__` (Arrow PyCapsule interface), `to_arrow`, `to_pandas`, and `to_polars`.
+///  - The results must implement `ToArrowCapsules`.
+macro_rules! pymethods_with_arrow {
+    (impl $name:ident { $($methods:tt)* }) => {
+        #[pymethods]
+        impl $name {
+            $($methods)*
+            /// Export as the Arrow `RecordBatch` through the Arrow PyCapsule interface.
+            #[pyo3(signature = (requested_schema=None))]
+            fn __arrow_c_array__<'py>(
+                &self,
+                py: Python<'py>,
+                requested_schema: Option<&Bound<'py, PyAny>>,
+            ) -> PyResult<(Bound<'py, pyo3::types::PyCapsule>, Bound<'py, pyo3::types::PyCapsule>)> {
+                let _ = requested_schema;
+                $crate::results::arrow::ToArrowCapsules::to_arrow_capsules(self, py)
+            }
+            fn to_arrow<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+                $crate::results::arrow::to_pyarrow_record_batch(slf.as_any())
+            }
+            fn to_pandas<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+                $crate::results::arrow::to_pandas_data_frame(slf.as_any())
+            }
+            fn to_polars<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+                $crate::results::arrow::to_polars_data_frame(slf.as_any())
+            }
+        }
+    };
+}
+pub(super) use pymethods_with_arrow;
+
+impl ToArrowCapsules for PyFastaAlignment {
+    fn to_arrow_capsules<'py>(&self, py: Python<'py>) -> PyResult<(Bound<'py, PyCapsule>, Bound<'py, PyCapsule>)> {
+        let mut columns = AlignmentColumns::new(true);
+        for read_alignment in &self.0 {
+            for target_alignment in &read_alignment.result.0 {
+                columns.push_target_alignment(
+                    Some((&read_alignment.read, read_alignment.is_forward)),
+                    target_alignment,
+                );
+            }
+        }
+        columns.into_py_capsules(py)
+    }
+}
+impl ToArrowCapsules for PyQueryAlignment {
+    fn to_arrow_capsules<'py>(&self, py: Python<'py>) -> PyResult<(Bound<'py, PyCapsule>, Bound<'py, PyCapsule>)> {
+        let mut columns = AlignmentColumns::new(false);
+        for target_alignment in &self.0 {
+            columns.push_target_alignment(None, target_alignment);
+        }
+        columns.into_py_capsules(py)
+    }
+}
+impl ToArrowCapsules for PyTargetAlignment {
+    fn to_arrow_capsules<'py>(&self, py: Python<'py>) -> PyResult<(Bound<'py, PyCapsule>, Bound<'py, PyCapsule>)> {
+        let mut columns = AlignmentColumns::new(false);
+        columns.push_target_alignment(None, self);
+        columns.into_py_capsules(py)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+    use super::*;
+    use crate::results::{PyAlignmentOperations, PyAlignmentOperation};
+
+    fn target_alignment(index: u32, label: Option<&str>, penalties: &[u32]) -> PyTargetAlignment {
+        PyTargetAlignment {
+            index,
+            label: label.map(|x| x.to_string()),
+            alignments: penalties.iter().map(|&penalty| PyAlignment {
+                penalty,
+                length: 10,
+                query_position: (0, 10),
+                target_position: (100, 110),
+                operations: vec![
+                    PyAlignmentOperations { operation: PyAlignmentOperation::Match, count: 9 },
+                    PyAlignmentOperations { operation: PyAlignmentOperation::Subst, count: 1 },
+                ],
+            }).collect(),
+        }
+    }
+    unsafe fn child_schema(schema: &FFI_ArrowSchema, index: usize) -> &FFI_ArrowSchema {
+        &**schema.children.add(index)
+    }
+    unsafe fn child_array(array: &FFI_ArrowArray, index: usize) -> &FFI_ArrowArray {
+        &**array.children.add(index)
+    }
+    unsafe fn buffer<T: Copy>(array: &FFI_ArrowArray, index: usize, length: usize) -> Vec<T> {
+        std::slice::from_raw_parts(*array.buffers.add(index) as *const T, length).to_vec()
+    }
+
+    #[test]
+    fn columns_are_exported_with_buffers() {
+        let mut columns = AlignmentColumns::new(true);
+        columns.push_target_alignment(Some(("read1", true)), &target_alignment(0, Some("t0"), &[1, 2]));
+        columns.push_target_alignment(Some(("read2", false)), &target_alignment(3, None, &[4]));
+        let (mut schema, mut array) = columns.into_ffi();
+
+        unsafe {
+            assert_eq!(CStr::from_ptr(schema.format).to_str().unwrap(), "+s");
+            assert_eq!(array.length, 3);
+            assert_eq!(array.null_count, 0);
+            assert_eq!(schema.n_children, 12);
+            assert_eq!(array.n_children, 12);
+            let names_and_formats: Vec<(&str, &str, i64)> = (0..12).map(|index| {
+                let child = child_schema(&schema, index);
+                (
+                    CStr::from_ptr(child.name).to_str().unwrap(),
+                    CStr::from_ptr(child.format).to_str().unwrap(),
+                    child.flags,
+                )
+            }).collect();
+            assert_eq!(names_and_formats, [
+                ("read", "U", 0),
+                ("is_forward", "b", 0),
+                ("target_index", "I", 0),
+                ("target_label", "U", ARROW_FLAG_NULLABLE),
+                ("penalty", "I", 0),
+                ("length", "I", 0),
+                ("query_start", "I", 0),
+                ("query_end", "I", 0),
+                ("target_start", "I", 0),
+                ("target_end", "I", 0),
+                ("cigar", "U", 0),
+                ("identity", "g", 0),
+            ]);
+            for index in 0..12 {
+                assert_eq!(child_array(&array, index).length, 3);
+            }
+
+            let read = child_array(&array, 0);
+            assert_eq!((read.n_buffers, read.null_count), (3, 0));
+            assert!((*read.buffers).is_null());
+            assert_eq!(buffer::<i64>(read, 1, 4), [0, 5, 10, 15]);
+            assert_eq!(buffer::<u8>(read, 2, 15), b"read1read1read2");
+            assert_eq!(buffer::<u8>(child_array(&array, 1), 1, 1), [0b011]);
+            assert_eq!(buffer::<u32>(child_array(&array, 2), 1, 3), [0, 0, 3]);
+
+            let target_label = child_array(&array, 3);
+            assert_eq!(target_label.null_count, 1);
+            assert_eq!(buffer::<u8>(target_label, 0, 1), [0b011]);
+            assert_eq!(buffer::<i64>(target_label, 1, 4), [0, 2, 4, 4]);
+            assert_eq!(buffer::<u8>(target_label, 2, 4), b"t0t0");
+
+            assert_eq!(buffer::<u32>(child_array(&array, 4), 1, 3), [1, 2, 4]);
+            assert_eq!(buffer::<i64>(child_array(&array, 10), 1, 4), [0, 4, 8, 12]);
+            assert_eq!(buffer::<f64>(child_array(&array, 11), 1, 3), [0.9, 0.9, 0.9]);
+
+            // A child moved by the consumer is released apart from the parent
+            release_array(*array.children);
+            assert!((*(*array.children)).release.is_none());
+            release_array(&mut array);
+            release_schema(&mut schema);
+        }
+        assert!(array.release.is_none());
+        assert!(schema.release.is_none());
+    }
+
+    #[test]
+    fn capsule_destructors_release_only_not_moved() {
+        let columns = || {
+            let mut columns = AlignmentColumns::new(false);
+            columns.push_target_alignment(None, &target_alignment(0, Some("t0"), &[1]));
+            columns.into_ffi()
+        };
+        // Not moved
+        let (schema, array) = columns();
+        drop_schema(schema);
+        drop_array(array);
+        // Moved: the consumer took the ownership and marked it as released
+        let (mut schema, mut array) = columns();
+        unsafe {
+            release_schema(&mut schema);
+            release_array(&mut array);
+        }
+        drop_schema(schema);
+        drop_array(array);
+    }
+}
@@ -1,10 +1,11 @@
 use sigalign::results::{
-    Alignment, AlignmentOperation, AlignmentOperations, LabeledQueryAlignment,
+    Alignment, AlignmentOperation, AlignmentOperations, AlignmentPosition, AlignmentStats, LabeledQueryAlignment,
     LabeledTargetAlignment, QueryAlignment, TargetAlignment,
 };
 
 use super::{
-    PyAlignment, PyAlignmentOperation, PyAlignmentOperations, PyQueryAlignment, PyTargetAlignment,
+    PyAlignment, PyAlignmentOperation, PyAlignmentOperations, PyAlignmentStats, PyQueryAlignment,
+    PyTargetAlignment,
 };
 
 impl From<QueryAlignment> for PyQueryAlignment {
@@ -75,6 +76,24 @@ impl From<Alignment> for PyAlignment {
     }
 }
 
+impl From<PyAlignment> for Alignment {
+    fn from(alignment: PyAlignment) -> Self {
+        Self {
+            penalty: alignment.penalty,
+            length: alignment.length,
+            position: AlignmentPosition {
+                query: alignment.query_position,
+                target: alignment.target_position,
+            },
+            operations: alignment
+                .operations
+                .into_iter()
+                .map(AlignmentOperations::from)
+                .collect(),
+        }
+    }
+}
+
 impl From<AlignmentOperations> for PyAlignmentOperations {
     fn from(op: AlignmentOperations) -> Self {
         Self {
@@ -94,3 +113,37 @@ impl From<AlignmentOperation> for PyAlignmentOperation {
         }
     }
 }
+
+impl From<PyAlignmentOperations> for AlignmentOperations {
+    fn from(op: PyAlignmentOperations) -> Self {
+        Self {
+            operation: AlignmentOperation::from(op.operation),
+            count: op.count,
+        }
+    }
+}
+
+impl From<PyAlignmentOperation> for AlignmentOperation {
+    fn from(op: PyAlignmentOperation) -> Self {
+        match op {
+            PyAlignmentOperation::Match => AlignmentOperation::Match,
+            PyAlignmentOperation::Subst => AlignmentOperation::Subst,
+            PyAlignmentOperation::Insertion => AlignmentOperation::Insertion,
+            PyAlignmentOperation::Deletion => AlignmentOperation::Deletion,
+        }
+    }
+}
+
+impl From<AlignmentStats> for PyAlignmentStats {
+    fn from(stats: AlignmentStats) -> Self {
+        Self {
+            matches: stats.matches,
+            mismatches: stats.mismatches,
+            insertions: stats.insertions,
+            deletions: stats.deletions,
+            gap_opens: stats.gap_opens,
+            gap_extensions: stats.gap_extensions,
+            identity: stats.identity(),
+        }
+    }
+}
@@ -2,13 +2,18 @@ use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::{to_string, to_string_pretty};
+use sigalign::results::Alignment;
 
 mod iterators; // Contains Python's iterators classes.
 pub use iterators::{FastaAlignmentIter, QueryAlignmentIter};
 mod from;
 mod py_debug;
 mod to_flat_result;
-use to_flat_result::{FlatReadAlignment, FlatTargetAlignment};
+mod paired;
+mod arrow;
+use arrow::pymethods_with_arrow;
+pub use paired::{PyReadPairAlignment, PyMateAlignment, PyConcordantPair};
+use to_flat_result::{FlatReadAlignment, FlatTargetAlignment, operations_to_cigars};
 
 pub fn register_results_module_as_submodule(parent_module: &Bound<'_, PyModule>) -> PyResult<()> {
     let results_module = PyModule::new_bound(parent_module.py(), "results")?;
@@ -17,8 +22,12 @@ pub fn register_results_module_as_submodule(parent_module: &Bound<'_, PyModule>)
     results_module.add_class::<PyQueryAlignment>()?;
     results_module.add_class::<PyTargetAlignment>()?;
     results_module.add_class::<PyAlignment>()?;
+    results_module.add_class::<PyAlignmentStats>()?;
     results_module.add_class::<PyAlignmentOperations>()?;
     results_module.add_class::<PyAlignmentOperation>()?;
+    results_module.add_class::<PyReadPairAlignment>()?;
+    results_module.add_class::<PyMateAlignment>()?;
+    results_module.add_class::<PyConcordantPair>()?;
     parent_module.add_submodule(&results_module)?;
     Ok(())
 }
@@ -27,39 +36,40 @@ pub fn register_results_module_as_submodule(parent_module: &Bound<'_, PyModule>)
 #[pyclass(name = "FastaAlignment", sequence, frozen, eq, hash)]
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct PyFastaAlignment(pub Vec<PyReadAlignment>);
-#[pymethods]
-impl PyFastaAlignment {
-    #[new]
-    fn py_new(read_alignments: Vec<PyReadAlignment>) -> Self {
-        Self(read_alignments)
-    }
-    fn to_list(&self) -> PyResult<Vec<PyReadAlignment>> {
-        Ok(self.0.clone())
-    }
-    fn to_json(&self) -> String {
-        to_string(self).unwrap()
-    }
-    fn to_json_pretty(&self) -> String {
-        to_string_pretty(self).unwrap()
-    }
-    fn to_rows(&self) -> Vec<FlatReadAlignment> {
-        self.to_flat_results()
-    }
-    fn num_alignments(&self) -> usize {
-        self.0.iter().map(|v| v.num_alignments()).sum()
-    }
-    fn __len__(&self) -> PyResult<usize> {
-        Ok(self.0.len())
-    }
-    fn __iter__(slf: PyRef<'_, Self>) -> PyResult<Py<FastaAlignmentIter>> {
-        let iter = FastaAlignmentIter::new(slf.0.clone());
-        Py::new(slf.py(), iter)
-    }
-    fn __str__(&self) -> PyResult<String> {
-        Ok(self.py_debug())
-    }
-    fn __repr__(&self) -> PyResult<String> {
-        Ok(self.py_debug())
+pymethods_with_arrow! {
+    impl PyFastaAlignment {
+        #[new]
+        fn py_new(read_alignments: Vec<PyReadAlignment>) -> Self {
+            Self(read_alignments)
+        }
+        fn to_list(&self) -> PyResult<Vec<PyReadAlignment>> {
+            Ok(self.0.clone())
+        }
+        fn to_json(&self) -> String {
+            to_string(self).unwrap()
+        }
+        fn to_json_pretty(&self) -> String {
+            to_string_pretty(self).unwrap()
+        }
+        fn to_rows(&self) -> Vec<FlatReadAlignment> {
+            self.to_flat_results()
+        }
+        fn num_alignments(&self) -> usize {
+            self.0.iter().map(|v| v.num_alignments()).sum()
+        }
+        fn __len__(&self) -> PyResult<usize> {
+            Ok(self.0.len())
+        }
+        fn __iter__(slf: PyRef<'_, Self>) -> PyResult<Py<FastaAlignmentIter>> {
+            let iter = FastaAlignmentIter::new(slf.0.clone());
+            Py::new(slf.py(), iter)
+        }
+        fn __str__(&self) -> PyResult<String> {
+            Ok(self.py_debug())
+        }
+        fn __repr__(&self) -> PyResult<String> {
+            Ok(self.py_debug())
+        }
     }
 }
 
@@ -109,39 +119,40 @@ impl PyReadAlignment {
 #[pyclass(name = "QueryAlignment", sequence, frozen, eq, hash)]
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
 pub struct PyQueryAlignment(pub Vec<PyTargetAlignment>);
-#[pymethods]
-impl PyQueryAlignment {
-    #[new]
-    fn py_new(target_alignments: Vec<PyTargetAlignment>) -> Self {
-        Self(target_alignments)
-    }
-    fn to_list(&self) -> PyResult<Vec<PyTargetAlignment>> {
-        Ok(self.0.clone())
-    }
-    fn to_json(&self) -> String {
-        to_string(self).unwrap()
-    }
-    fn to_json_pretty(&self) -> String {
-        to_string_pretty(self).unwrap()
-    }
-    fn to_rows(&self) -> Vec<FlatTargetAlignment> {
-        self.to_flat_results()
-    }
-    pub fn num_alignments(&self) -> usize {
-        self.0.iter().map(|v| v.num_alignments()).sum()
-    }
-    fn __len__(&self) -> PyResult<usize> {
-        Ok(self.0.len())
-    }
-    fn __iter__(slf: PyRef<'_, Self>) -> PyResult<Py<QueryAlignmentIter>> {
-        let iter = QueryAlignmentIter::new(slf.0.clone());
-        Py::new(slf.py(), iter)
-    }
-    fn __str__(&self) -> PyResult<String> {
-        Ok(self.py_debug())
-    }
-    fn __repr__(&self) -> PyResult<String> {
-        Ok(self.py_debug())
+pymethods_with_arrow! {
+    impl PyQueryAlignment {
+        #[new]
+        fn py_new(target_alignments: Vec<PyTargetAlignment>) -> Self {
+            Self(target_alignments)
+        }
+        fn to_list(&self) -> PyResult<Vec<PyTargetAlignment>> {
+            Ok(self.0.clone())
+        }
+        fn to_json(&self) -> String {
+            to_string(self).unwrap()
+        }
+        fn to_json_pretty(&self) -> String {
+            to_string_pretty(self).unwrap()
+        }
+        fn to_rows(&self) -> Vec<FlatTargetAlignment> {
+            self.to_flat_results()
+        }
+        pub fn num_alignments(&self) -> usize {
+            self.0.iter().map(|v| v.num_alignments()).sum()
+        }
+        fn __len__(&self) -> PyResult<usize> {
+            Ok(self.0.len())
+        }
+        fn __iter__(slf: PyRef<'_, Self>) -> PyResult<Py<QueryAlignmentIter>> {
+            let iter = QueryAlignmentIter::new(slf.0.clone());
+            Py::new(slf.py(), iter)
+        }
+        fn __str__(&self) -> PyResult<String> {
+            Ok(self.py_debug())
+        }
+        fn __repr__(&self) -> PyResult<String> {
+            Ok(self.py_debug())
+        }
     }
 }
 
@@ -156,34 +167,35 @@ pub struct PyTargetAlignment {
     #[pyo3(get)]
     pub alignments: Vec<PyAlignment>,
 }
-#[pymethods]
-impl PyTargetAlignment {
-    #[new]
-    #[pyo3(signature = (index, alignments, label=None))]
-    fn py_new(index: u32, alignments: Vec<PyAlignment>, label: Option<String>) -> Self {
-        Self {
-            index,
-            label,
-            alignments,
+pymethods_with_arrow! {
+    impl PyTargetAlignment {
+        #[new]
+        #[pyo3(signature = (index, alignments, label=None))]
+        fn py_new(index: u32, alignments: Vec<PyAlignment>, label: Option<String>) -> Self {
+            Self {
+                index,
+                label,
+                alignments,
+            }
+        }
+        fn to_json(&self) -> String {
+            to_string(self).unwrap()
+        }
+        fn to_json_pretty(&self) -> String {
+            to_string_pretty(self).unwrap()
+        }
+        fn to_rows(&self) -> Vec<FlatTargetAlignment> {
+            self.to_flat_results()
+        }
+        pub fn num_alignments(&self) -> usize {
+            self.alignments.len()
+        }
+        fn __str__(&self) -> PyResult<String> {
+            Ok(self.py_debug())
+        }
+        fn __repr__(&self) -> PyResult<String> {
+            Ok(self.py_debug())
         }
-    }
-    fn to_json(&self) -> String {
-        to_string(self).unwrap()
-    }
-    fn to_json_pretty(&self) -> String {
-        to_string_pretty(self).unwrap()
-    }
-    fn to_rows(&self) -> Vec<FlatTargetAlignment> {
-        self.to_flat_results()
-    }
-    pub fn num_alignments(&self) -> usize {
-        self.alignments.len()
-    }
-    fn __str__(&self) -> PyResult<String> {
-        Ok(self.py_debug())
-    }
-    fn __repr__(&self) -> PyResult<String> {
-        Ok(self.py_debug())
     }
 }
 
@@ -220,6 +232,53 @@ impl PyAlignment {
             operations,
         }
     }
+    fn to_cigar(&self) -> String {
+        operations_to_cigars(&self.operations)
+    }
+    fn stats(&self) -> PyAlignmentStats {
+        PyAlignmentStats::from(Alignment::from(self.clone()).stats())
+    }
+    fn query_coverage(&self, query_length: u32) -> f64 {
+        Alignment::from(self.clone()).query_coverage(query_length)
+    }
+    fn target_coverage(&self, target_length: u32) -> f64 {
+        Alignment::from(self.clone()).target_coverage(target_length)
+    }
+    fn to_json(&self) -> String {
+        to_string(self).unwrap()
+    }
+    fn to_json_pretty(&self) -> String {
+        to_string_pretty(self).unwrap()
+    }
+    fn __str__(&self) -> PyResult<String> {
+        Ok(self.py_debug())
+    }
+    fn __repr__(&self) -> PyResult<String> {
+        Ok(self.py_debug())
+    }
+}
+
+/// Summary of the alignment derived from the operations.
+#[pyclass(name = "AlignmentStats", frozen)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PyAlignmentStats {
+    #[pyo3(get)]
+    pub matches: u32,
+    #[pyo3(get)]
+    pub mismatches: u32,
+    #[pyo3(get)]
+    pub insertions: u32,
+    #[pyo3(get)]
+    pub deletions: u32,
+    #[pyo3(get)]
+    pub gap_opens: u32,
+    #[pyo3(get)]
+    pub gap_extensions: u32,
+    #[pyo3(get)]
+    pub identity: f64,
+}
+#[pymethods]
+impl PyAlignmentStats {
     fn to_json(&self) -> String {
         to_string(self).unwrap()
     }
@@ -0,0 +1,131 @@
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{to_string, to_string_pretty};
+use sigalign::{
+    results::{ConcordantPair, MateAlignment, ReadPairAlignment},
+    Reference,
+};
+
+use super::PyAlignment;
+
+/// Alignments of a read pair.
+#[pyclass(name = "ReadPairAlignment", frozen, eq, hash)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct PyReadPairAlignment {
+    #[pyo3(get)]
+    pub read: String,
+    #[pyo3(get)]
+    pub first: Vec<PyMateAlignment>,
+    #[pyo3(get)]
+    pub second: Vec<PyMateAlignment>,
+    /// Sorted by the penalty, then by the insert size.
+    #[pyo3(get)]
+    pub concordant_pairs: Vec<PyConcordantPair>,
+}
+#[pymethods]
+impl PyReadPairAlignment {
+    fn best_pair(&self) -> Option<PyConcordantPair> {
+        self.concordant_pairs.first().cloned()
+    }
+    fn is_concordant(&self) -> bool {
+        !self.concordant_pairs.is_empty()
+    }
+    fn to_json(&self) -> String {
+        to_string(self).unwrap()
+    }
+    fn to_json_pretty(&self) -> String {
+        to_string_pretty(self).unwrap()
+    }
+    fn __repr__(&self) -> String {
+        format!(
+            "ReadPairAlignment(read={}, first={}, second={}, concordant_pairs={})",
+            self.read,
+            self.first.len(),
+            self.second.len(),
+            self.concordant_pairs.len(),
+        )
+    }
+}
+
+/// Alignment of one mate with the target and the strand.
+#[pyclass(name = "MateAlignment", frozen, eq, hash)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct PyMateAlignment {
+    #[pyo3(get)]
+    pub target_index: u32,
+    #[pyo3(get)]
+    pub target_label: Option<String>,
+    #[pyo3(get)]
+    pub is_forward: bool,
+    #[pyo3(get)]
+    pub alignment: PyAlignment,
+}
+#[pymethods]
+impl PyMateAlignment {
+    fn __repr__(&self) -> String {
+        format!(
+            "MateAlignment(target_index={}, is_forward={}, penalty={}, target_position={:?})",
+            self.target_index,
+            self.is_forward,
+            self.alignment.penalty,
+            self.alignment.target_position,
+        )
+    }
+}
+
+/// Concordant pair as indices of `first` and `second` of `ReadPairAlignment`.
+#[pyclass(name = "ConcordantPair", frozen, eq, hash)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+pub struct PyConcordantPair {
+    #[pyo3(get)]
+    pub first: usize,
+    #[pyo3(get)]
+    pub second: usize,
+    #[pyo3(get)]
+    pub insert_size: u32,
+    #[pyo3(get)]
+    pub penalty: u32,
+}
+#[pymethods]
+impl PyConcordantPair {
+    fn __repr__(&self) -> String {
+        format!(
+            "ConcordantPair(first={}, second={}, insert_size={}, penalty={})",
+            self.first, self.second, self.insert_size, self.penalty,
+        )
+    }
+}
+
+impl PyReadPairAlignment {
+    pub fn new(
+        read: String,
+        read_pair_alignment: ReadPairAlignment,
+        reference: Option<&Reference>, // To label the targets
+    ) -> Self {
+        let to_py_mate = |mate: MateAlignment| PyMateAlignment {
+            target_index: mate.target_index,
+            target_label: reference.and_then(|reference| {
+                reference.get_label_str(mate.target_index).map(|x| x.to_string())
+            }),
+            is_forward: mate.is_forward,
+            alignment: PyAlignment::from(mate.alignment),
+        };
+        Self {
+            read,
+            first: read_pair_alignment.first.into_iter().map(to_py_mate).collect(),
+            second: read_pair_alignment.second.into_iter().map(to_py_mate).collect(),
+            concordant_pairs: read_pair_alignment.concordant_pairs.into_iter().map(PyConcordantPair::from).collect(),
+        }
+    }
+}
+
+impl From<ConcordantPair> for PyConcordantPair {
+    fn from(pair: ConcordantPair) -> Self {
+        Self {
+            first: pair.first,
+            second: pair.second,
+            insert_size: pair.insert_size,
+            penalty: pair.penalty,
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use core::fmt;
 
 use super::{
-    PyAlignment, PyAlignmentOperation, PyAlignmentOperations, PyFastaAlignment, PyQueryAlignment,
-    PyReadAlignment, PyTargetAlignment,
+    PyAlignment, PyAlignmentOperation, PyAlignmentOperations, PyAlignmentStats, PyFastaAlignment,
+    PyQueryAlignment, PyReadAlignment, PyTargetAlignment,
 };
 
 impl PyFastaAlignment {
@@ -52,6 +52,21 @@ impl PyAlignment {
     }
 }
 
+impl PyAlignmentStats {
+    pub fn py_debug(&self) -> String {
+        format!(
+            "AlignmentStats(matches={}, mismatches={}, insertions={}, deletions={}, gap_opens={}, gap_extensions={}, identity={:.4})",
+            self.matches,
+            self.mismatches,
+            self.insertions,
+            self.deletions,
+            self.gap_opens,
+            self.gap_extensions,
+            self.identity,
+        )
+    }
+}
+
 impl PyAlignmentOperations {
     pub fn py_debug(&self) -> String {
         format!(
@@ -1,6 +1,8 @@
+use sigalign::results::{AlignmentOperations, Cigar};
+
 use super::{
     PyFastaAlignment, PyQueryAlignment, PyReadAlignment, PyTargetAlignment,
-    PyAlignmentOperations, PyAlignmentOperation,
+    PyAlignmentOperations,
 };
 
 pub type FlatTargetAlignment = (
@@ -107,24 +109,10 @@ impl PyTargetAlignment {
     }
 }
 
-fn operations_to_cigars(operations: &[PyAlignmentOperations]) -> String {
-    let string_ops: Vec<String> = operations
+pub fn operations_to_cigars(operations: &[PyAlignmentOperations]) -> String {
+    let operations: Vec<AlignmentOperations> = operations
         .iter()
-        .map(|op| format!(
-            "{}{}",
-            op.count,
-            operation_to_cigar(&op.operation),
-        ))
+        .map(|op| AlignmentOperations::from(op.clone()))
         .collect();
-    string_ops.concat()
-}
-
-#[inline(always)]
-fn operation_to_cigar(op: &PyAlignmentOperation) -> char {
-    match op {
-        PyAlignmentOperation::Match => '=',
-        PyAlignmentOperation::Insertion => 'I',
-        PyAlignmentOperation::Deletion => 'D',
-        PyAlignmentOperation::Subst => 'X',
-    }
+    operations.to_cigar()
 }
@@ -0,0 +1,149 @@
+//! Sequences given as `str`, `bytes`, or objects supporting the buffer protocol (e.g., NumPy `uint8` array).
+//!
+//! The buffer is read through `memoryview`, since the buffer API is not in the limited API of Python 3.10.
+//! So the buffer is copied once, while `str` and `bytes` are borrowed.
+use std::borrow::Cow;
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyList, PyString, PyTuple};
+
+/// Mapping from the integer code to the ASCII residue.
+///  - The code `i` is decoded to the `i`-th character of the symbols (e.g., "ACGT" for 0–3).
+pub struct Symbols(Vec<u8>);
+
+impl Symbols {
+    pub fn new(symbols: &str) -> PyResult<Self> {
+        if symbols.is_empty() || !symbols.is_ascii() || symbols.len() > 256 {
+            return Err(PyValueError::new_err(
+                "The symbols must be 1 to 256 ASCII characters.",
+            ));
+        }
+        Ok(Self(symbols.as_bytes().to_vec()))
+    }
+    pub fn from_option(symbols: Option<&str>) -> PyResult<Option<Self>> {
+        symbols.map(Self::new).transpose()
+    }
+    fn decode(&self, codes: &[u8]) -> PyResult<Vec<u8>> {
+        codes.iter().enumerate().map(|(position, &code)| {
+            self.0.get(code as usize).copied().ok_or_else(|| PyValueError::new_err(format!(
+                "The code {} at position {} is out of the {} symbols.",
+                code, position, self.0.len(),
+            )))
+        }).collect()
+    }
+}
+
+/// Bytes of the sequence, decoded by the `symbols` if given.
+pub fn extract_sequence<'a>(
+    sequence: &'a Bound<'_, PyAny>,
+    symbols: Option<&Symbols>,
+) -> PyResult<Cow<'a, [u8]>> {
+    let bytes = if sequence.is_instance_of::<PyString>() {
+        Cow::Borrowed(sequence.downcast::<PyString>()?.to_str()?.as_bytes())
+    } else if sequence.is_instance_of::<PyBytes>() {
+        Cow::Borrowed(sequence.downcast::<PyBytes>()?.as_bytes())
+    } else {
+        let buffer = BytesBuffer::new(sequence)?;
+        if buffer.shape.len() != 1 {
+            return Err(PyValueError::new_err(format!(
+                "The sequence must be a 1D buffer, but {}D is given.",
+                buffer.shape.len(),
+            )));
+        }
+        Cow::Owned(buffer.bytes)
+    };
+    match symbols {
+        Some(symbols) => Ok(Cow::Owned(symbols.decode(&bytes)?)),
+        None => Ok(bytes),
+    }
+}
+
+/// Bytes of the sequences in a batch.
+///  - `sequences` is a 2D buffer of the padded rows, or a list (or tuple) of sequences.
+///  - With `lengths`, each sequence is truncated to the length (e.g., to remove the padding).
+pub fn extract_sequence_batch(
+    sequences: &Bound<'_, PyAny>,
+    lengths: Option<&[usize]>,
+    symbols: Option<&Symbols>,
+) -> PyResult<Vec<Vec<u8>>> {
+    let rows: Vec<Vec<u8>> = if sequences.is_instance_of::<PyList>() || sequences.is_instance_of::<PyTuple>() {
+        sequences.iter()?.map(|sequence| {
+            // Decoded after truncating
+            Ok(extract_sequence(&sequence?, None)?.into_owned())
+        }).collect::<PyResult<_>>()?
+    } else if sequences.is_instance_of::<PyString>() || sequences.is_instance_of::<PyBytes>() {
+        return Err(PyTypeError::new_err(
+            "The sequences must be a 2D buffer or a list of sequences, not a single sequence.",
+        ));
+    } else {
+        let buffer = BytesBuffer::new(sequences)?;
+        let &[num_rows, row_length] = buffer.shape.as_slice() else {
+            return Err(PyValueError::new_err(format!(
+                "The sequences must be a 2D buffer, but {}D is given.",
+                buffer.shape.len(),
+            )));
+        };
+        if row_length == 0 {
+            vec![Vec::new(); num_rows]
+        } else {
+            buffer.bytes.chunks_exact(row_length).map(|row| row.to_vec()).collect()
+        }
+    };
+
+    let mut rows = rows;
+    if let Some(lengths) = lengths {
+        if lengths.len() != rows.len() {
+            return Err(PyValueError::new_err(format!(
+                "The number of lengths ({}) is not the same as the number of sequences ({}).",
+                lengths.len(), rows.len(),
+            )));
+        }
+        for (row_index, (row, &length)) in rows.iter_mut().zip(lengths).enumerate() {
+            if length > row.len() {
+                return Err(PyValueError::new_err(format!(
+                    "The length {} of the sequence {} exceeds its size {}.",
+                    length, row_index, row.len(),
+                )));
+            }
+            row.truncate(length);
+        }
+    }
+    if let Some(symbols) = symbols {
+        for row in rows.iter_mut() {
+            *row = symbols.decode(row)?;
+        }
+    }
+    Ok(rows)
+}
+
+/// Copied bytes of the buffer in C order, with its shape.
+struct BytesBuffer {
+    bytes: Vec<u8>,
+    shape: Vec<usize>,
+}
+
+impl BytesBuffer {
+    fn new(object: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let py = object.py();
+        let memoryview = py.import_bound("builtins")?
+            .getattr("memoryview")?
+            .call1((object,))
+            .map_err(|_| PyTypeError::new_err(format!(
+                "The sequence must be str, bytes, or an object supporting the buffer protocol, not '{}'.",
+                object.get_type().name().map(|x| x.to_string()).unwrap_or_default(),
+            )))?;
+        let item_size: usize = memoryview.getattr("itemsize")?.extract()?;
+        if item_size != 1 {
+            let format: String = memoryview.getattr("format")?.extract()?;
+            return Err(PyValueError::new_err(format!(
+                "The buffer must have 1-byte items (e.g., uint8), but the format is '{}'.",
+                format,
+            )));
+        }
+        let shape: Vec<usize> = memoryview.getattr("shape")?.extract()?;
+        let bytes = memoryview.call_method0("tobytes")?;
+        let bytes = bytes.downcast::<PyBytes>()?.as_bytes().to_vec();
+        Ok(Self { bytes, shape })
+    }
+}
@@ -0,0 +1,195 @@
+/// Default offset of the quality scores (Phred+33).
+pub const DEFAULT_QUALITY_OFFSET: u8 = 33;
+
+/// Preprocessor of the query before the alignment.
+///  - The steps are applied in order of:
+///    (1) adapter trimming, (2) sliding-window quality trimming, and (3) low-quality masking.
+///  - The quality-based steps are skipped if the quality is not given (e.g., FASTA).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryPreprocessor {
+    quality_offset: u8,
+    sliding_window: Option<SlidingWindow>,
+    adapters: Vec<Vec<u8>>,
+    min_adapter_overlap: usize,
+    masking: Option<QualityMasking>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SlidingWindow {
+    window_size: usize,
+    min_quality: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QualityMasking {
+    min_quality: u8,
+    mask_symbol: u8,
+}
+
+/// Range of the original query that remains after the trimming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TrimmedRange {
+    pub start: u32,
+    pub end: u32,
+    pub original_length: u32,
+}
+
+impl Default for QueryPreprocessor {
+    fn default() -> Self {
+        Self {
+            quality_offset: DEFAULT_QUALITY_OFFSET,
+            sliding_window: None,
+            adapters: Vec::new(),
+            min_adapter_overlap: 0,
+            masking: None,
+        }
+    }
+}
+
+impl QueryPreprocessor {
+    /// Preprocessor that does nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Offset of the quality characters (default: 33).
+    pub fn with_quality_offset(mut self, quality_offset: u8) -> Self {
+        self.quality_offset = quality_offset;
+        self
+    }
+    /// Trim the 3' end from the first window whose mean quality is lower than `min_quality`.
+    /// The bases at the 5' end lower than `min_quality` are also trimmed.
+    pub fn with_sliding_window_trimming(mut self, window_size: usize, min_quality: u8) -> Self {
+        self.sliding_window = Some(SlidingWindow {
+            window_size: window_size.max(1),
+            min_quality,
+        });
+        self
+    }
+    /// Trim the 3' end from the leftmost occurrence of any adapter.
+    ///  - The adapter is matched exactly.
+    ///  - At the 3' end, the prefix of the adapter longer than or equal to `min_overlap` is also matched.
+    pub fn with_adapters(mut self, adapters: Vec<Vec<u8>>, min_overlap: usize) -> Self {
+        self.adapters = adapters.into_iter().filter(|x| !x.is_empty()).collect();
+        self.min_adapter_overlap = min_overlap.max(1);
+        self
+    }
+    /// Replace the bases lower than `min_quality` with `mask_symbol`.
+    ///  - `mask_symbol` should not be in the reference to never match.
+    pub fn with_quality_masking(mut self, min_quality: u8, mask_symbol: u8) -> Self {
+        self.masking = Some(QualityMasking {
+            min_quality,
+            mask_symbol,
+        });
+        self
+    }
+    /// `true` if no step is set.
+    pub fn is_empty(&self) -> bool {
+        self.sliding_window.is_none() && self.adapters.is_empty() && self.masking.is_none()
+    }
+    /// Preprocess the `sequence` into the `buffer` (cleared first).
+    ///  - `quality` must have the same length as `sequence`.
+    ///  - The returned range is the position of the `buffer` in the original `sequence`.
+    pub fn preprocess(
+        &self,
+        sequence: &[u8],
+        quality: Option<&[u8]>,
+        buffer: &mut Vec<u8>,
+    ) -> TrimmedRange {
+        let original_length = sequence.len();
+        let mut start = 0;
+        let mut end = self.adapter_position(sequence).unwrap_or(original_length);
+
+        if let (Some(sliding_window), Some(quality)) = (&self.sliding_window, quality) {
+            let min_quality = self.quality_offset.saturating_add(sliding_window.min_quality);
+            // 5' end
+            while start < end && quality[start] < min_quality {
+                start += 1;
+            }
+            // 3' end
+            end = start + sliding_window_end(
+                &quality[start..end],
+                sliding_window.window_size,
+                min_quality,
+            );
+        }
+
+        buffer.clear();
+        buffer.extend_from_slice(&sequence[start..end]);
+        if let (Some(masking), Some(quality)) = (&self.masking, quality) {
+            let min_quality = self.quality_offset.saturating_add(masking.min_quality);
+            buffer.iter_mut().zip(&quality[start..end]).for_each(|(base, qual)| {
+                if *qual < min_quality {
+                    *base = masking.mask_symbol;
+                }
+            });
+        }
+
+        TrimmedRange {
+            start: start as u32,
+            end: end as u32,
+            original_length: original_length as u32,
+        }
+    }
+    fn adapter_position(&self, sequence: &[u8]) -> Option<usize> {
+        self.adapters.iter().filter_map(|adapter| {
+            (0..sequence.len()).find(|&position| {
+                let remained = &sequence[position..];
+                if remained.len() >= adapter.len() {
+                    remained.starts_with(adapter)
+                } else {
+                    remained.len() >= self.min_adapter_overlap && adapter.starts_with(remained)
+                }
+            })
+        }).min()
+    }
+}
+
+/// Length to keep from the start of the `quality`.
+fn sliding_window_end(quality: &[u8], window_size: usize, min_quality: u8) -> usize {
+    if quality.len() < window_size {
+        // Shorter than window: the mean of the whole
+        let sum: usize = quality.iter().map(|x| *x as usize).sum();
+        return if sum >= min_quality as usize * quality.len() { quality.len() } else { 0 };
+    }
+    let min_sum = min_quality as usize * window_size;
+    let mut sum: usize = quality[..window_size].iter().map(|x| *x as usize).sum();
+    for window_start in 0..=quality.len() - window_size {
+        if window_start != 0 {
+            sum = sum + quality[window_start + window_size - 1] as usize - quality[window_start - 1] as usize;
+        }
+        if sum < min_sum {
+            // Keep the bases passing the quality in the window
+            let kept = quality[window_start..window_start + window_size]
+                .iter()
+                .take_while(|x| **x >= min_quality)
+                .count();
+            return window_start + kept;
+        }
+    }
+    quality.len()
+}
+
+impl TrimmedRange {
+    /// The untrimmed range.
+    pub fn full(length: u32) -> Self {
+        Self {
+            start: 0,
+            end: length,
+            original_length: length,
+        }
+    }
+    pub fn len(&self) -> u32 {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+    /// Range in the reverse complementary sequence of the original query.
+    pub fn reverse(&self) -> Self {
+        Self {
+            start: self.original_length - self.end,
+            end: self.original_length - self.start,
+            original_length: self.original_length,
+        }
+    }
+}
@@ -1,35 +1,72 @@
 /// Returns the reverse complement of a DNA sequence.
 /// A, C, G, T are converted to T, G, C, A and other characters are remain unchanged.
+/// The case is kept (e.g., a to t).
 pub fn reverse_complement_of_dna_sequence(sequence: &[u8]) -> Vec<u8> {
-    let mut reverse_complement = Vec::with_capacity(sequence.len());
-    for base in sequence.iter().rev() {
-        reverse_complement.push(match base {
-            b'A' => b'T',
-            b'C' => b'G',
-            b'G' => b'C',
-            b'T' => b'A',
-            _ => *base,
-        });
-    }
-    reverse_complement
+    sequence.iter().rev().map(|base| complement_of_dna_base(*base)).collect()
 }
 
 /// Returns the reverse complement of a DNA sequence (in-place).
 pub fn reverse_complement_of_dna_sequence_in_place(sequence: &mut [u8]) {
-    let mut i = 0;
-    let mut j = sequence.len() - 1;
-    while i < j {
-        sequence.swap(i, j);
-        i += 1;
-        j -= 1;
-    }
-    sequence.iter_mut().for_each(|x| {
-        *x = match x {
+    sequence.reverse();
+    sequence.iter_mut().for_each(|x| *x = complement_of_dna_base(*x));
+}
+
+/// Returns the reverse complement of a RNA sequence.
+/// A, C, G, U are converted to U, G, C, A and other characters are remain unchanged.
+/// The case is kept (e.g., a to u).
+pub fn reverse_complement_of_rna_sequence(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|base| {
+        let complement = match base.to_ascii_uppercase() {
+            b'A' => b'U',
+            b'C' => b'G',
+            b'G' => b'C',
+            b'U' => b'A',
+            _ => return *base,
+        };
+        with_case_of(*base, complement)
+    }).collect()
+}
+
+/// Returns the reverse complement of a DNA sequence with IUPAC ambiguity codes.
+/// The ambiguity codes are converted to their complements (e.g., R to Y, B to V).
+/// The case is kept (e.g., r to y).
+pub fn reverse_complement_of_iupac_dna_sequence(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|base| {
+        let complement = match base.to_ascii_uppercase() {
             b'A' => b'T',
             b'C' => b'G',
             b'G' => b'C',
             b'T' => b'A',
-            _ => *x,
-        }
-    });
+            b'R' => b'Y',
+            b'Y' => b'R',
+            b'K' => b'M',
+            b'M' => b'K',
+            b'B' => b'V',
+            b'V' => b'B',
+            b'D' => b'H',
+            b'H' => b'D',
+            _ => return *base, // S, W, N are self-complementary
+        };
+        with_case_of(*base, complement)
+    }).collect()
+}
+
+fn complement_of_dna_base(base: u8) -> u8 {
+    let complement = match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        _ => return base,
+    };
+    with_case_of(base, complement)
+}
+
+// Uppercase `complement` in the case of the `base`
+fn with_case_of(base: u8, complement: u8) -> u8 {
+    if base.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
 }
@@ -0,0 +1,97 @@
+use std::io::{self, Cursor, Read};
+
+use super::get_gzip_decoder;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const BZIP2_MAGIC: [u8; 3] = *b"BZh";
+// The longest magic bytes
+const MAGIC_LENGTH: usize = 4;
+
+/// Reader with the peeked bytes chained back in front of it.
+pub type PeekedReader<R> = io::Chain<Cursor<Vec<u8>>, R>;
+
+/// Compression format detected from the magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// Gzip, including the multi-member gzip and BGZF.
+    Gzip,
+    /// Zstandard (`zstd` feature is required to decode).
+    Zstd,
+    /// Bzip2 (`bzip2` feature is required to decode).
+    Bzip2,
+}
+
+impl Compression {
+    /// Detect the compression from the leading bytes of the file.
+    pub fn from_magic_bytes(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            Self::Gzip
+        } else if bytes.starts_with(&ZSTD_MAGIC) {
+            Self::Zstd
+        } else if bytes.starts_with(&BZIP2_MAGIC) {
+            Self::Bzip2
+        } else {
+            Self::None
+        }
+    }
+    /// Detect the compression from the leading bytes of the `reader`.
+    ///  - The bytes are read until there are enough for the magic bytes or the EOF,
+    ///    even if the `reader` returns them in small pieces (e.g., pipes).
+    ///  - The returned reader yields all bytes, including the peeked ones.
+    pub fn detect<R: Read>(mut reader: R) -> io::Result<(Self, PeekedReader<R>)> {
+        let mut peeked = Vec::with_capacity(MAGIC_LENGTH);
+        (&mut reader).take(MAGIC_LENGTH as u64).read_to_end(&mut peeked)?;
+        let compression = Self::from_magic_bytes(&peeked);
+        Ok((compression, Cursor::new(peeked).chain(reader)))
+    }
+    /// Whether the decoder is available in the current build.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            Self::None | Self::Gzip => true,
+            Self::Zstd => cfg!(feature = "zstd"),
+            Self::Bzip2 => cfg!(feature = "bzip2"),
+        }
+    }
+}
+
+/// Wrap the reader with the decoder of the compression.
+///  - Returns `ErrorKind::Unsupported` if the feature for the compression is disabled.
+pub fn get_decoder<'a, R: Read + Send + 'a>(
+    reader: R,
+    compression: Compression,
+) -> io::Result<Box<dyn Read + Send + 'a>> {
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        Compression::Gzip => Ok(Box::new(get_gzip_decoder(reader))),
+        Compression::Zstd => {
+            #[cfg(feature = "zstd")]
+            {
+                Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+            }
+            #[cfg(not(feature = "zstd"))]
+            {
+                Err(unsupported_compression(compression))
+            }
+        },
+        Compression::Bzip2 => {
+            #[cfg(feature = "bzip2")]
+            {
+                Ok(Box::new(bzip2::read::MultiBzDecoder::new(reader)))
+            }
+            #[cfg(not(feature = "bzip2"))]
+            {
+                Err(unsupported_compression(compression))
+            }
+        },
+    }
+}
+
+#[cfg(not(all(feature = "zstd", feature = "bzip2")))]
+fn unsupported_compression(compression: Compression) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{:?} compression is not supported in this build", compression),
+    )
+}
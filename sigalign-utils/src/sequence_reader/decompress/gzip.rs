@@ -1,6 +1,7 @@
 use std::io::prelude::*;
-use flate2::read::GzDecoder;
+use flate2::read::MultiGzDecoder;
 
-pub fn get_gzip_decoder<R: Read>(reader: R) -> GzDecoder<R> {
-    GzDecoder::new(reader)
+/// Decoder that reads all members of the gzip file (including BGZF).
+pub fn get_gzip_decoder<R: Read>(reader: R) -> MultiGzDecoder<R> {
+    MultiGzDecoder::new(reader)
 }
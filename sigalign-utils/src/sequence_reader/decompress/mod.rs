@@ -2,4 +2,7 @@ mod gzip;
 pub use gzip::get_gzip_decoder;
 
 mod zlib;
-pub use zlib::get_zlib_decoder;
\ No newline at end of file
+pub use zlib::get_zlib_decoder;
+
+mod detection;
+pub use detection::{Compression, PeekedReader, get_decoder};
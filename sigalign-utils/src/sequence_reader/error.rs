@@ -0,0 +1,56 @@
+use std::{io, str::Utf8Error};
+
+use thiserror::Error;
+
+/// Error while reading a sequence file.
+///  - The reading should be stopped after the error.
+#[derive(Error, Debug)]
+#[error("Failed to read record {record_number} (line {line_number}): {kind}")]
+pub struct ReadError {
+    kind: ReadErrorKind,
+    record_number: u64,
+    line_number: u64,
+}
+
+/// Cause of `ReadError`.
+#[derive(Error, Debug)]
+pub enum ReadErrorKind {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Malformed record: {0}")]
+    MalformedRecord(String),
+    #[error("ID is invalid UTF-8: {0}")]
+    InvalidId(#[from] Utf8Error),
+}
+
+impl ReadError {
+    pub fn new(kind: ReadErrorKind, record_number: u64, line_number: u64) -> Self {
+        Self {
+            kind,
+            record_number,
+            line_number,
+        }
+    }
+    pub fn kind(&self) -> &ReadErrorKind {
+        &self.kind
+    }
+    /// 1-based number of the record where the error occurred.
+    pub fn record_number(&self) -> u64 {
+        self.record_number
+    }
+    /// 1-based line number where the error occurred.
+    ///  - For the I/O error, this is the line of the last record successfully read.
+    pub fn line_number(&self) -> u64 {
+        self.line_number
+    }
+}
+
+impl From<ReadError> for io::Error {
+    fn from(err: ReadError) -> Self {
+        let kind = match err.kind {
+            ReadErrorKind::Io(ref e) => e.kind(),
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err)
+    }
+}
@@ -3,17 +3,21 @@ use std::{io::{Read, Error}, fs::File, path::Path, str::Utf8Error};
 use seq_io::fasta::{
     Reader as SeqIoReader,
     RefRecord as SeqIoRecord, Record,
+    Error as SeqIoError,
 };
 
 use super::{
     SeqRecord,
     IdRecord,
     IdRefRecord,
+    ReadError,
+    ReadErrorKind,
 };
 
 /// The reader of FASTA formatted file
 pub struct FastaReader<R: Read> {
     reader: SeqIoReader<R>,
+    record_count: u64,
 }
 
 pub struct FastaRecord<'a> {
@@ -24,28 +28,66 @@ impl<'a, R: Read> FastaReader<R> {
     pub fn new(reader: R) -> Self {
         let reader = SeqIoReader::new(reader);
         Self {
-            reader
+            reader,
+            record_count: 0,
         }
     }
-    pub fn next(&'a mut self) -> Option<FastaRecord<'a>> {
-        if let Some(Ok(seq)) = self.reader.next() {
-            Some(FastaRecord {
-                record: seq,
-            })
-        } else {
-            None
+    /// Read the next record.
+    ///  - `None` at the end of the file.
+    ///  - `Some(Err)` if the record is malformed or the reader fails.
+    pub fn next(&'a mut self) -> Option<Result<FastaRecord<'a>, ReadError>> {
+        let line_number = self.line_number();
+        match self.reader.next()? {
+            Ok(seq) => {
+                self.record_count += 1;
+                Some(Ok(FastaRecord {
+                    record: seq,
+                }))
+            },
+            Err(err) => {
+                let (kind, line_number) = match err {
+                    SeqIoError::Io(err) => (ReadErrorKind::Io(err), line_number),
+                    SeqIoError::InvalidStart { line, found } => (
+                        ReadErrorKind::MalformedRecord(format!(
+                            "expected '>' but found '{}'", (found as char).escape_default(),
+                        )),
+                        line as u64,
+                    ),
+                    SeqIoError::BufferLimit => (
+                        ReadErrorKind::MalformedRecord("buffer limit reached".to_string()),
+                        line_number,
+                    ),
+                };
+                Some(Err(ReadError::new(kind, self.record_count + 1, line_number)))
+            },
         }
     }
+    /// Number of records read so far (i.e., the 1-based number of the last record).
+    pub fn record_number(&self) -> u64 {
+        self.record_count
+    }
+    /// 1-based line number of the last record.
+    pub fn line_number(&self) -> u64 {
+        self.reader.position().map_or(0, |position| position.line())
+    }
 }
 impl FastaReader<File> {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let reader = SeqIoReader::from_path(path)?;
         Ok(Self {
-            reader
+            reader,
+            record_count: 0,
         })
     }
 }
 
+impl<'a> FastaRecord<'a> {
+    /// Description of the header (the text after the ID). `None` if absent.
+    pub fn desc_str(&self) -> Option<Result<&str, Utf8Error>> {
+        self.record.desc()
+    }
+}
+
 impl<'a> SeqRecord for FastaRecord<'a> {
     fn extend_seq_buf(&mut self, buf: &mut Vec<u8>) {
         self.record.seq_lines().for_each(|s| buf.extend_from_slice(s));
@@ -8,6 +8,7 @@ use std::{
 use seq_io::fastq::{
     Reader as SeqIoReader,
     RefRecord as SeqIoRecord, Record,
+    Error as SeqIoError,
 };
 
 use super::{
@@ -15,11 +16,14 @@ use super::{
     SeqRefRecord,
     IdRecord,
     IdRefRecord,
+    ReadError,
+    ReadErrorKind,
 };
 
-/// The reader of FASTA formatted file
+/// The reader of FASTQ formatted file
 pub struct FastqReader<R: Read> {
     reader: SeqIoReader<R>,
+    record_count: u64,
 }
 
 pub struct FastqRecord<'a> {
@@ -30,24 +34,72 @@ impl<'a, R: Read> FastqReader<R> {
     pub fn new(reader: R) -> Self {
         let reader = SeqIoReader::new(reader);
         Self {
-            reader
+            reader,
+            record_count: 0,
         }
     }
-    pub fn next(&'a mut self) -> Option<FastqRecord<'a>> {
-        if let Some(Ok(seq)) = self.reader.next() {
-            Some(FastqRecord {
-                record: seq,
-            })
-        } else {
-            None
+    /// Read the next record.
+    ///  - `None` at the end of the file.
+    ///  - `Some(Err)` if the record is malformed or the reader fails.
+    pub fn next(&'a mut self) -> Option<Result<FastqRecord<'a>, ReadError>> {
+        let line_number = self.line_number();
+        match self.reader.next()? {
+            Ok(seq) => {
+                self.record_count += 1;
+                Some(Ok(FastqRecord {
+                    record: seq,
+                }))
+            },
+            Err(err) => {
+                let (kind, line_number) = match err {
+                    SeqIoError::Io(err) => (ReadErrorKind::Io(err), line_number),
+                    SeqIoError::UnequalLengths { seq, qual, pos } => (
+                        ReadErrorKind::MalformedRecord(format!(
+                            "sequence length ({}) and quality length ({}) are not equal", seq, qual,
+                        )),
+                        pos.line,
+                    ),
+                    SeqIoError::InvalidStart { found, pos } => (
+                        ReadErrorKind::MalformedRecord(format!(
+                            "expected '@' but found '{}'", (found as char).escape_default(),
+                        )),
+                        pos.line,
+                    ),
+                    SeqIoError::InvalidSep { found, pos } => (
+                        ReadErrorKind::MalformedRecord(format!(
+                            "expected '+' but found '{}'", (found as char).escape_default(),
+                        )),
+                        pos.line,
+                    ),
+                    SeqIoError::UnexpectedEnd { pos } => (
+                        ReadErrorKind::MalformedRecord("record is truncated".to_string()),
+                        pos.line,
+                    ),
+                    SeqIoError::BufferLimit => (
+                        ReadErrorKind::MalformedRecord("buffer limit reached".to_string()),
+                        line_number,
+                    ),
+                };
+                Some(Err(ReadError::new(kind, self.record_count + 1, line_number)))
+            },
         }
     }
+    /// Number of records read so far (i.e., the 1-based number of the last record).
+    pub fn record_number(&self) -> u64 {
+        self.record_count
+    }
+    /// 1-based line number of the last record.
+    ///  - At the end of the file, this is the line after the last record.
+    pub fn line_number(&self) -> u64 {
+        self.reader.position().line()
+    }
 }
 impl FastqReader<File> {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
         let reader = SeqIoReader::from_path(path)?;
         Ok(Self {
-            reader
+            reader,
+            record_count: 0,
         })
     }
 }
@@ -57,6 +109,13 @@ impl<'a> FastqReader<&'a [u8]> {
     }
 }
 
+impl<'a> FastqRecord<'a> {
+    /// Raw quality characters of the record.
+    pub fn qual(&self) -> &[u8] {
+        self.record.qual()
+    }
+}
+
 impl<'a> SeqRecord for FastqRecord<'a> {
     fn extend_seq_buf(&mut self, buf: &mut Vec<u8>) {
         buf.extend_from_slice(self.record.seq());
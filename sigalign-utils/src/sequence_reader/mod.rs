@@ -7,6 +7,18 @@ pub mod fastq;
 
 pub mod decompress;
 
+mod error;
+pub use error::{ReadError, ReadErrorKind};
+
+mod open;
+pub use open::{
+    open_sequence_file, open_decompressed_file, open_decompressed_reader,
+    SequenceFileReader, SequenceFormat, OpenSequenceFileError,
+};
+
+mod paired;
+pub use paired::{PairedFastqReader, ReadPair, mate_name};
+
 pub trait SeqRecord {
     fn extend_seq_buf(&mut self, buf: &mut Vec<u8>);
 }
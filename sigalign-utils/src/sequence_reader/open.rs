@@ -0,0 +1,163 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+};
+
+use thiserror::Error;
+
+use super::{
+    decompress::{Compression, get_decoder},
+    fasta::FastaReader,
+    fastq::FastqReader,
+    IdRecord,
+    SeqRecord,
+    ReadError,
+    ReadErrorKind,
+};
+
+/// Format of the sequence file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceFormat {
+    Fasta,
+    Fastq,
+}
+
+/// Reader of FASTA or FASTQ file over the detected decoder.
+pub enum SequenceFileReader {
+    Fasta(FastaReader<Box<dyn Read + Send>>),
+    Fastq(FastqReader<Box<dyn Read + Send>>),
+}
+
+/// Error for opening a sequence file.
+#[derive(Error, Debug)]
+pub enum OpenSequenceFileError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("{0:?} compressed file is not supported in this build")]
+    UnsupportedCompression(Compression),
+    #[error("Not a FASTA or FASTQ file (starts with '{}')", (*.0 as char).escape_default())]
+    UnknownFormat(u8),
+}
+
+/// Open the FASTA or FASTQ file.
+///  - The compression (gzip, bgzip, zstd, bzip2) and the format are detected from the contents,
+///    regardless of the file extension.
+///  - An empty file is regarded as a FASTA file without records.
+pub fn open_sequence_file<P: AsRef<Path>>(path: P) -> Result<SequenceFileReader, OpenSequenceFileError> {
+    let file = File::open(path)?;
+    SequenceFileReader::new(file)
+}
+
+/// Open the file with the decoder of the detected compression.
+///  - Use this when the format is already known (e.g., `FastqReader::new(open_decompressed_file(path)?)`).
+pub fn open_decompressed_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn Read + Send>, OpenSequenceFileError> {
+    let file = File::open(path)?;
+    open_decompressed_reader(file)
+}
+
+/// Same as `open_decompressed_file`, but from a reader (e.g., the standard input).
+pub fn open_decompressed_reader<R: Read + Send + 'static>(reader: R) -> Result<Box<dyn Read + Send>, OpenSequenceFileError> {
+    let (compression, compressed) = Compression::detect(reader)?;
+    if !compression.is_supported() {
+        return Err(OpenSequenceFileError::UnsupportedCompression(compression));
+    }
+    Ok(get_decoder(BufReader::new(compressed), compression)?)
+}
+
+impl SequenceFileReader {
+    /// Detect the compression and the format of the `reader`.
+    pub fn new<R: Read + Send + 'static>(reader: R) -> Result<Self, OpenSequenceFileError> {
+        let mut decompressed = BufReader::new(open_decompressed_reader(reader)?);
+        let first_byte = skip_leading_whitespaces(&mut decompressed)?;
+
+        let reader: Box<dyn Read + Send> = Box::new(decompressed);
+        match first_byte {
+            None | Some(b'>') => Ok(Self::Fasta(FastaReader::new(reader))),
+            Some(b'@') => Ok(Self::Fastq(FastqReader::new(reader))),
+            Some(other) => Err(OpenSequenceFileError::UnknownFormat(other)),
+        }
+    }
+    pub fn format(&self) -> SequenceFormat {
+        match self {
+            Self::Fasta(_) => SequenceFormat::Fasta,
+            Self::Fastq(_) => SequenceFormat::Fastq,
+        }
+    }
+    /// Clear the buffers and fill them with the next record.
+    ///  - `None` at the end of the file.
+    pub fn read_record(
+        &mut self,
+        sequence_buffer: &mut Vec<u8>,
+        id_buffer: &mut String,
+    ) -> Option<Result<(), ReadError>> {
+        self.read_record_to_buffers(sequence_buffer, None, id_buffer)
+    }
+    /// Same as `read_record`, but also fill the quality buffer.
+    ///  - The quality buffer is left empty for FASTA.
+    pub fn read_record_with_quality(
+        &mut self,
+        sequence_buffer: &mut Vec<u8>,
+        quality_buffer: &mut Vec<u8>,
+        id_buffer: &mut String,
+    ) -> Option<Result<(), ReadError>> {
+        quality_buffer.clear();
+        self.read_record_to_buffers(sequence_buffer, Some(quality_buffer), id_buffer)
+    }
+    fn read_record_to_buffers(
+        &mut self,
+        sequence_buffer: &mut Vec<u8>,
+        quality_buffer: Option<&mut Vec<u8>>,
+        id_buffer: &mut String,
+    ) -> Option<Result<(), ReadError>> {
+        sequence_buffer.clear();
+        id_buffer.clear();
+        let (id_result, record_number, line_number) = match self {
+            Self::Fasta(reader) => {
+                let mut record = match reader.next()? {
+                    Ok(v) => v,
+                    Err(err) => return Some(Err(err)),
+                };
+                record.extend_seq_buf(sequence_buffer);
+                let id_result = record.extend_id_string(id_buffer);
+                (id_result, reader.record_number(), reader.line_number())
+            },
+            Self::Fastq(reader) => {
+                let mut record = match reader.next()? {
+                    Ok(v) => v,
+                    Err(err) => return Some(Err(err)),
+                };
+                record.extend_seq_buf(sequence_buffer);
+                if let Some(quality_buffer) = quality_buffer {
+                    quality_buffer.extend_from_slice(record.qual());
+                }
+                let id_result = record.extend_id_string(id_buffer);
+                (id_result, reader.record_number(), reader.line_number())
+            },
+        };
+        Some(id_result.map_err(|err| {
+            ReadError::new(ReadErrorKind::InvalidId(err), record_number, line_number)
+        }))
+    }
+}
+
+/// Consume the leading whitespaces and return the first byte.
+fn skip_leading_whitespaces<R: BufRead>(reader: &mut R) -> io::Result<Option<u8>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(index) => {
+                let first_byte = buf[index];
+                reader.consume(index);
+                return Ok(Some(first_byte));
+            },
+            None => {
+                let length = buf.len();
+                reader.consume(length);
+            },
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::{io::Read, path::Path};
+
+use super::{
+    fastq::FastqReader,
+    open_decompressed_file,
+    IdRecord,
+    SeqRecord,
+    OpenSequenceFileError,
+    ReadError,
+    ReadErrorKind,
+};
+
+/// Buffers for the both mates of a read pair.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadPair {
+    pub first_id: String,
+    pub first_sequence: Vec<u8>,
+    pub first_quality: Vec<u8>,
+    pub second_id: String,
+    pub second_sequence: Vec<u8>,
+    pub second_quality: Vec<u8>,
+}
+
+impl ReadPair {
+    /// Name of the pair: the ID of the first mate without `/1` suffix.
+    pub fn name(&self) -> &str {
+        mate_name(&self.first_id)
+    }
+}
+
+/// The reader of paired-end FASTQ.
+///  - `Separate`: mates are in two files in the same order.
+///  - `Interleaved`: mates are alternating in one file.
+pub enum PairedFastqReader<R: Read> {
+    Separate {
+        first: FastqReader<R>,
+        second: FastqReader<R>,
+    },
+    Interleaved(FastqReader<R>),
+}
+
+impl<R: Read> PairedFastqReader<R> {
+    pub fn new(first: R, second: R) -> Self {
+        Self::Separate {
+            first: FastqReader::new(first),
+            second: FastqReader::new(second),
+        }
+    }
+    pub fn interleaved(reader: R) -> Self {
+        Self::Interleaved(FastqReader::new(reader))
+    }
+    /// Clear the buffers and fill them with the next pair.
+    ///  - `None` at the end of the file(s).
+    ///  - `Some(Err)` if one of the mates is missing or the names of mates are different.
+    pub fn read_pair(&mut self, pair: &mut ReadPair) -> Option<Result<(), ReadError>> {
+        let second_reader = match self {
+            Self::Separate { first, second } => {
+                let first_result = read_mate(first, &mut pair.first_id, &mut pair.first_sequence, &mut pair.first_quality);
+                let second_result = read_mate(second, &mut pair.second_id, &mut pair.second_sequence, &mut pair.second_quality);
+                match (first_result, second_result) {
+                    (None, None) => return None,
+                    (Some(Err(err)), _) | (_, Some(Err(err))) => return Some(Err(err)),
+                    (Some(Ok(())), None) => return Some(Err(missing_mate(second, "second file has fewer records"))),
+                    (None, Some(Ok(()))) => return Some(Err(missing_mate(first, "first file has fewer records"))),
+                    (Some(Ok(())), Some(Ok(()))) => second,
+                }
+            },
+            Self::Interleaved(reader) => {
+                if let Err(err) = read_mate(reader, &mut pair.first_id, &mut pair.first_sequence, &mut pair.first_quality)? {
+                    return Some(Err(err));
+                }
+                match read_mate(reader, &mut pair.second_id, &mut pair.second_sequence, &mut pair.second_quality) {
+                    None => return Some(Err(missing_mate(reader, "second mate of the last pair is missing"))),
+                    Some(Err(err)) => return Some(Err(err)),
+                    Some(Ok(())) => reader,
+                }
+            },
+        };
+        if mate_name(&pair.first_id) != mate_name(&pair.second_id) {
+            return Some(Err(ReadError::new(
+                ReadErrorKind::MalformedRecord(format!(
+                    "IDs of mates are different ({} and {})", pair.first_id, pair.second_id,
+                )),
+                second_reader.record_number(),
+                second_reader.line_number(),
+            )));
+        }
+        Some(Ok(()))
+    }
+}
+
+impl PairedFastqReader<Box<dyn Read + Send>> {
+    /// Open two FASTQ files. The compression is detected from the contents.
+    pub fn from_paths<P: AsRef<Path>, Q: AsRef<Path>>(
+        first: P,
+        second: Q,
+    ) -> Result<Self, OpenSequenceFileError> {
+        Ok(Self::new(
+            open_decompressed_file(first)?,
+            open_decompressed_file(second)?,
+        ))
+    }
+    /// Open the interleaved FASTQ file. The compression is detected from the contents.
+    pub fn interleaved_from_path<P: AsRef<Path>>(path: P) -> Result<Self, OpenSequenceFileError> {
+        Ok(Self::interleaved(open_decompressed_file(path)?))
+    }
+}
+
+fn read_mate<R: Read>(
+    reader: &mut FastqReader<R>,
+    id_buffer: &mut String,
+    sequence_buffer: &mut Vec<u8>,
+    quality_buffer: &mut Vec<u8>,
+) -> Option<Result<(), ReadError>> {
+    id_buffer.clear();
+    sequence_buffer.clear();
+    quality_buffer.clear();
+    let mut record = match reader.next()? {
+        Ok(v) => v,
+        Err(err) => return Some(Err(err)),
+    };
+    record.extend_seq_buf(sequence_buffer);
+    quality_buffer.extend_from_slice(record.qual());
+    let id_result = record.extend_id_string(id_buffer);
+    Some(id_result.map_err(|err| {
+        ReadError::new(
+            ReadErrorKind::InvalidId(err),
+            reader.record_number(),
+            reader.line_number(),
+        )
+    }))
+}
+
+fn missing_mate<R: Read>(reader: &FastqReader<R>, message: &str) -> ReadError {
+    ReadError::new(
+        ReadErrorKind::MalformedRecord(message.to_string()),
+        reader.record_number() + 1,
+        reader.line_number(),
+    )
+}
+
+/// Remove the `/1` or `/2` suffix of the mate ID.
+pub fn mate_name(id: &str) -> &str {
+    id.strip_suffix("/1")
+        .or_else(|| id.strip_suffix("/2"))
+        .unwrap_or(id)
+}
@@ -0,0 +1,61 @@
+// Module worker aligning the queries in batches, so that the UI is not blocked.
+//  - Build the package with `wasm-pack build --target web` (to `../pkg`).
+//  - Start it with `new Worker(new URL("./worker.js", import.meta.url), { type: "module" })`.
+//
+// Messages from the main thread:
+//  - { type: "align", referenceBytes, queries, options }: `referenceBytes` from `Reference.toBytes()`,
+//    `queries` as FASTA or FASTQ (string or Uint8Array).
+//  - { type: "cancel" }: stop after the current batch.
+// Messages to the main thread:
+//  - { type: "progress", numQueries, results }, then { type: "done", numQueries } or { type: "error", message }.
+import init, { Aligner, QueryReader, Reference } from "../pkg/sigalign_wasm.js";
+
+const BATCH_SIZE = 256;
+const ready = init();
+let cancelled = false;
+
+self.onmessage = async (event) => {
+    const message = event.data;
+    if (message.type === "cancel") {
+        cancelled = true;
+        return;
+    }
+    if (message.type !== "align") {
+        return;
+    }
+    await ready;
+    cancelled = false;
+
+    let reference, aligner, reader;
+    try {
+        const { referenceBytes, queries, options } = message;
+        reference = Reference.fromBytes(referenceBytes);
+        aligner = new Aligner(
+            options.mismatchPenalty ?? 4,
+            options.gapOpenPenalty ?? 6,
+            options.gapExtendPenalty ?? 2,
+            options.minimumLength,
+            options.maximumPenaltyPerLength,
+            options.algorithm,
+        );
+        reader = new QueryReader(queries);
+        const withReverseComplement = options.withReverseComplement ?? reference.hasComplementaryStrand;
+        while (!cancelled) {
+            const results = aligner.alignBatch(reader, reference, BATCH_SIZE, withReverseComplement);
+            if (results.length === 0) {
+                break;
+            }
+            self.postMessage({ type: "progress", numQueries: reader.numQueries, results });
+            // Yield to receive the "cancel" message between the batches
+            await new Promise((resolve) => setTimeout(resolve, 0));
+        }
+        self.postMessage({ type: "done", numQueries: reader.numQueries, cancelled });
+    } catch (error) {
+        self.postMessage({ type: "error", message: String(error) });
+    } finally {
+        // The WASM memory is not garbage-collected
+        reader?.free();
+        aligner?.free();
+        reference?.free();
+    }
+};
@@ -0,0 +1,262 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use sigalign::{
+    algorithms::{
+        Local, LocalWithChunk, LocalWithLimit, SemiGlobal, SemiGlobalWithChunk, SemiGlobalWithLimit,
+    },
+    results::QueryAlignment,
+    Aligner as SigAligner,
+    Reference as SigReference,
+};
+
+use crate::{
+    reference::Reference,
+    query_reader::QueryReader,
+    results::{new_object, query_alignment_to_js},
+    sequence_from_js, to_js_error,
+    AlignerOptions, QueryAlignmentObject, ReadAlignmentArray, SequenceInput,
+};
+
+#[derive(Clone)]
+enum AlignerWrapper {
+    Local(SigAligner<Local>),
+    LocalWithLimit(SigAligner<LocalWithLimit>),
+    LocalWithChunk(SigAligner<LocalWithChunk>),
+    SemiGlobal(SigAligner<SemiGlobal>),
+    SemiGlobalWithLimit(SigAligner<SemiGlobalWithLimit>),
+    SemiGlobalWithChunk(SigAligner<SemiGlobalWithChunk>),
+}
+
+// Apply the same expression to the aligner of any algorithm
+macro_rules! dispatch {
+    ($wrapper:expr, $aligner:ident => $body:expr) => {
+        match $wrapper {
+            AlignerWrapper::Local($aligner) => $body,
+            AlignerWrapper::LocalWithLimit($aligner) => $body,
+            AlignerWrapper::LocalWithChunk($aligner) => $body,
+            AlignerWrapper::SemiGlobal($aligner) => $body,
+            AlignerWrapper::SemiGlobalWithLimit($aligner) => $body,
+            AlignerWrapper::SemiGlobalWithChunk($aligner) => $body,
+        }
+    };
+}
+
+/// Aligner with the penalties, the cutoffs, and the algorithm selected by the options.
+#[wasm_bindgen]
+pub struct Aligner {
+    inner: AlignerWrapper,
+    limit: Option<u32>,
+    chunk: Option<(u32, u32)>,
+}
+
+#[wasm_bindgen]
+impl Aligner {
+    /// - `options.semiGlobal`: semi-global alignment, instead of local alignment.
+    /// - `options.limit`: stop after this number of alignments per query.
+    /// - `options.chunk`: align the query in chunks of [size, step].
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        mismatch_penalty: u32,
+        gap_open_penalty: u32,
+        gap_extend_penalty: u32,
+        minimum_length: u32,
+        maximum_penalty_per_length: f32,
+        options: Option<AlignerOptions>,
+    ) -> Result<Aligner, JsError> {
+        let (px, po, pe) = (mismatch_penalty, gap_open_penalty, gap_extend_penalty);
+        let (minl, maxp) = (minimum_length, maximum_penalty_per_length);
+        let options = ParsedOptions::from_js(options.as_ref())?;
+
+        let inner = match (options.semi_global, options.limit, options.chunk) {
+            (false, None, None) => AlignerWrapper::Local(
+                SigAligner::new(Local::new(px, po, pe, minl, maxp).map_err(to_js_error)?)
+            ),
+            (false, Some(limit), None) => AlignerWrapper::LocalWithLimit(
+                SigAligner::new(LocalWithLimit::new(px, po, pe, minl, maxp, limit).map_err(to_js_error)?)
+            ),
+            (false, None, Some((size, step))) => AlignerWrapper::LocalWithChunk(
+                SigAligner::new(LocalWithChunk::new(px, po, pe, minl, maxp, size, step).map_err(to_js_error)?)
+            ),
+            (true, None, None) => AlignerWrapper::SemiGlobal(
+                SigAligner::new(SemiGlobal::new(px, po, pe, minl, maxp).map_err(to_js_error)?)
+            ),
+            (true, Some(limit), None) => AlignerWrapper::SemiGlobalWithLimit(
+                SigAligner::new(SemiGlobalWithLimit::new(px, po, pe, minl, maxp, limit).map_err(to_js_error)?)
+            ),
+            (true, None, Some((size, step))) => AlignerWrapper::SemiGlobalWithChunk(
+                SigAligner::new(SemiGlobalWithChunk::new(px, po, pe, minl, maxp, size, step).map_err(to_js_error)?)
+            ),
+            (_, Some(_), Some(_)) => return Err(JsError::new("'limit' and 'chunk' cannot be used together")),
+        };
+        Ok(Self {
+            inner,
+            limit: options.limit,
+            chunk: options.chunk,
+        })
+    }
+
+    /* Getters */
+    #[wasm_bindgen(getter)]
+    pub fn px(&self) -> u32 {
+        dispatch!(&self.inner, v => v.get_mismatch_penalty())
+    }
+    #[wasm_bindgen(getter)]
+    pub fn po(&self) -> u32 {
+        dispatch!(&self.inner, v => v.get_gap_open_penalty())
+    }
+    #[wasm_bindgen(getter)]
+    pub fn pe(&self) -> u32 {
+        dispatch!(&self.inner, v => v.get_gap_extend_penalty())
+    }
+    #[wasm_bindgen(getter)]
+    pub fn minl(&self) -> u32 {
+        dispatch!(&self.inner, v => v.get_minimum_length())
+    }
+    #[wasm_bindgen(getter)]
+    pub fn maxp(&self) -> f32 {
+        dispatch!(&self.inner, v => v.get_maximum_penalty_per_length())
+    }
+    #[wasm_bindgen(getter, js_name = patternSize)]
+    pub fn pattern_size(&self) -> u32 {
+        dispatch!(&self.inner, v => v.get_pattern_size())
+    }
+    /// Name of the algorithm (e.g., "LocalWithLimit").
+    #[wasm_bindgen(getter)]
+    pub fn algorithm(&self) -> String {
+        match &self.inner {
+            AlignerWrapper::Local(_) => "Local",
+            AlignerWrapper::LocalWithLimit(_) => "LocalWithLimit",
+            AlignerWrapper::LocalWithChunk(_) => "LocalWithChunk",
+            AlignerWrapper::SemiGlobal(_) => "SemiGlobal",
+            AlignerWrapper::SemiGlobalWithLimit(_) => "SemiGlobalWithLimit",
+            AlignerWrapper::SemiGlobalWithChunk(_) => "SemiGlobalWithChunk",
+        }.to_string()
+    }
+    #[wasm_bindgen(getter)]
+    pub fn limit(&self) -> Option<u32> {
+        self.limit
+    }
+    /// [size, step] of the chunk, `undefined` if not chunked.
+    #[wasm_bindgen(getter)]
+    pub fn chunk(&self) -> Option<Vec<u32>> {
+        self.chunk.map(|(size, step)| vec![size, step])
+    }
+
+    /* Alignments */
+    /// Align the query to all targets of the reference.
+    pub fn align(
+        &mut self,
+        query: &SequenceInput,
+        reference: &Reference,
+    ) -> Result<QueryAlignmentObject, JsError> {
+        let query = sequence_from_js(query)?;
+        let query_alignment = self.align_bytes(&query, reference.as_ref());
+        Ok(query_alignment_to_js(&query_alignment, reference.as_ref()).unchecked_into())
+    }
+    /// Align the query to the targets of the indices only.
+    #[wasm_bindgen(js_name = alignToTargets)]
+    pub fn align_to_targets(
+        &mut self,
+        query: &SequenceInput,
+        reference: &Reference,
+        target_indices: &[u32],
+    ) -> Result<QueryAlignmentObject, JsError> {
+        let query = sequence_from_js(query)?;
+        let query_alignment = dispatch!(
+            &mut self.inner,
+            v => v.align_to_targets(&query, reference.as_ref(), target_indices)
+        );
+        Ok(query_alignment_to_js(&query_alignment, reference.as_ref()).unchecked_into())
+    }
+    /// Align the next queries of the reader, up to `maxQueries`.
+    ///  - Returns an empty array at the end of the reader.
+    ///  - With `withReverseComplement`, the reverse complements are also aligned.
+    ///  - Call repeatedly in a Web Worker to report the progress between the batches.
+    #[wasm_bindgen(js_name = alignBatch)]
+    pub fn align_batch(
+        &mut self,
+        reader: &mut QueryReader,
+        reference: &Reference,
+        max_queries: u32,
+        with_reverse_complement: Option<bool>,
+    ) -> Result<ReadAlignmentArray, JsError> {
+        let reference = reference.as_ref();
+        let with_reverse_complement = with_reverse_complement.unwrap_or(false);
+        if with_reverse_complement && !reference.has_complementary_strand() {
+            return Err(JsError::new("The reference has no complementary strand"));
+        }
+
+        let read_alignments = Array::new();
+        for _ in 0..max_queries {
+            let Some((id, sequence)) = reader.read_next()? else {
+                break;
+            };
+            let forward = self.align_bytes(sequence, reference);
+            let read_alignment = new_object(&[
+                ("id", id.into()),
+                ("forward", query_alignment_to_js(&forward, reference).into()),
+            ]);
+            if with_reverse_complement {
+                let reverse_complement = reference.reverse_complement(sequence).map_err(to_js_error)?;
+                let reverse = self.align_bytes(&reverse_complement, reference);
+                set_reverse(&read_alignment, query_alignment_to_js(&reverse, reference));
+            }
+            read_alignments.push(&read_alignment);
+        }
+        Ok(read_alignments.unchecked_into())
+    }
+}
+
+impl Aligner {
+    fn align_bytes(&mut self, query: &[u8], reference: &SigReference) -> QueryAlignment {
+        dispatch!(&mut self.inner, v => v.align(query, reference))
+    }
+}
+
+fn set_reverse(read_alignment: &Object, reverse: Array) {
+    Reflect::set(read_alignment, &JsValue::from_str("reverse"), &reverse).unwrap_throw();
+}
+
+struct ParsedOptions {
+    semi_global: bool,
+    limit: Option<u32>,
+    chunk: Option<(u32, u32)>,
+}
+
+impl ParsedOptions {
+    fn from_js(options: Option<&AlignerOptions>) -> Result<Self, JsError> {
+        let mut parsed = Self { semi_global: false, limit: None, chunk: None };
+        let Some(options) = options.filter(|x| !x.is_undefined() && !x.is_null()) else {
+            return Ok(parsed);
+        };
+        if let Some(value) = get_field(options, "semiGlobal")? {
+            parsed.semi_global = value.as_bool().ok_or_else(|| JsError::new("'semiGlobal' must be a boolean"))?;
+        }
+        if let Some(value) = get_field(options, "limit")? {
+            parsed.limit = Some(to_u32(&value, "limit")?);
+        }
+        if let Some(value) = get_field(options, "chunk")? {
+            let values: Vec<JsValue> = value.dyn_ref::<Array>()
+                .filter(|x| x.length() == 2)
+                .ok_or_else(|| JsError::new("'chunk' must be an array of [size, step]"))?
+                .to_vec();
+            parsed.chunk = Some((to_u32(&values[0], "chunk")?, to_u32(&values[1], "chunk")?));
+        }
+        Ok(parsed)
+    }
+}
+
+// `None` if the field is undefined
+fn get_field(object: &JsValue, key: &str) -> Result<Option<JsValue>, JsError> {
+    let value = Reflect::get(object, &JsValue::from_str(key))
+        .map_err(|_| JsError::new("Options must be an object"))?;
+    Ok(if value.is_undefined() { None } else { Some(value) })
+}
+
+fn to_u32(value: &JsValue, key: &str) -> Result<u32, JsError> {
+    match value.as_f64() {
+        Some(v) if v >= 0.0 && v <= u32::MAX as f64 && v.fract() == 0.0 => Ok(v as u32),
+        _ => Err(JsError::new(&format!("'{}' must be a non-negative integer", key))),
+    }
+}
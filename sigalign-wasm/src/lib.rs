@@ -0,0 +1,105 @@
+/*!
+WebAssembly binding of SigAlign.
+
+- `ReferenceBuilder` and `Reference`: build the reference from FASTA, and save or load it as `Uint8Array`.
+- `Aligner`: align the queries with any algorithm of SigAlign.
+- `QueryReader`: read the FASTA or FASTQ queries to be aligned in batches.
+
+The results are returned as plain JS objects (see the TypeScript declarations below).
+
+No API depends on the main thread (e.g., `window`), so that the module can be run in a Web Worker.
+The reference can be sent to the worker as the bytes of `Reference.toBytes()`,
+and the queries can be aligned batch by batch with `Aligner.alignBatch`
+to report the progress or to be cancelled between the batches.
+*/
+use js_sys::Uint8Array;
+use wasm_bindgen::prelude::*;
+
+mod reference;
+pub use reference::{Reference, ReferenceBuilder};
+mod aligner;
+pub use aligner::Aligner;
+mod query_reader;
+pub use query_reader::QueryReader;
+mod results;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+/** Sequence as the string or the bytes of the residues. */
+export type SequenceInput = string | Uint8Array;
+
+export interface AlignerOptions {
+    /** Semi-global alignment, instead of local alignment (default: false). */
+    semiGlobal?: boolean;
+    /** Stop after this number of alignments per query. */
+    limit?: number;
+    /** Align the query in chunks of [size, step]. Cannot be used with `limit`. */
+    chunk?: [number, number];
+}
+
+export interface Alignment {
+    penalty: number;
+    length: number;
+    /** [start, end) on the query. */
+    queryPosition: [number, number];
+    /** [start, end) on the target. */
+    targetPosition: [number, number];
+    /** Extended CIGAR (`=`, `X`, `I`, `D`). */
+    cigar: string;
+}
+
+export interface TargetAlignment {
+    index: number;
+    label: string;
+    alignments: Alignment[];
+}
+
+export interface ReadAlignment {
+    id: string;
+    /** Results of the query as is. */
+    forward: TargetAlignment[];
+    /** Results of the reverse complement, if aligned. */
+    reverse?: TargetAlignment[];
+}
+
+export interface ReferenceSummary {
+    numTargets: number;
+    totalLength: number;
+    alphabet?: string;
+    hasComplementaryStrand: boolean;
+    hasTargetMetadata: boolean;
+    numSoftMaskedBases?: number;
+    patternIndexType: string;
+    bitsPerCharacter: number;
+    estimatedSizeInBytes: number;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "SequenceInput")]
+    pub type SequenceInput;
+    #[wasm_bindgen(typescript_type = "AlignerOptions")]
+    pub type AlignerOptions;
+    #[wasm_bindgen(typescript_type = "TargetAlignment[]")]
+    pub type QueryAlignmentObject;
+    #[wasm_bindgen(typescript_type = "ReadAlignment[]")]
+    pub type ReadAlignmentArray;
+    #[wasm_bindgen(typescript_type = "ReferenceSummary")]
+    pub type ReferenceSummaryObject;
+}
+
+/// Bytes of the sequence given as the string or `Uint8Array`.
+fn sequence_from_js(value: &JsValue) -> Result<Vec<u8>, JsError> {
+    if let Some(string) = value.as_string() {
+        Ok(string.into_bytes())
+    } else if let Some(bytes) = value.dyn_ref::<Uint8Array>() {
+        Ok(bytes.to_vec())
+    } else {
+        Err(JsError::new("Sequence must be a string or Uint8Array"))
+    }
+}
+
+fn to_js_error<E: std::fmt::Display>(err: E) -> JsError {
+    JsError::new(&err.to_string())
+}
@@ -0,0 +1,51 @@
+use std::io::Cursor;
+
+use wasm_bindgen::prelude::*;
+
+use sigalign_utils::sequence_reader::SequenceFileReader;
+
+use crate::{sequence_from_js, to_js_error, SequenceInput};
+
+/// Reader of the FASTA or FASTQ queries, to be aligned by `Aligner.alignBatch`.
+///  - The format and the compression (gzip) are detected from the contents.
+#[wasm_bindgen]
+pub struct QueryReader {
+    inner: SequenceFileReader,
+    sequence_buffer: Vec<u8>,
+    id_buffer: String,
+    num_queries: u32,
+}
+
+#[wasm_bindgen]
+impl QueryReader {
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &SequenceInput) -> Result<QueryReader, JsError> {
+        let data = sequence_from_js(data)?;
+        let inner = SequenceFileReader::new(Cursor::new(data)).map_err(to_js_error)?;
+        Ok(Self {
+            inner,
+            sequence_buffer: Vec::new(),
+            id_buffer: String::new(),
+            num_queries: 0,
+        })
+    }
+    /// Number of the queries read so far.
+    #[wasm_bindgen(getter, js_name = numQueries)]
+    pub fn num_queries(&self) -> u32 {
+        self.num_queries
+    }
+}
+
+impl QueryReader {
+    /// (id, sequence) of the next query, `None` at the end.
+    pub(crate) fn read_next(&mut self) -> Result<Option<(&str, &[u8])>, JsError> {
+        match self.inner.read_record(&mut self.sequence_buffer, &mut self.id_buffer) {
+            Some(result) => {
+                result.map_err(to_js_error)?;
+                self.num_queries += 1;
+                Ok(Some((&self.id_buffer, &self.sequence_buffer)))
+            },
+            None => Ok(None),
+        }
+    }
+}
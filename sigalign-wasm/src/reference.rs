@@ -0,0 +1,176 @@
+use wasm_bindgen::prelude::*;
+
+use sigalign::{
+    Alphabet,
+    Reference as SigReference,
+    ReferenceBuilder as SigReferenceBuilder,
+};
+
+use crate::{
+    results::new_object,
+    sequence_from_js, to_js_error, ReferenceSummaryObject, SequenceInput,
+};
+
+/// Builder for `Reference` with the same options as the Rust `ReferenceBuilder`.
+///  - Methods return the builder itself, so that they can be chained.
+///  - The builder is consumed by each method; use the returned one.
+#[wasm_bindgen]
+pub struct ReferenceBuilder {
+    inner: SigReferenceBuilder,
+}
+
+#[wasm_bindgen]
+impl ReferenceBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            inner: SigReferenceBuilder::new(),
+        }
+    }
+
+    /* Configuration */
+    /// Set all letters to uppercase when building (default: true).
+    #[wasm_bindgen(js_name = setUppercase)]
+    pub fn set_uppercase(self, uppercase: bool) -> Self {
+        Self { inner: self.inner.set_uppercase(uppercase) }
+    }
+    /// Set the bases that never match to any other bases (e.g., "N").
+    #[wasm_bindgen(js_name = ignoreBases)]
+    pub fn ignore_bases(self, bases: &str) -> Self {
+        Self { inner: self.inner.ignore_bases(bases.as_bytes()) }
+    }
+    /// Set the alphabet of the sequences: 'dna', 'dna_iupac', 'rna', or 'protein'.
+    #[wasm_bindgen(js_name = setAlphabet)]
+    pub fn set_alphabet(self, alphabet: &str) -> Result<ReferenceBuilder, JsError> {
+        let alphabet = Alphabet::from_name(alphabet).ok_or_else(|| JsError::new(
+            "The alphabet must be one of 'dna', 'dna_iupac', 'rna', or 'protein'",
+        ))?;
+        Ok(Self { inner: self.inner.set_alphabet(alphabet) })
+    }
+    /// Fail to build if any residue is not in the alphabet, instead of masking it.
+    #[wasm_bindgen(js_name = setStrictAlphabet)]
+    pub fn set_strict_alphabet(self, strict: bool) -> Self {
+        Self { inner: self.inner.set_strict_alphabet(strict) }
+    }
+    /// Treat lowercase regions as soft-masked (excluded from the seeding).
+    #[wasm_bindgen(js_name = setSoftMasking)]
+    pub fn set_soft_masking(self, soft_masking: bool) -> Self {
+        Self { inner: self.inner.set_soft_masking(soft_masking) }
+    }
+    /// Keep the FASTA descriptions as the metadata of targets.
+    #[wasm_bindgen(js_name = setKeepMetadata)]
+    pub fn set_keep_metadata(self, keep_metadata: bool) -> Self {
+        Self { inner: self.inner.set_keep_metadata(keep_metadata) }
+    }
+
+    /* Add Sequences */
+    /// Add a target.
+    #[wasm_bindgen(js_name = addTarget)]
+    pub fn add_target(self, label: &str, sequence: &SequenceInput) -> Result<ReferenceBuilder, JsError> {
+        let sequence = sequence_from_js(sequence)?;
+        Ok(Self { inner: self.inner.add_target(label, &sequence) })
+    }
+    /// Add the targets in FASTA (the string, or the bytes that can be compressed).
+    #[wasm_bindgen(js_name = addFasta)]
+    pub fn add_fasta(self, fasta: &SequenceInput) -> Result<ReferenceBuilder, JsError> {
+        let fasta = sequence_from_js(fasta)?;
+        let inner = self.inner.add_fasta(&fasta[..]).map_err(to_js_error)?;
+        Ok(Self { inner })
+    }
+
+    /* Build */
+    pub fn build(self) -> Result<Reference, JsError> {
+        let inner = self.inner.build().map_err(to_js_error)?;
+        Ok(Reference { inner })
+    }
+}
+
+impl Default for ReferenceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Reference {
+    inner: SigReference,
+}
+
+#[wasm_bindgen]
+impl Reference {
+    /// Build the reference from FASTA with the default options.
+    #[wasm_bindgen(js_name = fromFasta)]
+    pub fn from_fasta(fasta: &SequenceInput) -> Result<Reference, JsError> {
+        ReferenceBuilder::new().add_fasta(fasta)?.build()
+    }
+    /// Load the reference saved by `toBytes` (or by the other bindings).
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Reference, JsError> {
+        let inner = SigReference::load_from(bytes).map_err(to_js_error)?;
+        Ok(Self { inner })
+    }
+    /// Save the reference to the bytes (e.g., to be sent to the Web Worker or stored in IndexedDB).
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, JsError> {
+        let mut bytes = Vec::with_capacity(self.inner.get_estimated_size_in_bytes());
+        self.inner.save_to(&mut bytes).map_err(to_js_error)?;
+        Ok(bytes)
+    }
+
+    #[wasm_bindgen(getter, js_name = numTargets)]
+    pub fn num_targets(&self) -> u32 {
+        self.inner.get_num_targets()
+    }
+    #[wasm_bindgen(getter, js_name = totalLength)]
+    pub fn total_length(&self) -> u32 {
+        self.inner.get_total_length()
+    }
+    /// Name of the alphabet, `undefined` if not set.
+    #[wasm_bindgen(getter)]
+    pub fn alphabet(&self) -> Option<String> {
+        self.inner.get_alphabet().map(|alphabet| alphabet.as_str().to_string())
+    }
+    #[wasm_bindgen(getter, js_name = hasComplementaryStrand)]
+    pub fn has_complementary_strand(&self) -> bool {
+        self.inner.has_complementary_strand()
+    }
+    #[wasm_bindgen(js_name = getLabel)]
+    pub fn get_label(&self, target_index: u32) -> Option<String> {
+        self.inner.get_label(target_index)
+    }
+    #[wasm_bindgen(js_name = getSequence)]
+    pub fn get_sequence(&self, target_index: u32) -> Option<Vec<u8>> {
+        self.inner.get_sequence(target_index)
+    }
+    #[wasm_bindgen(js_name = getSequenceLength)]
+    pub fn get_sequence_length(&self, target_index: u32) -> Option<u32> {
+        self.inner.get_sequence_length(target_index)
+    }
+    /// Reverse complement of the sequence by the alphabet of the reference.
+    #[wasm_bindgen(js_name = reverseComplement)]
+    pub fn reverse_complement(&self, sequence: &SequenceInput) -> Result<Vec<u8>, JsError> {
+        let sequence = sequence_from_js(sequence)?;
+        self.inner.reverse_complement(&sequence).map_err(to_js_error)
+    }
+    #[wasm_bindgen(js_name = getSummary)]
+    pub fn get_summary(&self) -> ReferenceSummaryObject {
+        let summary = self.inner.get_summary();
+        new_object(&[
+            ("numTargets", summary.num_targets.into()),
+            ("totalLength", summary.total_length.into()),
+            ("alphabet", summary.alphabet.map(|x| x.as_str()).into()),
+            ("hasComplementaryStrand", self.inner.has_complementary_strand().into()),
+            ("hasTargetMetadata", summary.has_target_metadata.into()),
+            ("numSoftMaskedBases", summary.num_soft_masked_bases.map(|x| x as f64).into()),
+            ("patternIndexType", summary.pattern_index_type.into()),
+            ("bitsPerCharacter", summary.bits_per_character.into()),
+            ("estimatedSizeInBytes", (summary.estimated_size_in_bytes as f64).into()),
+        ]).unchecked_into()
+    }
+}
+
+impl AsRef<SigReference> for Reference {
+    fn as_ref(&self) -> &SigReference {
+        &self.inner
+    }
+}
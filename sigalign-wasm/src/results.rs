@@ -0,0 +1,50 @@
+//! Conversion of the results to the plain JS objects.
+//!  - The keys are in camelCase, and do not depend on the `short_key` feature of `sigalign`.
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use sigalign::{
+    results::{Alignment, QueryAlignment, TargetAlignment},
+    Reference,
+};
+
+pub(crate) fn new_object(fields: &[(&str, JsValue)]) -> Object {
+    let object = Object::new();
+    for (key, value) in fields {
+        // Never fails for the plain object
+        Reflect::set(&object, &JsValue::from_str(key), value).unwrap_throw();
+    }
+    object
+}
+
+/// Array of `TargetAlignment` objects.
+pub(crate) fn query_alignment_to_js(query_alignment: &QueryAlignment, reference: &Reference) -> Array {
+    query_alignment.0.iter()
+        .map(|target_alignment| JsValue::from(target_alignment_to_js(target_alignment, reference)))
+        .collect()
+}
+
+fn target_alignment_to_js(target_alignment: &TargetAlignment, reference: &Reference) -> Object {
+    let alignments: Array = target_alignment.alignments.iter()
+        .map(|alignment| JsValue::from(alignment_to_js(alignment)))
+        .collect();
+    new_object(&[
+        ("index", target_alignment.index.into()),
+        ("label", reference.get_label_str(target_alignment.index).unwrap_or_default().into()),
+        ("alignments", alignments.into()),
+    ])
+}
+
+fn alignment_to_js(alignment: &Alignment) -> Object {
+    new_object(&[
+        ("penalty", alignment.penalty.into()),
+        ("length", alignment.length.into()),
+        ("queryPosition", position_to_js(alignment.position.query).into()),
+        ("targetPosition", position_to_js(alignment.position.target).into()),
+        ("cigar", alignment.to_cigar().into()),
+    ])
+}
+
+fn position_to_js((start, end): (u32, u32)) -> Array {
+    Array::of2(&start.into(), &end.into())
+}
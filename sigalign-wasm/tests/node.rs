@@ -0,0 +1,191 @@
+//! Run with `wasm-pack test --node`.
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use sigalign_wasm::{Aligner, AlignerOptions, QueryReader, Reference, ReferenceBuilder, SequenceInput};
+
+fn random_sequence(length: usize, seed: &mut u64) -> String {
+    (0..length).map(|_| {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ['A', 'C', 'G', 'T'][(*seed >> 33) as usize % 4]
+    }).collect()
+}
+fn reverse_complement(sequence: &str) -> String {
+    sequence.chars().rev().map(|x| match x {
+        'A' => 'T', 'C' => 'G', 'G' => 'C', _ => 'A',
+    }).collect()
+}
+
+fn input(sequence: &str) -> SequenceInput {
+    JsValue::from_str(sequence).unchecked_into()
+}
+fn get(object: &JsValue, key: &str) -> JsValue {
+    Reflect::get(object, &JsValue::from_str(key)).unwrap()
+}
+fn options(fields: &[(&str, JsValue)]) -> Option<AlignerOptions> {
+    let object = Object::new();
+    for (key, value) in fields {
+        Reflect::set(&object, &JsValue::from_str(key), value).unwrap();
+    }
+    Some(object.unchecked_into())
+}
+
+// Three targets of 1000 bp and a query from the second one with a mismatch
+fn test_data() -> (String, String) {
+    let mut seed = 11;
+    let targets: Vec<String> = (0..3).map(|_| random_sequence(1000, &mut seed)).collect();
+    let fasta: String = targets.iter().enumerate()
+        .map(|(index, target)| format!(">target_{} description\n{}\n", index, target))
+        .collect();
+    let mut query = targets[1][200..400].to_string();
+    let replaced = if &query[100..101] == "A" { "C" } else { "A" };
+    query.replace_range(100..101, replaced);
+    (fasta, query)
+}
+fn default_aligner(options: Option<AlignerOptions>) -> Aligner {
+    Aligner::new(4, 6, 2, 50, 0.1, options).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn align_query_to_reference() {
+    let (fasta, query) = test_data();
+    let reference = Reference::from_fasta(&input(&fasta)).unwrap();
+    assert_eq!(reference.num_targets(), 3);
+    assert_eq!(reference.total_length(), 3000);
+    assert_eq!(reference.get_label(2).as_deref(), Some("target_2"));
+
+    let mut aligner = default_aligner(None);
+    let result: JsValue = aligner.align(&input(&query), &reference).unwrap().into();
+    let result: Array = result.unchecked_into();
+    assert_eq!(result.length(), 1);
+    let target_alignment = result.get(0);
+    assert_eq!(get(&target_alignment, "index"), 1);
+    assert_eq!(get(&target_alignment, "label"), "target_1");
+    let alignment = Array::from(&get(&target_alignment, "alignments")).get(0);
+    assert_eq!(get(&alignment, "penalty"), 4);
+    assert_eq!(get(&alignment, "length"), 200);
+    assert_eq!(Array::from(&get(&alignment, "targetPosition")).to_vec(), vec![JsValue::from(200), JsValue::from(400)]);
+    assert_eq!(get(&alignment, "cigar"), "100=1X99=");
+
+    // Bytes are the same as the string
+    let query_bytes = js_sys::Uint8Array::from(query.as_bytes());
+    let from_bytes: JsValue = aligner.align(query_bytes.unchecked_ref(), &reference).unwrap().into();
+    assert_eq!(js_sys::JSON::stringify(&from_bytes).unwrap(), js_sys::JSON::stringify(&result).unwrap());
+
+    // Other targets only
+    let result: JsValue = aligner.align_to_targets(&input(&query), &reference, &[0, 2]).unwrap().into();
+    assert_eq!(Array::from(&result).length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn save_and_load_reference() {
+    let (fasta, query) = test_data();
+    let reference = ReferenceBuilder::new()
+        .set_keep_metadata(true)
+        .add_fasta(&input(&fasta)).unwrap()
+        .add_target("extra", &input("ACGTACGTACGT")).unwrap()
+        .build().unwrap();
+    let bytes = reference.to_bytes().unwrap();
+    let loaded = Reference::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.to_bytes().unwrap(), bytes);
+    assert_eq!(loaded.num_targets(), 4);
+    assert_eq!(loaded.get_sequence(3).unwrap(), b"ACGTACGTACGT");
+
+    let summary: JsValue = loaded.get_summary().into();
+    assert_eq!(get(&summary, "numTargets"), 4);
+    assert_eq!(get(&summary, "hasTargetMetadata"), true);
+
+    let mut aligner = default_aligner(None);
+    let original: JsValue = aligner.align(&input(&query), &reference).unwrap().into();
+    let loaded: JsValue = aligner.align(&input(&query), &loaded).unwrap().into();
+    assert_eq!(js_sys::JSON::stringify(&original).unwrap(), js_sys::JSON::stringify(&loaded).unwrap());
+
+    assert!(Reference::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn aligner_of_each_algorithm() {
+    let (fasta, query) = test_data();
+    let reference = Reference::from_fasta(&input(&fasta)).unwrap();
+    let chunk = || JsValue::from(Array::of2(&100.into(), &50.into()));
+
+    let cases = [
+        (vec![], "Local"),
+        (vec![("limit", JsValue::from(1))], "LocalWithLimit"),
+        (vec![("chunk", chunk())], "LocalWithChunk"),
+        (vec![("semiGlobal", JsValue::TRUE)], "SemiGlobal"),
+        (vec![("semiGlobal", JsValue::TRUE), ("limit", JsValue::from(1))], "SemiGlobalWithLimit"),
+        (vec![("semiGlobal", JsValue::TRUE), ("chunk", chunk())], "SemiGlobalWithChunk"),
+    ];
+    for (fields, algorithm) in cases {
+        let mut aligner = default_aligner(options(&fields));
+        assert_eq!(aligner.algorithm(), algorithm);
+        let result: JsValue = aligner.align(&input(&query), &reference).unwrap().into();
+        assert!(Array::from(&result).length() >= 1, "{}", algorithm);
+    }
+    assert_eq!(default_aligner(options(&[("chunk", chunk())])).chunk(), Some(vec![100, 50]));
+
+    // Invalid
+    assert!(Aligner::new(0, 6, 2, 50, 0.1, None).is_err());
+    assert!(Aligner::new(4, 6, 2, 50, 0.1, options(&[("limit", 1.into()), ("chunk", chunk())])).is_err());
+    assert!(Aligner::new(4, 6, 2, 50, 0.1, options(&[("limit", JsValue::from(-1))])).is_err());
+}
+
+#[wasm_bindgen_test]
+fn align_queries_in_batches() {
+    let (fasta, query) = test_data();
+    let reference = Reference::from_fasta(&input(&fasta)).unwrap();
+    let quality = "I".repeat(query.len());
+    let fastq: String = (0..5).map(|index| {
+        let sequence = if index % 2 == 0 { query.clone() } else { reverse_complement(&query) };
+        format!("@read_{}\n{}\n+\n{}\n", index, sequence, quality)
+    }).collect();
+
+    let mut aligner = default_aligner(None);
+    let mut reader = QueryReader::new(&input(&fastq)).unwrap();
+    let mut batch_sizes = Vec::new();
+    loop {
+        let batch: JsValue = aligner.align_batch(&mut reader, &reference, 2, Some(true)).unwrap().into();
+        let batch = Array::from(&batch);
+        batch_sizes.push(batch.length());
+        if batch.length() == 0 {
+            break;
+        }
+        for read_alignment in batch.iter() {
+            let id = get(&read_alignment, "id").as_string().unwrap();
+            let is_reverse = id.ends_with('1') || id.ends_with('3');
+            let (aligned, unaligned) = if is_reverse { ("reverse", "forward") } else { ("forward", "reverse") };
+            assert_eq!(Array::from(&get(&read_alignment, aligned)).length(), 1);
+            assert_eq!(Array::from(&get(&read_alignment, unaligned)).length(), 0);
+        }
+    }
+    assert_eq!(batch_sizes, vec![2, 2, 1, 0]);
+    assert_eq!(reader.num_queries(), 5);
+
+    // Without the reverse complement
+    let mut reader = QueryReader::new(&input(&format!(">q\n{}\n", query))).unwrap();
+    let batch: JsValue = aligner.align_batch(&mut reader, &reference, 10, None).unwrap().into();
+    assert!(get(&Array::from(&batch).get(0), "reverse").is_undefined());
+
+    assert!(QueryReader::new(&input("not a sequence")).is_err());
+}
+
+#[wasm_bindgen_test]
+fn protein_reference_has_no_reverse_complement() {
+    let reference = ReferenceBuilder::new()
+        .set_alphabet("protein").unwrap()
+        .add_target("p", &input("MKVLAAGIVGLLLAAQPAMAQVQLVESGGGLVQPGGSLRLSCAAS")).unwrap()
+        .build().unwrap();
+    assert_eq!(reference.alphabet().as_deref(), Some("PROTEIN"));
+    assert!(!reference.has_complementary_strand());
+    assert!(reference.reverse_complement(&input("MKV")).is_err());
+
+    let mut aligner = Aligner::new(4, 6, 2, 20, 0.1, None).unwrap();
+    let mut reader = QueryReader::new(&input(">q\nMKVLAAGIVGLLLAAQPAMAQVQLV\n")).unwrap();
+    assert!(aligner.align_batch(&mut reader, &reference, 1, Some(true)).is_err());
+    let batch: JsValue = aligner.align_batch(&mut reader, &reference, 1, Some(false)).unwrap().into();
+    assert_eq!(Array::from(&get(&Array::from(&batch).get(0), "forward")).length(), 1);
+
+    assert!(ReferenceBuilder::new().set_alphabet("unknown").is_err());
+}
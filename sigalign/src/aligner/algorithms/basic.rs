@@ -73,12 +73,26 @@ impl Algorithm for Local {
         query: &[u8],
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        self.align_to_targets(
+            query,
+            reference,
+            sequence_buffer,
+            reference.get_full_sorted_target_indices(),
+        )
+    }
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
     ) -> QueryAlignment {
         self.inner.align(
             query,
             reference.as_ref(),
             sequence_buffer,
-            reference.get_full_sorted_target_indices(),
+            sorted_target_indices,
         )
     }
     fn regulator(&self) -> &AlignmentRegulator {
@@ -92,12 +106,26 @@ impl Algorithm for SemiGlobal {
         query: &[u8],
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        self.align_to_targets(
+            query,
+            reference,
+            sequence_buffer,
+            reference.get_full_sorted_target_indices(),
+        )
+    }
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
     ) -> QueryAlignment {
         self.inner.align(
             query,
             reference.as_ref(),
             sequence_buffer,
-            reference.get_full_sorted_target_indices(),
+            sorted_target_indices,
         )
     }
     fn regulator(&self) -> &AlignmentRegulator {
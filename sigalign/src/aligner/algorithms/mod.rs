@@ -94,6 +94,22 @@ pub trait Algorithm: std::fmt::Debug + Clone + Send + Sync {
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
     ) -> QueryAlignment;
+    // Low-level alignment method to the subset of targets
+    //  - `sorted_target_indices` are sorted, unique, and in the reference.
+    //  - By default, the alignments to the other targets are filtered out of `align`.
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
+    ) -> QueryAlignment {
+        let mut query_alignment = self.align(query, reference, sequence_buffer);
+        query_alignment.0.retain(|target_alignment| {
+            sorted_target_indices.binary_search(&target_alignment.index).is_ok()
+        });
+        query_alignment
+    }
     // Can access the regulator
     fn regulator(&self) -> &AlignmentRegulator;
 }
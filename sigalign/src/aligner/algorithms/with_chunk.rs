@@ -96,6 +96,20 @@ impl Algorithm for LocalWithChunk {
         query: &[u8],
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        self.align_to_targets(
+            query,
+            reference,
+            sequence_buffer,
+            reference.get_full_sorted_target_indices(),
+        )
+    }
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
     ) -> QueryAlignment {
         let mut results = Vec::new();
         
@@ -106,7 +120,7 @@ impl Algorithm for LocalWithChunk {
                 slice,
                 reference.as_ref(),
                 sequence_buffer,
-                reference.get_full_sorted_target_indices(),
+                sorted_target_indices,
             );
             adjust_positions(&mut alignment, start);
             results.append(&mut alignment.0);
@@ -127,6 +141,20 @@ impl Algorithm for SemiGlobalWithChunk {
         query: &[u8],
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        self.align_to_targets(
+            query,
+            reference,
+            sequence_buffer,
+            reference.get_full_sorted_target_indices(),
+        )
+    }
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
     ) -> QueryAlignment {
         let mut results = Vec::new();
         
@@ -137,7 +165,7 @@ impl Algorithm for SemiGlobalWithChunk {
                 slice,
                 reference.as_ref(),
                 sequence_buffer,
-                reference.get_full_sorted_target_indices(),
+                sorted_target_indices,
             );
             adjust_positions(&mut alignment, start);
             results.append(&mut alignment.0);
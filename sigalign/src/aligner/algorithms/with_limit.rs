@@ -75,12 +75,26 @@ impl Algorithm for LocalWithLimit {
         query: &[u8],
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        self.align_to_targets(
+            query,
+            reference,
+            sequence_buffer,
+            reference.get_full_sorted_target_indices(),
+        )
+    }
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
     ) -> QueryAlignment {
         self.inner.align(
             query,
             reference.as_ref(),
             sequence_buffer,
-            reference.get_full_sorted_target_indices(),
+            sorted_target_indices,
         )
     }
     fn regulator(&self) -> &AlignmentRegulator {
@@ -94,12 +108,26 @@ impl Algorithm for SemiGlobalWithLimit {
         query: &[u8],
         reference: &Reference,
         sequence_buffer: &mut DefaultSequenceBuffer,
+    ) -> QueryAlignment {
+        self.align_to_targets(
+            query,
+            reference,
+            sequence_buffer,
+            reference.get_full_sorted_target_indices(),
+        )
+    }
+    fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut DefaultSequenceBuffer,
+        sorted_target_indices: &[u32],
     ) -> QueryAlignment {
         self.inner.align(
             query,
             reference.as_ref(),
             sequence_buffer,
-            reference.get_full_sorted_target_indices(),
+            sorted_target_indices,
         )
     }
     fn regulator(&self) -> &AlignmentRegulator {
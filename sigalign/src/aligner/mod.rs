@@ -1,8 +1,11 @@
+use sigalign_utils::sequence_manipulation::preprocessing::TrimmedRange;
+
 use crate::{
-    results::QueryAlignment,
+    results::{QueryAlignment, MappingQualityEstimator, PairingOptions, ReadPairAlignment},
     reference::{
         Reference,
         DefaultSequenceBuffer,
+        StrandError,
     }
 };
 
@@ -27,6 +30,94 @@ impl<A: Algorithm> Aligner<A> {
     pub fn align(&mut self, query: &[u8], reference: &Reference) -> QueryAlignment {
         self.algorithm.align(query, reference, &mut self.sequence_buffer)
     }
+    /// Align a query to the subset of targets in a reference.
+    ///  - The target indices need not be sorted or unique.
+    ///  - The indices out of the reference are ignored.
+    pub fn align_to_targets(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        target_indices: &[u32],
+    ) -> QueryAlignment {
+        let num_targets = reference.get_num_targets();
+        let mut sorted_target_indices: Vec<u32> = target_indices
+            .iter()
+            .copied()
+            .filter(|&target_index| target_index < num_targets)
+            .collect();
+        sorted_target_indices.sort_unstable();
+        sorted_target_indices.dedup();
+        self.algorithm.align_to_targets(query, reference, &mut self.sequence_buffer, &sorted_target_indices)
+    }
+    /// Align the query preprocessed by `QueryPreprocessor`.
+    ///  - The query positions of the result refer to the original query.
+    pub fn align_preprocessed(
+        &mut self,
+        preprocessed_query: &[u8],
+        trimmed_range: &TrimmedRange,
+        reference: &Reference,
+    ) -> QueryAlignment {
+        let mut query_alignment = self.align(preprocessed_query, reference);
+        shift_query_positions(&mut query_alignment, trimmed_range.start);
+        query_alignment
+    }
+    /// Align both strands of the mates and pair the alignments.
+    ///  - Error if the reference has no complementary strand (protein).
+    pub fn align_read_pair(
+        &mut self,
+        first: &[u8],
+        second: &[u8],
+        reference: &Reference,
+        options: &PairingOptions,
+    ) -> Result<ReadPairAlignment, StrandError> {
+        self.align_preprocessed_read_pair(
+            first,
+            &TrimmedRange::full(first.len() as u32),
+            second,
+            &TrimmedRange::full(second.len() as u32),
+            reference,
+            options,
+        )
+    }
+    /// Same as `align_read_pair`, but the mates are preprocessed by `QueryPreprocessor`.
+    ///  - The query positions of the result refer to the original mates
+    ///    (or their reverse complements for the reverse strand).
+    pub fn align_preprocessed_read_pair(
+        &mut self,
+        first: &[u8],
+        first_trimmed_range: &TrimmedRange,
+        second: &[u8],
+        second_trimmed_range: &TrimmedRange,
+        reference: &Reference,
+        options: &PairingOptions,
+    ) -> Result<ReadPairAlignment, StrandError> {
+        let first_reversed = reference.reverse_complement(first)?;
+        let second_reversed = reference.reverse_complement(second)?;
+        let first_forward = self.align_preprocessed(
+            first, first_trimmed_range, reference,
+        );
+        let first_reverse = self.align_preprocessed(
+            &first_reversed, &first_trimmed_range.reverse(), reference,
+        );
+        let second_forward = self.align_preprocessed(
+            second, second_trimmed_range, reference,
+        );
+        let second_reverse = self.align_preprocessed(
+            &second_reversed, &second_trimmed_range.reverse(), reference,
+        );
+        Ok(ReadPairAlignment::new(
+            first_forward,
+            first_reverse,
+            second_forward,
+            second_reverse,
+            (first_trimmed_range.original_length, second_trimmed_range.original_length),
+            options,
+        ))
+    }
+    /// Mapping quality estimator with the penalties of this aligner.
+    pub fn mapping_quality_estimator(&self) -> MappingQualityEstimator {
+        MappingQualityEstimator::new(self.algorithm.regulator().get_mismatch_penalty())
+    }
 }
 
 impl<A: Algorithm> From<A> for Aligner<A> {
@@ -37,3 +128,15 @@ impl<A: Algorithm> From<A> for Aligner<A> {
         }
     }
 }
+
+fn shift_query_positions(query_alignment: &mut QueryAlignment, offset: u32) {
+    if offset == 0 {
+        return;
+    }
+    query_alignment.0.iter_mut().for_each(|target_alignment| {
+        target_alignment.alignments.iter_mut().for_each(|alignment| {
+            alignment.position.query.0 += offset;
+            alignment.position.query.1 += offset;
+        });
+    });
+}
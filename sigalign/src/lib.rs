@@ -85,6 +85,14 @@ pub use reference::{
     ReferenceBuilder,
     ReferenceBuildError,
     ReferenceLoadError,
+    ReferenceFileHeader,
+    ReferenceBuildOptions,
+    ReferenceSummary,
+    Alphabet,
+    StrandError,
+    MASKED_RESIDUE,
+    SoftMaskedRegions,
+    TargetMetadata,
 };
 
 mod aligner;
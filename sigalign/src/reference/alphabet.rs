@@ -0,0 +1,121 @@
+use std::fmt;
+
+use thiserror::Error;
+
+use sigalign_utils::sequence_manipulation::reverse_complementary::{
+    reverse_complement_of_dna_sequence,
+    reverse_complement_of_iupac_dna_sequence,
+    reverse_complement_of_rna_sequence,
+};
+
+/// Symbol replacing the residues not in the alphabet. It never matches to any other residue.
+pub const MASKED_RESIDUE: u8 = b'?';
+
+const DNA_RESIDUES: &[u8] = b"ACGT";
+const DNA_IUPAC_RESIDUES: &[u8] = b"ACGTRYSWKMBDHVN";
+const RNA_RESIDUES: &[u8] = b"ACGU";
+// 20 standard, B, Z (ambiguous), J (Leu/Ile), U (Sec), O (Pyl), X (unknown), and stop
+const PROTEIN_RESIDUES: &[u8] = b"ACDEFGHIKLMNPQRSTVWYBZJUOX*";
+
+/// Alphabet of the reference sequences.
+///  - `Dna`: A, C, G, T. U is converted to T.
+///  - `DnaIupac`: DNA with IUPAC ambiguity codes. U is converted to T.
+///  - `Rna`: A, C, G, U. T is converted to U.
+///  - `Protein`: amino acids (with B, Z, J, U, O, X, and `*`). No complementary strand.
+///
+/// The letters are case-insensitive, and the case is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alphabet {
+    Dna,
+    DnaIupac,
+    Rna,
+    Protein,
+}
+
+/// Error for the strand operations.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StrandError {
+    #[error("{0} reference has no complementary strand")]
+    NoComplementaryStrand(Alphabet),
+}
+
+impl Alphabet {
+    /// Name used in the saved reference.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dna => "DNA",
+            Self::DnaIupac => "DNA_IUPAC",
+            Self::Rna => "RNA",
+            Self::Protein => "PROTEIN",
+        }
+    }
+    /// Parse the name (case-insensitive). `None` if the name is unknown.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().as_str() {
+            "DNA" => Some(Self::Dna),
+            "DNA_IUPAC" | "IUPAC" => Some(Self::DnaIupac),
+            "RNA" => Some(Self::Rna),
+            "PROTEIN" => Some(Self::Protein),
+            _ => None,
+        }
+    }
+    /// Uppercase residues of the alphabet.
+    pub fn residues(&self) -> &'static [u8] {
+        match self {
+            Self::Dna => DNA_RESIDUES,
+            Self::DnaIupac => DNA_IUPAC_RESIDUES,
+            Self::Rna => RNA_RESIDUES,
+            Self::Protein => PROTEIN_RESIDUES,
+        }
+    }
+    /// `true` if the residue is in the alphabet or can be converted to it (e.g., U in DNA).
+    pub fn accepts(&self, residue: u8) -> bool {
+        self.normalize_residue(residue) != MASKED_RESIDUE
+    }
+    pub fn has_complementary_strand(&self) -> bool {
+        !matches!(self, Self::Protein)
+    }
+    /// Convert the residues to the alphabet in place.
+    /// The residues not in the alphabet are replaced with `MASKED_RESIDUE`.
+    pub fn normalize(&self, sequence: &mut [u8]) {
+        let byte_mapper = self.normalization_map();
+        sequence.iter_mut().for_each(|v| *v = byte_mapper[*v as usize]);
+    }
+    /// Reverse complement of the sequence. The case of each residue is kept.
+    pub fn reverse_complement(&self, sequence: &[u8]) -> Result<Vec<u8>, StrandError> {
+        match self {
+            Self::Dna => Ok(reverse_complement_of_dna_sequence(sequence)),
+            Self::DnaIupac => Ok(reverse_complement_of_iupac_dna_sequence(sequence)),
+            Self::Rna => Ok(reverse_complement_of_rna_sequence(sequence)),
+            Self::Protein => Err(StrandError::NoComplementaryStrand(*self)),
+        }
+    }
+    pub(crate) fn normalization_map(&self) -> [u8; 256] {
+        let mut byte_mapper = [0; 256];
+        for (i, item) in byte_mapper.iter_mut().enumerate() {
+            *item = self.normalize_residue(i as u8);
+        }
+        byte_mapper
+    }
+    fn normalize_residue(&self, residue: u8) -> u8 {
+        let uppercase = residue.to_ascii_uppercase();
+        let converted = match (self, uppercase) {
+            (Self::Dna | Self::DnaIupac, b'U') => b'T',
+            (Self::Rna, b'T') => b'U',
+            _ => uppercase,
+        };
+        if !self.residues().contains(&converted) {
+            MASKED_RESIDUE
+        } else if residue.is_ascii_lowercase() {
+            converted.to_ascii_lowercase()
+        } else {
+            converted
+        }
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
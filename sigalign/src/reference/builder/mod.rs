@@ -1,15 +1,23 @@
-use std::{io::Read, fs::File};
+use std::collections::HashMap;
+use std::io::Read;
 
 use thiserror::Error;
 
-use sigalign_core::reference::Reference as RawReference;
+use sigalign_core::reference::{
+    Reference as RawReference,
+    SequenceStorage as _,
+};
 use sigalign_impl::{
     pattern_index::dynamic_lfi::{
         DynamicLfiOption, LfiBuildError,
     },
     sequence_storage::in_memory::InMemoryStorage,
 };
-use super::Reference;
+use sigalign_utils::sequence_reader::{
+    ReadError, OpenSequenceFileError, open_decompressed_file,
+};
+use super::{Reference, Alphabet, SoftMaskedRegions, TargetMetadata, MASKED_RESIDUE};
+use super::metadata::{parse_metadata_tsv, MetadataRows};
 
 /// Builder for `Reference`.
 /// 
@@ -18,12 +26,32 @@ use super::Reference;
 ///      - Reference treats uppercase and lowercase letters as different bases.
 ///   - Ignore bases: None
 ///      - Reference treats all characters as bases.
+///   - Alphabet: None
+///      - Sequences are not validated, and DNA is assumed for the strand.
+///   - Soft-masking: false
+///      - Lowercase regions are used for seeding.
+///   - Metadata: None
+///      - FASTA descriptions are dropped.
 pub struct ReferenceBuilder {
     uppercase: bool,
     to_ignore_bases: Vec<u8>,
+    alphabet: Option<Alphabet>,
+    strict_alphabet: bool,
+    soft_masking: bool,
+    keep_metadata: bool,
+    metadata_rows: MetadataRows,
+    descriptions: Vec<String>,
     sequence_storage: InMemoryStorage,
 }
 
+/// Options used to build the `Reference` (recorded in the reference file).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReferenceBuildOptions {
+    pub uppercase: bool,
+    pub strict_alphabet: bool,
+    pub ignored_bases: Vec<u8>,
+}
+
 /// Error for building `Reference`.
 #[derive(Error, Debug)]
 pub enum ReferenceBuildError {
@@ -33,8 +61,14 @@ pub enum ReferenceBuildError {
     InvalidSequence(String),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ReadError(#[from] ReadError),
+    #[error(transparent)]
+    OpenFileError(#[from] OpenSequenceFileError),
     #[error("Sequence is empty")]
     EmptySequence,
+    #[error("Invalid metadata: {0}")]
+    InvalidMetadata(String),
 }
 
 impl ReferenceBuilder {
@@ -43,6 +77,12 @@ impl ReferenceBuilder {
         Self {
             uppercase: true,
             to_ignore_bases: Vec::new(),
+            alphabet: None,
+            strict_alphabet: false,
+            soft_masking: false,
+            keep_metadata: false,
+            metadata_rows: Vec::new(),
+            descriptions: Vec::new(),
             sequence_storage: InMemoryStorage::new(),
         }
     }
@@ -67,40 +107,165 @@ impl ReferenceBuilder {
         self.to_ignore_bases.clear();
         self
     }
+    /// Set the alphabet of the sequences.
+    ///  - The residues are normalized to the alphabet (e.g., U to T for DNA).
+    ///  - The residues not in the alphabet are masked to never match.
+    pub fn set_alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = Some(alphabet);
+        self
+    }
+    /// Fail to build if any residue is not in the alphabet, instead of masking it.
+    pub fn set_strict_alphabet(mut self, strict: bool) -> Self {
+        self.strict_alphabet = strict;
+        self
+    }
+    /// Treat lowercase regions as soft-masked (e.g., repeats).
+    ///  - The soft-masked regions are excluded from the seeding,
+    ///    but can be aligned in the extension as uppercase.
+    ///  - Sequences are always set to uppercase if enabled.
+    pub fn set_soft_masking(mut self, soft_masking: bool) -> Self {
+        self.soft_masking = soft_masking;
+        self
+    }
+    /// Keep the FASTA descriptions as the metadata of targets.
+    ///  - The words formatted as `key=value` in the descriptions are parsed as attributes.
+    pub fn set_keep_metadata(mut self, keep_metadata: bool) -> Self {
+        self.keep_metadata = keep_metadata;
+        self
+    }
     /* Add Sequences */
     pub fn add_target(mut self, label: &str, sequence: &[u8]) -> Self {
         self.sequence_storage.add_target(label, sequence);
+        self.descriptions.push(String::new());
         self
     }
     pub fn add_fasta<R: Read>(mut self, reader: R) -> Result<Self, ReferenceBuildError> {
-        self.sequence_storage.add_fasta(reader).map_err(|_| ReferenceBuildError::invalid_fasta_record())?;
+        self.sequence_storage.add_fasta_with_descriptions(reader, &mut self.descriptions)?;
         Ok(self)
     }
+    /// Add FASTA file. The compressed file (gzip, bgzip, zstd, bzip2) is decoded automatically.
     pub fn add_fasta_file<P>(mut self, path: P) -> Result<Self, ReferenceBuildError> where
         P: AsRef<std::path::Path> + std::fmt::Debug,
     {
-        let file = File::open(path)?;
-        self.sequence_storage.add_fasta(file).map_err(|_| ReferenceBuildError::invalid_fasta_record())?;
+        let file = open_decompressed_file(path)?;
+        self.sequence_storage.add_fasta_with_descriptions(file, &mut self.descriptions)?;
         Ok(self)
     }
+    /* Add Metadata */
+    /// Add attributes of targets from the tab-separated table.
+    ///  - The first line is the header: the first column is the label, and the others are the keys.
+    ///  - The attributes are applied to all targets with the label. Unknown labels are ignored.
+    ///  - The attributes override those from the FASTA descriptions.
+    pub fn add_metadata_tsv<R: Read>(mut self, reader: R) -> Result<Self, ReferenceBuildError> {
+        let rows = parse_metadata_tsv(reader).map_err(ReferenceBuildError::InvalidMetadata)?;
+        self.metadata_rows.extend(rows);
+        Ok(self)
+    }
+    /// Add attributes of targets from the tab-separated file. See `add_metadata_tsv`.
+    pub fn add_metadata_tsv_file<P>(self, path: P) -> Result<Self, ReferenceBuildError> where
+        P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(path)?;
+        self.add_metadata_tsv(file)
+    }
 
     /// Finish building `Reference`.
     pub fn build(mut self) -> Result<Reference, ReferenceBuildError> {
         // Sequence Storage
-        if self.uppercase {
+        let soft_masked_regions = if self.soft_masking {
+            Some(SoftMaskedRegions::new(self.sequence_storage.get_lowercase_regions()))
+        } else {
+            None
+        };
+        if self.uppercase || self.soft_masking {
             self.sequence_storage.set_sequences_to_uppercase()
         }
+        if let Some(alphabet) = self.alphabet {
+            if self.strict_alphabet {
+                Self::validate_alphabet(&self.sequence_storage, alphabet)?;
+            }
+            self.sequence_storage.map_bases(&alphabet.normalization_map());
+        }
         if !self.to_ignore_bases.is_empty() {
-            self.sequence_storage.change_bases_to(&self.to_ignore_bases, b'?');
+            self.sequence_storage.change_bases_to(&self.to_ignore_bases, MASKED_RESIDUE);
         }
 
+        let build_options = ReferenceBuildOptions {
+            uppercase: self.uppercase || self.soft_masking,
+            strict_alphabet: self.strict_alphabet,
+            ignored_bases: self.to_ignore_bases.clone(),
+        };
+
+        // Metadata
+        let target_metadata = if self.keep_metadata || !self.metadata_rows.is_empty() {
+            Some(self.get_target_metadata())
+        } else {
+            None
+        };
+
         // Pattern index option
         let dynamic_lfi_option = Self::get_option_for_dynamic_lfi(&self.sequence_storage);
-        let raw_reference = RawReference::new(
-            self.sequence_storage,
-            dynamic_lfi_option,
-        )?;
-        Ok(Reference::from(raw_reference))
+        let raw_reference = match &soft_masked_regions {
+            Some(regions) => RawReference::new_with_modified_index(
+                self.sequence_storage,
+                dynamic_lfi_option,
+                |sequence, target_boundaries| regions.mask_sequence_for_index(sequence, target_boundaries),
+            )?,
+            None => RawReference::new(
+                self.sequence_storage,
+                dynamic_lfi_option,
+            )?,
+        };
+        Ok(
+            Reference::from(raw_reference)
+                .with_alphabet(self.alphabet)
+                .with_build_options(Some(build_options))
+                .with_soft_masked_regions(soft_masked_regions)
+                .with_target_metadata(target_metadata)
+        )
+    }
+
+    fn get_target_metadata(&self) -> Vec<TargetMetadata> {
+        let mut target_metadata: Vec<TargetMetadata> = self.descriptions.iter().map(|description| {
+            if self.keep_metadata {
+                TargetMetadata::from_fasta_description(description)
+            } else {
+                TargetMetadata::default()
+            }
+        }).collect();
+        if !self.metadata_rows.is_empty() {
+            let mut target_indices_by_label: HashMap<&str, Vec<usize>> = HashMap::new();
+            for target_index in 0..self.sequence_storage.num_targets() {
+                let label = self.sequence_storage.get_label_ref_safely(target_index).unwrap_or_default();
+                target_indices_by_label.entry(label).or_default().push(target_index as usize);
+            }
+            for (label, attributes) in &self.metadata_rows {
+                if let Some(target_indices) = target_indices_by_label.get(label.as_str()) {
+                    for target_index in target_indices {
+                        target_metadata[*target_index].attributes.extend(attributes.iter().cloned());
+                    }
+                }
+            }
+        }
+        target_metadata
+    }
+    fn validate_alphabet(
+        sequence_storage: &InMemoryStorage,
+        alphabet: Alphabet,
+    ) -> Result<(), ReferenceBuildError> {
+        for target_index in 0..sequence_storage.num_targets() {
+            let sequence = sequence_storage.get_sequence_safely(target_index).unwrap();
+            if let Some(position) = sequence.iter().position(|&x| !alphabet.accepts(x)) {
+                return Err(ReferenceBuildError::InvalidSequence(format!(
+                    "'{}' at position {} of target {} is not in the {} alphabet",
+                    (sequence[position] as char).escape_default(),
+                    position,
+                    target_index,
+                    alphabet,
+                )));
+            }
+        }
+        Ok(())
     }
 
     fn get_option_for_dynamic_lfi(sequence_storage: &InMemoryStorage) -> DynamicLfiOption {
@@ -118,10 +283,3 @@ impl ReferenceBuilder {
         }
     }
 }
-
-impl ReferenceBuildError {
-    // ID of FASTA record is invalid UTF8.
-    fn invalid_fasta_record() -> Self {
-        Self::InvalidSequence("ID of FASTA record is invalid UTF8".to_string())
-    }
-}
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use super::{ReferenceLoadError, CORE_VERSION};
+use super::section::SectionKind;
+use crate::reference::{Reference, Alphabet, ReferenceBuildOptions};
+
+const STORAGE_TYPE: &str = "InMemoryStorage";
+const INDEX_TYPE: &str = "DynamicLfi";
+
+/// Header of the reference file.
+///  - The legacy file (format version 1) has no header. It is derived from the loaded reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceFileHeader {
+    pub format_version: u32,
+    pub core_version: String,
+    pub storage_type: String,
+    pub index_type: String,
+    pub num_targets: u32,
+    pub total_length: u32,
+    pub alphabet: Option<Alphabet>,
+    /// None if the reference is not built by `ReferenceBuilder`.
+    pub build_options: Option<ReferenceBuildOptions>,
+    /// Names and lengths (in bytes) of the sections except the padding. Empty for the legacy file.
+    pub sections: Vec<(String, u64)>,
+}
+
+impl ReferenceFileHeader {
+    pub(super) fn of_reference(reference: &Reference, format_version: u32) -> Self {
+        Self {
+            format_version,
+            core_version: CORE_VERSION.to_string(),
+            storage_type: STORAGE_TYPE.to_string(),
+            index_type: INDEX_TYPE.to_string(),
+            num_targets: reference.get_num_targets(),
+            total_length: reference.get_total_length(),
+            alphabet: reference.alphabet,
+            build_options: reference.build_options.clone(),
+            sections: Vec::new(),
+        }
+    }
+    /// `true` if this version can load the storage and index.
+    pub(super) fn check_types(&self) -> Result<(), ReferenceLoadError> {
+        if self.core_version != CORE_VERSION {
+            Err(ReferenceLoadError::IncompatibleVersion(format!("core {}", self.core_version)))
+        } else if self.storage_type != STORAGE_TYPE {
+            Err(ReferenceLoadError::UnsupportedType(self.storage_type.clone()))
+        } else if self.index_type != INDEX_TYPE {
+            Err(ReferenceLoadError::UnsupportedType(self.index_type.clone()))
+        } else {
+            Ok(())
+        }
+    }
+    pub(super) fn record_section(&mut self, kind: SectionKind, length: u64) {
+        if kind != SectionKind::Padding {
+            self.sections.push((kind.name(), length));
+        }
+    }
+    pub(super) fn check_raw_reference_exists(&self) -> Result<(), ReferenceLoadError> {
+        if self.sections.iter().any(|(name, _)| *name == SectionKind::RawReference.name()) {
+            Ok(())
+        } else {
+            Err(ReferenceLoadError::InvalidSection("raw reference is missing".to_string()))
+        }
+    }
+    /// Lines of `key=value`.
+    pub(super) fn encode(&self) -> Vec<u8> {
+        let mut entries = vec![
+            ("format_version", self.format_version.to_string()),
+            ("core_version", self.core_version.clone()),
+            ("storage_type", self.storage_type.clone()),
+            ("index_type", self.index_type.clone()),
+            ("num_targets", self.num_targets.to_string()),
+            ("total_length", self.total_length.to_string()),
+        ];
+        if let Some(alphabet) = self.alphabet {
+            entries.push(("alphabet", alphabet.as_str().to_string()));
+        }
+        if let Some(build_options) = &self.build_options {
+            entries.push(("uppercase", build_options.uppercase.to_string()));
+            entries.push(("strict_alphabet", build_options.strict_alphabet.to_string()));
+            let ignored_bases: Vec<String> = build_options.ignored_bases.iter().map(|x| x.to_string()).collect();
+            entries.push(("ignored_bases", ignored_bases.join(",")));
+        }
+        entries.into_iter().map(|(key, value)| format!("{}={}\n", key, value)).collect::<String>().into_bytes()
+    }
+    /// The unknown keys (from the newer version) are ignored.
+    pub(super) fn decode(bytes: &[u8]) -> Result<Self, ReferenceLoadError> {
+        let invalid = |message: &str| ReferenceLoadError::InvalidSection(format!("header: {}", message));
+        let text = std::str::from_utf8(bytes).map_err(|_| invalid("not UTF-8"))?;
+        let entries: BTreeMap<&str, &str> = text.lines().filter_map(|line| line.split_once('=')).collect();
+        let get = |key: &str| entries.get(key).copied().ok_or_else(|| invalid(&format!("'{}' is missing", key)));
+        let parse_number = |key: &str| -> Result<u32, ReferenceLoadError> {
+            get(key)?.parse().map_err(|_| invalid(&format!("'{}' is not a number", key)))
+        };
+        let parse_bool = |key: &str| -> Result<bool, ReferenceLoadError> {
+            get(key)?.parse().map_err(|_| invalid(&format!("'{}' is not a boolean", key)))
+        };
+
+        let alphabet = match entries.get("alphabet") {
+            Some(name) => Some(
+                Alphabet::from_name(name).ok_or(ReferenceLoadError::UnknownAlphabet(name.to_string()))?
+            ),
+            None => None,
+        };
+        let build_options = if entries.contains_key("uppercase") {
+            let ignored_bases = get("ignored_bases")?
+                .split(',')
+                .filter(|x| !x.is_empty())
+                .map(|x| x.parse().map_err(|_| invalid("'ignored_bases' is not a list of bytes")))
+                .collect::<Result<_, _>>()?;
+            Some(ReferenceBuildOptions {
+                uppercase: parse_bool("uppercase")?,
+                strict_alphabet: parse_bool("strict_alphabet")?,
+                ignored_bases,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            format_version: parse_number("format_version")?,
+            core_version: get("core_version")?.to_string(),
+            storage_type: get("storage_type")?.to_string(),
+            index_type: get("index_type")?.to_string(),
+            num_targets: parse_number("num_targets")?,
+            total_length: parse_number("total_length")?,
+            alphabet,
+            build_options,
+            sections: Vec::new(),
+        })
+    }
+}
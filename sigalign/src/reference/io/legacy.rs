@@ -0,0 +1,17 @@
+//! Migration from the legacy format (format version 1; SigAlign 0.4).
+//!  - The raw reference follows the signature without the header and the checksum.
+use std::io::Read;
+
+use sigalign_core::reference::{
+    Reference as RawReference, extensions::Serialize,
+};
+use super::{ReferenceLoadError, section::map_eof_to_truncated};
+use crate::reference::Reference;
+
+pub(super) const LEGACY_WRAPPER_VERSION: &str = "0.4.0";
+pub(super) const LEGACY_FORMAT_VERSION: u32 = 1;
+
+pub(super) fn load_legacy_reference<R: Read>(mut reader: R) -> Result<Reference, ReferenceLoadError> {
+    let raw_reference = RawReference::load_from(&mut reader).map_err(map_eof_to_truncated)?;
+    Ok(Reference::from(raw_reference))
+}
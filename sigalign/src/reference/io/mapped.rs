@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+
+use sigalign_core::reference::{
+    Reference as RawReference, extensions::{LoadFromShared, SharedBytes},
+};
+use super::{
+    Reference, ReferenceLoadError, ReferenceFileHeader, Signature,
+    decode_soft_masked_regions, decode_target_metadata,
+};
+use super::section::{
+    SectionKind, SectionHeader, SECTION_HEADER_SIZE, SECTION_CHECKSUM_SIZE,
+    read_section_header, get_payload, get_checked_payload,
+};
+use super::legacy::load_legacy_reference;
+
+impl Reference {
+    /// Load `Reference` from the memory-mapped file.
+    ///  - The sequences and the buffers of the pattern index (suffix array, lookup table and BWT blocks)
+    ///    are borrowed from the mapped file instead of being copied,
+    ///    so the references loaded by multiple processes share the page cache.
+    ///  - The checksum of the raw reference is not checked, not to read the whole file when loaded.
+    ///    Use `Reference::verify` to check all sections (the other sections are checked when loaded).
+    ///  - The legacy file is loaded by copying, same as `load_from`.
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated while the loaded `Reference` is alive.
+    pub unsafe fn load_from_mapped_file<P: AsRef<Path>>(path: P) -> Result<Self, ReferenceLoadError> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Err(ReferenceLoadError::UnknownFile)
+        }
+        let mmap = Mmap::map(&file)?;
+        let bytes: SharedBytes = Arc::new(mmap);
+        Self::load_from_shared_bytes(&bytes)
+    }
+
+    fn load_from_shared_bytes(bytes: &SharedBytes) -> Result<Self, ReferenceLoadError> {
+        let whole: &[u8] = (**bytes).as_ref();
+        let mut reader = whole;
+        if let Signature::Legacy = Self::read_signature(&mut reader)? {
+            return load_legacy_reference(reader)
+        }
+        let mut offset = whole.len() - reader.len();
+
+        let (section_header, payload, _) = next_section(whole, &mut offset)?;
+        if section_header.kind != SectionKind::Header {
+            return Err(ReferenceLoadError::InvalidSection("header is not the first section".to_string()));
+        }
+        let mut header = ReferenceFileHeader::decode(payload)?;
+        header.check_types()?;
+
+        let mut raw_reference = None;
+        let mut soft_masked_regions = None;
+        let mut target_metadata = None;
+        loop {
+            let (section_header, payload, payload_offset) = next_section(whole, &mut offset)?;
+            let kind = section_header.kind;
+            match kind {
+                SectionKind::End => break,
+                SectionKind::Header => {
+                    return Err(ReferenceLoadError::InvalidSection("header is duplicated".to_string()));
+                },
+                SectionKind::RawReference => {
+                    let (loaded, end) = RawReference::load_from_shared(bytes, payload_offset)
+                        .map_err(|_| ReferenceLoadError::InvalidSection(kind.name()))?;
+                    if end != payload_offset + payload.len() {
+                        return Err(ReferenceLoadError::InvalidSection(kind.name()));
+                    }
+                    raw_reference = Some(loaded);
+                },
+                SectionKind::SoftMaskedRegions => {
+                    soft_masked_regions = Some(decode_soft_masked_regions(payload)?);
+                },
+                SectionKind::TargetMetadata => {
+                    target_metadata = Some(decode_target_metadata(payload)?);
+                },
+                _ => {},
+            }
+            header.record_section(kind, section_header.length);
+        }
+        header.check_raw_reference_exists()?;
+        Self::assemble(raw_reference.unwrap(), &header, soft_masked_regions, target_metadata)
+    }
+}
+
+/// (Header, payload, offset of the payload), and move the `offset` to the next section.
+///  - The checksum is checked except for the raw reference.
+fn next_section<'a>(
+    whole: &'a [u8],
+    offset: &mut usize,
+) -> Result<(SectionHeader, &'a [u8], usize), ReferenceLoadError> {
+    let mut reader = whole.get(*offset..).ok_or(ReferenceLoadError::Truncated)?;
+    let section_header = read_section_header(&mut reader)?;
+    let payload_offset = *offset + SECTION_HEADER_SIZE as usize;
+    let payload = if section_header.kind == SectionKind::RawReference {
+        get_payload(whole, payload_offset, section_header)?
+    } else {
+        get_checked_payload(whole, payload_offset, section_header)?
+    };
+    *offset = payload_offset + payload.len() + SECTION_CHECKSUM_SIZE as usize;
+    Ok((section_header, payload, payload_offset))
+}
@@ -1,46 +1,237 @@
-use std::io::{Write, Read};
+//! Reference file format (format version 2):
+//!  - Signature: base64 encoded `SIGALIGN_REFERENCE:FORMAT:{format version}`.
+//!  - Sections: (kind, length, payload, checksum). The header section comes first,
+//!    and the end section comes last. The unknown sections are skipped.
+//!  - The payloads start at the offset of multiple of 64 bytes, padded by the padding sections,
+//!    so that they can be borrowed from the memory-mapped file.
+//!  - The legacy file (format version 1) is migrated when loaded.
+use std::io::{BufReader, Read, Write};
+use std::fs::File;
+use std::path::Path;
 
 use base64::{Engine as _, engine::{general_purpose, GeneralPurpose}};
 use thiserror::Error;
 use capwriter::{Save, Load};
 
 use sigalign_core::reference::{
-    Reference as RawReference, extensions::Serialize,
+    Reference as RawReference, extensions::{Serialize, EstimateSize as _},
 };
-use super::Reference;
+use sigalign_impl::{
+    pattern_index::dynamic_lfi::DynamicLfi,
+    sequence_storage::in_memory::InMemoryStorage,
+};
+use super::{Reference, SoftMaskedRegions, TargetMetadata};
+use super::metadata::{metadata_to_flat, metadata_from_flat};
+
+mod section;
+use section::{
+    SectionKind, CountingWriter, write_aligned_section, write_aligned_section_with, write_section,
+    read_section_header, read_section, skip_section, map_eof_to_truncated,
+};
+mod header;
+pub use header::ReferenceFileHeader;
+#[cfg(not(target_arch = "wasm32"))]
+mod mapped;
+mod legacy;
+use legacy::{load_legacy_reference, LEGACY_WRAPPER_VERSION, LEGACY_FORMAT_VERSION};
 
 const PREFIX: &str = "SIGALIGN_REFERENCE";
-const LOWEST_COMPARABLE_WRAPPER_VERSION: &str = "0.4.0";
 const CORE_VERSION: &str = "0.2.0";
+const FORMAT_TAG: &str = "FORMAT";
+const FORMAT_VERSION: u32 = 2;
 const DELIMITER: &str = ":";
+// Encoded signatures are much shorter
+const MAX_SIGNATURE_LENGTH: usize = 1024;
+
+enum Signature {
+    Legacy,
+    Sectioned,
+}
 
 impl Reference {
     /// Save `Reference` to a writer.
-    pub fn save_to<W>(&self, mut writer: W) -> Result<(), std::io::Error> where
+    pub fn save_to<W>(&self, writer: W) -> Result<(), std::io::Error> where
         W: Write
     {
+        let mut writer = CountingWriter::new(writer);
         let signature = Self::get_base64_encoded_signature_of_current_version();
         signature.as_bytes().save_to(&mut writer)?;
-        self.raw_reference.save_to(writer)?;
+
+        let header = ReferenceFileHeader::of_reference(self, FORMAT_VERSION);
+        write_section(&mut writer, SectionKind::Header, &header.encode())?;
+
+        // The raw reference is streamed, not to buffer the whole index
+        write_aligned_section_with(
+            &mut writer,
+            SectionKind::RawReference,
+            self.raw_reference.serialized_size() as u64,
+            |payload_writer| self.raw_reference.save_to(payload_writer),
+        )?;
+
+        let mut payload = Vec::new();
+        if let Some(soft_masked_regions) = &self.soft_masked_regions {
+            let (counts, flattened) = soft_masked_regions.to_flat();
+            counts.save_to(&mut payload)?;
+            flattened.save_to(&mut payload)?;
+            write_aligned_section(&mut writer, SectionKind::SoftMaskedRegions, &payload)?;
+        }
+        if let Some(target_metadata) = &self.target_metadata {
+            payload.clear();
+            let (counts, lengths, concatenated) = metadata_to_flat(target_metadata);
+            counts.save_to(&mut payload)?;
+            lengths.save_to(&mut payload)?;
+            concatenated.save_to(&mut payload)?;
+            write_aligned_section(&mut writer, SectionKind::TargetMetadata, &payload)?;
+        }
+        write_section(&mut writer, SectionKind::End, &[])?;
         Ok(())
     }
     /// Load `Reference` from a reader.
+    ///  - The legacy file is migrated to the current version.
     pub fn load_from<R>(mut reader: R) -> Result<Self, ReferenceLoadError> where
         R: Read,
         Self: Sized
     {
-        let encoded_signature: Vec<u8> = Vec::load_from(&mut reader)?;
+        match Self::read_signature(&mut reader)? {
+            Signature::Legacy => load_legacy_reference(reader),
+            Signature::Sectioned => {
+                let (reference, _) = Self::read_sections(reader, true)?;
+                Ok(reference.unwrap())
+            },
+        }
+    }
+    /// Validate the reference file without fully loading it, and get the header.
+    ///  - The checksums of all sections are checked.
+    ///  - The legacy file without the checksum is fully loaded to be validated.
+    pub fn verify<P: AsRef<Path>>(path: P) -> Result<ReferenceFileHeader, ReferenceLoadError> {
+        let file = File::open(path)?;
+        Self::verify_from(BufReader::new(file))
+    }
+    /// Same as `verify`, but from a reader.
+    pub fn verify_from<R: Read>(mut reader: R) -> Result<ReferenceFileHeader, ReferenceLoadError> {
+        match Self::read_signature(&mut reader)? {
+            Signature::Legacy => {
+                let reference = load_legacy_reference(reader)?;
+                Ok(ReferenceFileHeader::of_reference(&reference, LEGACY_FORMAT_VERSION))
+            },
+            Signature::Sectioned => {
+                let (_, header) = Self::read_sections(reader, false)?;
+                Ok(header)
+            },
+        }
+    }
+
+    fn read_sections<R: Read>(
+        mut reader: R,
+        to_load: bool,
+    ) -> Result<(Option<Self>, ReferenceFileHeader), ReferenceLoadError> {
+        let section_header = read_section_header(&mut reader)?;
+        if section_header.kind != SectionKind::Header {
+            return Err(ReferenceLoadError::InvalidSection("header is not the first section".to_string()));
+        }
+        let mut header = read_section(&mut reader, section_header, |section_reader| {
+            let mut bytes = Vec::new();
+            section_reader.read_to_end(&mut bytes)?;
+            ReferenceFileHeader::decode(&bytes)
+        })?;
+        header.check_types()?;
+
+        let mut raw_reference = None;
+        let mut soft_masked_regions = None;
+        let mut target_metadata = None;
+        loop {
+            let section_header = read_section_header(&mut reader)?;
+            let kind = section_header.kind;
+            match kind {
+                SectionKind::End => {
+                    skip_section(&mut reader, section_header)?;
+                    break;
+                },
+                SectionKind::Header => {
+                    return Err(ReferenceLoadError::InvalidSection("header is duplicated".to_string()));
+                },
+                SectionKind::RawReference if to_load => {
+                    raw_reference = Some(read_section(&mut reader, section_header, |section_reader| {
+                        RawReference::load_from(section_reader).map_err(map_eof_to_truncated)
+                    })?);
+                },
+                SectionKind::SoftMaskedRegions if to_load => {
+                    soft_masked_regions = Some(read_section(
+                        &mut reader, section_header, |section_reader| decode_soft_masked_regions(section_reader),
+                    )?);
+                },
+                SectionKind::TargetMetadata if to_load => {
+                    target_metadata = Some(read_section(
+                        &mut reader, section_header, |section_reader| decode_target_metadata(section_reader),
+                    )?);
+                },
+                _ => skip_section(&mut reader, section_header)?,
+            }
+            header.record_section(kind, section_header.length);
+        }
+        header.check_raw_reference_exists()?;
+        if !to_load {
+            return Ok((None, header))
+        }
+        let reference = Self::assemble(raw_reference.unwrap(), &header, soft_masked_regions, target_metadata)?;
+        Ok((Some(reference), header))
+    }
+    fn assemble(
+        raw_reference: RawReference<DynamicLfi, InMemoryStorage>,
+        header: &ReferenceFileHeader,
+        soft_masked_regions: Option<SoftMaskedRegions>,
+        target_metadata: Option<Vec<TargetMetadata>>,
+    ) -> Result<Self, ReferenceLoadError> {
+        let reference = Self::from(raw_reference)
+            .with_alphabet(header.alphabet)
+            .with_build_options(header.build_options.clone())
+            .with_soft_masked_regions(soft_masked_regions)
+            .with_target_metadata(target_metadata);
+        if reference.get_num_targets() != header.num_targets {
+            return Err(ReferenceLoadError::InvalidSection(format!(
+                "{} targets in header, but {} in raw reference",
+                header.num_targets,
+                reference.get_num_targets(),
+            )));
+        }
+        Ok(reference)
+    }
+    fn read_signature<R: Read>(reader: &mut R) -> Result<Signature, ReferenceLoadError> {
+        let eof_as_unknown_file = |err: std::io::Error| {
+            if err.kind() == std::io::ErrorKind::UnexpectedEof {
+                ReferenceLoadError::UnknownFile
+            } else {
+                ReferenceLoadError::IoError(err)
+            }
+        };
+        // Same layout as `Vec<u8>` of `capwriter`, but the length is checked
+        // not to allocate a huge buffer for the file of the other format.
+        let mut length_bytes = [0; std::mem::size_of::<usize>()];
+        reader.read_exact(&mut length_bytes).map_err(eof_as_unknown_file)?;
+        let length = usize::from_ne_bytes(length_bytes);
+        if length > MAX_SIGNATURE_LENGTH {
+            return Err(ReferenceLoadError::UnknownFile)
+        }
+        let mut encoded_signature = vec![0; length];
+        reader.read_exact(&mut encoded_signature).map_err(eof_as_unknown_file)?;
         let signatures = Self::get_base64_decoded_signature(&encoded_signature)?;
-        if signatures[0] == PREFIX && signatures[1] == LOWEST_COMPARABLE_WRAPPER_VERSION && signatures[2] == CORE_VERSION {
-            let raw_reference = RawReference::load_from(reader)?;
-            Ok(Self::from(raw_reference))
+        if signatures.len() != 3 || signatures[0] != PREFIX {
+            return Err(ReferenceLoadError::UnknownFile)
+        }
+        if signatures[1] == LEGACY_WRAPPER_VERSION && signatures[2] == CORE_VERSION {
+            Ok(Signature::Legacy)
+        } else if signatures[1] == FORMAT_TAG {
+            match signatures[2].parse::<u32>() {
+                Ok(FORMAT_VERSION) => Ok(Signature::Sectioned),
+                _ => Err(ReferenceLoadError::IncompatibleVersion(format!("format {}", signatures[2]))),
+            }
         } else {
             Err(ReferenceLoadError::IncompatibleVersion(signatures[1].clone()))
         }
     }
     fn get_base64_encoded_signature_of_current_version() -> String {
         let engine = Self::get_base64_engine();
-        let combined_signature = [PREFIX, DELIMITER, LOWEST_COMPARABLE_WRAPPER_VERSION, DELIMITER, CORE_VERSION].concat();
+        let combined_signature = [PREFIX, DELIMITER, FORMAT_TAG, DELIMITER, &FORMAT_VERSION.to_string()].concat();
         let mut encoded_signature = String::new();
         engine.encode_string(combined_signature, &mut encoded_signature);
 
@@ -62,6 +253,22 @@ impl Reference {
     }
 }
 
+fn decode_soft_masked_regions<R: Read>(mut reader: R) -> Result<SoftMaskedRegions, ReferenceLoadError> {
+    let counts: Vec<u32> = Vec::load_from(&mut reader)?;
+    let flattened: Vec<u32> = Vec::load_from(&mut reader)?;
+    SoftMaskedRegions::from_flat(&counts, &flattened).ok_or_else(|| {
+        ReferenceLoadError::InvalidSection(SectionKind::SoftMaskedRegions.name())
+    })
+}
+fn decode_target_metadata<R: Read>(mut reader: R) -> Result<Vec<TargetMetadata>, ReferenceLoadError> {
+    let counts: Vec<u32> = Vec::load_from(&mut reader)?;
+    let lengths: Vec<u32> = Vec::load_from(&mut reader)?;
+    let concatenated: Vec<u8> = Vec::load_from(&mut reader)?;
+    metadata_from_flat(&counts, &lengths, &concatenated).ok_or_else(|| {
+        ReferenceLoadError::InvalidSection(SectionKind::TargetMetadata.name())
+    })
+}
+
 /// Error for loading `Reference`.
 #[derive(Debug, Error)]
 pub enum ReferenceLoadError {
@@ -69,6 +276,16 @@ pub enum ReferenceLoadError {
     UnknownFile,
     #[error("This reference file is incompatible with the current version of SigAlign. Detected version: {0}")]
     IncompatibleVersion(String),
+    #[error("Unknown alphabet in the reference file: {0}")]
+    UnknownAlphabet(String),
+    #[error("The reference file is truncated.")]
+    Truncated,
+    #[error("Checksum mismatch in the {0} section. The reference file is corrupted.")]
+    ChecksumMismatch(String),
+    #[error("Invalid section in the reference file: {0}")]
+    InvalidSection(String),
+    #[error("Unsupported storage or index type in the reference file: {0}")]
+    UnsupportedType(String),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
 }
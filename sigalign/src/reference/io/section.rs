@@ -0,0 +1,287 @@
+use std::io::{self, Read, Take, Write};
+
+use crc32fast::Hasher;
+
+use super::ReferenceLoadError;
+
+/// Kind of the section in the reference file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SectionKind {
+    End,
+    Header,
+    RawReference,
+    SoftMaskedRegions,
+    TargetMetadata,
+    // Bytes to align the payload of the next section
+    Padding,
+    // Section from the newer version: skipped
+    Unknown(u32),
+}
+
+impl SectionKind {
+    fn id(&self) -> u32 {
+        match self {
+            Self::End => 0,
+            Self::Header => 1,
+            Self::RawReference => 2,
+            Self::SoftMaskedRegions => 3,
+            Self::TargetMetadata => 4,
+            Self::Padding => 5,
+            Self::Unknown(id) => *id,
+        }
+    }
+    fn from_id(id: u32) -> Self {
+        match id {
+            0 => Self::End,
+            1 => Self::Header,
+            2 => Self::RawReference,
+            3 => Self::SoftMaskedRegions,
+            4 => Self::TargetMetadata,
+            5 => Self::Padding,
+            _ => Self::Unknown(id),
+        }
+    }
+    pub(super) fn name(&self) -> String {
+        match self {
+            Self::End => "end".to_string(),
+            Self::Header => "header".to_string(),
+            Self::RawReference => "raw_reference".to_string(),
+            Self::SoftMaskedRegions => "soft_masked_regions".to_string(),
+            Self::TargetMetadata => "target_metadata".to_string(),
+            Self::Padding => "padding".to_string(),
+            Self::Unknown(id) => format!("unknown_{}", id),
+        }
+    }
+}
+
+/// Payloads start at the offset of multiple of this, to be borrowed from the memory-mapped file.
+pub(super) const SECTION_ALIGNMENT: u64 = 64;
+pub(super) const SECTION_HEADER_SIZE: u64 = 12;
+pub(super) const SECTION_CHECKSUM_SIZE: u64 = 4;
+
+/// (kind: u32, length: u64) in little endian.
+///  - The CRC32 of the payload (u32) follows the payload,
+///    so that the payload can be written without buffering.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct SectionHeader {
+    pub kind: SectionKind,
+    pub length: u64,
+}
+
+fn write_section_header<W: Write>(
+    writer: &mut W,
+    kind: SectionKind,
+    length: u64,
+) -> Result<(), io::Error> {
+    writer.write_all(&kind.id().to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())
+}
+
+pub(super) fn write_section<W: Write>(
+    writer: &mut W,
+    kind: SectionKind,
+    payload: &[u8],
+) -> Result<(), io::Error> {
+    write_section_header(writer, kind, payload.len() as u64)?;
+    writer.write_all(payload)?;
+    writer.write_all(&crc32fast::hash(payload).to_le_bytes())
+}
+
+/// Write the section with the padding section before it to align the payload.
+///  - `writer` must have counted all bytes from the start of the file.
+pub(super) fn write_aligned_section<W: Write>(
+    writer: &mut CountingWriter<W>,
+    kind: SectionKind,
+    payload: &[u8],
+) -> Result<(), io::Error> {
+    write_padding_section(writer)?;
+    write_section(writer, kind, payload)
+}
+
+/// Same as `write_aligned_section`, but the payload of the `length` is streamed by `write_payload`.
+pub(super) fn write_aligned_section_with<W, F>(
+    writer: &mut CountingWriter<W>,
+    kind: SectionKind,
+    length: u64,
+    write_payload: F,
+) -> Result<(), io::Error> where
+    W: Write,
+    F: FnOnce(&mut ChecksumWriter<&mut CountingWriter<W>>) -> Result<(), io::Error>,
+{
+    write_padding_section(writer)?;
+    write_section_header(writer, kind, length)?;
+    let mut payload_writer = ChecksumWriter {
+        inner: &mut *writer,
+        hasher: Hasher::new(),
+        count: 0,
+    };
+    write_payload(&mut payload_writer)?;
+    if payload_writer.count != length {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+            "{} bytes are written to the {} section of {} bytes",
+            payload_writer.count, kind.name(), length,
+        )));
+    }
+    let checksum = payload_writer.hasher.finalize();
+    writer.write_all(&checksum.to_le_bytes())
+}
+
+// Padding section to align the payload of the next section
+fn write_padding_section<W: Write>(writer: &mut CountingWriter<W>) -> Result<(), io::Error> {
+    let payload_offset = writer.count + SECTION_HEADER_SIZE;
+    if payload_offset % SECTION_ALIGNMENT != 0 {
+        // The padding section also has a header and a checksum
+        let padding_section_size = SECTION_HEADER_SIZE + SECTION_CHECKSUM_SIZE;
+        let padding_length = (
+            SECTION_ALIGNMENT - (payload_offset + padding_section_size) % SECTION_ALIGNMENT
+        ) % SECTION_ALIGNMENT;
+        write_section(writer, SectionKind::Padding, &vec![0; padding_length as usize])?;
+    }
+    Ok(())
+}
+
+/// Writer of the payload that computes the checksum.
+pub(super) struct ChecksumWriter<W: Write> {
+    inner: W,
+    hasher: Hasher,
+    count: u64,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.inner.write(buf)?;
+        self.hasher.update(&buf[..count]);
+        self.count += count as u64;
+        Ok(count)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writer counting the written bytes.
+pub(super) struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner, count: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.inner.write(buf)?;
+        self.count += count as u64;
+        Ok(count)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub(super) fn read_section_header<R: Read>(reader: &mut R) -> Result<SectionHeader, ReferenceLoadError> {
+    let mut buffer = [0; SECTION_HEADER_SIZE as usize];
+    reader.read_exact(&mut buffer).map_err(map_eof_to_truncated)?;
+    Ok(SectionHeader {
+        kind: SectionKind::from_id(u32::from_le_bytes(buffer[0..4].try_into().unwrap())),
+        length: u64::from_le_bytes(buffer[4..12].try_into().unwrap()),
+    })
+}
+
+/// Parse the payload of the section, and check the integrity.
+///  - The integrity error precedes the parsing error,
+///    since the parsing error of the corrupted payload is meaningless.
+pub(super) fn read_section<R, T, F>(
+    reader: &mut R,
+    header: SectionHeader,
+    parse: F,
+) -> Result<T, ReferenceLoadError> where
+    R: Read,
+    F: FnOnce(&mut SectionReader<&mut R>) -> Result<T, ReferenceLoadError>,
+{
+    let mut section_reader = SectionReader {
+        inner: reader.by_ref().take(header.length),
+        hasher: Hasher::new(),
+    };
+    let parsed = parse(&mut section_reader);
+    let checksum = section_reader.finish()?;
+    let mut checksum_bytes = [0; SECTION_CHECKSUM_SIZE as usize];
+    reader.read_exact(&mut checksum_bytes).map_err(map_eof_to_truncated)?;
+    if u32::from_le_bytes(checksum_bytes) != checksum {
+        return Err(ReferenceLoadError::ChecksumMismatch(header.kind.name()));
+    }
+    parsed
+}
+
+/// Check the integrity of the section without parsing.
+pub(super) fn skip_section<R: Read>(reader: &mut R, header: SectionHeader) -> Result<(), ReferenceLoadError> {
+    read_section(reader, header, |_| Ok(()))
+}
+
+/// Reader of the payload that computes the checksum.
+pub(super) struct SectionReader<R: Read> {
+    inner: Take<R>,
+    hasher: Hasher,
+}
+
+impl<R: Read> Read for SectionReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.hasher.update(&buf[..count]);
+        Ok(count)
+    }
+}
+
+impl<R: Read> SectionReader<R> {
+    // Checksum of the whole payload
+    fn finish(mut self) -> Result<u32, ReferenceLoadError> {
+        // The unparsed bytes are also included in the checksum
+        io::copy(&mut self, &mut io::sink())?;
+        if self.inner.limit() != 0 {
+            return Err(ReferenceLoadError::Truncated);
+        }
+        Ok(self.hasher.finalize())
+    }
+}
+
+/// Get the payload from the whole bytes of the file, without checking the integrity.
+///  - `offset` is right after the section header.
+pub(super) fn get_payload(
+    bytes: &[u8],
+    offset: usize,
+    header: SectionHeader,
+) -> Result<&[u8], ReferenceLoadError> {
+    let payload = usize::try_from(header.length).ok()
+        .and_then(|length| bytes.get(offset..offset.checked_add(length)?))
+        .ok_or(ReferenceLoadError::Truncated)?;
+    let checksum_offset = offset + payload.len();
+    if bytes.len() < checksum_offset + SECTION_CHECKSUM_SIZE as usize {
+        return Err(ReferenceLoadError::Truncated);
+    }
+    Ok(payload)
+}
+/// Same as `get_payload`, and check the integrity.
+pub(super) fn get_checked_payload(
+    bytes: &[u8],
+    offset: usize,
+    header: SectionHeader,
+) -> Result<&[u8], ReferenceLoadError> {
+    let payload = get_payload(bytes, offset, header)?;
+    let checksum_offset = offset + payload.len();
+    let checksum = &bytes[checksum_offset..checksum_offset + SECTION_CHECKSUM_SIZE as usize];
+    if crc32fast::hash(payload).to_le_bytes() != checksum {
+        return Err(ReferenceLoadError::ChecksumMismatch(header.kind.name()));
+    }
+    Ok(payload)
+}
+
+pub(super) fn map_eof_to_truncated(err: io::Error) -> ReferenceLoadError {
+    if err.kind() == io::ErrorKind::UnexpectedEof {
+        ReferenceLoadError::Truncated
+    } else {
+        ReferenceLoadError::IoError(err)
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read};
+
+use serde::{Deserialize, Serialize};
+
+/// Metadata of a target.
+///  - `description`: text after the ID in the FASTA header.
+///  - `attributes`: key-value pairs (e.g., taxid, species).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TargetMetadata {
+    pub description: String,
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl TargetMetadata {
+    /// Metadata from the FASTA description.
+    ///  - The words formatted as `key=value` are parsed as attributes.
+    ///  - The words are separated by whitespace or `;`, and the brackets around a word are ignored
+    ///    (e.g., `[taxid=562]`).
+    pub fn from_fasta_description(description: &str) -> Self {
+        let attributes = description
+            .split(|c: char| c.is_whitespace() || c == ';')
+            .filter_map(|word| {
+                let word = word.trim_matches(|c| c == '[' || c == ']');
+                let (key, value) = word.split_once('=')?;
+                if key.is_empty() {
+                    None
+                } else {
+                    Some((key.to_string(), value.to_string()))
+                }
+            })
+            .collect();
+        Self {
+            description: description.to_string(),
+            attributes,
+        }
+    }
+    pub fn get_attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(|x| x.as_str())
+    }
+}
+
+/// Rows of the metadata table: (label, attributes)
+pub(crate) type MetadataRows = Vec<(String, Vec<(String, String)>)>;
+
+/// Parse the tab-separated metadata table.
+///  - The first line is the header: the first column is the label, and the others are the keys.
+///  - The empty values are skipped.
+pub(crate) fn parse_metadata_tsv<R: Read>(reader: R) -> Result<MetadataRows, String> {
+    let mut lines = BufReader::new(reader).lines();
+    let header = match lines.next() {
+        Some(line) => line.map_err(|err| err.to_string())?,
+        None => return Err("metadata table is empty".to_string()),
+    };
+    let keys: Vec<&str> = header.trim_end_matches('\r').split('\t').skip(1).collect();
+
+    let mut rows = Vec::new();
+    for (line_index, line) in lines.enumerate() {
+        let line = line.map_err(|err| err.to_string())?;
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        let mut columns = line.split('\t');
+        let label = columns.next().unwrap_or_default().to_string();
+        let values: Vec<&str> = columns.collect();
+        if values.len() != keys.len() {
+            return Err(format!(
+                "line {} of metadata table has {} columns, but the header has {}",
+                line_index + 2,
+                values.len() + 1,
+                keys.len() + 1,
+            ));
+        }
+        let attributes = keys.iter().zip(values)
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        rows.push((label, attributes));
+    }
+    Ok(rows)
+}
+
+/// (Number of attributes of each target, lengths of the strings, concatenated strings)
+///  - The strings of each target: description, key 1, value 1, key 2, value 2, ...
+pub(crate) fn metadata_to_flat(metadata: &[TargetMetadata]) -> (Vec<u32>, Vec<u32>, Vec<u8>) {
+    let mut counts = Vec::with_capacity(metadata.len());
+    let mut lengths = Vec::new();
+    let mut concatenated = Vec::new();
+    for target_metadata in metadata {
+        counts.push(target_metadata.attributes.len() as u32);
+        let strings = std::iter::once(&target_metadata.description)
+            .chain(target_metadata.attributes.iter().flat_map(|(key, value)| [key, value]));
+        for string in strings {
+            lengths.push(string.len() as u32);
+            concatenated.extend_from_slice(string.as_bytes());
+        }
+    }
+    (counts, lengths, concatenated)
+}
+
+pub(crate) fn metadata_from_flat(counts: &[u32], lengths: &[u32], concatenated: &[u8]) -> Option<Vec<TargetMetadata>> {
+    let mut lengths = lengths.iter();
+    let mut offset = 0;
+    let mut next_string = || -> Option<String> {
+        let length = *lengths.next()? as usize;
+        let bytes = concatenated.get(offset..offset + length)?;
+        offset += length;
+        String::from_utf8(bytes.to_vec()).ok()
+    };
+    counts.iter().map(|count| {
+        let description = next_string()?;
+        let attributes = (0..*count).map(|_| {
+            Some((next_string()?, next_string()?))
+        }).collect::<Option<_>>()?;
+        Some(TargetMetadata { description, attributes })
+    }).collect()
+}
@@ -7,14 +7,22 @@ use sigalign_impl::{
     sequence_storage::in_memory::{InMemoryStorage, InMemoryBuffer},
 };
 use crate::results::{
-    QueryAlignment, TargetAlignment, LabeledQueryAlignment, LabeledTargetAlignment,
+    Alignment, QueryAlignment, TargetAlignment, LabeledQueryAlignment, LabeledTargetAlignment,
 };
 
 mod io;
-pub use io::ReferenceLoadError;
+pub use io::{ReferenceLoadError, ReferenceFileHeader};
 mod debug;
+mod summary;
+pub use summary::ReferenceSummary;
 mod builder;
-pub use builder::{ReferenceBuilder, ReferenceBuildError};
+pub use builder::{ReferenceBuilder, ReferenceBuildError, ReferenceBuildOptions};
+mod alphabet;
+pub use alphabet::{Alphabet, StrandError, MASKED_RESIDUE};
+mod soft_mask;
+pub use soft_mask::SoftMaskedRegions;
+mod metadata;
+pub use metadata::TargetMetadata;
 
 pub type DefaultSequenceBuffer = InMemoryBuffer;
 /// A database for multiple target sequences.
@@ -22,6 +30,10 @@ pub type DefaultSequenceBuffer = InMemoryBuffer;
 pub struct Reference {
     raw_reference: RawReference<DynamicLfi, InMemoryStorage>,
     full_sorted_target_indices: Vec<u32>,
+    alphabet: Option<Alphabet>,
+    soft_masked_regions: Option<SoftMaskedRegions>,
+    target_metadata: Option<Vec<TargetMetadata>>,
+    build_options: Option<ReferenceBuildOptions>,
 }
 
 impl AsRef<RawReference<DynamicLfi, InMemoryStorage>> for Reference {
@@ -36,6 +48,14 @@ impl Reference {
     pub fn get_sequence(&self, target_index: u32) -> Option<Vec<u8>> {
         self.as_ref().get_sequence_storage().get_sequence_safely(target_index)
     }
+    /// `true` if the sequences are borrowed from the shared bytes (e.g., memory-mapped file).
+    pub fn is_sequence_shared(&self) -> bool {
+        self.as_ref().get_sequence_storage().is_sequence_shared()
+    }
+    /// `true` if the pattern index is borrowed from the shared bytes (e.g., memory-mapped file).
+    pub fn is_pattern_index_shared(&self) -> bool {
+        self.as_ref().get_pattern_index().is_shared()
+    }
     /// Get the sequence length of the target. None if the target index is out of range.
     pub fn get_sequence_length(&self, target_index: u32) -> Option<u32> {
         self.as_ref().get_sequence_storage().get_sequence_length_safely(target_index)
@@ -56,6 +76,36 @@ impl Reference {
     pub fn get_total_length(&self) -> u32 {
         self.as_ref().get_sequence_storage().get_total_length()
     }
+    /// Get the alphabet. None if the reference is built without the alphabet.
+    pub fn get_alphabet(&self) -> Option<Alphabet> {
+        self.alphabet
+    }
+    /// Get the options used to build. None if the reference is not built by `ReferenceBuilder`.
+    pub fn get_build_options(&self) -> Option<&ReferenceBuildOptions> {
+        self.build_options.as_ref()
+    }
+    /// Get the soft-masked regions. None if the reference is built without the soft-masking.
+    pub fn get_soft_masked_regions(&self) -> Option<&SoftMaskedRegions> {
+        self.soft_masked_regions.as_ref()
+    }
+    /// Get the metadata of the target.
+    /// None if the reference is built without the metadata or the target index is out of range.
+    pub fn get_target_metadata(&self, target_index: u32) -> Option<&TargetMetadata> {
+        self.target_metadata.as_ref()?.get(target_index as usize)
+    }
+    /// Set the metadata of all targets.
+    ///  - Error if the number of metadata is not the same as the number of targets.
+    pub fn set_target_metadata(&mut self, target_metadata: Vec<TargetMetadata>) -> Result<(), ReferenceBuildError> {
+        if target_metadata.len() != self.get_num_targets() as usize {
+            return Err(ReferenceBuildError::InvalidMetadata(format!(
+                "{} metadata for {} targets",
+                target_metadata.len(),
+                self.get_num_targets(),
+            )));
+        }
+        self.target_metadata = Some(target_metadata);
+        Ok(())
+    }
     /// Get estimated size in bytes. (This is an estimate, not the exact size.)
     pub fn get_estimated_size_in_bytes(&self) -> usize {
         self.as_ref().serialized_size()
@@ -71,7 +121,27 @@ impl Reference {
         &self.full_sorted_target_indices
     }
 
+    /* Strand */
+    /// Reverse complement of the query in the alphabet of the reference.
+    ///  - DNA is assumed if the alphabet is not set.
+    ///  - Error for the protein reference.
+    pub fn reverse_complement(&self, query: &[u8]) -> Result<Vec<u8>, StrandError> {
+        self.alphabet.unwrap_or(Alphabet::Dna).reverse_complement(query)
+    }
+    /// `false` for the protein reference.
+    #[allow(clippy::unnecessary_map_or)] // `is_none_or` needs a newer compiler
+    pub fn has_complementary_strand(&self) -> bool {
+        self.alphabet.map_or(true, |x| x.has_complementary_strand())
+    }
+
     /* Manipulate Results */
+    /// Count the soft-masked bases in the target range of the alignment.
+    ///  - 0 if the reference is built without the soft-masking.
+    pub fn count_soft_masked_bases(&self, target_index: u32, alignment: &Alignment) -> u32 {
+        self.soft_masked_regions.as_ref().map_or(0, |regions| {
+            regions.count_in_range(target_index, alignment.position.target)
+        })
+    }
     /// Label the query alignment.
     pub fn label_query_alignment(&self, query_alignment: QueryAlignment) -> LabeledQueryAlignment {
         let labeled_target_alignments = query_alignment.0.into_iter().map(|x| {
@@ -103,6 +173,29 @@ impl From<RawReference<DynamicLfi, InMemoryStorage>> for Reference {
         Self {
             raw_reference,
             full_sorted_target_indices,
+            alphabet: None,
+            soft_masked_regions: None,
+            target_metadata: None,
+            build_options: None,
         }
     }
 }
+
+impl Reference {
+    pub(crate) fn with_alphabet(mut self, alphabet: Option<Alphabet>) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+    pub(crate) fn with_build_options(mut self, build_options: Option<ReferenceBuildOptions>) -> Self {
+        self.build_options = build_options;
+        self
+    }
+    pub(crate) fn with_soft_masked_regions(mut self, soft_masked_regions: Option<SoftMaskedRegions>) -> Self {
+        self.soft_masked_regions = soft_masked_regions;
+        self
+    }
+    pub(crate) fn with_target_metadata(mut self, target_metadata: Option<Vec<TargetMetadata>>) -> Self {
+        self.target_metadata = target_metadata;
+        self
+    }
+}
@@ -0,0 +1,54 @@
+use super::MASKED_RESIDUE;
+
+/// Soft-masked (lowercase in the input) regions of the targets.
+///  - The regions are (start, end) in each target, sorted and not overlapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoftMaskedRegions {
+    regions_by_target: Vec<Vec<(u32, u32)>>,
+}
+
+impl SoftMaskedRegions {
+    pub(crate) fn new(regions_by_target: Vec<Vec<(u32, u32)>>) -> Self {
+        Self { regions_by_target }
+    }
+    /// Regions of the target. None if the target index is out of range.
+    pub fn of_target(&self, target_index: u32) -> Option<&[(u32, u32)]> {
+        self.regions_by_target.get(target_index as usize).map(|x| x.as_slice())
+    }
+    /// Number of the soft-masked bases in the `range` (start, end) of the target.
+    pub fn count_in_range(&self, target_index: u32, range: (u32, u32)) -> u32 {
+        let Some(regions) = self.of_target(target_index) else {
+            return 0
+        };
+        let first = regions.partition_point(|region| region.1 <= range.0);
+        regions[first..].iter()
+            .take_while(|region| region.0 < range.1)
+            .map(|region| region.1.min(range.1) - region.0.max(range.0))
+            .sum()
+    }
+    /// Replace the regions with `MASKED_RESIDUE` in the sequence for the pattern index.
+    pub(crate) fn mask_sequence_for_index(&self, concatenated_sequence: &mut [u8], target_boundaries: &[u32]) {
+        self.regions_by_target.iter().zip(target_boundaries).for_each(|(regions, target_start)| {
+            regions.iter().for_each(|(start, end)| {
+                concatenated_sequence[(target_start + start) as usize..(target_start + end) as usize]
+                    .fill(MASKED_RESIDUE);
+            });
+        });
+    }
+    /// (Number of regions of each target, flattened starts and ends)
+    pub(crate) fn to_flat(&self) -> (Vec<u32>, Vec<u32>) {
+        let counts = self.regions_by_target.iter().map(|x| x.len() as u32).collect();
+        let flattened = self.regions_by_target.iter().flatten().flat_map(|(start, end)| [*start, *end]).collect();
+        (counts, flattened)
+    }
+    pub(crate) fn from_flat(counts: &[u32], flattened: &[u32]) -> Option<Self> {
+        if counts.iter().map(|x| *x as usize * 2).sum::<usize>() != flattened.len() {
+            return None
+        }
+        let mut pairs = flattened.chunks_exact(2).map(|x| (x[0], x[1]));
+        let regions_by_target = counts.iter().map(|count| {
+            pairs.by_ref().take(*count as usize).collect()
+        }).collect();
+        Some(Self { regions_by_target })
+    }
+}
@@ -0,0 +1,48 @@
+use super::{Reference, Alphabet, ReferenceBuildOptions};
+
+/// Overview of the `Reference`, including the choices made when building.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceSummary {
+    pub num_targets: u32,
+    pub total_length: u32,
+    pub alphabet: Option<Alphabet>,
+    /// None if the reference is loaded from the legacy file.
+    pub build_options: Option<ReferenceBuildOptions>,
+    /// None if the reference is built without the soft-masking.
+    pub num_soft_masked_bases: Option<u64>,
+    pub has_target_metadata: bool,
+    /// Type of the pattern index chosen by the number of characters (e.g., `Lfi32B2V64`).
+    pub pattern_index_type: &'static str,
+    pub bits_per_character: u32,
+    /// True if the sequences are borrowed from the memory-mapped file.
+    pub is_sequence_shared: bool,
+    /// True if the pattern index is borrowed from the memory-mapped file.
+    pub is_pattern_index_shared: bool,
+    pub estimated_size_in_bytes: usize,
+}
+
+impl Reference {
+    /// Get the summary of the reference.
+    pub fn get_summary(&self) -> ReferenceSummary {
+        let pattern_index = self.as_ref().get_pattern_index();
+        let num_soft_masked_bases = self.soft_masked_regions.as_ref().map(|regions| {
+            (0..self.get_num_targets())
+                .flat_map(|target_index| regions.of_target(target_index).unwrap_or_default())
+                .map(|(start, end)| (end - start) as u64)
+                .sum()
+        });
+        ReferenceSummary {
+            num_targets: self.get_num_targets(),
+            total_length: self.get_total_length(),
+            alphabet: self.alphabet,
+            build_options: self.build_options.clone(),
+            num_soft_masked_bases,
+            has_target_metadata: self.target_metadata.is_some(),
+            pattern_index_type: pattern_index.type_name(),
+            bits_per_character: pattern_index.bits_per_character(),
+            is_sequence_shared: self.is_sequence_shared(),
+            is_pattern_index_shared: self.is_pattern_index_shared(),
+            estimated_size_in_bytes: self.get_estimated_size_in_bytes(),
+        }
+    }
+}
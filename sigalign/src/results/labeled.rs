@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::Alignment;
+use crate::reference::{Reference, TargetMetadata};
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,3 +21,11 @@ pub struct LabeledTargetAlignment {
     #[cfg_attr(feature = "short_key", serde(rename = "aln"))]
     pub alignments: Vec<Alignment>,
 }
+
+impl LabeledTargetAlignment {
+    /// Metadata of the target in the `reference`. None if the reference has no metadata.
+    ///  - Looked up by the index, so it must be the reference used for the alignment.
+    pub fn metadata<'a>(&self, reference: &'a Reference) -> Option<&'a TargetMetadata> {
+        reference.get_target_metadata(self.index)
+    }
+}
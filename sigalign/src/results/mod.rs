@@ -49,6 +49,13 @@ pub use sigalign_core::results::{
     AlignmentPosition,
     AlignmentOperations,
     AlignmentOperation,
+    AlignmentStats,
+    Cigar,
+    CigarError,
+    AlignmentOrder,
+    DeduplicationStrategy,
+    DeduplicationPriority,
+    MappingQualityEstimator,
 };
 // Export labeled results
 pub use labeled::{
@@ -56,4 +63,24 @@ pub use labeled::{
     LabeledTargetAlignment,
 };
 
+/// Compact binary encoding and streams of the results.
+pub mod binary {
+    pub use sigalign_core::results::binary::*;
+    use super::LabeledQueryAlignment;
+
+    /// Writer of `LabeledQueryAlignment` stream.
+    pub type LabeledQueryAlignmentWriter<W> = BinaryResultWriter<W, LabeledQueryAlignment>;
+    /// Reader of `LabeledQueryAlignment` stream.
+    pub type LabeledQueryAlignmentReader<R> = BinaryResultReader<R, LabeledQueryAlignment>;
+}
+
 mod count_alignments;
+
+mod paired;
+pub use paired::{
+    PairOrientation,
+    PairingOptions,
+    MateAlignment,
+    ConcordantPair,
+    ReadPairAlignment,
+};
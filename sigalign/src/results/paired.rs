@@ -0,0 +1,182 @@
+use super::{
+    QueryAlignment,
+    Alignment,
+};
+
+/// Expected relative orientation of the mates in a concordant pair.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairOrientation {
+    /// Mates face each other (e.g., Illumina paired-end).
+    ForwardReverse,
+    /// Mates face away from each other (e.g., mate-pair libraries).
+    ReverseForward,
+    /// Mates are on the same strand, the first mate upstream.
+    ForwardForward,
+}
+
+/// Criteria for the concordant pair.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingOptions {
+    pub orientation: PairOrientation,
+    pub min_insert_size: u32,
+    pub max_insert_size: u32,
+}
+
+/// Alignment of one mate with the target and the strand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MateAlignment {
+    pub target_index: u32,
+    pub is_forward: bool,
+    pub alignment: Alignment,
+}
+
+/// Pair of mate alignments satisfying the `PairingOptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcordantPair {
+    /// Index of `ReadPairAlignment::first`
+    pub first: usize,
+    /// Index of `ReadPairAlignment::second`
+    pub second: usize,
+    /// Length from the leftmost to the rightmost aligned base of the pair.
+    pub insert_size: u32,
+    /// Sum of the penalties of the mates.
+    pub penalty: u32,
+}
+
+/// Alignments of a read pair.
+#[derive(Debug, Clone)]
+pub struct ReadPairAlignment {
+    /// All alignments of the first mate.
+    pub first: Vec<MateAlignment>,
+    /// All alignments of the second mate.
+    pub second: Vec<MateAlignment>,
+    /// Lengths of the (first, second) mates to flip the query positions of the reverse strand.
+    pub mate_lengths: (u32, u32),
+    /// Sorted by the penalty, then by the insert size.
+    pub concordant_pairs: Vec<ConcordantPair>,
+}
+
+impl Default for PairingOptions {
+    /// Forward-reverse with insert size of 0 to 1000.
+    fn default() -> Self {
+        Self {
+            orientation: PairOrientation::ForwardReverse,
+            min_insert_size: 0,
+            max_insert_size: 1000,
+        }
+    }
+}
+
+impl PairingOptions {
+    pub fn new(orientation: PairOrientation, min_insert_size: u32, max_insert_size: u32) -> Self {
+        Self {
+            orientation,
+            min_insert_size,
+            max_insert_size,
+        }
+    }
+    /// Insert size if the two mate alignments are concordant.
+    pub fn concordant_insert_size(&self, first: &MateAlignment, second: &MateAlignment) -> Option<u32> {
+        if first.target_index != second.target_index {
+            return None;
+        }
+        let first_start = first.alignment.position.target.0;
+        let second_start = second.alignment.position.target.0;
+        let is_expected_orientation = match (self.orientation, first.is_forward, second.is_forward) {
+            (PairOrientation::ForwardReverse, true, false) => first_start <= second_start,
+            (PairOrientation::ForwardReverse, false, true) => second_start <= first_start,
+            (PairOrientation::ReverseForward, false, true) => first_start <= second_start,
+            (PairOrientation::ReverseForward, true, false) => second_start <= first_start,
+            (PairOrientation::ForwardForward, true, true) => first_start <= second_start,
+            (PairOrientation::ForwardForward, false, false) => second_start <= first_start,
+            _ => false,
+        };
+        if !is_expected_orientation {
+            return None;
+        }
+        let insert_size = first.insert_size_with(second);
+        if self.min_insert_size <= insert_size && insert_size <= self.max_insert_size {
+            Some(insert_size)
+        } else {
+            None
+        }
+    }
+}
+
+impl MateAlignment {
+    /// Length from the leftmost to the rightmost aligned base of the two alignments.
+    /// The targets are not checked.
+    pub fn insert_size_with(&self, other: &Self) -> u32 {
+        let start = self.alignment.position.target.0.min(other.alignment.position.target.0);
+        let end = self.alignment.position.target.1.max(other.alignment.position.target.1);
+        end - start
+    }
+}
+
+impl ReadPairAlignment {
+    /// Pair the alignments of the mates.
+    ///  - `*_reverse` is the result of the reverse complementary sequence of the mate.
+    ///  - `mate_lengths` are the lengths of the original (first, second) mates.
+    pub fn new(
+        first_forward: QueryAlignment,
+        first_reverse: QueryAlignment,
+        second_forward: QueryAlignment,
+        second_reverse: QueryAlignment,
+        mate_lengths: (u32, u32),
+        options: &PairingOptions,
+    ) -> Self {
+        let first = mate_alignments(first_forward, first_reverse);
+        let second = mate_alignments(second_forward, second_reverse);
+
+        let mut concordant_pairs = Vec::new();
+        for (first_index, first_mate) in first.iter().enumerate() {
+            for (second_index, second_mate) in second.iter().enumerate() {
+                if let Some(insert_size) = options.concordant_insert_size(first_mate, second_mate) {
+                    concordant_pairs.push(ConcordantPair {
+                        first: first_index,
+                        second: second_index,
+                        insert_size,
+                        penalty: first_mate.alignment.penalty + second_mate.alignment.penalty,
+                    });
+                }
+            }
+        }
+        concordant_pairs.sort_by(|a, b| {
+            a.penalty.cmp(&b.penalty)
+                .then(a.insert_size.cmp(&b.insert_size))
+                .then(a.first.cmp(&b.first))
+                .then(a.second.cmp(&b.second))
+        });
+
+        Self {
+            first,
+            second,
+            mate_lengths,
+            concordant_pairs,
+        }
+    }
+    /// Concordant pair with the lowest penalty.
+    pub fn best_pair(&self) -> Option<&ConcordantPair> {
+        self.concordant_pairs.first()
+    }
+    /// Mate alignments of the pair.
+    pub fn mates_of(&self, pair: &ConcordantPair) -> (&MateAlignment, &MateAlignment) {
+        (&self.first[pair.first], &self.second[pair.second])
+    }
+    pub fn is_concordant(&self) -> bool {
+        !self.concordant_pairs.is_empty()
+    }
+}
+
+fn mate_alignments(forward: QueryAlignment, reverse: QueryAlignment) -> Vec<MateAlignment> {
+    [(forward, true), (reverse, false)].into_iter().flat_map(|(query_alignment, is_forward)| {
+        query_alignment.0.into_iter().flat_map(move |target_alignment| {
+            let target_index = target_alignment.index;
+            target_alignment.alignments.into_iter().map(move |alignment| MateAlignment {
+                target_index,
+                is_forward,
+                alignment,
+            })
+        })
+    }).collect()
+}
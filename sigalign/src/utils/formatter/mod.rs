@@ -1,3 +1,4 @@
 mod to_json;
+mod to_binary;
 mod to_sam;
 pub use to_sam::SamFormatter;
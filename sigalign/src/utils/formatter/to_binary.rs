@@ -0,0 +1,38 @@
+use std::io::{Read, Write, Error};
+use sigalign_core::{
+    impl_translate_between_binary,
+    results::binary::{BinaryEncode, BinaryRecord},
+};
+
+use crate::results::{
+    LabeledQueryAlignment,
+    LabeledTargetAlignment,
+};
+
+impl BinaryEncode for LabeledQueryAlignment {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.0.encode_to(writer)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        Ok(Self(Vec::decode_from(reader)?))
+    }
+}
+impl BinaryRecord for LabeledQueryAlignment {
+    const RECORD_TYPE: u8 = 2;
+}
+impl BinaryEncode for LabeledTargetAlignment {
+    fn encode_to<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        self.index.encode_to(writer)?;
+        self.label.encode_to(writer)?;
+        self.alignments.encode_to(writer)
+    }
+    fn decode_from<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let index = u32::decode_from(reader)?;
+        let label = String::decode_from(reader)?;
+        let alignments = Vec::decode_from(reader)?;
+        Ok(Self { index, label, alignments })
+    }
+}
+
+impl_translate_between_binary!(LabeledQueryAlignment);
+impl_translate_between_binary!(LabeledTargetAlignment);
@@ -2,21 +2,41 @@ use std::io::{self, Write};
 
 use crate::{
     results::{
-        AlignmentOperation, LabeledQueryAlignment, QueryAlignment
+        Alignment, AlignmentOperations, LabeledQueryAlignment, MappingQualityEstimator, QueryAlignment,
+        MateAlignment, ReadPairAlignment,
     }, Reference
 };
 
+// SAM flags for the read pair
+const FLAG_PAIRED: u16 = 0x1;
+const FLAG_PROPER_PAIR: u16 = 0x2;
+const FLAG_UNMAPPED: u16 = 0x4;
+const FLAG_MATE_UNMAPPED: u16 = 0x8;
+const FLAG_REVERSE: u16 = 0x10;
+const FLAG_MATE_REVERSE: u16 = 0x20;
+const FLAG_FIRST_MATE: u16 = 0x40;
+const FLAG_SECOND_MATE: u16 = 0x80;
+const FLAG_SECONDARY: u16 = 0x100;
+
 /// A formatter that writes SAM records.
 #[derive(Clone)]
 pub struct SamFormatter {
     itoa_buffer: itoa::Buffer,
+    mapping_quality_estimator: Option<MappingQualityEstimator>,
 }
 impl SamFormatter {
+    /// MAPQ is not assigned (255) by default.
     pub fn new() -> Self {
         Self {
             itoa_buffer: itoa::Buffer::new(),
+            mapping_quality_estimator: None,
         }
     }
+    /// Write the MAPQ estimated by the `estimator`.
+    pub fn with_mapping_quality_estimator(mut self, estimator: MappingQualityEstimator) -> Self {
+        self.mapping_quality_estimator = Some(estimator);
+        self
+    }
     pub fn write_hd_header(&self, writer: &mut impl Write) -> Result<(), io::Error> {
         writer.write_all(b"@HD\tVN:1.6\tSO:unsorted\n")?;
         Ok(())
@@ -30,6 +50,10 @@ impl SamFormatter {
         writer.write_all(format!("@SQ\tSN:{}\tLN:{}\n", sn, ln).as_bytes())?;
         Ok(())
     }
+    /// Write the alignments of one strand.
+    ///  - MAPQ is estimated among the alignments of this strand only.
+    ///  - Every record is primary, since the best alignment of the read is not known from one strand.
+    #[deprecated(note = "the other strand of the read is not considered; use `write_read_alignment`")]
     pub fn write_query_alignment(
         &mut self,
         writer: &mut impl Write,
@@ -38,50 +62,34 @@ impl SamFormatter {
         is_forward: bool,
         reference: &Reference, // To parse the target label
     ) -> Result<(), io::Error> {
-        for target_alignment in query_alignment.0.iter() {
-            let target_label = reference.get_label_str(target_alignment.index).unwrap_or_default();
-            for alignment in target_alignment.alignments.iter() {
-                // (1) QNAME
-                writer.write(qname.as_bytes())?;
-                // (2) FLAG
-                writer.write(if is_forward { b"\t0\t" } else { b"\t16\t" })?;
-                // (3) RNAME
-                writer.write(target_label.as_bytes())?;
-                writer.write(b"\t")?;
-                // (4) POS
-                //   SAM is 1-based, so add 1 to the 0-based position.
-                writer.write(
-                    self.itoa_buffer.format(alignment.position.target.0 + 1).as_bytes()
-                )?;
-                // (5) MAPQ: 255 to indicate the score is not assigned
-                writer.write(b"\t255\t")?;
-                // (6) CIGAR
-                for op in alignment.operations.iter() {
-                    writer.write(
-                        self.itoa_buffer.format(op.count).as_bytes()
-                    )?;
-                    writer.write(
-                        match op.operation {
-                            AlignmentOperation::Match => b"=",
-                            AlignmentOperation::Subst => b"X",
-                            AlignmentOperation::Insertion => b"I",
-                            AlignmentOperation::Deletion => b"D",
-                        }
-                    )?;
-                }
-                // (7) RNEXT
-                // (8) PNEXT
-                // (9) TLEN
-                // (10) SEQ
-                // (11) QUAL
-                // For a minimal single-end record (no mate information, no sequence/qual data)
-                writer.write(b"\t*\t0\t0\t*\t*\n")?;
-            }
+        let records = Self::records(query_alignment, is_forward, reference);
+        self.write_single_end_records(writer, &records, qname, None, None)
+    }
+    /// Write the alignments of both strands of a read.
+    ///  - MAPQ is estimated over the alignments of both strands together,
+    ///    so the hits on the forward strand compete with the hits on the reverse strand.
+    ///    The query positions of the `reverse` are flipped with the `query_length` to be compared.
+    ///  - Only the record of the best alignment is primary, and the others are secondary (0x100).
+    pub fn write_read_alignment(
+        &mut self,
+        writer: &mut impl Write,
+        forward: Option<&QueryAlignment>,
+        reverse: Option<&QueryAlignment>,
+        qname: &str,
+        query_length: u32,
+        reference: &Reference, // To parse the target label
+    ) -> Result<(), io::Error> {
+        let mut records = Vec::new();
+        if let Some(forward) = forward {
+            records.extend(Self::records(forward, true, reference));
         }
-
-        Ok(())
+        if let Some(reverse) = reverse {
+            records.extend(Self::records(reverse, false, reference));
+        }
+        self.write_single_end_records(writer, &records, qname, Some(query_length), None)
     }
-    // TODO: Remove duplicated code
+    /// Same as `write_query_alignment`, but with the labeled alignments.
+    #[deprecated(note = "the other strand of the read is not considered; use `write_labeled_read_alignment_with_hclip`")]
     pub fn write_labeled_query_alignment(
         &mut self,
         writer: &mut impl Write,
@@ -89,49 +97,11 @@ impl SamFormatter {
         qname: &str,
         is_forward: bool,
     ) -> Result<(), io::Error> {
-        for labeled_target_alignment in labeled_query_alignment.0.iter() {
-            for alignment in labeled_target_alignment.alignments.iter() {
-                // (1) QNAME
-                writer.write(qname.as_bytes())?;
-                // (2) FLAG
-                writer.write(if is_forward { b"\t0\t" } else { b"\t16\t" })?;
-                // (3) RNAME
-                writer.write(labeled_target_alignment.label.as_bytes())?;
-                writer.write(b"\t")?;
-                // (4) POS
-                //   SAM is 1-based, so add 1 to the 0-based position.
-                writer.write(
-                    self.itoa_buffer.format(alignment.position.target.0 + 1).as_bytes()
-                )?;
-                // (5) MAPQ: 255 to indicate the score is not assigned
-                writer.write(b"\t255\t")?;
-                // (6) CIGAR
-                for op in alignment.operations.iter() {
-                    writer.write(
-                        self.itoa_buffer.format(op.count).as_bytes()
-                    )?;
-                    writer.write(
-                        match op.operation {
-                            AlignmentOperation::Match => b"=",
-                            AlignmentOperation::Subst => b"X",
-                            AlignmentOperation::Insertion => b"I",
-                            AlignmentOperation::Deletion => b"D",
-                        }
-                    )?;
-                }
-                // (7) RNEXT
-                // (8) PNEXT
-                // (9) TLEN
-                // (10) SEQ
-                // (11) QUAL
-                // For a minimal single-end record (no mate information, no sequence/qual data)
-                writer.write(b"\t*\t0\t0\t*\t*\n")?;
-            }
-        }
-
-        Ok(())
+        let records = Self::labeled_records(labeled_query_alignment, is_forward);
+        self.write_single_end_records(writer, &records, qname, None, None)
     }
-    // TODO: Remove duplicated code
+    /// Same as `write_labeled_query_alignment`, but the unaligned ends of the query are hard-clipped.
+    #[deprecated(note = "the other strand of the read is not considered; use `write_labeled_read_alignment_with_hclip`")]
     pub fn write_labeled_query_alignment_with_hclip(
         &mut self,
         writer: &mut impl Write,
@@ -140,61 +110,328 @@ impl SamFormatter {
         is_forward: bool,
         query_length: u32,
     ) -> Result<(), io::Error> {
-        for labeled_target_alignment in labeled_query_alignment.0.iter() {
-            for alignment in labeled_target_alignment.alignments.iter() {
+        let records = Self::labeled_records(labeled_query_alignment, is_forward);
+        self.write_single_end_records(writer, &records, qname, None, Some(query_length))
+    }
+    /// Write the labeled alignments of both strands of a read with the hard-clips.
+    ///  - MAPQ and the secondary flags are decided over both strands together (as `write_read_alignment`).
+    pub fn write_labeled_read_alignment_with_hclip(
+        &mut self,
+        writer: &mut impl Write,
+        forward: &LabeledQueryAlignment,
+        reverse: Option<&LabeledQueryAlignment>,
+        qname: &str,
+        query_length: u32,
+    ) -> Result<(), io::Error> {
+        let mut records = Self::labeled_records(forward, true);
+        if let Some(reverse) = reverse {
+            records.extend(Self::labeled_records(reverse, false));
+        }
+        self.write_single_end_records(writer, &records, qname, Some(query_length), Some(query_length))
+    }
+    fn records<'a>(
+        query_alignment: &'a QueryAlignment,
+        is_forward: bool,
+        reference: &'a Reference,
+    ) -> Vec<(bool, &'a str, &'a Alignment)> {
+        query_alignment.0.iter().flat_map(|target_alignment| {
+            let target_label = reference.get_label_str(target_alignment.index).unwrap_or_default();
+            target_alignment.alignments.iter().map(move |alignment| (is_forward, target_label, alignment))
+        }).collect()
+    }
+    fn labeled_records(
+        labeled_query_alignment: &LabeledQueryAlignment,
+        is_forward: bool,
+    ) -> Vec<(bool, &str, &Alignment)> {
+        labeled_query_alignment.0.iter().flat_map(|labeled_target_alignment| {
+            labeled_target_alignment.alignments.iter().map(move |alignment| {
+                (is_forward, labeled_target_alignment.label.as_str(), alignment)
+            })
+        }).collect()
+    }
+    // Records of (is_forward, target label, alignment) of one read.
+    //  - With the `read_length`, the records are all the alignments of both strands of the read:
+    //    MAPQ is estimated over both strands, and only the record of the best alignment
+    //    (the least penalty) is primary while the others are flagged as secondary (0x100).
+    //    Without it, the records are of one strand and all primary.
+    //  - With the `hclip_length`, the unaligned ends are hard-clipped.
+    fn write_single_end_records(
+        &mut self,
+        writer: &mut impl Write,
+        records: &[(bool, &str, &Alignment)],
+        qname: &str,
+        read_length: Option<u32>,
+        hclip_length: Option<u32>,
+    ) -> Result<(), io::Error> {
+        let mut mapping_qualities = self.mapping_quality_estimator.as_ref().map(|estimator| {
+            let alignments = records.iter().map(|(is_forward, _, alignment)| (*is_forward, *alignment));
+            match read_length {
+                Some(read_length) => estimator.estimate_on_both_strands(alignments, read_length),
+                None => estimator.estimate(alignments.map(|(_, alignment)| alignment)),
+            }
+        }).into_iter().flatten();
+        let primary_index = match read_length {
+            Some(_) => records.iter().enumerate()
+                .min_by_key(|(_, (_, _, alignment))| alignment.penalty)
+                .map(|(index, _)| index),
+            None => None,
+        };
+        for (index, (is_forward, target_label, alignment)) in records.iter().enumerate() {
+            let mut flag = if *is_forward { 0 } else { FLAG_REVERSE };
+            if read_length.is_some() && primary_index != Some(index) {
+                flag |= FLAG_SECONDARY;
+            }
+            // (1) QNAME
+            writer.write_all(qname.as_bytes())?;
+            // (2) FLAG
+            writer.write_all(b"\t")?;
+            writer.write_all(self.itoa_buffer.format(flag).as_bytes())?;
+            writer.write_all(b"\t")?;
+            // (3) RNAME
+            writer.write_all(target_label.as_bytes())?;
+            writer.write_all(b"\t")?;
+            // (4) POS
+            //   SAM is 1-based, so add 1 to the 0-based position.
+            writer.write_all(
+                self.itoa_buffer.format(alignment.position.target.0 + 1).as_bytes()
+            )?;
+            // (5) MAPQ
+            self.write_mapq(writer, mapping_qualities.next())?;
+            // (6) CIGAR
+            match hclip_length {
+                Some(query_length) => self.write_cigar_with_hclip(writer, alignment, query_length)?,
+                None => self.write_cigar(writer, &alignment.operations)?,
+            }
+            // (7) RNEXT
+            // (8) PNEXT
+            // (9) TLEN
+            // (10) SEQ
+            // (11) QUAL
+            // For a minimal single-end record (no mate information, no sequence/qual data)
+            writer.write_all(b"\t*\t0\t0\t*\t*\n")?;
+        }
+
+        Ok(())
+    }
+    /// Write the alignments of both mates with the mate fields (RNEXT, PNEXT, TLEN).
+    ///  - The mate of an alignment is the partner in the best concordant pair including it,
+    ///    or the best alignment of the other mate if there is no such pair.
+    ///  - If only one mate is aligned, an unmapped record is written for the other mate.
+    ///  - Each mate has one primary record: the mate in the best concordant pair,
+    ///    or the best alignment of the mate if there is no concordant pair.
+    ///    The other records are flagged as secondary (0x100).
+    pub fn write_read_pair_alignment(
+        &mut self,
+        writer: &mut impl Write,
+        read_pair_alignment: &ReadPairAlignment,
+        qname: &str,
+        reference: &Reference, // To parse the target label
+    ) -> Result<(), io::Error> {
+        for is_first in [true, false] {
+            let (mates, other_mates) = if is_first {
+                (&read_pair_alignment.first, &read_pair_alignment.second)
+            } else {
+                (&read_pair_alignment.second, &read_pair_alignment.first)
+            };
+            let mate_flag = if is_first { FLAG_PAIRED | FLAG_FIRST_MATE } else { FLAG_PAIRED | FLAG_SECOND_MATE };
+            let primary_index = primary_mate_index(read_pair_alignment, is_first);
+            let best_of_other_mates = primary_mate_index(read_pair_alignment, !is_first)
+                .map(|index| &other_mates[index]);
+
+            if mates.is_empty() {
+                if let Some(other) = best_of_other_mates {
+                    self.write_unmapped_mate_record(writer, qname, mate_flag, other, reference)?;
+                }
+                continue;
+            }
+
+            let mate_length = if is_first {
+                read_pair_alignment.mate_lengths.0
+            } else {
+                read_pair_alignment.mate_lengths.1
+            };
+            let mut mapping_qualities = self.mapping_quality_estimator.as_ref().map(|estimator| {
+                estimator.estimate_on_both_strands(mates.iter().map(|x| (x.is_forward, &x.alignment)), mate_length)
+            }).into_iter().flatten();
+            for (index, mate) in mates.iter().enumerate() {
+                let concordant_partner = read_pair_alignment.concordant_pairs.iter().find_map(|pair| {
+                    let (this_index, other_index) = if is_first {
+                        (pair.first, pair.second)
+                    } else {
+                        (pair.second, pair.first)
+                    };
+                    (this_index == index).then(|| &other_mates[other_index])
+                });
+                let (partner, mut flag) = match concordant_partner {
+                    Some(partner) => (Some(partner), mate_flag | FLAG_PROPER_PAIR),
+                    None => (best_of_other_mates, mate_flag),
+                };
+                if !mate.is_forward {
+                    flag |= FLAG_REVERSE;
+                }
+                if primary_index != Some(index) {
+                    flag |= FLAG_SECONDARY;
+                }
+                match partner {
+                    Some(partner) if !partner.is_forward => flag |= FLAG_MATE_REVERSE,
+                    None => flag |= FLAG_MATE_UNMAPPED,
+                    _ => {},
+                }
+                let target_label = reference.get_label_str(mate.target_index).unwrap_or_default();
+
                 // (1) QNAME
-                writer.write(qname.as_bytes())?;
+                writer.write_all(qname.as_bytes())?;
                 // (2) FLAG
-                writer.write(if is_forward { b"\t0\t" } else { b"\t16\t" })?;
+                writer.write_all(b"\t")?;
+                writer.write_all(self.itoa_buffer.format(flag).as_bytes())?;
+                writer.write_all(b"\t")?;
                 // (3) RNAME
-                writer.write(labeled_target_alignment.label.as_bytes())?;
-                writer.write(b"\t")?;
+                writer.write_all(target_label.as_bytes())?;
+                writer.write_all(b"\t")?;
                 // (4) POS
-                //   SAM is 1-based, so add 1 to the 0-based position.
-                writer.write(
-                    self.itoa_buffer.format(alignment.position.target.0 + 1).as_bytes()
+                writer.write_all(
+                    self.itoa_buffer.format(mate.alignment.position.target.0 + 1).as_bytes()
                 )?;
-                // (5) MAPQ: 255 to indicate the score is not assigned
-                writer.write(b"\t255\t")?;
+                // (5) MAPQ
+                self.write_mapq(writer, mapping_qualities.next())?;
                 // (6) CIGAR
-                let lclip_size = alignment.position.query.0;
-                if lclip_size != 0 {
-                    writer.write(
-                        self.itoa_buffer.format(lclip_size).as_bytes()
-                    )?;
-                    writer.write(b"H")?;
-                }
-                for op in alignment.operations.iter() {
-                    writer.write(
-                        self.itoa_buffer.format(op.count).as_bytes()
-                    )?;
-                    writer.write(
-                        match op.operation {
-                            AlignmentOperation::Match => b"=",
-                            AlignmentOperation::Subst => b"X",
-                            AlignmentOperation::Insertion => b"I",
-                            AlignmentOperation::Deletion => b"D",
-                        }
-                    )?;
-                }
-                let rclip_size = query_length - alignment.position.query.1;
-                if rclip_size != 0 {
-                    writer.write(
-                        self.itoa_buffer.format(rclip_size).as_bytes()
-                    )?;
-                    writer.write(b"H")?;
-                }
-
+                self.write_cigar(writer, &mate.alignment.operations)?;
                 // (7) RNEXT
                 // (8) PNEXT
                 // (9) TLEN
+                match partner {
+                    Some(partner) => {
+                        self.write_mate_fields(writer, mate, partner, is_first, reference)?;
+                    },
+                    None => {
+                        writer.write_all(b"\t*\t0\t0")?;
+                    },
+                }
                 // (10) SEQ
                 // (11) QUAL
-                // For a minimal single-end record (no mate information, no sequence/qual data)
-                writer.write(b"\t*\t0\t0\t*\t*\n")?;
+                writer.write_all(b"\t*\t*\n")?;
             }
         }
-
         Ok(())
     }
+    fn write_mate_fields(
+        &mut self,
+        writer: &mut impl Write,
+        mate: &MateAlignment,
+        partner: &MateAlignment,
+        is_first: bool,
+        reference: &Reference,
+    ) -> Result<(), io::Error> {
+        writer.write_all(b"\t")?;
+        if mate.target_index == partner.target_index {
+            writer.write_all(b"=")?;
+        } else {
+            let partner_label = reference.get_label_str(partner.target_index).unwrap_or_default();
+            writer.write_all(partner_label.as_bytes())?;
+        }
+        writer.write_all(b"\t")?;
+        writer.write_all(
+            self.itoa_buffer.format(partner.alignment.position.target.0 + 1).as_bytes()
+        )?;
+        writer.write_all(b"\t")?;
+        // TLEN is positive for the leftmost mate and negative for the other.
+        let tlen = if mate.target_index == partner.target_index {
+            let insert_size = mate.insert_size_with(partner) as i64;
+            let mate_start = mate.alignment.position.target.0;
+            let partner_start = partner.alignment.position.target.0;
+            if mate_start < partner_start || (mate_start == partner_start && is_first) {
+                insert_size
+            } else {
+                -insert_size
+            }
+        } else {
+            0
+        };
+        writer.write_all(self.itoa_buffer.format(tlen).as_bytes())
+    }
+    fn write_unmapped_mate_record(
+        &mut self,
+        writer: &mut impl Write,
+        qname: &str,
+        mate_flag: u16,
+        partner: &MateAlignment,
+        reference: &Reference,
+    ) -> Result<(), io::Error> {
+        let mut flag = mate_flag | FLAG_UNMAPPED;
+        if !partner.is_forward {
+            flag |= FLAG_MATE_REVERSE;
+        }
+        // The unmapped mate is placed at the position of the partner.
+        let partner_label = reference.get_label_str(partner.target_index).unwrap_or_default();
+        let partner_position = partner.alignment.position.target.0 + 1;
+        writer.write_all(qname.as_bytes())?;
+        writer.write_all(b"\t")?;
+        writer.write_all(self.itoa_buffer.format(flag).as_bytes())?;
+        writer.write_all(b"\t")?;
+        writer.write_all(partner_label.as_bytes())?;
+        writer.write_all(b"\t")?;
+        writer.write_all(self.itoa_buffer.format(partner_position).as_bytes())?;
+        writer.write_all(b"\t0\t*\t=\t")?;
+        writer.write_all(self.itoa_buffer.format(partner_position).as_bytes())?;
+        writer.write_all(b"\t0\t*\t*\n")
+    }
+    fn write_mapq(
+        &mut self,
+        writer: &mut impl Write,
+        mapping_quality: Option<u8>,
+    ) -> Result<(), io::Error> {
+        writer.write_all(b"\t")?;
+        match mapping_quality {
+            Some(mapping_quality) => writer.write_all(self.itoa_buffer.format(mapping_quality).as_bytes())?,
+            None => writer.write_all(b"255")?,
+        }
+        writer.write_all(b"\t")
+    }
+    fn write_cigar_with_hclip(
+        &mut self,
+        writer: &mut impl Write,
+        alignment: &Alignment,
+        query_length: u32,
+    ) -> Result<(), io::Error> {
+        let lclip_size = alignment.position.query.0;
+        if lclip_size != 0 {
+            writer.write_all(
+                self.itoa_buffer.format(lclip_size).as_bytes()
+            )?;
+            writer.write_all(b"H")?;
+        }
+        self.write_cigar(writer, &alignment.operations)?;
+        let rclip_size = query_length - alignment.position.query.1;
+        if rclip_size != 0 {
+            writer.write_all(
+                self.itoa_buffer.format(rclip_size).as_bytes()
+            )?;
+            writer.write_all(b"H")?;
+        }
+        Ok(())
+    }
+    fn write_cigar(
+        &mut self,
+        writer: &mut impl Write,
+        operations: &[AlignmentOperations],
+    ) -> Result<(), io::Error> {
+        for op in operations.iter() {
+            writer.write_all(
+                self.itoa_buffer.format(op.count).as_bytes()
+            )?;
+            writer.write_all(&[op.operation.to_cigar_code()])?;
+        }
+        Ok(())
+    }
+}
+
+// Index of the primary alignment of the first (or second) mate.
+fn primary_mate_index(read_pair_alignment: &ReadPairAlignment, is_first: bool) -> Option<usize> {
+    match read_pair_alignment.best_pair() {
+        Some(pair) => Some(if is_first { pair.first } else { pair.second }),
+        None => {
+            let mates = if is_first { &read_pair_alignment.first } else { &read_pair_alignment.second };
+            mates.iter().enumerate().min_by_key(|(_, x)| x.alignment.penalty).map(|(index, _)| index)
+        },
+    }
 }
@@ -1,3 +1,8 @@
+mod recommender;
+pub use recommender::{
+    ParameterRecommender, ParameterRecommendation, ReferenceStatistics, CutoffCandidate,
+};
+
 /// Returns (MinL, MaxP) with throughput constant.
 ///
 /// For the throughput constant (λ), length of query (l) and mismatch penalty (x):
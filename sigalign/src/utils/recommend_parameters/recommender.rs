@@ -0,0 +1,231 @@
+use sigalign_core::{
+    aligner::AlignmentRegulator,
+    reference::PatternIndex,
+};
+
+use crate::algorithms::ParamsError;
+use crate::reference::Reference;
+use super::cutoffs_from_throughput_constant;
+
+const DEFAULT_THROUGHPUT_CONSTANTS: [f32; 9] = [1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0, 4.5, 5.0];
+// Same as the minimum pattern size of the algorithms
+const MINIMUM_PATTERN_SIZE: u32 = 4;
+
+/// Recommends the cutoffs (MinL, MaxP) from the statistics of the reference and the sample queries.
+///
+/// For each throughput constant, the cutoffs are derived with `cutoffs_from_throughput_constant`
+/// from the median length of the queries. Then, the pattern size of the cutoffs is used to count
+/// the anchors of the sample queries in the pattern index, which reflects the repeat content of the reference.
+///
+/// ```rust
+/// use sigalign::{ReferenceBuilder, utils::recommend_parameters::ParameterRecommender};
+///
+/// let reference = ReferenceBuilder::new()
+///     .add_target("target", b"ACACAGATCGCAAACTCACAATTGTATTTCTTTGCCACCTGGGCATATACTTTTTGCGCCCCCTCATTTA")
+///     .build().unwrap();
+/// let recommendation = ParameterRecommender::new(&reference, 4, 6, 2)
+///     .add_query(b"CAAACTCACAATTGTATTTCTTTGCCAGCTGGGCATATACTTTTTCCGCCCCCTCATTTA")
+///     .recommend().unwrap();
+/// for candidate in recommendation.candidates {
+///     println!("MinL: {}, MaxP: {}", candidate.min_length, candidate.max_penalty);
+/// }
+/// ```
+pub struct ParameterRecommender<'a> {
+    reference: &'a Reference,
+    mismatch_penalty: u32,
+    gap_open_penalty: u32,
+    gap_extend_penalty: u32,
+    queries: Vec<Vec<u8>>,
+    throughput_constants: Vec<f32>,
+}
+
+/// Result of `ParameterRecommender`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterRecommendation {
+    pub reference_statistics: ReferenceStatistics,
+    /// Median length of the sample queries.
+    pub query_length: u32,
+    /// Sorted from the most sensitive (lenient) to the fastest (strict).
+    pub candidates: Vec<CutoffCandidate>,
+}
+
+/// Statistics of the reference used for the recommendation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReferenceStatistics {
+    pub num_targets: u32,
+    pub total_length: u32,
+    /// Number of distinct residues.
+    pub num_residues: u32,
+    /// Exponential of the entropy of the residue composition (≤ `num_residues`).
+    pub effective_alphabet_size: f64,
+}
+
+/// Suggested cutoffs with the predicted trade-off.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutoffCandidate {
+    pub throughput_constant: f32,
+    pub min_length: u32,
+    pub max_penalty: f32,
+    pub pattern_size: u32,
+    /// Expected count of the anchors per pattern in the random sequence with the same composition.
+    pub random_anchors_per_pattern: f64,
+    /// Mean count of the anchors per pattern of the sample queries.
+    pub observed_anchors_per_pattern: f64,
+    /// Mean count of the anchors per query. The alignment time grows with this.
+    pub anchors_per_query: f64,
+    /// Maximum mismatch rate of the alignment that can satisfy the cutoffs.
+    ///  - The higher, the more sensitive to the divergent sequences.
+    pub max_divergence: f32,
+    /// `anchors_per_query` relative to the cheapest candidate (≥ 1.0).
+    pub relative_cost: f64,
+}
+
+impl<'a> ParameterRecommender<'a> {
+    pub fn new(
+        reference: &'a Reference,
+        mismatch_penalty: u32,
+        gap_open_penalty: u32,
+        gap_extend_penalty: u32,
+    ) -> Self {
+        Self {
+            reference,
+            mismatch_penalty,
+            gap_open_penalty,
+            gap_extend_penalty,
+            queries: Vec::new(),
+            throughput_constants: DEFAULT_THROUGHPUT_CONSTANTS.to_vec(),
+        }
+    }
+    /// Add a sample query.
+    pub fn add_query(mut self, query: &[u8]) -> Self {
+        self.queries.push(query.to_vec());
+        self
+    }
+    /// Add sample queries.
+    pub fn add_queries<I, Q>(mut self, queries: I) -> Self where
+        I: IntoIterator<Item = Q>,
+        Q: AsRef<[u8]>,
+    {
+        self.queries.extend(queries.into_iter().map(|query| query.as_ref().to_vec()));
+        self
+    }
+    /// Set the throughput constants (λ) of the candidates.
+    ///  - Default: 1.0 to 5.0 by 0.5.
+    pub fn set_throughput_constants(mut self, throughput_constants: Vec<f32>) -> Self {
+        self.throughput_constants = throughput_constants;
+        self
+    }
+
+    pub fn recommend(&self) -> Result<ParameterRecommendation, ParamsError> {
+        if self.queries.is_empty() {
+            return Err(ParamsError::InvalidValue("No sample query is added.".to_string()));
+        }
+        let mut query_lengths: Vec<u32> = self.queries.iter().map(|x| x.len() as u32).collect();
+        query_lengths.sort_unstable();
+        let query_length = query_lengths[query_lengths.len() / 2];
+
+        let (reference_statistics, residue_frequencies) = self.get_reference_statistics();
+
+        let mut candidates = Vec::with_capacity(self.throughput_constants.len());
+        for &throughput_constant in &self.throughput_constants {
+            if throughput_constant <= 0.0 {
+                return Err(ParamsError::InvalidValue("Throughput constant must be positive.".to_string()));
+            }
+            let (min_length, max_penalty) = cutoffs_from_throughput_constant(
+                self.mismatch_penalty,
+                query_length,
+                throughput_constant,
+            );
+            let regulator = AlignmentRegulator::new(
+                self.mismatch_penalty,
+                self.gap_open_penalty,
+                self.gap_extend_penalty,
+                min_length,
+                max_penalty,
+            )?;
+            let pattern_size = regulator.get_pattern_size();
+            // Cannot be used by the aligner
+            if pattern_size < MINIMUM_PATTERN_SIZE {
+                continue;
+            }
+            let (observed_anchors_per_pattern, anchors_per_query) = self.count_anchors(pattern_size);
+            let random_anchors_per_pattern = reference_statistics.total_length as f64
+                * residue_frequencies.iter().map(|p| p.powi(pattern_size as i32)).sum::<f64>();
+
+            candidates.push(CutoffCandidate {
+                throughput_constant,
+                min_length,
+                max_penalty,
+                pattern_size,
+                random_anchors_per_pattern,
+                observed_anchors_per_pattern,
+                anchors_per_query,
+                max_divergence: max_penalty / self.mismatch_penalty.max(1) as f32,
+                relative_cost: 0.0,
+            });
+        }
+        if candidates.is_empty() {
+            return Err(ParamsError::InhibitedLowEfficiency(
+                "Cutoffs are too low to detect the pattern for all throughput constants.".to_string(),
+            ));
+        }
+        candidates.sort_by(|a, b| b.max_divergence.total_cmp(&a.max_divergence));
+        let minimum_cost = candidates.iter()
+            .map(|x| x.anchors_per_query)
+            .fold(f64::INFINITY, f64::min)
+            .max(1.0);
+        candidates.iter_mut().for_each(|x| {
+            x.relative_cost = x.anchors_per_query.max(1.0) / minimum_cost;
+        });
+
+        Ok(ParameterRecommendation {
+            reference_statistics,
+            query_length,
+            candidates,
+        })
+    }
+
+    // (Statistics, frequencies of the residues)
+    fn get_reference_statistics(&self) -> (ReferenceStatistics, Vec<f64>) {
+        let mut counts = [0_u64; 256];
+        for target_index in 0..self.reference.get_num_targets() {
+            if let Some(sequence) = self.reference.get_sequence(target_index) {
+                sequence.iter().for_each(|&residue| counts[residue as usize] += 1);
+            }
+        }
+        let total_count = counts.iter().sum::<u64>().max(1) as f64;
+        let frequencies: Vec<f64> = counts.iter()
+            .filter(|&&count| count != 0)
+            .map(|&count| count as f64 / total_count)
+            .collect();
+        let entropy: f64 = frequencies.iter().map(|p| -p * p.ln()).sum();
+
+        let statistics = ReferenceStatistics {
+            num_targets: self.reference.get_num_targets(),
+            total_length: self.reference.get_total_length(),
+            num_residues: frequencies.len() as u32,
+            effective_alphabet_size: entropy.exp(),
+        };
+        (statistics, frequencies)
+    }
+    // (Mean anchors per pattern, mean anchors per query)
+    //  - Query is split into the non-overlapping patterns, as in the alignment.
+    fn count_anchors(&self, pattern_size: u32) -> (f64, f64) {
+        let pattern_index = self.reference.as_ref().get_pattern_index();
+        let pattern_size = pattern_size as usize;
+        let mut pattern_count = 0;
+        let mut anchor_count = 0;
+        for query in &self.queries {
+            for pattern in query.chunks_exact(pattern_size) {
+                pattern_count += 1;
+                anchor_count += pattern_index.get_sorted_positions(pattern).len();
+            }
+        }
+        let per_pattern = if pattern_count == 0 {
+            0.0
+        } else {
+            anchor_count as f64 / pattern_count as f64
+        };
+        (per_pattern, anchor_count as f64 / self.queries.len() as f64)
+    }
+}
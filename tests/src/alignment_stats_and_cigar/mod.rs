@@ -0,0 +1,147 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, ReferenceBuilder,
+    algorithms::{Local, SemiGlobal},
+    results::{
+        Alignment, AlignmentPosition, AlignmentOperations, AlignmentOperation,
+        Cigar, CigarError,
+    },
+};
+
+const PX: u32 = 4;
+const PO: u32 = 6;
+const PE: u32 = 2;
+
+fn alignment_from_cigar(query: (u32, u32), target: (u32, u32), cigar: &str) -> Alignment {
+    let operations = Vec::from_cigar(cigar).unwrap();
+    Alignment {
+        penalty: 0,
+        length: 0,
+        position: AlignmentPosition { query, target },
+        operations,
+    }
+}
+
+#[test]
+fn stats_are_consistent_with_penalty_and_position() {
+    init_logger();
+    let targets: Vec<Vec<u8>> = (0..5).map(|_| gen_rand_text(b"ACGT", 500, 1000)).collect();
+    let mut builder = ReferenceBuilder::new();
+    for (index, target) in targets.iter().enumerate() {
+        builder = builder.add_target(&format!("target_{}", index), target);
+    }
+    let reference = builder.build().unwrap();
+
+    let mut local_aligner = Aligner::new(Local::new(PX, PO, PE, 50, 0.1).unwrap());
+    let mut semi_global_aligner = Aligner::new(SemiGlobal::new(PX, PO, PE, 50, 0.1).unwrap());
+
+    let mut alignment_count = 0;
+    for target in targets.iter().cycle().take(30) {
+        let mut query = gen_rand_pattern(target, 100, 200);
+        // Add some errors
+        query[10] = b'A';
+        query.remove(50);
+        query.insert(80, b'C');
+
+        for result in [
+            local_aligner.align(&query, &reference),
+            semi_global_aligner.align(&query, &reference),
+        ] {
+            for target_alignment in result.0.iter() {
+                let target_length = reference.get_sequence_length(target_alignment.index).unwrap();
+                for alignment in target_alignment.alignments.iter() {
+                    alignment_count += 1;
+                    let stats = alignment.stats();
+                    assert_eq!(stats.penalty(PX, PO, PE), alignment.penalty);
+                    assert_eq!(stats.alignment_length(), alignment.length);
+                    assert_eq!(
+                        stats.matches + stats.mismatches + stats.insertions,
+                        alignment.position.query.1 - alignment.position.query.0,
+                    );
+                    assert_eq!(
+                        stats.matches + stats.mismatches + stats.deletions,
+                        alignment.position.target.1 - alignment.position.target.0,
+                    );
+                    assert!(alignment.identity() <= 1.0);
+                    assert!(alignment.query_coverage(query.len() as u32) <= 1.0);
+                    assert!(alignment.target_coverage(target_length) <= 1.0);
+                    // CIGAR round-trip
+                    let cigar = alignment.to_cigar();
+                    assert_eq!(Vec::from_cigar(&cigar).unwrap(), alignment.operations);
+                }
+                assert!(target_alignment.query_coverage(query.len() as u32) <= 1.0);
+                assert!(target_alignment.target_coverage(target_length) <= 1.0);
+            }
+        }
+    }
+    assert!(alignment_count > 0);
+}
+
+#[test]
+fn stats_of_known_operations() {
+    let alignment = alignment_from_cigar((10, 60), (9, 60), "20=1X2=1D2D27=");
+    assert_eq!(alignment.operations.len(), 5); // Consecutive deletions are merged
+    let stats = alignment.stats();
+    assert_eq!(stats.matches, 49);
+    assert_eq!(stats.mismatches, 1);
+    assert_eq!(stats.insertions, 0);
+    assert_eq!(stats.deletions, 3);
+    assert_eq!(stats.gap_opens, 1);
+    assert_eq!(stats.gap_extensions, 3);
+    assert_eq!(stats.penalty(PX, PO, PE), 4 + 6 + 2 * 3);
+    assert_eq!(stats.identity(), 49.0 / 53.0);
+    assert_eq!(alignment.query_coverage(100), 0.5);
+    assert_eq!(alignment.target_coverage(0), 0.0);
+
+    let alignment = alignment_from_cigar((0, 10), (0, 10), "3=2I2D5=");
+    let stats = alignment.stats();
+    assert_eq!(stats.gap_opens, 2);
+    assert_eq!(stats.gap_extensions, 4);
+    assert_eq!(alignment.to_cigar(), "3=2I2D5=");
+}
+
+#[test]
+fn target_coverage_counts_overlap_once() {
+    use sigalign::results::TargetAlignment;
+    let target_alignment = TargetAlignment {
+        index: 0,
+        alignments: vec![
+            alignment_from_cigar((0, 40), (0, 40), "40="),
+            alignment_from_cigar((20, 60), (100, 140), "40="),
+            alignment_from_cigar((80, 90), (200, 210), "10="),
+        ],
+    };
+    assert_eq!(target_alignment.query_coverage(100), 0.7);
+    assert_eq!(target_alignment.target_coverage(1000), 0.09);
+    assert_eq!(target_alignment.stats().matches, 90);
+}
+
+#[test]
+fn invalid_cigar_is_error() {
+    assert_eq!(
+        Vec::<AlignmentOperations>::from_cigar("10M"),
+        Err(CigarError::UnsupportedOperation('M')),
+    );
+    assert_eq!(
+        Vec::<AlignmentOperations>::from_cigar("10=X"),
+        Err(CigarError::MissingCount('X')),
+    );
+    assert_eq!(
+        Vec::<AlignmentOperations>::from_cigar("10=5"),
+        Err(CigarError::MissingOperation("5".to_string())),
+    );
+    assert_eq!(
+        Vec::<AlignmentOperations>::from_cigar("0="),
+        Err(CigarError::InvalidCount),
+    );
+    assert_eq!(
+        Vec::<AlignmentOperations>::from_cigar("99999999999="),
+        Err(CigarError::InvalidCount),
+    );
+    assert_eq!(Vec::<AlignmentOperations>::from_cigar(""), Ok(Vec::new()));
+    assert_eq!(
+        AlignmentOperation::from_cigar_code(b'I'),
+        Some(AlignmentOperation::Insertion),
+    );
+}
@@ -72,7 +72,8 @@ pub fn dp_semi_global_to_ref_file(
 
     let mut result = Vec::new();
 
-    while let Some(mut record) = ref_reader.next() {
+    while let Some(record) = ref_reader.next() {
+        let mut record = record.unwrap();
         target_buffer.clear();
         record.extend_seq_buf(&mut target_buffer);
 
@@ -70,7 +70,8 @@ fn dp_matrix_calculates_penalty_accurately() {
     let mut qry_index = 0;
     let mut qry_buffer = Vec::new();
     let mut qry_reader = FastaReader::from_path(&qry_file).unwrap();
-    while let Some(mut record) = qry_reader.next() {
+    while let Some(record) = qry_reader.next() {
+        let mut record = record.unwrap();
         qry_buffer.clear();
         record.extend_seq_buf(&mut qry_buffer);
         if qry_index == qry_count {
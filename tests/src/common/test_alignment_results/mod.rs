@@ -20,7 +20,8 @@ pub fn get_test_alignment_results() -> Result<Vec<(String, LabeledQueryAlignment
     let mut fasta_reader = FastaReader::from_path(qry_file)?;
 
     let mut results = Vec::new();
-    while let Some(mut record) = fasta_reader.next() {
+    while let Some(record) = fasta_reader.next() {
+        let mut record = record.unwrap();
         query.clear();
         label.clear();
         record.extend_seq_buf(&mut query);
@@ -18,5 +18,19 @@ pub mod result_validation_with_dynamic_programming_matrix;
 mod reference_gives_correct_data;
 mod reference_save_and_load;
 mod reference_with_short_sequences;
+mod reference_alphabet;
+mod reference_soft_masking;
+mod reference_metadata;
+mod reference_file_format;
+mod reference_mapped_loading;
+mod target_subset_alignment;
 // Test utilities functions
-mod print_results_as_sam;
\ No newline at end of file
+mod print_results_as_sam;
+mod results_binary_serialization;
+mod alignment_stats_and_cigar;
+mod results_sort_and_filter;
+mod results_deduplication;
+mod mapping_quality_estimation;
+mod paired_end_alignment;
+mod query_preprocessing;
+mod parameter_recommendation;
\ No newline at end of file
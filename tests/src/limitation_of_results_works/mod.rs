@@ -96,7 +96,8 @@ fn test_limit_works<A1, A2, F1, F2>(
         let mut query_buffer = Vec::new();
         let mut query_index = 0;
         let mut query_step = 0;
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record.unwrap();
             query_step += 1;
             if query_step == settings.query_interval {
                 query_step = 0;
@@ -0,0 +1,205 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, ReferenceBuilder,
+    algorithms::Local,
+    results::{
+        QueryAlignment, TargetAlignment, Alignment, AlignmentPosition,
+        Cigar, MappingQualityEstimator,
+    },
+    utils::formatter::SamFormatter,
+};
+
+fn alignment(penalty: u32, query: (u32, u32), target: (u32, u32), cigar: &str) -> Alignment {
+    let operations = Vec::from_cigar(cigar).unwrap();
+    let length = operations.iter().map(|x| x.count).sum();
+    Alignment {
+        penalty,
+        length,
+        position: AlignmentPosition { query, target },
+        operations,
+    }
+}
+
+#[test]
+fn mapping_quality_by_competitors() {
+    let estimator = MappingQualityEstimator::new(4);
+
+    // Unique
+    let unique = [alignment(4, (0, 100), (0, 100), "50=1X49=")];
+    assert_eq!(estimator.estimate(unique.iter()), vec![60]);
+    // Short alignment
+    let short = [alignment(0, (0, 50), (0, 50), "50=")];
+    assert_eq!(estimator.estimate(short.iter()), vec![30]);
+    // Two equally good hits
+    let repeats = [
+        alignment(4, (0, 100), (0, 100), "50=1X49="),
+        alignment(4, (0, 100), (500, 600), "20=1X79="),
+    ];
+    assert_eq!(estimator.estimate(repeats.iter()), vec![3, 3]);
+    // One mismatch and two mismatches
+    let gapped = [
+        alignment(4, (0, 100), (0, 100), "50=1X49="),
+        alignment(12, (0, 100), (500, 600), "20=3X77="),
+        alignment(8, (10, 100), (800, 890), "20=2X68="),
+    ];
+    assert_eq!(estimator.estimate(gapped.iter()), vec![10, 0, 0]);
+    // Not overlapped in query are not competitors
+    let separated = [
+        alignment(4, (0, 100), (0, 100), "50=1X49="),
+        alignment(0, (100, 200), (500, 600), "100="),
+    ];
+    assert_eq!(estimator.estimate(separated.iter()), vec![60, 60]);
+
+    let estimator = estimator
+        .max_mapping_quality(40)
+        .phred_per_mismatch(30.0)
+        .full_confidence_length(0);
+    assert_eq!(estimator.estimate(gapped.iter()), vec![30, 0, 0]);
+    assert_eq!(estimator.estimate(short.iter()), vec![40]);
+}
+
+#[test]
+fn mapping_qualities_have_the_same_structure() {
+    let query_alignment = QueryAlignment(vec![
+        TargetAlignment {
+            index: 0,
+            alignments: vec![
+                alignment(4, (0, 100), (0, 100), "50=1X49="),
+                alignment(0, (150, 250), (300, 400), "100="),
+            ],
+        },
+        TargetAlignment {
+            index: 1,
+            alignments: vec![
+                alignment(12, (0, 100), (0, 100), "20=3X77="),
+            ],
+        },
+    ]);
+    let estimator = MappingQualityEstimator::new(4);
+    assert_eq!(
+        query_alignment.mapping_qualities(&estimator),
+        vec![vec![20, 60], vec![0]],
+    );
+}
+
+#[test]
+fn sam_has_estimated_mapping_quality() {
+    init_logger();
+    let unique_target = gen_rand_text(b"ACGT", 1000, 1000);
+    let repeat = gen_rand_text(b"ACGT", 300, 300);
+    let reference = ReferenceBuilder::new()
+        .add_target("unique", &unique_target)
+        .add_target("repeat_1", &repeat)
+        .add_target("repeat_2", &repeat)
+        .build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let estimator = aligner.mapping_quality_estimator();
+
+    let get_mapqs = |sam: Vec<u8>| -> Vec<String> {
+        String::from_utf8(sam).unwrap().lines().map(|line| {
+            line.split('\t').nth(4).unwrap().to_string()
+        }).collect()
+    };
+
+    let query = gen_rand_pattern(&repeat, 150, 200);
+    let query_alignment = aligner.align(&query, &reference);
+    assert!(query_alignment.count_alignments() >= 2);
+    let mut sam = Vec::new();
+    SamFormatter::new().with_mapping_quality_estimator(estimator.clone()).write_read_alignment(
+        &mut sam, Some(&query_alignment), None, "query", query.len() as u32, &reference,
+    ).unwrap();
+    assert!(get_mapqs(sam).iter().all(|x| x.parse::<u8>().unwrap() <= 3));
+
+    let query = gen_rand_pattern(&unique_target, 150, 200);
+    let labeled = reference.label_query_alignment(aligner.align(&query, &reference));
+    let mut sam = Vec::new();
+    SamFormatter::new().with_mapping_quality_estimator(estimator).write_labeled_read_alignment_with_hclip(
+        &mut sam, &labeled, None, "query", query.len() as u32,
+    ).unwrap();
+    assert!(get_mapqs(sam).contains(&"60".to_string()));
+
+    // Not assigned by default
+    let mut sam = Vec::new();
+    SamFormatter::new().write_labeled_read_alignment_with_hclip(
+        &mut sam, &labeled, None, "query", query.len() as u32,
+    ).unwrap();
+    assert!(get_mapqs(sam).iter().all(|x| x == "255"));
+}
+
+#[test]
+fn both_strands_compete_in_sam() {
+    let reference = ReferenceBuilder::new()
+        .add_target("target_1", &gen_rand_text(b"ACGT", 1000, 1000))
+        .add_target("target_2", &gen_rand_text(b"ACGT", 1000, 1000))
+        .build().unwrap();
+    let forward = QueryAlignment(vec![TargetAlignment {
+        index: 0,
+        alignments: vec![alignment(4, (0, 100), (0, 100), "50=1X49=")],
+    }]);
+    let reverse = QueryAlignment(vec![TargetAlignment {
+        index: 1,
+        alignments: vec![alignment(4, (0, 100), (500, 600), "20=1X79=")],
+    }]);
+    let mut sam_formatter = SamFormatter::new()
+        .with_mapping_quality_estimator(MappingQualityEstimator::new(4));
+
+    let mut sam = Vec::new();
+    sam_formatter.write_read_alignment(
+        &mut sam, Some(&forward), Some(&reverse), "query", 100, &reference,
+    ).unwrap();
+    let fields: Vec<(String, String)> = String::from_utf8(sam).unwrap().lines().map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        (fields[1].to_string(), fields[4].to_string())
+    }).collect();
+    // The tie is broken by the order, and the other is secondary
+    assert_eq!(fields, vec![
+        ("0".to_string(), "3".to_string()),
+        ("272".to_string(), "3".to_string()),
+    ]);
+
+    // Each strand alone is unique
+    let mut sam = Vec::new();
+    sam_formatter.write_read_alignment(
+        &mut sam, Some(&forward), None, "query", 100, &reference,
+    ).unwrap();
+    assert_eq!(String::from_utf8(sam).unwrap().split('\t').nth(4), Some("60"));
+}
+
+#[test]
+fn reverse_strand_positions_are_flipped_to_compete() {
+    let reference = ReferenceBuilder::new()
+        .add_target("target_1", &gen_rand_text(b"ACGT", 1000, 1000))
+        .add_target("target_2", &gen_rand_text(b"ACGT", 1000, 1000))
+        .build().unwrap();
+    let estimator = MappingQualityEstimator::new(4);
+    // Query of 200 bases: the forward hit is on the first half
+    let forward = alignment(4, (0, 100), (0, 100), "50=1X49=");
+    // The second half of the reverse complement is the first half of the query
+    let reverse_on_same_bases = alignment(4, (100, 200), (500, 600), "20=1X79=");
+    let reverse_on_other_bases = alignment(4, (0, 100), (500, 600), "20=1X79=");
+
+    assert_eq!(
+        estimator.estimate_on_both_strands([(true, &forward), (false, &reverse_on_same_bases)], 200),
+        vec![3, 3],
+    );
+    assert_eq!(
+        estimator.estimate_on_both_strands([(true, &forward), (false, &reverse_on_other_bases)], 200),
+        vec![60, 60],
+    );
+
+    let mut sam_formatter = SamFormatter::new().with_mapping_quality_estimator(estimator);
+    let get_mapqs = |forward: &Alignment, reverse: &Alignment, sam_formatter: &mut SamFormatter| {
+        let forward = QueryAlignment(vec![TargetAlignment { index: 0, alignments: vec![forward.clone()] }]);
+        let reverse = QueryAlignment(vec![TargetAlignment { index: 1, alignments: vec![reverse.clone()] }]);
+        let mut sam = Vec::new();
+        sam_formatter.write_read_alignment(
+            &mut sam, Some(&forward), Some(&reverse), "query", 200, &reference,
+        ).unwrap();
+        String::from_utf8(sam).unwrap().lines().map(|line| {
+            line.split('\t').nth(4).unwrap().to_string()
+        }).collect::<Vec<_>>()
+    };
+    assert_eq!(get_mapqs(&forward, &reverse_on_same_bases, &mut sam_formatter), vec!["3", "3"]);
+    assert_eq!(get_mapqs(&forward, &reverse_on_other_bases, &mut sam_formatter), vec!["60", "60"]);
+}
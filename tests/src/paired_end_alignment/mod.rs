@@ -0,0 +1,196 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::gen_rand_text;
+use sigalign::{
+    Aligner, Reference, ReferenceBuilder,
+    algorithms::Local,
+    results::{PairOrientation, PairingOptions, ReadPairAlignment},
+    utils::formatter::SamFormatter,
+};
+use sigalign_utils::sequence_manipulation::reverse_complementary::reverse_complement_of_dna_sequence as reverse_complement;
+use sigalign_utils::sequence_reader::{PairedFastqReader, ReadPair, ReadError, ReadErrorKind};
+
+fn read_all_pairs<R: std::io::Read>(reader: &mut PairedFastqReader<R>) -> Result<Vec<ReadPair>, ReadError> {
+    let mut pairs = Vec::new();
+    let mut pair = ReadPair::default();
+    while let Some(result) = reader.read_pair(&mut pair) {
+        result?;
+        pairs.push(pair.clone());
+    }
+    Ok(pairs)
+}
+
+#[test]
+fn paired_reader_reads_separate_and_interleaved_files() {
+    let first = b"@r1/1\nACGT\n+\nIIII\n@r2/1\nGGCC\n+\nIIII\n";
+    let second = b"@r1/2\nTTAA\n+\nIIII\n@r2/2\nCCAA\n+\nIIII\n";
+    let interleaved = b"@r1/1\nACGT\n+\nIIII\n@r1/2\nTTAA\n+\nIIII\n@r2/1\nGGCC\n+\nIIII\n@r2/2\nCCAA\n+\nIIII\n";
+
+    let separate_pairs = read_all_pairs(&mut PairedFastqReader::new(&first[..], &second[..])).unwrap();
+    let interleaved_pairs = read_all_pairs(&mut PairedFastqReader::interleaved(&interleaved[..])).unwrap();
+    assert_eq!(separate_pairs, interleaved_pairs);
+    assert_eq!(separate_pairs.len(), 2);
+    assert_eq!(separate_pairs[0].name(), "r1");
+    assert_eq!(separate_pairs[0].first_sequence, b"ACGT");
+    assert_eq!(separate_pairs[0].second_sequence, b"TTAA");
+    assert_eq!(separate_pairs[1].name(), "r2");
+}
+
+#[test]
+fn paired_reader_reports_broken_pairs() {
+    // Different names
+    let first = b"@r1/1\nACGT\n+\nIIII\n";
+    let second = b"@r2/2\nTTAA\n+\nIIII\n";
+    let result = read_all_pairs(&mut PairedFastqReader::new(&first[..], &second[..]));
+    assert!(matches!(result.unwrap_err().kind(), ReadErrorKind::MalformedRecord(_)));
+
+    // Missing mate in the second file
+    let first = b"@r1/1\nACGT\n+\nIIII\n@r2/1\nGGCC\n+\nIIII\n";
+    let second = b"@r1/2\nTTAA\n+\nIIII\n";
+    let result = read_all_pairs(&mut PairedFastqReader::new(&first[..], &second[..]));
+    assert!(matches!(result.unwrap_err().kind(), ReadErrorKind::MalformedRecord(_)));
+
+    // Odd number of records in the interleaved file
+    let interleaved = b"@r1/1\nACGT\n+\nIIII\n@r1/2\nTTAA\n+\nIIII\n@r2/1\nGGCC\n+\nIIII\n";
+    let result = read_all_pairs(&mut PairedFastqReader::interleaved(&interleaved[..]));
+    assert!(matches!(result.unwrap_err().kind(), ReadErrorKind::MalformedRecord(_)));
+}
+
+/// Mates of the fragment `target[400..800]` in forward-reverse orientation.
+fn simulate_read_pair() -> (Reference, Vec<u8>, Vec<u8>) {
+    let target = gen_rand_text(b"ACGT", 2000, 2000);
+    let reference = ReferenceBuilder::new()
+        .add_target("target", &target)
+        .build().unwrap();
+    let first = target[400..500].to_vec();
+    let second = reverse_complement(&target[700..800]);
+    (reference, first, second)
+}
+
+#[test]
+fn concordant_pair_follows_orientation_and_insert_size() {
+    init_logger();
+    let (reference, first, second) = simulate_read_pair();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let result = aligner.align_read_pair(&first, &second, &reference, &PairingOptions::default()).unwrap();
+    let best_pair = result.best_pair().expect("Mates must be paired");
+    let (first_mate, second_mate) = result.mates_of(best_pair);
+    assert!(first_mate.is_forward);
+    assert!(!second_mate.is_forward);
+    assert_eq!(first_mate.alignment.position.target, (400, 500));
+    assert_eq!(second_mate.alignment.position.target, (700, 800));
+    assert_eq!(best_pair.insert_size, 400);
+    assert_eq!(best_pair.penalty, 0);
+
+    // Swapped mates are also concordant
+    let result = aligner.align_read_pair(&second, &first, &reference, &PairingOptions::default()).unwrap();
+    assert_eq!(result.best_pair().unwrap().insert_size, 400);
+
+    // Out of the insert size range
+    let options = PairingOptions::new(PairOrientation::ForwardReverse, 0, 399);
+    let result = aligner.align_read_pair(&first, &second, &reference, &options).unwrap();
+    assert!(!result.is_concordant());
+    assert!(!result.first.is_empty() && !result.second.is_empty());
+    let options = PairingOptions::new(PairOrientation::ForwardReverse, 401, 1000);
+    assert!(!aligner.align_read_pair(&first, &second, &reference, &options).unwrap().is_concordant());
+
+    // Unexpected orientation
+    for orientation in [PairOrientation::ReverseForward, PairOrientation::ForwardForward] {
+        let options = PairingOptions::new(orientation, 0, 1000);
+        assert!(!aligner.align_read_pair(&first, &second, &reference, &options).unwrap().is_concordant());
+    }
+    let options = PairingOptions::new(PairOrientation::ForwardForward, 0, 1000);
+    let second_forward = reverse_complement(&second);
+    assert!(aligner.align_read_pair(&first, &second_forward, &reference, &options).unwrap().is_concordant());
+}
+
+fn sam_fields(
+    read_pair_alignment: &ReadPairAlignment,
+    reference: &Reference,
+) -> Vec<Vec<String>> {
+    let mut sam = Vec::new();
+    SamFormatter::new().write_read_pair_alignment(
+        &mut sam, read_pair_alignment, "pair", reference,
+    ).unwrap();
+    String::from_utf8(sam).unwrap().lines().map(|line| {
+        line.split('\t').map(|x| x.to_string()).collect()
+    }).collect()
+}
+
+#[test]
+fn sam_has_mate_fields() {
+    init_logger();
+    let (reference, first, second) = simulate_read_pair();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let result = aligner.align_read_pair(&first, &second, &reference, &PairingOptions::default()).unwrap();
+    let records = sam_fields(&result, &reference);
+    assert_eq!(records.len(), 2);
+    // FLAG, RNAME, POS, RNEXT, PNEXT, TLEN
+    let mate_fields = |record: &Vec<String>| {
+        [1, 2, 3, 6, 7, 8].map(|i| record[i].clone())
+    };
+    assert_eq!(mate_fields(&records[0]), ["99", "target", "401", "=", "701", "400"]);
+    assert_eq!(mate_fields(&records[1]), ["147", "target", "701", "=", "401", "-400"]);
+
+    // Not a proper pair
+    let options = PairingOptions::new(PairOrientation::ForwardReverse, 0, 300);
+    let result = aligner.align_read_pair(&first, &second, &reference, &options).unwrap();
+    let records = sam_fields(&result, &reference);
+    assert_eq!(records[0][1], "97");
+    assert_eq!(records[1][1], "145");
+
+    // Only the first mate is aligned
+    let unaligned = gen_rand_text(b"ACGT", 100, 100);
+    let result = aligner.align_read_pair(&first, &unaligned, &reference, &PairingOptions::default()).unwrap();
+    let records = sam_fields(&result, &reference);
+    assert_eq!(records.len(), 2);
+    assert_eq!(mate_fields(&records[0]), ["73", "target", "401", "*", "0", "0"]);
+    // Unmapped record placed at the mate
+    assert_eq!(mate_fields(&records[1]), ["133", "target", "401", "=", "401", "0"]);
+}
+
+#[test]
+fn sam_has_one_primary_record_per_mate() {
+    init_logger();
+    let (reference, first, second) = simulate_read_pair();
+    // The fragment is repeated in the other target
+    let target = reference.get_sequence(0).unwrap();
+    let reference = ReferenceBuilder::new()
+        .add_target("target", &target)
+        .add_target("repeat", &target[300..900])
+        .build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    for options in [
+        PairingOptions::default(),
+        // No concordant pair
+        PairingOptions::new(PairOrientation::ForwardForward, 0, 1000),
+    ] {
+        let result = aligner.align_read_pair(&first, &second, &reference, &options).unwrap();
+        assert_eq!(result.first.len(), 2);
+        assert_eq!(result.second.len(), 2);
+        let records = sam_fields(&result, &reference);
+        assert_eq!(records.len(), 4);
+        for mate_flag in [0x40, 0x80] {
+            let primary_records: Vec<&Vec<String>> = records.iter().filter(|record| {
+                let flag: u16 = record[1].parse().unwrap();
+                flag & mate_flag != 0 && flag & 0x100 == 0
+            }).collect();
+            assert_eq!(primary_records.len(), 1);
+        }
+    }
+
+    // The primary records are the mates of the best pair
+    let result = aligner.align_read_pair(&first, &second, &reference, &PairingOptions::default()).unwrap();
+    let (first_mate, second_mate) = result.mates_of(result.best_pair().unwrap());
+    let records = sam_fields(&result, &reference);
+    let primary_positions: Vec<(String, String)> = records.iter().filter(|record| {
+        record[1].parse::<u16>().unwrap() & 0x100 == 0
+    }).map(|record| (record[2].clone(), record[3].clone())).collect();
+    let label = |index: u32| reference.get_label_str(index).unwrap().to_string();
+    assert_eq!(primary_positions, vec![
+        (label(first_mate.target_index), (first_mate.alignment.position.target.0 + 1).to_string()),
+        (label(second_mate.target_index), (second_mate.alignment.position.target.0 + 1).to_string()),
+    ]);
+}
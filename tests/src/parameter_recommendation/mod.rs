@@ -0,0 +1,96 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, Reference, ReferenceBuilder,
+    algorithms::{Local, ParamsError},
+    utils::recommend_parameters::ParameterRecommender,
+};
+
+fn build_reference(sequence: &[u8]) -> Reference {
+    ReferenceBuilder::new()
+        .add_target("target", sequence)
+        .build().unwrap()
+}
+
+#[test]
+fn candidates_are_ordered_by_trade_off() {
+    init_logger();
+    let target = gen_rand_text(b"ACGT", 5000, 5000);
+    let reference = build_reference(&target);
+    let queries: Vec<Vec<u8>> = (0..20).map(|_| gen_rand_pattern(&target, 200, 300)).collect();
+
+    let recommendation = ParameterRecommender::new(&reference, 4, 6, 2)
+        .add_queries(&queries)
+        .recommend().unwrap();
+    let statistics = &recommendation.reference_statistics;
+    assert_eq!(statistics.num_targets, 1);
+    assert_eq!(statistics.total_length, 5000);
+    assert_eq!(statistics.num_residues, 4);
+    assert!(statistics.effective_alphabet_size > 3.9 && statistics.effective_alphabet_size <= 4.0);
+
+    let candidates = &recommendation.candidates;
+    assert!(!candidates.is_empty());
+    for pair in candidates.windows(2) {
+        assert!(pair[0].max_divergence >= pair[1].max_divergence);
+        assert!(pair[0].min_length <= pair[1].min_length);
+        assert!(pair[0].pattern_size <= pair[1].pattern_size);
+        assert!(pair[0].anchors_per_query >= pair[1].anchors_per_query);
+    }
+    for candidate in candidates {
+        assert!(candidate.min_length <= recommendation.query_length);
+        assert!(candidate.pattern_size >= 4);
+        assert!(candidate.relative_cost >= 1.0);
+        // Each query pattern is found at least in its origin (if not mutated)
+        assert!(candidate.observed_anchors_per_pattern > 0.0);
+    }
+}
+
+#[test]
+fn repeats_increase_anchors() {
+    let random_target = gen_rand_text(b"ACGT", 10000, 10000);
+    let repeat_unit = gen_rand_text(b"ACGT", 200, 200);
+    let repeat_target = repeat_unit.repeat(50);
+
+    let observed_anchors = |target: &[u8]| {
+        let reference = build_reference(target);
+        let queries: Vec<Vec<u8>> = (0..10).map(|_| target[1000..1300].to_vec()).collect();
+        ParameterRecommender::new(&reference, 4, 6, 2)
+            .add_queries(&queries)
+            .set_throughput_constants(vec![2.0])
+            .recommend().unwrap()
+            .candidates[0].observed_anchors_per_pattern
+    };
+    let random_anchors = observed_anchors(&random_target);
+    let repeat_anchors = observed_anchors(&repeat_target);
+    // Patterns near the end of the target occur 49 times
+    assert!(repeat_anchors >= 49.0);
+    assert!(repeat_anchors > random_anchors * 10.0);
+}
+
+#[test]
+fn recommended_cutoffs_can_be_used() {
+    let target = gen_rand_text(b"ACGT", 3000, 3000);
+    let reference = build_reference(&target);
+    let queries: Vec<Vec<u8>> = (0..10).map(|_| target[500..800].to_vec()).collect();
+    let recommendation = ParameterRecommender::new(&reference, 4, 6, 2)
+        .add_queries(&queries)
+        .recommend().unwrap();
+    for candidate in recommendation.candidates {
+        let algorithm = Local::new(4, 6, 2, candidate.min_length, candidate.max_penalty).unwrap();
+        let mut aligner = Aligner::new(algorithm);
+        assert!(aligner.align(&queries[0], &reference).count_alignments() > 0);
+    }
+
+    // Invalid inputs
+    assert!(matches!(
+        ParameterRecommender::new(&reference, 4, 6, 2).recommend(),
+        Err(ParamsError::InvalidValue(_)),
+    ));
+    assert!(matches!(
+        ParameterRecommender::new(&reference, 4, 6, 2)
+            .add_query(b"ACGTACGTACGT")
+            .set_throughput_constants(vec![-1.0])
+            .recommend(),
+        Err(ParamsError::InvalidValue(_)),
+    ));
+}
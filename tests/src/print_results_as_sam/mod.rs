@@ -42,7 +42,8 @@ fn print_results_as_sam() {
     );
     let mut query_buffer = Vec::new();
     let mut query_labels = String::new();
-    while let Some(mut record) = fasta_reader.next() {
+    while let Some(record) = fasta_reader.next() {
+        let mut record = record.unwrap();
         record.extend_seq_buf(&mut query_buffer);
         record.extend_id_string(&mut query_labels).unwrap();
         break;
@@ -67,27 +68,21 @@ fn print_results_as_sam() {
             ).unwrap();
         }
         // (1) Raw results
-        sam_formatter.write_query_alignment(
+        sam_formatter.write_read_alignment(
             &mut sam_results_buf_writer,
-            &query_alignment,
+            Some(&query_alignment),
+            None,
             &query_labels,
-            true,
+            query_buffer.len() as u32,
             &reference,
         ).unwrap();
-        // (2) Treat as reverse
-        sam_formatter.write_query_alignment(
+        // (2) Treat as both strands
+        sam_formatter.write_read_alignment(
             &mut sam_results_buf_writer,
-            &query_alignment,
+            Some(&query_alignment),
+            Some(&query_alignment),
             &query_labels,
-            false,
-            &reference,
-        ).unwrap();
-        // (3) Raw results again
-        sam_formatter.write_query_alignment(
-            &mut sam_results_buf_writer,
-            &query_alignment,
-            &query_labels,
-            true,
+            query_buffer.len() as u32,
             &reference,
         ).unwrap();
 
@@ -97,6 +92,15 @@ fn print_results_as_sam() {
         String::from_utf8(sam_results_buffer).unwrap()
     };
 
+    // One primary record for each read
+    if query_alignment.count_alignments() != 0 {
+        let num_primary_records = sam_results.lines().filter(|line| !line.starts_with('@')).filter(|line| {
+            let flag: u16 = line.split('\t').nth(1).unwrap().parse().unwrap();
+            flag & 0x100 == 0
+        }).count();
+        assert_eq!(num_primary_records, 2);
+    }
+
     // Print
     println!("{}", sam_results);
 }
\ No newline at end of file
@@ -0,0 +1,122 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::gen_rand_text;
+use sigalign::{
+    Aligner, ReferenceBuilder,
+    algorithms::Local,
+};
+use sigalign_utils::sequence_manipulation::{
+    preprocessing::{QueryPreprocessor, TrimmedRange},
+    reverse_complementary::reverse_complement_of_dna_sequence as reverse_complement,
+};
+
+/// Phred+33 quality string.
+fn quality(scores: &[u8]) -> Vec<u8> {
+    scores.iter().map(|x| x + 33).collect()
+}
+
+#[test]
+fn sliding_window_trims_low_quality_ends() {
+    let preprocessor = QueryPreprocessor::new().with_sliding_window_trimming(4, 20);
+    let sequence = b"ACGTACGTACGTACGT";
+    let qual = quality(&[
+        2, 2, 30, 30, 30, 30, 30, 30,
+        30, 30, 25, 10, 10, 10, 2, 2,
+    ]);
+    let mut buffer = Vec::new();
+    let range = preprocessor.preprocess(sequence, Some(&qual), &mut buffer);
+    // Two bases at 5' end; from the first window with low mean (starting at 9), bases passing remain.
+    assert_eq!((range.start, range.end, range.original_length), (2, 11, 16));
+    assert_eq!(buffer, &sequence[2..11]);
+
+    // Whole read is low quality
+    let qual = quality(&[2; 16]);
+    let range = preprocessor.preprocess(sequence, Some(&qual), &mut buffer);
+    assert!(range.is_empty());
+    assert!(buffer.is_empty());
+
+    // Without quality, nothing is trimmed
+    let range = preprocessor.preprocess(sequence, None, &mut buffer);
+    assert_eq!(range, TrimmedRange::full(16));
+    assert_eq!(buffer, sequence);
+}
+
+#[test]
+fn adapters_are_trimmed_from_3_prime_end() {
+    let preprocessor = QueryPreprocessor::new().with_adapters(
+        vec![b"AGATCGGAAG".to_vec(), b"CTGTCTCTTA".to_vec()],
+        4,
+    );
+    let mut buffer = Vec::new();
+
+    // Full adapter
+    let range = preprocessor.preprocess(b"TTTTTTTTTTCTGTCTCTTATTTT", None, &mut buffer);
+    assert_eq!((range.start, range.end), (0, 10));
+    // Leftmost of the adapters
+    let range = preprocessor.preprocess(b"TTTTAGATCGGAAGCTGTCTCTTA", None, &mut buffer);
+    assert_eq!((range.start, range.end), (0, 4));
+    // Partial adapter at the end
+    let range = preprocessor.preprocess(b"TTTTTTTTTTAGATC", None, &mut buffer);
+    assert_eq!((range.start, range.end), (0, 10));
+    // Shorter than the minimum overlap
+    let range = preprocessor.preprocess(b"TTTTTTTTTTAGA", None, &mut buffer);
+    assert_eq!((range.start, range.end), (0, 13));
+}
+
+#[test]
+fn low_quality_bases_are_masked() {
+    let preprocessor = QueryPreprocessor::new().with_quality_masking(20, b'#');
+    let mut buffer = Vec::new();
+    let qual = quality(&[30, 10, 30, 19, 20]);
+    let range = preprocessor.preprocess(b"ACGTA", Some(&qual), &mut buffer);
+    assert_eq!(range, TrimmedRange::full(5));
+    assert_eq!(buffer, b"A#G#A");
+}
+
+#[test]
+fn trimmed_range_of_reverse_strand() {
+    let range = TrimmedRange { start: 2, end: 7, original_length: 10 };
+    assert_eq!(range.reverse(), TrimmedRange { start: 3, end: 8, original_length: 10 });
+    assert_eq!(range.reverse().reverse(), range);
+}
+
+#[test]
+fn alignment_of_preprocessed_query_refers_to_original_read() {
+    init_logger();
+    let target = gen_rand_text(b"ACGT", 1000, 1000);
+    let reference = ReferenceBuilder::new()
+        .add_target("target", &target)
+        .build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    // Low quality random bases at both ends of the read from `target[300..450]`
+    let head = gen_rand_text(b"ACGT", 20, 20);
+    let tail = gen_rand_text(b"ACGT", 30, 30);
+    let read: Vec<u8> = [&head[..], &target[300..450], &tail[..]].concat();
+    let qual: Vec<u8> = quality(&[
+        vec![2; head.len()],
+        vec![35; 150],
+        vec![2; tail.len()],
+    ].concat());
+
+    let preprocessor = QueryPreprocessor::new().with_sliding_window_trimming(5, 20);
+    let mut preprocessed = Vec::new();
+    let range = preprocessor.preprocess(&read, Some(&qual), &mut preprocessed);
+    assert_eq!((range.start, range.end), (20, 170));
+
+    let result = aligner.align_preprocessed(&preprocessed, &range, &reference);
+    let alignment = &result.0[0].alignments[0];
+    assert_eq!(alignment.position.query, (20, 170));
+    assert_eq!(alignment.position.target, (300, 450));
+
+    // Reverse strand
+    let reversed = reverse_complement(&preprocessed);
+    let reversed_read = reverse_complement(&read);
+    let reverse_range = range.reverse();
+    assert_eq!(&reversed_read[reverse_range.start as usize..reverse_range.end as usize], &reversed[..]);
+    let reversed_target = reverse_complement(&target);
+    let reference = ReferenceBuilder::new()
+        .add_target("reversed", &reversed_target)
+        .build().unwrap();
+    let result = aligner.align_preprocessed(&reversed, &reverse_range, &reference);
+    assert_eq!(result.0[0].alignments[0].position.query, (30, 180));
+}
@@ -0,0 +1,124 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, Alphabet, Reference, ReferenceBuilder, ReferenceBuildError, StrandError,
+    algorithms::Local,
+    results::PairingOptions,
+};
+
+#[test]
+fn residues_are_normalized_to_alphabet() {
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Dna)
+        .add_target("a", b"ACGUNX")
+        .build().unwrap();
+    assert_eq!(reference.get_alphabet(), Some(Alphabet::Dna));
+    assert_eq!(reference.get_sequence(0).unwrap(), b"ACGT??");
+
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::DnaIupac)
+        .add_target("a", b"ACGURYN-")
+        .build().unwrap();
+    assert_eq!(reference.get_sequence(0).unwrap(), b"ACGTRYN?");
+
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Rna)
+        .add_target("a", b"ACGTUN")
+        .build().unwrap();
+    assert_eq!(reference.get_sequence(0).unwrap(), b"ACGUU?");
+
+    // Case is kept
+    let reference = ReferenceBuilder::new()
+        .set_uppercase(false)
+        .set_alphabet(Alphabet::Dna)
+        .add_target("a", b"ACgun")
+        .build().unwrap();
+    assert_eq!(reference.get_sequence(0).unwrap(), b"ACgt?");
+
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Protein)
+        .add_target("a", b"MKVLAX*1")
+        .build().unwrap();
+    assert_eq!(reference.get_sequence(0).unwrap(), b"MKVLAX*?");
+}
+
+#[test]
+fn strict_alphabet_rejects_unknown_residues() {
+    let result = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Dna)
+        .set_strict_alphabet(true)
+        .add_target("a", b"ACGT")
+        .add_target("b", b"ACGTN")
+        .build();
+    assert!(matches!(result, Err(ReferenceBuildError::InvalidSequence(_))));
+
+    // Convertible residues are accepted
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Dna)
+        .set_strict_alphabet(true)
+        .add_target("a", b"ACGU")
+        .build().unwrap();
+    assert_eq!(reference.get_sequence(0).unwrap(), b"ACGT");
+}
+
+#[test]
+fn alphabet_is_kept_in_saved_reference() {
+    for alphabet in [None, Some(Alphabet::Dna), Some(Alphabet::DnaIupac), Some(Alphabet::Rna), Some(Alphabet::Protein)] {
+        let mut builder = ReferenceBuilder::new().add_target("a", b"ACGTACGT");
+        if let Some(alphabet) = alphabet {
+            builder = builder.set_alphabet(alphabet);
+        }
+        let reference = builder.build().unwrap();
+        let mut buffer = Vec::new();
+        reference.save_to(&mut buffer).unwrap();
+        let loaded = Reference::load_from(&buffer[..]).unwrap();
+        assert_eq!(loaded.get_alphabet(), alphabet);
+        assert_eq!(loaded.get_sequence(0), reference.get_sequence(0));
+    }
+}
+
+#[test]
+fn strand_follows_alphabet() {
+    let build = |alphabet: Option<Alphabet>| {
+        let mut builder = ReferenceBuilder::new().add_target("a", b"ACGU");
+        if let Some(alphabet) = alphabet {
+            builder = builder.set_alphabet(alphabet);
+        }
+        builder.build().unwrap()
+    };
+    assert_eq!(build(None).reverse_complement(b"AACGT").unwrap(), b"ACGTT");
+    assert_eq!(build(Some(Alphabet::Dna)).reverse_complement(b"AACGT").unwrap(), b"ACGTT");
+    assert_eq!(build(Some(Alphabet::DnaIupac)).reverse_complement(b"ARBN").unwrap(), b"NVYT");
+    assert_eq!(build(Some(Alphabet::Rna)).reverse_complement(b"AACGU").unwrap(), b"ACGUU");
+    // The case is kept
+    assert_eq!(build(None).reverse_complement(b"aaCgT").unwrap(), b"AcGtt");
+    assert_eq!(build(Some(Alphabet::DnaIupac)).reverse_complement(b"arBn").unwrap(), b"nVyt");
+    assert_eq!(build(Some(Alphabet::Rna)).reverse_complement(b"aaCgU").unwrap(), b"AcGuu");
+
+    let protein_reference = build(Some(Alphabet::Protein));
+    assert!(!protein_reference.has_complementary_strand());
+    assert_eq!(
+        protein_reference.reverse_complement(b"MKV"),
+        Err(StrandError::NoComplementaryStrand(Alphabet::Protein)),
+    );
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let result = aligner.align_read_pair(b"MKV", b"MKV", &protein_reference, &PairingOptions::default());
+    assert!(result.is_err());
+}
+
+#[test]
+fn protein_reference_can_be_aligned() {
+    init_logger();
+    let amino_acids = b"ACDEFGHIKLMNPQRSTVWY";
+    let target = gen_rand_text(amino_acids, 500, 500);
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Protein)
+        .set_strict_alphabet(true)
+        .add_target("protein", &target)
+        .build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let query = gen_rand_pattern(&target, 100, 150);
+    let result = aligner.align(&query, &reference);
+    assert!(result.count_alignments() > 0);
+}
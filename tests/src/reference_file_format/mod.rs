@@ -0,0 +1,160 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD_NO_PAD};
+use capwriter::Save;
+use sigalign_core::reference::extensions::Serialize as _;
+use sigalign::{
+    Alphabet, Reference, ReferenceBuilder, ReferenceBuildOptions, ReferenceLoadError,
+};
+
+use crate::common::random_text_and_pattern::gen_rand_text;
+
+fn build_reference() -> Reference {
+    ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Dna)
+        .set_soft_masking(true)
+        .set_keep_metadata(true)
+        .ignore_base(b'N')
+        .add_fasta(format!(
+            ">a taxid=1\n{}\n>b\n{}acgt\n",
+            String::from_utf8(gen_rand_text(b"ACGT", 1000, 1000)).unwrap(),
+            String::from_utf8(gen_rand_text(b"ACGTN", 500, 500)).unwrap(),
+        ).as_bytes()).unwrap()
+        .build().unwrap()
+}
+
+fn saved_bytes(reference: &Reference) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    reference.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+fn assert_same_reference(a: &Reference, b: &Reference) {
+    assert_eq!(a.get_num_targets(), b.get_num_targets());
+    for target_index in 0..a.get_num_targets() {
+        assert_eq!(a.get_sequence(target_index), b.get_sequence(target_index));
+        assert_eq!(a.get_label(target_index), b.get_label(target_index));
+        assert_eq!(a.get_target_metadata(target_index), b.get_target_metadata(target_index));
+    }
+    assert_eq!(a.get_alphabet(), b.get_alphabet());
+    assert_eq!(a.get_soft_masked_regions(), b.get_soft_masked_regions());
+}
+
+#[test]
+fn header_records_types_and_options() {
+    let reference = build_reference();
+    let buffer = saved_bytes(&reference);
+
+    let header = Reference::verify_from(&buffer[..]).unwrap();
+    assert_eq!(header.format_version, 2);
+    assert_eq!(header.storage_type, "InMemoryStorage");
+    assert_eq!(header.index_type, "DynamicLfi");
+    assert_eq!(header.num_targets, 2);
+    assert_eq!(header.total_length, reference.get_total_length());
+    assert_eq!(header.alphabet, Some(Alphabet::Dna));
+    assert_eq!(header.build_options, Some(ReferenceBuildOptions {
+        uppercase: true,
+        strict_alphabet: false,
+        ignored_bases: b"N".to_vec(),
+    }));
+    let section_names: Vec<&str> = header.sections.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(section_names, ["raw_reference", "soft_masked_regions", "target_metadata"]);
+
+    let loaded = Reference::load_from(&buffer[..]).unwrap();
+    assert_same_reference(&reference, &loaded);
+    assert_eq!(loaded.get_build_options(), reference.get_build_options());
+}
+
+#[test]
+fn verify_file_from_path() {
+    let reference = build_reference();
+    let path = std::env::temp_dir().join(format!("sigalign_reference_format_{}.sigref", std::process::id()));
+    reference.save_to(std::fs::File::create(&path).unwrap()).unwrap();
+    let header = Reference::verify(&path).unwrap();
+    assert_eq!(header.num_targets, 2);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(Reference::verify(&path), Err(ReferenceLoadError::IoError(_))));
+}
+
+#[test]
+fn truncated_file_is_detected() {
+    let buffer = saved_bytes(&build_reference());
+    for length in [buffer.len() - 1, buffer.len() - 16, buffer.len() / 2, 100] {
+        let truncated = &buffer[..length];
+        assert!(matches!(Reference::load_from(truncated), Err(ReferenceLoadError::Truncated)), "length {}", length);
+        assert!(matches!(Reference::verify_from(truncated), Err(ReferenceLoadError::Truncated)), "length {}", length);
+    }
+    assert!(matches!(Reference::load_from(&buffer[..0]), Err(ReferenceLoadError::UnknownFile)));
+    // Not to allocate the length read from the other format
+    assert!(matches!(Reference::load_from(&b"not a reference file"[..]), Err(ReferenceLoadError::UnknownFile)));
+}
+
+#[test]
+fn corrupted_file_is_detected() {
+    let mut buffer = saved_bytes(&build_reference());
+    let middle = buffer.len() / 2;
+    buffer[middle] ^= 0xFF;
+    match Reference::load_from(&buffer[..]) {
+        Err(ReferenceLoadError::ChecksumMismatch(section)) => assert_eq!(section, "raw_reference"),
+        other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+    }
+    assert!(matches!(
+        Reference::verify_from(&buffer[..]),
+        Err(ReferenceLoadError::ChecksumMismatch(_)),
+    ));
+}
+
+#[test]
+fn unknown_sections_are_skipped() {
+    let reference = build_reference();
+    let mut buffer = saved_bytes(&reference);
+    // Insert a section from the newer version before the end section (the last 16 bytes)
+    let payload = b"from the future";
+    let mut section = Vec::new();
+    section.extend_from_slice(&99_u32.to_le_bytes());
+    section.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    section.extend_from_slice(payload);
+    section.extend_from_slice(&crc32fast::hash(payload).to_le_bytes());
+    let end_position = buffer.len() - 16;
+    buffer.splice(end_position..end_position, section);
+
+    let loaded = Reference::load_from(&buffer[..]).unwrap();
+    assert_same_reference(&reference, &loaded);
+    let header = Reference::verify_from(&buffer[..]).unwrap();
+    assert_eq!(header.sections.last().unwrap(), &("unknown_99".to_string(), payload.len() as u64));
+}
+
+#[test]
+fn newer_format_is_rejected() {
+    let mut buffer = Vec::new();
+    STANDARD_NO_PAD.encode("SIGALIGN_REFERENCE:FORMAT:3").as_bytes().save_to(&mut buffer).unwrap();
+    assert!(matches!(
+        Reference::load_from(&buffer[..]),
+        Err(ReferenceLoadError::IncompatibleVersion(_)),
+    ));
+}
+
+#[test]
+fn legacy_file_is_migrated() {
+    let reference = build_reference();
+    // Legacy: signature, and the raw reference without the sections
+    let mut buffer = Vec::new();
+    STANDARD_NO_PAD.encode("SIGALIGN_REFERENCE:0.4.0:0.2.0").as_bytes().save_to(&mut buffer).unwrap();
+    reference.as_ref().save_to(&mut buffer).unwrap();
+
+    let loaded = Reference::load_from(&buffer[..]).unwrap();
+    assert_eq!(loaded.get_alphabet(), None);
+    assert_eq!(loaded.get_sequence(0), reference.get_sequence(0));
+    let header = Reference::verify_from(&buffer[..]).unwrap();
+    assert_eq!(header.format_version, 1);
+    assert!(header.sections.is_empty());
+
+    // Saved in the current format
+    let migrated = saved_bytes(&loaded);
+    assert_eq!(Reference::verify_from(&migrated[..]).unwrap().format_version, 2);
+
+    // More parts in the signature are not of this file
+    let mut buffer = Vec::new();
+    STANDARD_NO_PAD.encode("SIGALIGN_REFERENCE:0.4.0:0.2.0:DNA").as_bytes().save_to(&mut buffer).unwrap();
+    reference.as_ref().save_to(&mut buffer).unwrap();
+    assert!(matches!(Reference::load_from(&buffer[..]), Err(ReferenceLoadError::UnknownFile)));
+}
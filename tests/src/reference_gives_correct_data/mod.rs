@@ -32,7 +32,8 @@ fn test_reference_gives_correct_data() {
             let mut label_buffer = String::new();
             let mut fasta_reader = FastaReader::from_path(&fasta_file).unwrap();
 
-            while let Some(mut record) = fasta_reader.next() {
+            while let Some(record) = fasta_reader.next() {
+                let mut record = record.unwrap();
                 seq_buffer.clear();
                 label_buffer.clear();
 
@@ -55,7 +56,8 @@ fn test_reference_gives_correct_data() {
         let mut fasta_reader = FastaReader::from_path(&fasta_file).unwrap();
 
         let mut target_index = 0;
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record.unwrap();
             seq_buffer.clear();
             label_buffer.clear();
 
@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use sigalign::{
+    Aligner, Alphabet, Reference, ReferenceBuilder, ReferenceLoadError,
+    algorithms::Local,
+};
+
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+
+fn build_reference() -> Reference {
+    ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Dna)
+        .set_soft_masking(true)
+        .set_keep_metadata(true)
+        .add_fasta(format!(
+            ">a taxid=1\n{}\n>b\n{}acgt\n",
+            String::from_utf8(gen_rand_text(b"ACGT", 1000, 1000)).unwrap(),
+            String::from_utf8(gen_rand_text(b"ACGT", 500, 500)).unwrap(),
+        ).as_bytes()).unwrap()
+        .build().unwrap()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("sigalign_mapped_{}_{}.sigref", name, std::process::id()))
+}
+
+fn save_to_path(reference: &Reference, name: &str) -> PathBuf {
+    let path = temp_path(name);
+    reference.save_to(std::fs::File::create(&path).unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn mapped_reference_is_same_as_loaded() {
+    init_logger();
+    let reference = build_reference();
+    let path = save_to_path(&reference, "same");
+
+    let loaded = Reference::load_from(std::fs::File::open(&path).unwrap()).unwrap();
+    let mapped = unsafe { Reference::load_from_mapped_file(&path) }.unwrap();
+    assert!(!loaded.is_sequence_shared());
+    assert!(!loaded.is_pattern_index_shared());
+    assert!(mapped.is_sequence_shared());
+    assert!(mapped.is_pattern_index_shared());
+
+    assert_eq!(mapped.get_num_targets(), loaded.get_num_targets());
+    for target_index in 0..mapped.get_num_targets() {
+        assert_eq!(mapped.get_sequence(target_index), loaded.get_sequence(target_index));
+        assert_eq!(mapped.get_label(target_index), loaded.get_label(target_index));
+        assert_eq!(mapped.get_target_metadata(target_index), loaded.get_target_metadata(target_index));
+    }
+    assert_eq!(mapped.get_alphabet(), loaded.get_alphabet());
+    assert_eq!(mapped.get_soft_masked_regions(), loaded.get_soft_masked_regions());
+    assert_eq!(mapped.get_build_options(), loaded.get_build_options());
+
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let target = mapped.get_sequence(0).unwrap();
+    for _ in 0..10 {
+        let query = gen_rand_pattern(&target, 100, 200);
+        assert_eq!(
+            aligner.align(&query, &mapped).to_json(),
+            aligner.align(&query, &loaded).to_json(),
+        );
+    }
+
+    // Saved again in the same bytes
+    let mut resaved = Vec::new();
+    mapped.save_to(&mut resaved).unwrap();
+    assert_eq!(resaved, std::fs::read(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn mapped_index_of_each_type_is_same_as_loaded() {
+    init_logger();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let cases: [(&[u8], &str); 4] = [
+        (b"ACG", "Lfi32B2V64"),
+        (b"ACGTN", "Lfi32B3V64"),
+        (b"ACDEFGHIKL", "Lfi32B4V64"),
+        (b"ACDEFGHIKLMNPQRSTVWY", "Lfi32B5V64"),
+    ];
+    for (characters, pattern_index_type) in cases {
+        let reference = ReferenceBuilder::new()
+            .add_target("a", &gen_rand_text(characters, 2000, 2000))
+            .add_target("b", &gen_rand_text(characters, 999, 999))
+            .build().unwrap();
+        assert_eq!(reference.get_summary().pattern_index_type, pattern_index_type);
+        let path = save_to_path(&reference, pattern_index_type);
+        let mapped = unsafe { Reference::load_from_mapped_file(&path) }.unwrap();
+        assert!(mapped.is_pattern_index_shared());
+        assert_eq!(mapped.get_summary().pattern_index_type, pattern_index_type);
+        assert_eq!(mapped.get_estimated_size_in_bytes(), reference.get_estimated_size_in_bytes());
+
+        for target_index in 0..2 {
+            let target = reference.get_sequence(target_index).unwrap();
+            for _ in 0..5 {
+                let query = gen_rand_pattern(&target, 60, 200);
+                assert_eq!(
+                    aligner.align(&query, &mapped).to_json(),
+                    aligner.align(&query, &reference).to_json(),
+                );
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+fn payloads_are_aligned() {
+    let buffer = {
+        let mut buffer = Vec::new();
+        build_reference().save_to(&mut buffer).unwrap();
+        buffer
+    };
+    let signature_length = u64::from_le_bytes(buffer[0..8].try_into().unwrap()) as usize;
+    let mut offset = 8 + signature_length;
+    let mut kinds = Vec::new();
+    loop {
+        let kind = u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap());
+        let length = u64::from_le_bytes(buffer[offset + 4..offset + 12].try_into().unwrap()) as usize;
+        let payload_offset = offset + 12;
+        kinds.push(kind);
+        if kind == 0 {
+            break;
+        } else if kind != 1 && kind != 5 {
+            // Except the header and the padding
+            assert_eq!(payload_offset % 64, 0, "section {}", kind);
+        }
+        // With the checksum after the payload
+        offset = payload_offset + length + 4;
+    }
+    assert_eq!(offset + 16, buffer.len());
+    // Padding sections are not listed
+    let header = Reference::verify_from(&buffer[..]).unwrap();
+    let section_names: Vec<&str> = header.sections.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(section_names, ["raw_reference", "soft_masked_regions", "target_metadata"]);
+    assert!(kinds.contains(&5));
+}
+
+#[test]
+fn broken_file_is_detected_when_mapped() {
+    let reference = build_reference();
+    let buffer = {
+        let mut buffer = Vec::new();
+        reference.save_to(&mut buffer).unwrap();
+        buffer
+    };
+    let path = temp_path("broken");
+
+    // The raw reference is checked by `verify`, not when mapped
+    let mut corrupted = buffer.clone();
+    let middle = corrupted.len() / 2;
+    corrupted[middle] ^= 0xFF;
+    std::fs::write(&path, &corrupted).unwrap();
+    assert!(matches!(
+        Reference::verify(&path),
+        Err(ReferenceLoadError::ChecksumMismatch(_)),
+    ));
+    // The other sections are checked
+    let mut corrupted = buffer.clone();
+    let metadata_offset = corrupted.windows(5).rposition(|x| x == b"taxid").unwrap();
+    corrupted[metadata_offset] ^= 0xFF;
+    std::fs::write(&path, &corrupted).unwrap();
+    assert!(matches!(
+        unsafe { Reference::load_from_mapped_file(&path) },
+        Err(ReferenceLoadError::ChecksumMismatch(_)),
+    ));
+
+    std::fs::write(&path, &buffer[..buffer.len() - 1]).unwrap();
+    assert!(matches!(
+        unsafe { Reference::load_from_mapped_file(&path) },
+        Err(ReferenceLoadError::Truncated),
+    ));
+
+    std::fs::write(&path, []).unwrap();
+    assert!(matches!(
+        unsafe { Reference::load_from_mapped_file(&path) },
+        Err(ReferenceLoadError::UnknownFile),
+    ));
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(
+        unsafe { Reference::load_from_mapped_file(&path) },
+        Err(ReferenceLoadError::IoError(_)),
+    ));
+}
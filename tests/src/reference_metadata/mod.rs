@@ -0,0 +1,171 @@
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, Reference, ReferenceBuilder, ReferenceBuildError, TargetMetadata,
+    algorithms::Local,
+    results::LabeledQueryAlignment,
+};
+
+const FASTA: &str = "\
+>seq1 Escherichia coli chromosome taxid=562 [strain=K-12]
+ACGTACGTACGT
+>seq2
+ACGTACGT
+>seq3 plasmid;copy=high
+ACGT
+";
+
+#[test]
+fn descriptions_are_parsed_from_fasta() {
+    let metadata = TargetMetadata::from_fasta_description("E. coli taxid=562 [strain=K-12]; =x");
+    assert_eq!(metadata.description, "E. coli taxid=562 [strain=K-12]; =x");
+    assert_eq!(metadata.get_attribute("taxid"), Some("562"));
+    assert_eq!(metadata.get_attribute("strain"), Some("K-12"));
+    assert_eq!(metadata.attributes.len(), 2);
+
+    let reference = ReferenceBuilder::new()
+        .set_keep_metadata(true)
+        .add_fasta(FASTA.as_bytes()).unwrap()
+        .add_target("seq4", b"ACGT")
+        .build().unwrap();
+    let metadata = reference.get_target_metadata(0).unwrap();
+    assert_eq!(metadata.description, "Escherichia coli chromosome taxid=562 [strain=K-12]");
+    assert_eq!(metadata.get_attribute("taxid"), Some("562"));
+    assert_eq!(metadata.get_attribute("strain"), Some("K-12"));
+    assert_eq!(reference.get_target_metadata(1).unwrap(), &TargetMetadata::default());
+    assert_eq!(reference.get_target_metadata(2).unwrap().get_attribute("copy"), Some("high"));
+    assert_eq!(reference.get_target_metadata(3).unwrap(), &TargetMetadata::default());
+    assert!(reference.get_target_metadata(4).is_none());
+    // Labels are not changed
+    assert_eq!(reference.get_label(0).unwrap(), "seq1");
+
+    // Not kept by default
+    let reference = ReferenceBuilder::new()
+        .add_fasta(FASTA.as_bytes()).unwrap()
+        .build().unwrap();
+    assert!(reference.get_target_metadata(0).is_none());
+}
+
+#[test]
+fn attributes_are_added_from_table() {
+    let table = "label\ttaxid\tspecies\n\
+        seq1\t511145\tEscherichia coli\n\
+        seq3\t\tunknown\n\
+        seq9\t1\tnot in reference\n";
+    let reference = ReferenceBuilder::new()
+        .set_keep_metadata(true)
+        .add_fasta(FASTA.as_bytes()).unwrap()
+        .add_metadata_tsv(table.as_bytes()).unwrap()
+        .build().unwrap();
+    // Table overrides the description
+    let metadata = reference.get_target_metadata(0).unwrap();
+    assert_eq!(metadata.get_attribute("taxid"), Some("511145"));
+    assert_eq!(metadata.get_attribute("species"), Some("Escherichia coli"));
+    assert_eq!(metadata.get_attribute("strain"), Some("K-12"));
+    assert!(reference.get_target_metadata(1).unwrap().attributes.is_empty());
+    let metadata = reference.get_target_metadata(2).unwrap();
+    assert_eq!(metadata.get_attribute("taxid"), None);
+    assert_eq!(metadata.get_attribute("species"), Some("unknown"));
+
+    // Only the table
+    let reference = ReferenceBuilder::new()
+        .add_fasta(FASTA.as_bytes()).unwrap()
+        .add_metadata_tsv(table.as_bytes()).unwrap()
+        .build().unwrap();
+    let metadata = reference.get_target_metadata(0).unwrap();
+    assert_eq!(metadata.description, "");
+    assert_eq!(metadata.get_attribute("taxid"), Some("511145"));
+    assert_eq!(metadata.get_attribute("strain"), None);
+
+    // Malformed table
+    let result = ReferenceBuilder::new()
+        .add_metadata_tsv("label\ttaxid\nseq1\t1\t2\n".as_bytes());
+    assert!(matches!(result, Err(ReferenceBuildError::InvalidMetadata(_))));
+}
+
+#[test]
+fn metadata_is_saved_and_labeled() {
+    let table = "label\tspecies\nseq1\tEscherichia coli\n";
+    let reference = ReferenceBuilder::new()
+        .set_keep_metadata(true)
+        .set_soft_masking(true)
+        .add_fasta(FASTA.as_bytes()).unwrap()
+        .add_metadata_tsv(table.as_bytes()).unwrap()
+        .build().unwrap();
+    let mut buffer = Vec::new();
+    reference.save_to(&mut buffer).unwrap();
+    let loaded = Reference::load_from(&buffer[..]).unwrap();
+    for target_index in 0..reference.get_num_targets() {
+        assert_eq!(
+            loaded.get_target_metadata(target_index),
+            reference.get_target_metadata(target_index),
+        );
+    }
+    assert!(loaded.get_soft_masked_regions().is_some());
+
+    // Set metadata manually
+    let mut reference = ReferenceBuilder::new()
+        .add_fasta(FASTA.as_bytes()).unwrap()
+        .build().unwrap();
+    assert!(reference.set_target_metadata(vec![TargetMetadata::default()]).is_err());
+    let metadata = vec![TargetMetadata::from_fasta_description("taxid=1"); 3];
+    reference.set_target_metadata(metadata).unwrap();
+    assert_eq!(reference.get_target_metadata(2).unwrap().get_attribute("taxid"), Some("1"));
+}
+
+#[test]
+fn labeled_results_have_metadata() {
+    let target = gen_rand_text(b"ACGT", 500, 500);
+    let fasta = format!(">target taxid=562\n{}\n", String::from_utf8(target.clone()).unwrap());
+    let query = gen_rand_pattern(&target, 100, 150);
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let reference = ReferenceBuilder::new()
+        .set_keep_metadata(true)
+        .add_fasta(fasta.as_bytes()).unwrap()
+        .build().unwrap();
+    let result = aligner.align(&query, &reference);
+    assert!(result.count_alignments() > 0);
+    let labeled = reference.label_query_alignment(result);
+    let target_alignment = &labeled.0[0];
+    assert_eq!(target_alignment.label, "target");
+    assert_eq!(
+        target_alignment.metadata(&reference).unwrap().get_attribute("taxid"),
+        Some("562"),
+    );
+
+    // None without metadata
+    let reference = ReferenceBuilder::new()
+        .add_fasta(fasta.as_bytes()).unwrap()
+        .build().unwrap();
+    let labeled = reference.label_query_alignment(aligner.align(&query, &reference));
+    assert!(labeled.0[0].metadata(&reference).is_none());
+}
+
+#[test]
+fn metadata_of_results_survive_round_trip() {
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let target = String::from_utf8(gen_rand_text(b"ACGT", 500, 500)).unwrap();
+    let fasta = format!(">seq1 taxid=562\n{}\n>seq2 taxid=1280\n{}\n", target, target);
+    let reference = ReferenceBuilder::new()
+        .set_keep_metadata(true)
+        .add_fasta(fasta.as_bytes()).unwrap()
+        .build().unwrap();
+    let query = gen_rand_pattern(target.as_bytes(), 100, 150);
+    let labeled = reference.label_query_alignment(aligner.align(&query, &reference));
+    assert_eq!(labeled.0.len(), 2);
+
+    // Both the reference and the results are saved and loaded
+    let mut buffer = Vec::new();
+    reference.save_to(&mut buffer).unwrap();
+    let loaded_reference = Reference::load_from(&buffer[..]).unwrap();
+    let loaded_labeled = LabeledQueryAlignment::from_binary(&labeled.to_binary()).unwrap();
+
+    assert_eq!(loaded_labeled.0.len(), labeled.0.len());
+    for (loaded, original) in loaded_labeled.0.iter().zip(labeled.0.iter()) {
+        assert_eq!(loaded.label, original.label);
+        assert_eq!(loaded.metadata(&loaded_reference), original.metadata(&reference));
+        assert!(loaded.metadata(&loaded_reference).is_some());
+    }
+    let seq1 = loaded_labeled.0.iter().find(|x| x.label == "seq1").unwrap();
+    assert_eq!(seq1.metadata(&loaded_reference).unwrap().get_attribute("taxid"), Some("562"));
+}
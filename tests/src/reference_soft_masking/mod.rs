@@ -0,0 +1,102 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::gen_rand_text;
+use sigalign::{
+    Aligner, Alphabet, Reference, ReferenceBuilder,
+    algorithms::Local,
+};
+
+// Uppercase (200) + lowercase (200) + uppercase (200)
+fn gen_soft_masked_target() -> Vec<u8> {
+    let mut target = gen_rand_text(b"ACGT", 200, 200);
+    target.extend(gen_rand_text(b"acgt", 200, 200));
+    target.extend(gen_rand_text(b"ACGT", 200, 200));
+    target
+}
+
+#[test]
+fn lowercase_regions_are_recorded() {
+    let reference = ReferenceBuilder::new()
+        .set_soft_masking(true)
+        .add_target("a", b"aaCCggTT")
+        .add_target("b", b"ACGT")
+        .add_target("c", b"acgt")
+        .build().unwrap();
+    let regions = reference.get_soft_masked_regions().unwrap();
+    assert_eq!(regions.of_target(0), Some(&[(0, 2), (4, 6)][..]));
+    assert_eq!(regions.of_target(1), Some(&[][..]));
+    assert_eq!(regions.of_target(2), Some(&[(0, 4)][..]));
+    assert_eq!(regions.of_target(3), None);
+    // Sequences are uppercased
+    assert_eq!(reference.get_sequence(0).unwrap(), b"AACCGGTT");
+
+    assert_eq!(regions.count_in_range(0, (0, 8)), 4);
+    assert_eq!(regions.count_in_range(0, (1, 5)), 2);
+    assert_eq!(regions.count_in_range(0, (2, 4)), 0);
+    assert_eq!(regions.count_in_range(3, (0, 4)), 0);
+
+    let reference = ReferenceBuilder::new()
+        .add_target("a", b"aaCCggTT")
+        .build().unwrap();
+    assert!(reference.get_soft_masked_regions().is_none());
+}
+
+#[test]
+fn soft_masked_regions_are_excluded_from_seeding() {
+    init_logger();
+    let target = gen_soft_masked_target();
+    let query = target[250..350].to_ascii_uppercase();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let reference = ReferenceBuilder::new()
+        .add_target("target", &target)
+        .build().unwrap();
+    assert!(aligner.align(&query, &reference).count_alignments() > 0);
+
+    let soft_masked_reference = ReferenceBuilder::new()
+        .set_soft_masking(true)
+        .add_target("target", &target)
+        .build().unwrap();
+    assert_eq!(aligner.align(&query, &soft_masked_reference).count_alignments(), 0);
+}
+
+#[test]
+fn soft_masked_regions_are_aligned_in_extension() {
+    init_logger();
+    let target = gen_soft_masked_target();
+    let query = target[140..460].to_ascii_uppercase();
+    let reference = ReferenceBuilder::new()
+        .set_soft_masking(true)
+        .add_target("target", &target)
+        .build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let result = aligner.align(&query, &reference);
+    let alignment = result.0.iter()
+        .flat_map(|x| x.alignments.iter())
+        .find(|x| x.position.target == (140, 460))
+        .expect("Alignment through the soft-masked region is not found");
+    assert_eq!(alignment.penalty, 0);
+    assert_eq!(reference.count_soft_masked_bases(0, alignment), 200);
+}
+
+#[test]
+fn soft_masked_regions_are_saved() {
+    let target = gen_soft_masked_target();
+    let reference = ReferenceBuilder::new()
+        .set_soft_masking(true)
+        .set_alphabet(Alphabet::Dna)
+        .add_target("target", &target)
+        .add_target("unmasked", b"ACGTACGT")
+        .build().unwrap();
+    let mut buffer = Vec::new();
+    reference.save_to(&mut buffer).unwrap();
+    let loaded = Reference::load_from(&buffer[..]).unwrap();
+
+    assert_eq!(loaded.get_alphabet(), Some(Alphabet::Dna));
+    assert_eq!(loaded.get_soft_masked_regions(), reference.get_soft_masked_regions());
+    assert_eq!(
+        loaded.get_soft_masked_regions().unwrap().of_target(0),
+        Some(&[(200, 400)][..]),
+    );
+    assert_eq!(loaded.get_sequence(0), reference.get_sequence(0));
+}
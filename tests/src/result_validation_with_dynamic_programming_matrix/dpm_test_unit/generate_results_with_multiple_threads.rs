@@ -68,7 +68,8 @@ impl DpmTestUnit{
         let (ref_file, qry_file) = self.test_data.get_data_paths();
         let mut qry_reader = FastaReader::from_path(qry_file)?;
         let mut qry_index = 0;
-        while let Some(mut record) = qry_reader.next() {
+        while let Some(record) = qry_reader.next() {
+            let mut record = record.unwrap();
             let mut query = Vec::new();
             record.extend_seq_buf(&mut query);
 
@@ -0,0 +1,159 @@
+use std::io::ErrorKind;
+
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, Reference, ReferenceBuilder,
+    algorithms::Local,
+    results::{
+        QueryAlignment,
+        TargetAlignment,
+        Alignment,
+        AlignmentPosition,
+        LabeledQueryAlignment,
+        binary::{
+            QueryAlignmentWriter,
+            QueryAlignmentReader,
+            LabeledQueryAlignmentWriter,
+            LabeledQueryAlignmentReader,
+        },
+    },
+};
+
+fn get_reference_and_results() -> (Reference, Vec<QueryAlignment>) {
+    let targets: Vec<Vec<u8>> = (0..5).map(|_| gen_rand_text(b"ACGT", 500, 1000)).collect();
+    let mut builder = ReferenceBuilder::new();
+    for (index, target) in targets.iter().enumerate() {
+        builder = builder.add_target(&format!("target_{}", index), target);
+    }
+    let reference = builder.build().unwrap();
+
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let results = targets.iter().cycle().take(20).map(|target| {
+        let query = gen_rand_pattern(target, 100, 200);
+        aligner.align(&query, &reference)
+    }).collect();
+    (reference, results)
+}
+
+#[test]
+fn binary_encoding_is_same_as_json() {
+    init_logger();
+    let (reference, results) = get_reference_and_results();
+    assert!(results.iter().any(|x| !x.0.is_empty()));
+
+    for result in results {
+        let decoded = QueryAlignment::from_binary(&result.to_binary()).unwrap();
+        assert_eq!(result.to_json(), decoded.to_json());
+        assert!(result.to_binary().len() < result.to_json().len());
+
+        let labeled = reference.label_query_alignment(result);
+        let decoded = LabeledQueryAlignment::from_binary(&labeled.to_binary()).unwrap();
+        assert_eq!(labeled.to_json(), decoded.to_json());
+    }
+}
+
+#[test]
+fn binary_stream_can_be_appended_and_reread() {
+    init_logger();
+    let (reference, results) = get_reference_and_results();
+    let (first, second) = results.split_at(results.len() / 2);
+
+    // In memory
+    let mut buffer = Vec::new();
+    let mut writer = QueryAlignmentWriter::new(&mut buffer).unwrap();
+    for result in first {
+        writer.write_record(result).unwrap();
+    }
+    writer.finish().unwrap();
+    let mut writer = QueryAlignmentWriter::appending(&mut buffer);
+    for result in second {
+        writer.write_record(result).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let decoded: Vec<QueryAlignment> = QueryAlignmentReader::new(&buffer[..]).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(decoded.len(), results.len());
+    for (result, decoded) in results.iter().zip(decoded.iter()) {
+        assert_eq!(result.to_json(), decoded.to_json());
+    }
+
+    // In file
+    let file_path = std::env::temp_dir().join(format!("sigalign_binary_stream_{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&file_path);
+    let labeled: Vec<LabeledQueryAlignment> = results.iter()
+        .map(|x| reference.label_query_alignment(x.clone()))
+        .collect();
+    let (first, second) = labeled.split_at(labeled.len() / 2);
+    for chunk in [first, second] {
+        let mut writer = LabeledQueryAlignmentWriter::append_to_path(&file_path).unwrap();
+        for result in chunk {
+            writer.write_record(result).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    // Simulate the killed writer
+    let file_size = std::fs::metadata(&file_path).unwrap().len();
+    let mut bytes = std::fs::read(&file_path).unwrap();
+    let mut writer = LabeledQueryAlignmentWriter::new(Vec::new()).unwrap();
+    writer.write_record(&labeled[0]).unwrap();
+    let one_record = writer.finish().unwrap();
+    bytes.extend_from_slice(&one_record[6..one_record.len() - 1]);
+    std::fs::write(&file_path, &bytes).unwrap();
+    // Incomplete record is dropped before appending
+    let writer = LabeledQueryAlignmentWriter::append_to_path(&file_path).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(std::fs::metadata(&file_path).unwrap().len(), file_size);
+
+    let file = std::fs::File::open(&file_path).unwrap();
+    let decoded: Vec<LabeledQueryAlignment> = LabeledQueryAlignmentReader::new(std::io::BufReader::new(file)).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    std::fs::remove_file(&file_path).unwrap();
+    assert_eq!(decoded.len(), labeled.len());
+    for (result, decoded) in labeled.iter().zip(decoded.iter()) {
+        assert_eq!(result.to_json(), decoded.to_json());
+    }
+}
+
+#[test]
+fn invalid_binary_stream_is_error() {
+    init_logger();
+    let (_, results) = get_reference_and_results();
+
+    let mut writer = QueryAlignmentWriter::new(Vec::new()).unwrap();
+    for result in results.iter() {
+        writer.write_record(result).unwrap();
+    }
+    let buffer = writer.finish().unwrap();
+
+    // Truncated
+    let truncated = &buffer[..buffer.len() - 1];
+    let last = QueryAlignmentReader::new(truncated).unwrap().last().unwrap();
+    assert_eq!(last.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    // Wrong record type
+    assert!(LabeledQueryAlignmentReader::new(&buffer[..]).is_err());
+    // Not a stream
+    assert!(QueryAlignmentReader::new(&b"SGR"[..]).is_err());
+    assert!(QueryAlignmentReader::new(&b"NOTSTREAM"[..]).is_err());
+    // Trailing bytes
+    let mut bytes = results[0].to_binary();
+    bytes.push(0);
+    assert!(QueryAlignment::from_binary(&bytes).is_err());
+}
+
+#[test]
+fn any_position_is_encoded() {
+    // Positions are not validated: the end can be smaller than the start
+    let result = QueryAlignment(vec![TargetAlignment {
+        index: 0,
+        alignments: vec![Alignment {
+            penalty: 0,
+            length: 0,
+            position: AlignmentPosition { query: (10, 5), target: (u32::MAX, 0) },
+            operations: Vec::new(),
+        }],
+    }]);
+    let decoded = QueryAlignment::from_binary(&result.to_binary()).unwrap();
+    assert_eq!(decoded.0[0].alignments[0].position, result.0[0].alignments[0].position);
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, ReferenceBuilder,
+    algorithms::Local,
+    results::{
+        TargetAlignment, Alignment, AlignmentPosition, AlignmentOperation,
+        Cigar, DeduplicationStrategy, DeduplicationPriority,
+    },
+};
+
+fn alignment(penalty: u32, query: (u32, u32), target: (u32, u32), cigar: &str) -> Alignment {
+    let operations = Vec::from_cigar(cigar).unwrap();
+    let length = operations.iter().map(|x| x.count).sum();
+    Alignment {
+        penalty,
+        length,
+        position: AlignmentPosition { query, target },
+        operations,
+    }
+}
+
+// Previous implementation hashing every position
+fn deduplicated_by_hashing_path(mut target_alignment: TargetAlignment) -> TargetAlignment {
+    fn get_path(alignment: &Alignment) -> HashSet<(u32, u32)> {
+        let (mut query_index, mut target_index) = (alignment.position.query.0, alignment.position.target.0);
+        let mut paths = HashSet::new();
+        alignment.operations.iter().for_each(|operation| {
+            match operation.operation {
+                AlignmentOperation::Match | AlignmentOperation::Subst => {
+                    for _ in 0..operation.count {
+                        paths.insert((query_index, target_index));
+                        query_index += 1;
+                        target_index += 1;
+                    }
+                },
+                AlignmentOperation::Deletion => target_index += operation.count,
+                AlignmentOperation::Insertion => query_index += operation.count,
+            }
+        });
+        paths
+    }
+    target_alignment.alignments.sort_unstable_by(|a, b| {
+        (b.position.query.1 - b.position.query.0).cmp(&(a.position.query.1 - a.position.query.0))
+            .then(a.position.query.0.cmp(&b.position.query.0))
+    });
+    let mut paths = HashSet::new();
+    target_alignment.alignments.retain(|v| {
+        let path = get_path(v);
+        if paths.is_disjoint(&path) {
+            paths.extend(path);
+            true
+        } else {
+            false
+        }
+    });
+    target_alignment
+}
+
+#[test]
+fn shared_path_deduplication_is_same_as_hashing_positions() {
+    init_logger();
+    // Repetitive targets to get many overlapped alignments
+    let unit = gen_rand_text(b"ACGT", 60, 60);
+    let targets: Vec<Vec<u8>> = (0..3).map(|_| {
+        let mut target = Vec::new();
+        for _ in 0..10 {
+            target.extend_from_slice(&unit);
+            target.extend(gen_rand_text(b"ACGT", 0, 5));
+        }
+        target
+    }).collect();
+    let mut builder = ReferenceBuilder::new();
+    for (index, target) in targets.iter().enumerate() {
+        builder = builder.add_target(&format!("target_{}", index), target);
+    }
+    let reference = builder.build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 30, 0.2).unwrap());
+
+    let mut removed_count = 0;
+    for target in targets.iter().cycle().take(15) {
+        let query = gen_rand_pattern(target, 100, 200);
+        let result = aligner.align(&query, &reference);
+        for target_alignment in result.0 {
+            let expected = deduplicated_by_hashing_path(target_alignment.clone());
+            let deduplicated = target_alignment.clone().deduplicated();
+            removed_count += target_alignment.alignments.len() - deduplicated.alignments.len();
+            assert_eq!(expected.alignments, deduplicated.alignments);
+        }
+    }
+    assert!(removed_count > 0);
+}
+
+#[test]
+fn shared_path_ignores_crossing_without_shared_position() {
+    let target_alignment = TargetAlignment {
+        index: 0,
+        alignments: vec![
+            alignment(0, (0, 100), (0, 100), "100="),
+            // Same region, but shifted diagonal
+            alignment(0, (0, 90), (10, 100), "90="),
+            // Shares the diagonal after the gap
+            alignment(10, (50, 80), (40, 80), "10D30="),
+        ],
+    };
+    let deduplicated = target_alignment.clone().deduplicated();
+    assert_eq!(deduplicated.alignments.len(), 2);
+
+    let by_query = target_alignment.clone().deduplicated_with(
+        DeduplicationStrategy::QueryOverlap(0.5),
+        DeduplicationPriority::Longest,
+    );
+    assert_eq!(by_query.alignments.len(), 1);
+}
+
+#[test]
+fn overlap_strategies() {
+    let target_alignment = TargetAlignment {
+        index: 0,
+        alignments: vec![
+            alignment(4, (0, 100), (0, 100), "50=1X49="),
+            alignment(0, (0, 60), (500, 560), "60="),
+            alignment(2, (40, 140), (60, 160), "100="),
+        ],
+    };
+    let dedup = |strategy, priority| {
+        let mut penalties: Vec<u32> = target_alignment.clone()
+            .deduplicated_with(strategy, priority)
+            .alignments.iter().map(|x| x.penalty).collect();
+        penalties.sort();
+        penalties
+    };
+    // Query: (0, 60) is covered fully by (0, 100), (40, 140) is covered 60%
+    assert_eq!(dedup(DeduplicationStrategy::QueryOverlap(0.5), DeduplicationPriority::Longest), vec![4]);
+    assert_eq!(dedup(DeduplicationStrategy::QueryOverlap(0.7), DeduplicationPriority::Longest), vec![2, 4]);
+    assert_eq!(dedup(DeduplicationStrategy::QueryOverlap(0.6), DeduplicationPriority::LowerPenalty), vec![0, 2]);
+    // Target: only (60, 160) overlaps (0, 100) by 40%
+    assert_eq!(dedup(DeduplicationStrategy::TargetOverlap(0.4), DeduplicationPriority::Longest), vec![0, 4]);
+    assert_eq!(dedup(DeduplicationStrategy::TargetOverlap(0.5), DeduplicationPriority::Longest), vec![0, 2, 4]);
+    // Reciprocal: (0, 60) covers only 60% of (0, 100) in query, and does not overlap in target
+    assert_eq!(dedup(DeduplicationStrategy::ReciprocalOverlap(0.4), DeduplicationPriority::Longest), vec![0, 4]);
+    assert_eq!(dedup(DeduplicationStrategy::ReciprocalOverlap(0.5), DeduplicationPriority::Longest), vec![0, 2, 4]);
+    // Out of range fractions are clamped into (0, 1]
+    assert_eq!(dedup(DeduplicationStrategy::QueryOverlap(0.0), DeduplicationPriority::Longest), vec![4]);
+    assert_eq!(dedup(DeduplicationStrategy::TargetOverlap(-1.0), DeduplicationPriority::Longest), vec![0, 4]);
+    assert_eq!(dedup(DeduplicationStrategy::QueryOverlap(1.5), DeduplicationPriority::Longest), vec![2, 4]);
+    assert_eq!(dedup(DeduplicationStrategy::QueryOverlap(f64::NAN), DeduplicationPriority::Longest), vec![2, 4]);
+}
+
+#[test]
+fn penalty_aware_tie_breaking() {
+    let target_alignment = TargetAlignment {
+        index: 0,
+        alignments: vec![
+            alignment(8, (0, 100), (0, 100), "50=2X48="),
+            alignment(4, (0, 100), (0, 100), "50=1X49="),
+            alignment(0, (20, 50), (20, 50), "30="),
+        ],
+    };
+    let kept = |priority| {
+        target_alignment.clone()
+            .deduplicated_with(DeduplicationStrategy::SharedPath, priority)
+            .alignments.iter().map(|x| x.penalty).collect::<Vec<u32>>()
+    };
+    assert_eq!(kept(DeduplicationPriority::LongestWithLowerPenalty), vec![4]);
+    assert_eq!(kept(DeduplicationPriority::LowerPenalty), vec![0]);
+    assert_eq!(kept(DeduplicationPriority::LowerPenaltyPerLength), vec![0]);
+}
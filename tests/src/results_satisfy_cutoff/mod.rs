@@ -99,7 +99,8 @@ fn test_of_current_algorithm<F, A>(
         let mut query_buffer = Vec::new();
         let mut query_index = 0;
         let mut query_step = 0;
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record.unwrap();
             query_step += 1;
             if query_step == query_sampling_interval {
                 query_step = 0;
@@ -0,0 +1,147 @@
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+use sigalign::{
+    Aligner, ReferenceBuilder,
+    algorithms::Local,
+    results::{
+        QueryAlignment, TargetAlignment, Alignment, AlignmentPosition,
+        AlignmentOrder, Cigar,
+    },
+};
+use sigalign_utils::sequence_manipulation::reverse_complementary::reverse_complement_of_dna_sequence as reverse_complement;
+
+fn alignment(penalty: u32, query: (u32, u32), target: (u32, u32), cigar: &str) -> Alignment {
+    let operations = Vec::from_cigar(cigar).unwrap();
+    let length = operations.iter().map(|x| x.count).sum();
+    Alignment {
+        penalty,
+        length,
+        position: AlignmentPosition { query, target },
+        operations,
+    }
+}
+
+fn get_query_alignment() -> QueryAlignment {
+    QueryAlignment(vec![
+        TargetAlignment {
+            index: 3,
+            alignments: vec![
+                alignment(8, (0, 100), (500, 600), "50=2X48="),
+                alignment(4, (0, 100), (0, 100), "50=1X49="),
+                alignment(0, (20, 50), (200, 230), "30="),
+            ],
+        },
+        TargetAlignment {
+            index: 1,
+            alignments: vec![
+                alignment(2, (150, 200), (10, 60), "50="),
+                alignment(12, (10, 110), (300, 400), "40=3X57="),
+            ],
+        },
+    ])
+}
+
+fn penalties(query_alignment: &QueryAlignment) -> Vec<(u32, Vec<u32>)> {
+    query_alignment.0.iter().map(|x| {
+        (x.index, x.alignments.iter().map(|a| a.penalty).collect())
+    }).collect()
+}
+
+#[test]
+fn filter_alignments() {
+    let result = get_query_alignment();
+    assert_eq!(
+        penalties(&result.clone().filtered_by_penalty(4)),
+        vec![(3, vec![4, 0]), (1, vec![2])],
+    );
+    assert_eq!(
+        penalties(&result.clone().filtered_by_length(100)),
+        vec![(3, vec![8, 4]), (1, vec![12])],
+    );
+    assert_eq!(
+        penalties(&result.clone().filtered_by_identity(0.985)),
+        vec![(3, vec![4, 0]), (1, vec![2])],
+    );
+    assert_eq!(
+        penalties(&result.clone().filtered_by_targets(&[1, 5])),
+        vec![(1, vec![2, 12])],
+    );
+    // Empty target is removed
+    assert_eq!(
+        penalties(&result.clone().filtered(|index, alignment| index == 3 || alignment.penalty == 0)),
+        vec![(3, vec![8, 4, 0])],
+    );
+}
+
+#[test]
+fn sort_alignments() {
+    let result = get_query_alignment();
+    assert_eq!(
+        penalties(&result.clone().sorted(AlignmentOrder::Penalty)),
+        vec![(3, vec![0, 4, 8]), (1, vec![2, 12])],
+    );
+    // 2/50 < 4/100 = 0.04 < 8/100 < 12/100, and 0/30 is the smallest
+    assert_eq!(
+        penalties(&result.clone().sorted(AlignmentOrder::PenaltyPerLength)),
+        vec![(3, vec![0, 4, 8]), (1, vec![2, 12])],
+    );
+    assert_eq!(
+        penalties(&result.clone().sorted(AlignmentOrder::QueryPosition)),
+        vec![(3, vec![4, 8, 0]), (1, vec![12, 2])],
+    );
+    assert_eq!(
+        penalties(&result.clone().sorted(AlignmentOrder::TargetPosition)),
+        vec![(1, vec![2, 12]), (3, vec![4, 0, 8])],
+    );
+}
+
+#[test]
+fn rank_alignments() {
+    let result = get_query_alignment();
+    assert_eq!(
+        penalties(&result.clone().best_k_per_target(1, AlignmentOrder::Penalty)),
+        vec![(3, vec![0]), (1, vec![2])],
+    );
+    assert_eq!(
+        penalties(&result.clone().best_k_per_target(0, AlignmentOrder::Penalty)),
+        vec![],
+    );
+    // Penalty order: 0 (20-50), 2 (150-200), 4 (0-100), 8 (0-100), 12 (10-110)
+    assert_eq!(
+        penalties(&result.clone().best_k_per_query_region(1, AlignmentOrder::Penalty)),
+        vec![(3, vec![0]), (1, vec![2])],
+    );
+    assert_eq!(
+        penalties(&result.clone().best_k_per_query_region(2, AlignmentOrder::Penalty)),
+        vec![(3, vec![0, 4]), (1, vec![2])],
+    );
+    assert_eq!(
+        result.clone().best_k_per_query_region(5, AlignmentOrder::Penalty).count_alignments(),
+        result.count_alignments(),
+    );
+}
+
+#[test]
+fn merge_forward_and_reverse_results() {
+    init_logger();
+    let target = gen_rand_text(b"ACGT", 1000, 1000);
+    let reference = ReferenceBuilder::new()
+        .add_target("forward", &target)
+        .add_target("reverse", &reverse_complement(&target))
+        .build().unwrap();
+    let mut aligner = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+
+    let query = gen_rand_pattern(&target, 100, 200);
+    let forward = aligner.align(&query, &reference);
+    let reverse = aligner.align(&reverse_complement(&query), &reference);
+    let forward_count = forward.count_alignments();
+    let reverse_count = reverse.count_alignments();
+    assert!(forward_count > 0 && reverse_count > 0);
+
+    let merged = forward.clone().merged(reverse);
+    assert_eq!(merged.count_alignments(), forward_count + reverse_count);
+    assert_eq!(merged.0.len(), 2);
+    // Identical alignments are not duplicated
+    let merged_again = merged.clone().merged(forward);
+    assert_eq!(merged_again.count_alignments(), merged.count_alignments());
+}
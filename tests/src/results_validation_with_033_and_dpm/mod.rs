@@ -135,7 +135,8 @@ fn test_current_is_equal_to_stable_or_dpm<A, F1, F2>(
         );
         let mut query_buffer = Vec::new();
         let mut query_index = 0;
-        while let Some(mut record) = fasta_reader.next() {
+        while let Some(record) = fasta_reader.next() {
+            let mut record = record.unwrap();
             query_buffer.clear();
             if query_index % 100 == 0 {
                 info!("Processed {} queries", query_index);
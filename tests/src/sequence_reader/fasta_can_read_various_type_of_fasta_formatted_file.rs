@@ -28,9 +28,10 @@ fn generate_same_record_regardless_of_compression() {
     let mut fa_buf = Vec::new();
     let mut gzip_buf = Vec::new();
     let mut zlib_buf = Vec::new();
-    while let Some(mut fa_record) = fa_reader.next() {
-        let mut gzip_record = gzip_reader.next().unwrap();
-        let mut zlib_record = zlib_reader.next().unwrap();
+    while let Some(fa_record) = fa_reader.next() {
+        let mut fa_record = fa_record.unwrap();
+        let mut gzip_record = gzip_reader.next().unwrap().unwrap();
+        let mut zlib_record = zlib_reader.next().unwrap().unwrap();
 
         // Compare sequences
         fa_buf.clear();
@@ -70,9 +71,10 @@ fn generate_same_record_regardless_of_line_ending() {
     let mut crlf_buf = Vec::new();
     let mut two_line_buf = Vec::new();
 
-    while let Some(mut lf_record) = lf_reader.next() {
-        let mut crlf_record = crlf_reader.next().unwrap();
-        let mut two_line_record = two_line_reader.next().unwrap();
+    while let Some(lf_record) = lf_reader.next() {
+        let mut lf_record = lf_record.unwrap();
+        let mut crlf_record = crlf_reader.next().unwrap().unwrap();
+        let mut two_line_record = two_line_reader.next().unwrap().unwrap();
 
         // Compare sequences
         lf_buf.clear();
@@ -44,7 +44,8 @@ fn test_read_fasta_file() {
         let mut label_buffer = String::new();
         
         let mut total_records = 0;
-        while let Some(mut record) = reader_from_utils.next() {
+        while let Some(record) = reader_from_utils.next() {
+            let mut record = record.unwrap();
             total_records += 1;
             query_buffer.clear();
             label_buffer.clear();
@@ -1,2 +1,4 @@
 mod fasta_gives_accurate_query_and_label;
 mod fasta_can_read_various_type_of_fasta_formatted_file;
+mod reports_malformed_records;
+mod open_sequence_file_detects_compression;
@@ -0,0 +1,145 @@
+/*!
+Sequence file can be opened regardless of the compression and the file extension
+*/
+use std::io::{Cursor, Read, Write};
+
+use crate::common::test_data::DataForRefBuild;
+
+use sigalign_utils::sequence_reader::{
+    open_sequence_file,
+    decompress::Compression,
+    SequenceFileReader, SequenceFormat, OpenSequenceFileError,
+};
+
+const FASTA: &[u8] = b">a desc\nACGT\nAC\n>b\nGGTT\n";
+const FASTQ: &[u8] = b"@a desc\nACGTAC\n+\nIIIIII\n@b\nGGTT\n+\nIIII\n";
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+fn zstd(data: &[u8]) -> Vec<u8> {
+    zstd::stream::encode_all(data, 0).unwrap()
+}
+fn bzip2(data: &[u8]) -> Vec<u8> {
+    let mut encoder = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+/// Concatenated gzip members, like BGZF.
+fn multi_member_gzip(data: &[u8]) -> Vec<u8> {
+    let half = data.len() / 2;
+    let mut compressed = gzip(&data[..half]);
+    compressed.extend(gzip(&data[half..]));
+    compressed
+}
+
+fn read_all(mut reader: SequenceFileReader) -> Vec<(String, Vec<u8>)> {
+    let mut records = Vec::new();
+    let mut sequence = Vec::new();
+    let mut id = String::new();
+    while let Some(result) = reader.read_record(&mut sequence, &mut id) {
+        result.unwrap();
+        records.push((id.clone(), sequence.clone()));
+    }
+    records
+}
+
+#[test]
+fn compression_is_detected_from_magic_bytes() {
+    assert_eq!(Compression::from_magic_bytes(FASTA), Compression::None);
+    assert_eq!(Compression::from_magic_bytes(&gzip(FASTA)), Compression::Gzip);
+    assert_eq!(Compression::from_magic_bytes(&zstd(FASTA)), Compression::Zstd);
+    assert_eq!(Compression::from_magic_bytes(&bzip2(FASTA)), Compression::Bzip2);
+    assert_eq!(Compression::from_magic_bytes(&[]), Compression::None);
+}
+
+/// Reader returning one byte at a time, like a slow pipe.
+struct ByteByByte(Cursor<Vec<u8>>);
+impl Read for ByteByByte {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = buf.len().min(1);
+        self.0.read(&mut buf[..length])
+    }
+}
+
+#[test]
+fn compression_is_detected_from_short_reads() {
+    for (encoded, compression) in [
+        (FASTA.to_vec(), Compression::None),
+        (gzip(FASTA), Compression::Gzip),
+        (zstd(FASTA), Compression::Zstd),
+        (bzip2(FASTA), Compression::Bzip2),
+        (b"B".to_vec(), Compression::None),
+    ] {
+        let (detected, mut reader) = Compression::detect(ByteByByte(Cursor::new(encoded.clone()))).unwrap();
+        assert_eq!(detected, compression);
+        // Peeked bytes are not consumed
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, encoded);
+    }
+
+    let reader = SequenceFileReader::new(ByteByByte(Cursor::new(zstd(FASTQ)))).unwrap();
+    assert_eq!(reader.format(), SequenceFormat::Fastq);
+    assert_eq!(read_all(reader).len(), 2);
+}
+
+#[test]
+fn same_records_regardless_of_compression() {
+    let expected = vec![
+        ("a".to_string(), b"ACGTAC".to_vec()),
+        ("b".to_string(), b"GGTT".to_vec()),
+    ];
+    for (data, format) in [
+        (FASTA, SequenceFormat::Fasta),
+        (FASTQ, SequenceFormat::Fastq),
+    ] {
+        for encoded in [
+            data.to_vec(),
+            gzip(data),
+            multi_member_gzip(data),
+            zstd(data),
+            bzip2(data),
+        ] {
+            let reader = SequenceFileReader::new(Cursor::new(encoded)).unwrap();
+            assert_eq!(reader.format(), format);
+            assert_eq!(read_all(reader), expected);
+        }
+    }
+}
+
+#[test]
+fn misnamed_file_can_be_opened() {
+    let gzip_fa = DataForRefBuild::Gz.get_data_path();
+    let lf_fa = DataForRefBuild::LF.get_data_path();
+
+    let misnamed = std::env::temp_dir().join("sigalign_misnamed_gzip_fasta.txt");
+    std::fs::copy(&gzip_fa, &misnamed).unwrap();
+
+    let from_misnamed = read_all(open_sequence_file(&misnamed).unwrap());
+    let from_plain = read_all(open_sequence_file(&lf_fa).unwrap());
+    std::fs::remove_file(&misnamed).unwrap();
+
+    assert!(!from_plain.is_empty());
+    assert_eq!(from_misnamed, from_plain);
+}
+
+#[test]
+fn leading_empty_lines_and_empty_file() {
+    let mut data = b"\n\r\n".to_vec();
+    data.extend_from_slice(FASTQ);
+    let reader = SequenceFileReader::new(Cursor::new(data)).unwrap();
+    assert_eq!(reader.format(), SequenceFormat::Fastq);
+    assert_eq!(read_all(reader).len(), 2);
+
+    let reader = SequenceFileReader::new(Cursor::new(gzip(b""))).unwrap();
+    assert!(read_all(reader).is_empty());
+}
+
+#[test]
+fn unknown_format_is_rejected() {
+    let result = SequenceFileReader::new(Cursor::new(gzip(b"ACGT\nACGT\n")));
+    assert!(matches!(result, Err(OpenSequenceFileError::UnknownFormat(b'A'))));
+}
@@ -0,0 +1,157 @@
+use std::io::{self, Read};
+
+use sigalign::ReferenceBuilder;
+use sigalign::ReferenceBuildError;
+use sigalign_utils::sequence_reader::{
+    fasta::FastaReader,
+    fastq::FastqReader,
+    ReadError,
+    ReadErrorKind,
+};
+
+fn count_fasta_records<R: Read>(reader: &mut FastaReader<R>) -> Result<usize, ReadError> {
+    let mut count = 0;
+    while let Some(record) = reader.next() {
+        record?;
+        count += 1;
+    }
+    Ok(count)
+}
+fn count_fastq_records<R: Read>(reader: &mut FastqReader<R>) -> Result<usize, ReadError> {
+    let mut count = 0;
+    while let Some(record) = reader.next() {
+        record?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[test]
+fn valid_records_are_counted() {
+    let fasta = b">a\nACGT\nAC\n>b\nGGT\n";
+    let mut reader = FastaReader::new(&fasta[..]);
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!((reader.record_number(), reader.line_number()), (1, 1));
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!((reader.record_number(), reader.line_number()), (2, 4));
+    assert!(reader.next().is_none());
+
+    let fastq = b"@a\nACGT\n+\nIIII\n@b\nGGT\n+\nIII\n";
+    let mut reader = FastqReader::new(&fastq[..]);
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!((reader.record_number(), reader.line_number()), (1, 1));
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!((reader.record_number(), reader.line_number()), (2, 5));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn fasta_with_invalid_start_is_reported() {
+    let fasta = b"ACGT\n>a\nACGT\n";
+    let mut reader = FastaReader::new(&fasta[..]);
+    let err = count_fasta_records(&mut reader).unwrap_err();
+    assert!(matches!(err.kind(), ReadErrorKind::MalformedRecord(_)));
+    assert_eq!(err.record_number(), 1);
+    assert_eq!(err.line_number(), 1);
+}
+
+#[test]
+fn fastq_with_invalid_separator_is_reported() {
+    let fastq = b"@a\nACGT\n+\nIIII\n@b\nGGT\n-\nIII\n";
+    let mut reader = FastqReader::new(&fastq[..]);
+    let err = count_fastq_records(&mut reader).unwrap_err();
+    assert!(matches!(err.kind(), ReadErrorKind::MalformedRecord(_)));
+    assert_eq!(err.record_number(), 2);
+    // Line of the separator
+    assert_eq!(err.line_number(), 7);
+}
+
+#[test]
+fn fastq_with_unequal_lengths_is_reported() {
+    let fastq = b"@a\nACGT\n+\nIII\n";
+    let mut reader = FastqReader::new(&fastq[..]);
+    let err = count_fastq_records(&mut reader).unwrap_err();
+    assert!(matches!(err.kind(), ReadErrorKind::MalformedRecord(_)));
+    assert_eq!(err.record_number(), 1);
+    assert_eq!(err.line_number(), 1);
+}
+
+#[test]
+fn truncated_fastq_is_reported() {
+    let fastq = b"@a\nACGT\n+\nIIII\n@b\nGGT\n";
+    let mut reader = FastqReader::new(&fastq[..]);
+    let err = count_fastq_records(&mut reader).unwrap_err();
+    assert!(matches!(err.kind(), ReadErrorKind::MalformedRecord(_)));
+    assert_eq!(err.record_number(), 2);
+    // Line where the file ends
+    assert_eq!(err.line_number(), 7);
+}
+
+/// Fails after giving the `inner` bytes.
+struct FailingReader<'a> {
+    inner: &'a [u8],
+}
+impl Read for FailingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.inner.is_empty() {
+            Err(io::Error::other("disconnected"))
+        } else {
+            self.inner.read(buf)
+        }
+    }
+}
+
+#[test]
+fn io_error_in_the_middle_is_reported() {
+    // Larger than the buffer of the reader
+    let record_count = 10_000;
+
+    let fasta: Vec<u8> = (0..record_count).flat_map(|i| {
+        format!(">r{}\nACGTACGTACGT\n", i).into_bytes()
+    }).collect();
+    let mut reader = FastaReader::new(FailingReader { inner: &fasta });
+    let mut count = 0;
+    let err = loop {
+        match reader.next().unwrap() {
+            Ok(_) => count += 1,
+            Err(err) => break err,
+        }
+    };
+    assert!(matches!(err.kind(), ReadErrorKind::Io(_)));
+    assert!(count > 0 && count < record_count);
+    assert_eq!(err.record_number(), count as u64 + 1);
+    assert_eq!(err.line_number(), count as u64 * 2 - 1);
+
+    let fastq: Vec<u8> = (0..record_count).flat_map(|i| {
+        format!("@r{}\nACGTACGTACGT\n+\nIIIIIIIIIIII\n", i).into_bytes()
+    }).collect();
+    let mut reader = FastqReader::new(FailingReader { inner: &fastq });
+    let mut count = 0;
+    let err = loop {
+        match reader.next().unwrap() {
+            Ok(_) => count += 1,
+            Err(err) => break err,
+        }
+    };
+    assert!(matches!(err.kind(), ReadErrorKind::Io(_)));
+    assert!(count > 0 && count < record_count);
+    assert_eq!(err.record_number(), count as u64 + 1);
+}
+
+#[test]
+fn reference_builder_propagates_read_error() {
+    let fasta = b">a\nACGT\n>b\nGG";
+    let result = ReferenceBuilder::new().add_fasta(FailingReader { inner: &fasta[..] });
+    assert!(matches!(result, Err(ReferenceBuildError::ReadError(_))));
+
+    let fasta = b">a\xff\nACGT\n";
+    let result = ReferenceBuilder::new().add_fasta(&fasta[..]);
+    match result {
+        Err(ReferenceBuildError::ReadError(err)) => {
+            assert!(matches!(err.kind(), ReadErrorKind::InvalidId(_)));
+            assert_eq!(err.record_number(), 1);
+            assert_eq!(err.line_number(), 1);
+        },
+        _ => panic!("Invalid ID must be reported"),
+    }
+}
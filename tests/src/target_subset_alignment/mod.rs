@@ -0,0 +1,106 @@
+use sigalign::{
+    Aligner, Alphabet, Reference, ReferenceBuilder,
+    algorithms::{Algorithm, Local, SemiGlobalWithLimit},
+    results::{AlignmentOrder, QueryAlignment},
+};
+use sigalign_core::aligner::AlignmentRegulator;
+use sigalign_impl::sequence_storage::in_memory::InMemoryBuffer;
+
+use crate::common::init_logger;
+use crate::common::random_text_and_pattern::{gen_rand_text, gen_rand_pattern};
+
+#[test]
+fn alignment_to_targets_is_same_as_filtered() {
+    init_logger();
+    let sequences: Vec<Vec<u8>> = (0..8).map(|_| gen_rand_text(b"ACGT", 300, 600)).collect();
+    let reference = sequences.iter().enumerate().fold(ReferenceBuilder::new(), |builder, (index, sequence)| {
+        builder.add_target(&format!("target_{}", index), sequence)
+    }).build().unwrap();
+
+    let mut local = Aligner::new(Local::new(4, 6, 2, 50, 0.1).unwrap());
+    let mut with_limit = Aligner::new(SemiGlobalWithLimit::new(4, 6, 2, 50, 0.1, 100).unwrap());
+    let subsets: [&[u32]; 4] = [&[], &[3], &[5, 1, 1, 3], &[0, 2, 100]];
+    for sequence in &sequences {
+        let query = gen_rand_pattern(sequence, 100, 200);
+        for subset in subsets {
+            let expected = local.align(&query, &reference)
+                .filtered_by_targets(subset)
+                .sorted(AlignmentOrder::TargetPosition);
+            let result = local.align_to_targets(&query, &reference, subset)
+                .sorted(AlignmentOrder::TargetPosition);
+            assert_eq!(result.to_json(), expected.to_json());
+
+            let result = with_limit.align_to_targets(&query, &reference, subset);
+            assert!(result.0.iter().all(|x| subset.contains(&x.index)));
+        }
+    }
+}
+
+/// Algorithm implementing only the required methods.
+#[derive(Debug, Clone)]
+struct AlignToAllTargets(Local);
+impl Algorithm for AlignToAllTargets {
+    fn align(
+        &mut self,
+        query: &[u8],
+        reference: &Reference,
+        sequence_buffer: &mut InMemoryBuffer,
+    ) -> QueryAlignment {
+        self.0.align(query, reference, sequence_buffer)
+    }
+    fn regulator(&self) -> &AlignmentRegulator {
+        self.0.regulator()
+    }
+}
+
+#[test]
+fn alignment_to_targets_is_filtered_by_default() {
+    init_logger();
+    let sequence = gen_rand_text(b"ACGT", 300, 600);
+    let reference = ReferenceBuilder::new()
+        .add_target("target_0", &sequence)
+        .add_target("target_1", &sequence)
+        .add_target("target_2", &sequence)
+        .build().unwrap();
+    let query = gen_rand_pattern(&sequence, 100, 200);
+
+    let algorithm = Local::new(4, 6, 2, 50, 0.1).unwrap();
+    let mut expected_aligner = Aligner::new(algorithm.clone());
+    let mut aligner = Aligner::new(AlignToAllTargets(algorithm));
+    for subset in [&[][..], &[1], &[2, 0]] {
+        let expected = expected_aligner.align_to_targets(&query, &reference, subset)
+            .sorted(AlignmentOrder::TargetPosition);
+        let result = aligner.align_to_targets(&query, &reference, subset)
+            .sorted(AlignmentOrder::TargetPosition);
+        assert_eq!(result.0.len(), subset.len());
+        assert_eq!(result.to_json(), expected.to_json());
+    }
+}
+
+#[test]
+fn summary_of_reference() {
+    let reference = ReferenceBuilder::new()
+        .set_alphabet(Alphabet::Dna)
+        .set_soft_masking(true)
+        .add_target("a", b"ACGTacgtACGTAAAAAAAAAAAAAAAAAACCCCCCCCCC")
+        .add_target("b", b"ttttACGTACGT")
+        .build().unwrap();
+    let summary = reference.get_summary();
+    assert_eq!(summary.num_targets, 2);
+    assert_eq!(summary.total_length, 52);
+    assert_eq!(summary.alphabet, Some(Alphabet::Dna));
+    assert_eq!(summary.num_soft_masked_bases, Some(8));
+    assert!(!summary.has_target_metadata);
+    assert!(!summary.is_sequence_shared);
+    assert_eq!(summary.build_options, reference.get_build_options().cloned());
+    assert_eq!(summary.estimated_size_in_bytes, reference.get_estimated_size_in_bytes());
+    // A, C, G, T and the masked residue
+    assert_eq!(summary.pattern_index_type, "Lfi32B3V64");
+    assert_eq!(summary.bits_per_character, 3);
+
+    let protein = ReferenceBuilder::new()
+        .add_target("p", b"ACDEFGHIKLMNPQRSTVWY")
+        .build().unwrap();
+    assert_eq!(protein.get_summary().bits_per_character, 5);
+    assert_eq!(protein.get_summary().num_soft_masked_bases, None);
+}